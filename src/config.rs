@@ -9,6 +9,7 @@ pub struct Config {
     pub database_url: String,
     pub app_secret: String,
     pub ollama_url: String,
+    pub tax_rules_dir: Option<String>,
 }
 
 pub static ENV: LazyLock<Config> = LazyLock::new(|| {
@@ -33,6 +34,7 @@ pub static ENV: LazyLock<Config> = LazyLock::new(|| {
         std::process::exit(1);
     });
     let ollama_url = env::var("OLLAMA_URL").unwrap_or("http://localhost:11434".to_string());
+    let tax_rules_dir = env::var("TAX_RULES_DIR").ok().filter(|dir| !dir.is_empty());
 
     Config {
         app_url,
@@ -41,5 +43,6 @@ pub static ENV: LazyLock<Config> = LazyLock::new(|| {
         database_url,
         app_secret,
         ollama_url,
+        tax_rules_dir,
     }
 });
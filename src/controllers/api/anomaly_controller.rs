@@ -0,0 +1,69 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{AnomalyRulePathParams, TaxYearPathParams};
+use crate::models::anomaly_model::{AnomalyRule, SaveAnomalyRuleSettingSchema};
+use crate::services::anomaly_service::AnomalyService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+fn rule(path: Path<AnomalyRulePathParams>) -> Result<AnomalyRule, ErrorBag> {
+    path.into_inner().rule.parse().map_err(|_| ErrorBag::NotFound("Anomaly rule".into()))
+}
+
+/// Stored findings across the user's years. `?year=`, `?rule=` and `?severity=` narrow them down.
+pub async fn index(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let year = req.get_query("year")
+        .map(|year| year.parse().map_err(|_| ErrorBag::Validation { field: "year".into(), message: "Year must be a number".into() }))
+        .transpose()?;
+    let rule = req.get_query("rule")
+        .map(|rule| rule.parse().map_err(|e| ErrorBag::Validation { field: "rule".into(), message: e }))
+        .transpose()?;
+    let severity = req.get_query("severity")
+        .map(|severity| severity.parse().map_err(|e| ErrorBag::Validation { field: "severity".into(), message: e }))
+        .transpose()?;
+    let findings = AnomalyService::findings(&app.pool, &user.id, year, rule, severity).await?;
+
+    Ok(JsonResponse::success(json!({ "anomalies": findings })))
+}
+
+/// Checks the year again and replaces its findings. Saving tax data does this automatically;
+/// this is for after a rule setting changes.
+pub async fn check(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let findings = AnomalyService::check(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "anomalies": findings })))
+}
+
+/// Every rule with whether it runs and its threshold.
+pub async fn rules(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let settings = AnomalyService::settings(&app.pool, &user.id).await?;
+
+    Ok(JsonResponse::success(json!({ "rules": settings })))
+}
+
+pub async fn update_rule(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<AnomalyRulePathParams>,
+    body: Json<SaveAnomalyRuleSettingSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let setting = AnomalyService::update_setting(&app.pool, &user.id, rule(path)?, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "rule": setting })))
+}
+
+/// Puts the rule back to its defaults.
+pub async fn reset_rule(req: HttpRequest, app: Data<AppState>, path: Path<AnomalyRulePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let setting = AnomalyService::reset_setting(&app.pool, &user.id, rule(path)?).await?;
+
+    Ok(JsonResponse::success(json!({ "rule": setting })))
+}
@@ -0,0 +1,119 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{BusinessExpensePathParams, TaxYearPathParams, TaxYearResourcePathParams};
+use crate::models::business_model::{SaveBusinessExpenseSchema, SaveBusinessSchema};
+use crate::repositories::business_repository::BusinessRepository;
+use crate::services::business_service::BusinessService;
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// Lists the year's businesses with their expenses and Schedule C. 1099 receipts that no
+/// business claims are returned under `unassigned`.
+pub async fn index(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let year = path.into_inner().year;
+
+    let businesses = BusinessRepository::find_all_by_user_and_year(&app.pool, &user.id, year).await?;
+    let expenses = BusinessRepository::find_expenses_by_user_and_year(&app.pool, &user.id, year).await?;
+    let (schedules, warnings) = TaxCalculationService::schedule_c(&app.pool, &user.id, year).await?;
+
+    let businesses: Vec<_> = businesses.iter()
+        .map(|business| json!({
+            "business": business,
+            "expenses": expenses.iter().filter(|expense| expense.business_id == business.id).collect::<Vec<_>>(),
+            "schedule_c": schedules.iter().find(|schedule| schedule.business_id == Some(business.id)),
+        }))
+        .collect();
+    let unassigned = schedules.iter().find(|schedule| schedule.business_id.is_none());
+
+    Ok(JsonResponse::success(json!({ "businesses": businesses, "unassigned": unassigned, "warnings": warnings })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    let business = BusinessRepository::find_by_id(&app.pool, &user.id, year, &id).await?;
+    let expenses = BusinessRepository::find_expenses_by_business(&app.pool, &business.id).await?;
+    let (schedules, _) = TaxCalculationService::schedule_c(&app.pool, &user.id, year).await?;
+    let schedule_c = schedules.into_iter().find(|schedule| schedule.business_id == Some(business.id));
+
+    Ok(JsonResponse::success(json!({ "business": business, "expenses": expenses, "schedule_c": schedule_c })))
+}
+
+pub async fn create(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveBusinessSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let business = BusinessService::save(&app.pool, &user.id, path.into_inner().year, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "business": business })))
+}
+
+pub async fn update(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveBusinessSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let business = BusinessService::save(&app.pool, &user.id, year, Some(&id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "business": business })))
+}
+
+/// Soft deletes a business together with its expenses.
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    match BusinessRepository::delete(&app.pool, &user.id, year, &id).await? {
+        0 => Err(ErrorBag::NotFound("Business".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Business deleted successfully" }))),
+    }
+}
+
+pub async fn create_expense(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveBusinessExpenseSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let expense = BusinessService::save_expense(&app.pool, &user.id, year, &id, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "expense": expense })))
+}
+
+pub async fn update_expense(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<BusinessExpensePathParams>,
+    body: Json<SaveBusinessExpenseSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let BusinessExpensePathParams { year, id, expense_id } = path.into_inner();
+    let expense = BusinessService::save_expense(&app.pool, &user.id, year, &id, Some(&expense_id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "expense": expense })))
+}
+
+pub async fn delete_expense(req: HttpRequest, app: Data<AppState>, path: Path<BusinessExpensePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let BusinessExpensePathParams { year, id, expense_id } = path.into_inner();
+
+    match BusinessService::delete_expense(&app.pool, &user.id, year, &id, &expense_id).await? {
+        0 => Err(ErrorBag::NotFound("Business expense".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Business expense deleted successfully" }))),
+    }
+}
@@ -0,0 +1,34 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::TaxYearPathParams;
+use crate::services::carryforward_service::CarryforwardService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// Amounts carried into the year from the prior year's return and on into the next.
+pub async fn index(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let ledger = CarryforwardService::ledger(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "carryforwards": ledger })))
+}
+
+/// Recomputes the year and every following year with tax data, replacing what each carries forward.
+/// Saving tax data does this automatically; this is for changes made elsewhere, such as to businesses or dependents.
+pub async fn roll_forward(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let carried = CarryforwardService::roll_forward(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "carryforwards": carried })))
+}
@@ -0,0 +1,76 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{TaxYearPathParams, TaxYearResourcePathParams};
+use crate::models::dependent_model::SaveDependentSchema;
+use crate::repositories::dependent_repository::DependentRepository;
+use crate::repositories::filing_profile_repository::FilingProfileRepository;
+use crate::services::dependent_service::DependentService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// Lists the year's dependents, each with its qualifying child/relative tests and credit eligibility.
+pub async fn index(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let year = path.into_inner().year;
+
+    let profile = FilingProfileRepository::find_by_user_and_year(&app.pool, &user.id, year).await?;
+    let dependents = DependentRepository::find_all_by_user_and_year(&app.pool, &user.id, year).await?;
+    let dependents = dependents.iter()
+        .map(|dependent| Ok(json!({
+            "dependent": dependent,
+            "eligibility": DependentService::evaluate(dependent, profile.as_ref())?,
+        })))
+        .collect::<Result<Vec<_>, ErrorBag>>()?;
+
+    Ok(JsonResponse::success(json!({ "dependents": dependents })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    let profile = FilingProfileRepository::find_by_user_and_year(&app.pool, &user.id, year).await?;
+    let dependent = DependentRepository::find_by_id(&app.pool, &user.id, year, &id).await?;
+    let eligibility = DependentService::evaluate(&dependent, profile.as_ref())?;
+
+    Ok(JsonResponse::success(json!({ "dependent": dependent, "eligibility": eligibility })))
+}
+
+pub async fn create(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveDependentSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let dependent = DependentService::save(&app.pool, &user.id, path.into_inner().year, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "dependent": dependent })))
+}
+
+pub async fn update(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveDependentSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let dependent = DependentService::save(&app.pool, &user.id, year, Some(&id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "dependent": dependent })))
+}
+
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    match DependentRepository::delete(&app.pool, &user.id, year, &id).await? {
+        0 => Err(ErrorBag::NotFound("Dependent".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Dependent deleted successfully" }))),
+    }
+}
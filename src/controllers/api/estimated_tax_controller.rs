@@ -0,0 +1,101 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use chrono::NaiveDate;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{TaxYearPathParams, TaxYearResourcePathParams};
+use crate::models::estimated_payment_model::SaveEstimatedPaymentSchema;
+use crate::repositories::estimated_payment_repository::EstimatedPaymentRepository;
+use crate::services::estimated_tax_service::EstimatedTaxService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// `?as_of=YYYY-MM-DD`: date the year's tax data runs through; today when omitted.
+fn as_of(req: &HttpRequest) -> Result<Option<NaiveDate>, ErrorBag> {
+    req.get_query("as_of")
+        .map(|date| date.parse().map_err(|_| ErrorBag::Validation { field: "as_of".into(), message: "Date must be YYYY-MM-DD".into() }))
+        .transpose()
+}
+
+/// Safe-harbor installments for the year with the recorded payments applied.
+pub async fn plan(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let plan = EstimatedTaxService::plan(&app.pool, &user.id, path.into_inner().year, as_of(&req)?).await?;
+
+    Ok(JsonResponse::success(json!({ "estimated_tax": plan })))
+}
+
+/// Form 1040-ES voucher amounts: what is still owed on each installment.
+pub async fn vouchers(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let plan = EstimatedTaxService::plan(&app.pool, &user.id, path.into_inner().year, as_of(&req)?).await?;
+    let vouchers = plan.installments.iter()
+        .map(|installment| json!({
+            "voucher": installment.quarter,
+            "due_date": installment.due_date,
+            "amount": installment.voucher_amount,
+        }))
+        .collect::<Vec<_>>();
+
+    Ok(JsonResponse::success(json!({ "year": plan.year, "vouchers": vouchers })))
+}
+
+pub async fn payments(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let payments = EstimatedPaymentRepository::find_all_by_user_and_year(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "estimated_payments": payments })))
+}
+
+pub async fn show_payment(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let payment = EstimatedPaymentRepository::find_by_id(&app.pool, &user.id, year, &id).await?;
+
+    Ok(JsonResponse::success(json!({ "estimated_payment": payment })))
+}
+
+pub async fn create_payment(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveEstimatedPaymentSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let payment = EstimatedTaxService::save_payment(&app.pool, &user.id, path.into_inner().year, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "estimated_payment": payment })))
+}
+
+pub async fn update_payment(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveEstimatedPaymentSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let payment = EstimatedTaxService::save_payment(&app.pool, &user.id, year, Some(&id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "estimated_payment": payment })))
+}
+
+pub async fn delete_payment(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    match EstimatedPaymentRepository::delete(&app.pool, &user.id, year, &id).await? {
+        0 => Err(ErrorBag::NotFound("Estimated payment".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Estimated payment deleted successfully" }))),
+    }
+}
@@ -0,0 +1,52 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::TaxYearPathParams;
+use crate::models::filing_profile_model::SaveFilingProfileSchema;
+use crate::repositories::filing_profile_repository::FilingProfileRepository;
+use crate::services::filing_profile_service::FilingProfileService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+pub async fn index(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let profiles = FilingProfileRepository::find_all_by_user(&app.pool, &user.id).await?;
+
+    Ok(JsonResponse::success(json!({ "profiles": profiles })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let profile = FilingProfileRepository::find_by_user_and_year(&app.pool, &user.id, path.into_inner().year).await?
+        .ok_or_else(|| ErrorBag::NotFound("Filing profile".into()))?;
+
+    Ok(JsonResponse::success(json!({ "profile": profile })))
+}
+
+/// Creates the year's profile or replaces the existing one.
+pub async fn update(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveFilingProfileSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+
+    let mut tx = app.pool.begin().await?;
+    let profile = FilingProfileService::save(&mut tx, &user.id, path.into_inner().year, &body).await?;
+    tx.commit().await?;
+
+    Ok(JsonResponse::success(json!({ "profile": profile })))
+}
+
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+
+    match FilingProfileRepository::delete(&app.pool, &user.id, path.into_inner().year).await? {
+        0 => Err(ErrorBag::NotFound("Filing profile".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Filing profile deleted successfully" }))),
+    }
+}
@@ -2,6 +2,16 @@ pub mod default_controller;
 pub mod api_auth_controller;
 pub mod user_controller;
 pub mod tax_controller;
+pub mod tax_year_controller;
+pub mod filing_profile_controller;
+pub mod dependent_controller;
+pub mod business_controller;
+pub mod residency_controller;
+pub mod tax_scenario_controller;
+pub mod estimated_tax_controller;
+pub mod carryforward_controller;
+pub mod anomaly_controller;
+pub mod penalty_controller;
 
 use serde::Deserialize;
 use uuid::Uuid;
@@ -9,4 +19,27 @@ use uuid::Uuid;
 #[derive(Debug, Deserialize)]
 pub struct OrganizationPathParams {
     pub id: Uuid,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaxYearPathParams {
+    pub year: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaxYearResourcePathParams {
+    pub year: i32,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BusinessExpensePathParams {
+    pub year: i32,
+    pub id: Uuid,
+    pub expense_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnomalyRulePathParams {
+    pub rule: String,
+}
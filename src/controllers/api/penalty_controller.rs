@@ -0,0 +1,29 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use chrono::NaiveDate;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::TaxYearPathParams;
+use crate::services::penalty_service::PenaltyService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+fn date(req: &HttpRequest, name: &str) -> Result<Option<NaiveDate>, ErrorBag> {
+    req.get_query(name)
+        .map(|date| date.parse().map_err(|_| ErrorBag::Validation { field: name.into(), message: "Date must be YYYY-MM-DD".into() }))
+        .transpose()
+}
+
+/// Form 2210 underpayment penalty, and late filing and payment penalties and interest.
+/// `?filed_on=YYYY-MM-DD&paid_on=YYYY-MM-DD`: filing defaults to today, payment to the filing date.
+pub async fn show(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let penalties = PenaltyService::estimate(&app.pool, &user.id, path.into_inner().year, date(&req, "filed_on")?, date(&req, "paid_on")?).await?;
+
+    Ok(JsonResponse::success(json!({ "penalties": penalties })))
+}
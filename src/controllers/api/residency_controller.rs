@@ -0,0 +1,63 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{TaxYearPathParams, TaxYearResourcePathParams};
+use crate::models::residency_period_model::SaveResidencyPeriodSchema;
+use crate::repositories::residency_repository::ResidencyRepository;
+use crate::services::residency_service::ResidencyService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// Lists the year's residency periods in date order.
+pub async fn index(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let periods = ResidencyRepository::find_all_by_user_and_year(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "residency_periods": periods })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let period = ResidencyRepository::find_by_id(&app.pool, &user.id, year, &id).await?;
+
+    Ok(JsonResponse::success(json!({ "residency_period": period })))
+}
+
+pub async fn create(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveResidencyPeriodSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let period = ResidencyService::save(&app.pool, &user.id, path.into_inner().year, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "residency_period": period })))
+}
+
+pub async fn update(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveResidencyPeriodSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let period = ResidencyService::save(&app.pool, &user.id, year, Some(&id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "residency_period": period })))
+}
+
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    match ResidencyRepository::delete(&app.pool, &user.id, year, &id).await? {
+        0 => Err(ErrorBag::NotFound("Residency period".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Residency period deleted successfully" }))),
+    }
+}
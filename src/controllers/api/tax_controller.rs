@@ -7,7 +7,11 @@ use std::io::Write;
 use uuid::Uuid;
 use crate::AppState;
 use crate::models::tax_model::SaveTaxDataSchema;
+use crate::repositories::filing_profile_repository::FilingProfileRepository;
 use crate::repositories::tax_repository::TaxRepository;
+use crate::services::anomaly_service::AnomalyService;
+use crate::services::carryforward_service::CarryforwardService;
+use crate::services::tax_calculation_service::TaxCalculationService;
 use crate::utilities::error_bag::ErrorBag;
 use crate::utilities::http_request::HttpRequestExt;
 use crate::utilities::json_response::JsonResponse;
@@ -87,15 +91,45 @@ fn parse_tax_data(text: &str, doc_type: &str) -> Vec<Value> {
         if doc_type == "W2" {
             let mut wages = 0.0;
             let mut tax_withheld = 0.0;
+            let mut social_security_wages = None;
+            let mut social_security_tax_withheld = None;
+            let mut medicare_wages = None;
+            let mut medicare_tax_withheld = None;
+            let mut state = None;
+            let mut state_wages = None;
+            let mut state_tax_withheld = 0.0;
+            let mut local_tax_withheld = 0.0;
             let mut employer = "Unknown Employer".to_string();
+            let mut employer_ein = None;
             let mut found = false;
 
             for line in &lines {
                 let lower = line.to_lowercase();
-                if lower.contains("wages") || lower.contains("tips") || lower.contains("other compensation") {
+                // Boxes 3-6 also mention wages and tips, so match them before box 1
+                if lower.contains("social security wages") {
+                    if let Some(val) = extract_amount(line) { social_security_wages = Some(val); found = true; }
+                } else if lower.contains("social security tax withheld") {
+                    if let Some(val) = extract_amount(line) { social_security_tax_withheld = Some(val); found = true; }
+                } else if lower.contains("medicare wages") {
+                    if let Some(val) = extract_amount(line) { medicare_wages = Some(val); found = true; }
+                } else if lower.contains("medicare tax withheld") {
+                    if let Some(val) = extract_amount(line) { medicare_tax_withheld = Some(val); found = true; }
+                } else if lower.contains("state income tax") {
+                    if let Some(val) = extract_amount(line) { state_tax_withheld = val; found = true; }
+                } else if lower.contains("local income tax") {
+                    if let Some(val) = extract_amount(line) { local_tax_withheld = val; found = true; }
+                } else if lower.contains("state wages") {
+                    if let Some(val) = extract_amount(line) { state_wages = Some(val); found = true; }
+                } else if lower.contains("employer's state id") || lower.contains("employer state id") {
+                    if let Some(code) = extract_state_code(line) { state = Some(code); found = true; }
+                } else if lower.contains("social security tips") || lower.contains("allocated tips") || lower.contains("local wages") {
+                    continue;
+                } else if lower.contains("wages") || lower.contains("tips") || lower.contains("other compensation") {
                     if let Some(val) = extract_amount(line) { wages = val; found = true; }
                 } else if lower.contains("federal income tax withheld") {
                     if let Some(val) = extract_amount(line) { tax_withheld = val; found = true; }
+                } else if lower.contains("employer identification number") || lower.contains("employer's ein") {
+                    employer_ein = extract_ein(line);
                 } else if lower.contains("employer's name") || lower.contains("employer name") {
                     employer = line.split(':').last().unwrap_or(line).trim().to_string();
                     found = true;
@@ -105,21 +139,200 @@ fn parse_tax_data(text: &str, doc_type: &str) -> Vec<Value> {
             if found {
                 record = json!({
                     "employer": employer,
+                    "employer_ein": employer_ein,
                     "wages": wages,
                     "tax_withheld": tax_withheld,
+                    "social_security_wages": social_security_wages,
+                    "social_security_tax_withheld": social_security_tax_withheld,
+                    "medicare_wages": medicare_wages,
+                    "medicare_tax_withheld": medicare_tax_withheld,
+                    "state": state,
+                    "state_wages": state_wages,
+                    "state_tax_withheld": state_tax_withheld,
+                    "local_tax_withheld": local_tax_withheld,
                 });
                 all_records.push(record);
             }
-        } else if doc_type == "1099" {
+        } else if doc_type == "1098-T" {
+            let mut institution = "Unknown Institution".to_string();
+            let mut student = String::new();
+            let mut qualified_tuition = 0.0;
+            let mut scholarships = 0.0;
+            let mut at_least_half_time = false;
+            let mut graduate_student = false;
+            let mut found = false;
+
+            for line in &lines {
+                let lower = line.to_lowercase();
+                if lower.contains("payments received for qualified tuition") {
+                    if let Some(val) = extract_amount(line) { qualified_tuition = val; found = true; }
+                } else if lower.contains("scholarships or grants") {
+                    if let Some(val) = extract_amount(line) { scholarships = val; found = true; }
+                } else if lower.contains("at least half-time") {
+                    at_least_half_time = is_checked(line);
+                } else if lower.contains("graduate student") {
+                    graduate_student = is_checked(line);
+                } else if lower.contains("filer's name") {
+                    institution = line.split(':').last().unwrap_or(line).trim().to_string();
+                    found = true;
+                } else if lower.contains("student's name") {
+                    student = line.split(':').last().unwrap_or(line).trim().to_string();
+                }
+            }
+
+            if found {
+                record = json!({
+                    "institution": institution,
+                    "student": student,
+                    "qualified_tuition": qualified_tuition,
+                    "scholarships": scholarships,
+                    "at_least_half_time": at_least_half_time,
+                    "graduate_student": graduate_student,
+                });
+                all_records.push(record);
+            }
+        } else if doc_type == "1098" {
+            let mut lender = "Unknown Lender".to_string();
+            let mut mortgage_interest = 0.0;
+            let mut outstanding_principal = None;
+            let mut origination_date = None;
+            let mut points = 0.0;
+            let mut real_estate_taxes = 0.0;
+            let mut found = false;
+
+            for line in &lines {
+                let lower = line.to_lowercase();
+                if lower.contains("mortgage interest received") {
+                    if let Some(val) = extract_amount(line) { mortgage_interest = val; found = true; }
+                } else if lower.contains("outstanding mortgage principal") {
+                    if let Some(val) = extract_amount(line) { outstanding_principal = Some(val); found = true; }
+                } else if lower.contains("origination date") {
+                    origination_date = extract_date(line);
+                } else if lower.contains("points paid") {
+                    if let Some(val) = extract_amount(line) { points = val; found = true; }
+                } else if lower.contains("property tax") || lower.contains("real estate tax") {
+                    if let Some(val) = extract_amount(line) { real_estate_taxes = val; }
+                } else if lower.contains("lender's name") || lower.contains("recipient's name") {
+                    lender = line.split(':').last().unwrap_or(line).trim().to_string();
+                    found = true;
+                }
+            }
+
+            if found {
+                record = json!({
+                    "lender": lender,
+                    "mortgage_interest": mortgage_interest,
+                    "outstanding_principal": outstanding_principal,
+                    "origination_date": origination_date,
+                    "points": points,
+                    "real_estate_taxes": real_estate_taxes,
+                });
+                all_records.push(record);
+            }
+        } else if doc_type == "1099-B" {
+            // One sale per page: boxes 1a-1g and the short/long-term checkbox
+            let mut description = "Unknown Security".to_string();
+            let mut date_acquired = None;
+            let mut date_sold = None;
+            let mut proceeds = 0.0;
+            let mut cost_basis = 0.0;
+            let mut wash_sale_loss_disallowed = 0.0;
+            let mut term = None;
+            let mut found = false;
+
+            for line in &lines {
+                let lower = line.to_lowercase();
+                if lower.contains("description of property") {
+                    description = line.split(':').last().unwrap_or(line).trim().to_string();
+                    found = true;
+                } else if lower.contains("date acquired") {
+                    date_acquired = extract_date(line);
+                } else if lower.contains("date sold") {
+                    date_sold = extract_date(line);
+                } else if lower.contains("proceeds") {
+                    if let Some(val) = extract_amount(line) { proceeds = val; found = true; }
+                } else if lower.contains("cost or other basis") {
+                    if let Some(val) = extract_amount(line) { cost_basis = val; found = true; }
+                } else if lower.contains("wash sale loss disallowed") {
+                    if let Some(val) = extract_amount(line) { wash_sale_loss_disallowed = val; }
+                } else if lower.contains("short-term") {
+                    term = Some("short_term");
+                } else if lower.contains("long-term") {
+                    term = Some("long_term");
+                }
+            }
+
+            if found {
+                record = json!({
+                    "description": description,
+                    "date_acquired": date_acquired,
+                    "date_sold": date_sold,
+                    "proceeds": proceeds,
+                    "cost_basis": cost_basis,
+                    "wash_sale_loss_disallowed": wash_sale_loss_disallowed,
+                    "term": term,
+                });
+                all_records.push(record);
+            }
+        } else if doc_type == "3921" {
+            // One exercise per page: boxes 2-5
+            let mut corporation = "Unknown Corporation".to_string();
+            let mut exercise_date = None;
+            let mut exercise_price = 0.0;
+            let mut fair_market_value = 0.0;
+            let mut shares = 0.0;
+            let mut found = false;
+
+            for line in &lines {
+                let lower = line.to_lowercase();
+                if lower.contains("date option exercised") {
+                    exercise_date = extract_date(line);
+                } else if lower.contains("exercise price per share") {
+                    if let Some(val) = extract_amount(line) { exercise_price = val; found = true; }
+                } else if lower.contains("fair market value per share") {
+                    if let Some(val) = extract_amount(line) { fair_market_value = val; found = true; }
+                } else if lower.contains("number of shares transferred") {
+                    if let Some(val) = extract_amount(line) { shares = val; found = true; }
+                } else if lower.contains("transferor's name") || lower.contains("corporation's name") {
+                    corporation = line.split(':').last().unwrap_or(line).trim().to_string();
+                    found = true;
+                }
+            }
+
+            if found {
+                record = json!({
+                    "corporation": corporation,
+                    "exercise_date": exercise_date,
+                    "exercise_price": exercise_price,
+                    "fair_market_value": fair_market_value,
+                    "shares": shares,
+                });
+                all_records.push(record);
+            }
+        } else if doc_type.starts_with("1099") {
             let mut income = 0.0;
             let mut payer = "Unknown Payer".to_string();
+            let mut payer_tin = None;
+            let mut private_activity_bond_interest = 0.0;
+            // Nonemployee compensation and card payments are Schedule C receipts, not interest
+            let mut form = None;
             let mut found = false;
 
             for line in &lines {
                 let lower = line.to_lowercase();
-                // Check for generic 1099 interest or NEC nonemployee compensation
-                if lower.contains("interest income") || lower.contains("nonemployee compensation") || lower.contains("box 1") {
+                // 1099-DIV box 13 would otherwise match "box 1"
+                if lower.contains("private activity bond interest") {
+                    // Read after the label so the box number is not taken for the amount
+                    let amount = lower.rfind("interest").map(|i| &lower[i..]).unwrap_or(&lower);
+                    if let Some(val) = extract_amount(amount) { private_activity_bond_interest = val; found = true; }
+                } else if lower.contains("nonemployee compensation") {
+                    if let Some(val) = extract_amount(line) { income = val; form = Some("NEC"); found = true; }
+                } else if lower.contains("payment card") || lower.contains("third party network") {
+                    if let Some(val) = extract_amount(line) { income = val; form = Some("K"); found = true; }
+                } else if lower.contains("interest income") || lower.contains("box 1") {
                     if let Some(val) = extract_amount(line) { income = val; found = true; }
+                } else if lower.contains("payer's tin") || lower.contains("payer's federal identification number") {
+                    payer_tin = extract_ein(line);
                 } else if lower.contains("payer's name") || lower.contains("payer name") {
                     payer = line.split(':').last().unwrap_or(line).trim().to_string();
                     found = true;
@@ -129,7 +342,10 @@ fn parse_tax_data(text: &str, doc_type: &str) -> Vec<Value> {
             if found {
                 record = json!({
                     "payer": payer,
+                    "payer_tin": payer_tin,
+                    "form": form,
                     "income": income,
+                    "private_activity_bond_interest": private_activity_bond_interest,
                 });
                 all_records.push(record);
             }
@@ -161,6 +377,35 @@ fn extract_amount(line: &str) -> Option<f64> {
     None
 }
 
+/// Finds a two-letter postal code such as "CA" in a W-2 box 15 line.
+fn extract_state_code(line: &str) -> Option<String> {
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_uppercase()) && *part != "ID")
+        .map(str::to_string)
+}
+
+/// Finds a nine-digit employer identification number and returns it as NN-NNNNNNN.
+fn extract_ein(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .map(|part| part.chars().filter(|c| c.is_ascii_digit() || *c == '-').collect::<String>())
+        .map(|part| part.replace('-', ""))
+        .find(|digits| digits.len() == 9)
+        .map(|digits| format!("{}-{}", &digits[..2], &digits[2..]))
+}
+
+/// Whether a checkbox line is marked, e.g. "[X] at least half-time".
+fn is_checked(line: &str) -> bool {
+    line.contains("[X]") || line.contains("[x]") || line.contains('☒') || line.split_whitespace().any(|part| part == "X")
+}
+
+/// Finds a MM/DD/YYYY date in a line and returns it as YYYY-MM-DD.
+fn extract_date(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .filter_map(|part| chrono::NaiveDate::parse_from_str(part.trim_matches(|c: char| !c.is_ascii_digit()), "%m/%d/%Y").ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .next()
+}
+
 pub async fn list_documents(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
     let user = req.get_user();
     let docs = TaxRepository::find_documents_by_user(&app.pool, &user.id).await?;
@@ -246,6 +491,8 @@ pub async fn upload_document(
     }
 
     TaxRepository::upsert_tax_data(&app.pool, &user.id, year, &current_data).await?;
+    roll_carryforwards(&app, &user.id, year).await;
+    check_anomalies(&app, &user.id, year).await;
 
     Ok(JsonResponse::success(json!({ "document": doc, "extracted_records": extracted_records })))
 }
@@ -257,9 +504,27 @@ pub async fn save_manual_data(
 ) -> Result<HttpResponse, ErrorBag> {
     let user = req.get_user();
     let data = TaxRepository::upsert_tax_data(&app.pool, &user.id, body.year, &body.data).await?;
+    roll_carryforwards(&app, &user.id, body.year).await;
+    check_anomalies(&app, &user.id, body.year).await;
     Ok(JsonResponse::success(json!({ "data": data })))
 }
 
+/// Keeps the carryforward ledger in step with saved tax data. A year that cannot be
+/// calculated yet (e.g. no rule table) must not fail the save, so errors are only logged.
+async fn roll_carryforwards(app: &AppState, user_id: &Uuid, year: i32) {
+    if let Err(e) = CarryforwardService::roll_forward(&app.pool, user_id, year).await {
+        tracing::warn!("Carryforwards from {year} were not rolled forward: {e}");
+    }
+}
+
+/// Flags suspicious values in the saved data. Like the carryforward roll, a failed check must
+/// not fail the save.
+async fn check_anomalies(app: &AppState, user_id: &Uuid, year: i32) {
+    if let Err(e) = AnomalyService::check(&app.pool, user_id, year).await {
+        tracing::warn!("Tax data for {year} was not checked for anomalies: {e}");
+    }
+}
+
 pub async fn get_tax_data(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
     let user = req.get_user();
     let data = TaxRepository::find_all_data_by_user(&app.pool, &user.id).await?;
@@ -276,12 +541,57 @@ pub async fn ai_helper(
     
     // Fetch user tax data for context
     let tax_data = TaxRepository::find_all_data_by_user(&app.pool, &user.id).await?;
+    let profiles = FilingProfileRepository::find_all_by_user(&app.pool, &user.id).await?;
+
+    // Filing status questions are answered from the optimizer for the latest profile year
+    let asks_filing_status = ["married", "jointly", "separately", "filing status"].iter()
+        .any(|topic| question.to_lowercase().contains(topic));
+    let comparison = match (asks_filing_status, profiles.first()) {
+        (true, Some(profile)) => TaxCalculationService::filing_status_comparison(&app.pool, &user.id, profile.year).await.ok()
+            .map(|comparison| (profile.year, comparison)),
+        _ => None,
+    };
+
+    // "Why" questions are answered from the calculation trace of the year asked about, or the latest year
+    let asks_why = ["why", "how did", "how was", "how is", "explain", "where does"].iter()
+        .any(|phrase| question.to_lowercase().contains(phrase));
+    let trace_year = question.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|word| word.parse::<i32>().ok())
+        .find(|year| tax_data.iter().any(|data| data.year == *year))
+        .or_else(|| tax_data.first().map(|data| data.year));
+    let trace = match (asks_why, trace_year) {
+        (true, Some(year)) => TaxCalculationService::trace(&app.pool, &user.id, year).await.ok(),
+        _ => None,
+    };
+    let explained = trace.as_ref().map(|trace| trace.relevant(question, 5)).unwrap_or_default();
+
     let context = json!({
         "user": {
             "first_name": user.first_name,
             "last_name": user.last_name,
         },
-        "tax_records": tax_data
+        "filing_profiles": profiles.iter().map(|p| json!({
+            "year": p.year,
+            "filing_status": p.filing_status,
+            "has_spouse": p.spouse_first_name.is_some(),
+        })).collect::<Vec<_>>(),
+        "tax_records": tax_data,
+        "filing_status_comparison": comparison.as_ref().map(|(year, comparison)| json!({
+            "year": year,
+            "current": comparison.current,
+            "recommended": comparison.recommended,
+            "savings": comparison.savings,
+            "options": comparison.options.iter().map(|option| json!({
+                "filing_status": option.filing_status,
+                "total_tax": option.total_tax,
+                "difference": option.difference,
+                "drivers": option.drivers.iter().map(|driver| &driver.explanation).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })),
+        "calculation_trace": trace.as_ref().map(|trace| json!({
+            "year": trace.year,
+            "lines": explained,
+        })),
     });
 
     let prompt = format!(
@@ -310,8 +620,44 @@ pub async fn ai_helper(
         },
         Err(_) => {
             // Fallback to smart mock if Ollama is not available
-            if question.contains("married") {
-                "Based on your current tax records, I see you filed as Single last year. If your marital status changed in 2024, you should file accordingly. Generally, you can file as Married Filing Jointly if you were legally married by Dec 31st.".to_string()
+            if let Some((year, comparison)) = &comparison {
+                let reason = comparison.options.get(1)
+                    .and_then(|option| option.drivers.first().map(|driver| {
+                        format!(" The biggest difference against {}: {}.", option.filing_status.as_str().replace('_', " "), driver.explanation)
+                    }))
+                    .unwrap_or_default();
+                match comparison.savings.is_zero() {
+                    true => format!(
+                        "For {year}, {} already costs the least of the statuses you can use.{reason}",
+                        comparison.recommended.as_str().replace('_', " "),
+                    ),
+                    false => format!(
+                        "For {year}, filing as {} would save ${} compared with {}.{reason}",
+                        comparison.recommended.as_str().replace('_', " "),
+                        comparison.savings,
+                        comparison.current.as_str().replace('_', " "),
+                    ),
+                }
+            } else if let (Some(trace), Some(line)) = (&trace, explained.first()) {
+                let inputs = line.inputs.iter()
+                    .filter(|input| !input.amount.is_zero())
+                    .map(|input| format!("{} (${})", input.name, input.amount))
+                    .collect::<Vec<_>>();
+                let figured_from = match inputs.is_empty() {
+                    true => String::new(),
+                    false => format!(" It is figured from {}.", inputs.join(", ")),
+                };
+                format!("For {}, {} is ${}: {}.{figured_from}{}", trace.year, line.label, line.amount, line.formula,
+                    line.notes.iter().map(|note| format!(" {note}.")).collect::<String>())
+            } else if question.contains("married") {
+                match profiles.first() {
+                    Some(profile) => format!(
+                        "Based on your filing profile, you filed as {} for {}. If your marital status changed since then, you should file accordingly. Generally, you can file as Married Filing Jointly if you were legally married by Dec 31st.",
+                        profile.filing_status.replace('_', " "),
+                        profile.year
+                    ),
+                    None => "I don't have a filing profile for you yet. Generally, you can file as Married Filing Jointly if you were legally married by Dec 31st.".to_string(),
+                }
             } else if question.contains("how much") || question.contains("wages") {
                 let total_wages: f64 = tax_data.iter().map(|d| {
                     d.data.get("documents").and_then(|docs| docs.as_array()).map(|docs| {
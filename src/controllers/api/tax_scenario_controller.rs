@@ -0,0 +1,79 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use actix_web_validator::Json;
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::{TaxYearPathParams, TaxYearResourcePathParams};
+use crate::models::tax_scenario_model::SaveTaxScenarioSchema;
+use crate::repositories::tax_scenario_repository::TaxScenarioRepository;
+use crate::services::tax_scenario_service::TaxScenarioService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+/// Lists the year's scenarios with the current `tax_data` revision; a scenario whose
+/// `base_revision` differs was forked before the latest changes to the return.
+pub async fn index(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let (scenarios, revision) = TaxScenarioService::list(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "scenarios": scenarios, "revision": revision })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let scenario = TaxScenarioRepository::find_by_id(&app.pool, &user.id, year, &id).await?;
+
+    Ok(JsonResponse::success(json!({ "scenario": scenario })))
+}
+
+/// Forks the year's current tax data into a new scenario.
+pub async fn create(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearPathParams>,
+    body: Json<SaveTaxScenarioSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let scenario = TaxScenarioService::save(&app.pool, &user.id, path.into_inner().year, None, &body).await?;
+
+    Ok(JsonResponse::success(json!({ "scenario": scenario })))
+}
+
+pub async fn update(
+    req: HttpRequest,
+    app: Data<AppState>,
+    path: Path<TaxYearResourcePathParams>,
+    body: Json<SaveTaxScenarioSchema>,
+) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let scenario = TaxScenarioService::save(&app.pool, &user.id, year, Some(&id), &body).await?;
+
+    Ok(JsonResponse::success(json!({ "scenario": scenario })))
+}
+
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+
+    match TaxScenarioRepository::delete(&app.pool, &user.id, year, &id).await? {
+        0 => Err(ErrorBag::NotFound("Tax scenario".into())),
+        _ => Ok(JsonResponse::success(json!({ "message": "Tax scenario deleted successfully" }))),
+    }
+}
+
+/// The real return and the scenario calculated side by side, with the change on each line.
+pub async fn comparison(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearResourcePathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let TaxYearResourcePathParams { year, id } = path.into_inner();
+    let (scenario, revision, comparison) = TaxScenarioService::compare(&app.pool, &user.id, year, &id).await?;
+
+    Ok(JsonResponse::success(json!({
+        "scenario": scenario,
+        "revision": revision,
+        "stale": scenario.base_revision != revision,
+        "comparison": comparison,
+    })))
+}
@@ -0,0 +1,142 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path};
+use serde_json::json;
+use crate::AppState;
+use crate::controllers::api::TaxYearPathParams;
+use crate::repositories::anomaly_repository::AnomalyRepository;
+use crate::repositories::carryforward_repository::CarryforwardRepository;
+use crate::repositories::tax_repository::TaxRepository;
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::tax_engine::rules::TaxRules;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::http_request::HttpRequestExt;
+use crate::utilities::json_response::JsonResponse;
+
+pub async fn index(req: HttpRequest, app: Data<AppState>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let years = TaxRepository::find_year_summaries_by_user(&app.pool, &user.id).await?;
+
+    Ok(JsonResponse::success(json!({ "years": years })))
+}
+
+/// Years the calculation engine has a rule table for.
+pub async fn supported() -> Result<HttpResponse, ErrorBag> {
+    let years: Vec<_> = TaxRules::supported()
+        .map(|rules| json!({ "year": rules.year, "version": rules.version, "source": rules.source }))
+        .collect();
+
+    Ok(JsonResponse::success(json!({ "years": years })))
+}
+
+pub async fn show(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let year = path.into_inner().year;
+
+    let data = TaxRepository::find_data_by_user_and_year(&app.pool, &user.id, year).await?;
+    let documents = TaxRepository::find_documents_by_user_and_year(&app.pool, &user.id, year).await?;
+    if data.is_none() && documents.is_empty() {
+        return Err(ErrorBag::NotFound("Tax year".into()));
+    }
+
+    Ok(JsonResponse::success(json!({ "year": year, "tax_data": data, "documents": documents })))
+}
+
+pub async fn documents(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let documents = TaxRepository::find_documents_by_user_and_year(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "documents": documents })))
+}
+
+/// Forms expected from the prior year's employers, payers and lenders and from the profile, with the ones still missing.
+pub async fn document_checklist(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let checklist = TaxCalculationService::document_checklist(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "checklist": checklist })))
+}
+
+/// Runs the federal calculation engine over the year's stored data.
+pub async fn calculation(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let calculation = TaxCalculationService::calculate(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "calculation": calculation })))
+}
+
+/// How each line of the calculation was figured: formula, inputs, source records and rule table entries.
+pub async fn trace(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let trace = TaxCalculationService::trace(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "trace": trace })))
+}
+
+/// Refund or balance due, effective and marginal rates, and which inputs are still missing.
+pub async fn summary(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let summary = TaxCalculationService::summary(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "summary": summary })))
+}
+
+/// Social Security and Medicare reconciliation across the year's W-2s.
+pub async fn payroll(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let payroll = TaxCalculationService::reconcile_payroll(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "payroll": payroll })))
+}
+
+/// Schedule A and whether itemizing beats the standard deduction.
+pub async fn itemized(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let (schedule_a, deduction) = TaxCalculationService::itemized_deductions(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "schedule_a": schedule_a, "deduction": deduction })))
+}
+
+/// A return for each state with W-2 wages, with its withholding reconciled.
+pub async fn states(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let states = TaxCalculationService::state_returns(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "states": states })))
+}
+
+/// Every filing status the filing profile allows, cheapest first, with what drives the differences.
+pub async fn filing_status(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let comparison = TaxCalculationService::filing_status_comparison(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "filing_status": comparison })))
+}
+
+/// What the next $1,000 of income or deductions would cost or save, how close income is to the
+/// phase-outs that apply, and contributions that would lower the tax, each backed by a scenario run.
+pub async fn analytics(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let analytics = TaxCalculationService::analytics(&app.pool, &user.id, path.into_inner().year).await?;
+
+    Ok(JsonResponse::success(json!({ "analytics": analytics })))
+}
+
+/// Soft deletes a year: its `tax_data` row, every document uploaded for it and what it carries forward.
+/// Files stay on disk so the year can be restored by clearing `deleted_at`.
+pub async fn delete(req: HttpRequest, app: Data<AppState>, path: Path<TaxYearPathParams>) -> Result<HttpResponse, ErrorBag> {
+    let user = req.get_user();
+    let year = path.into_inner().year;
+
+    let mut tx = app.pool.begin().await?;
+    let deleted_data = TaxRepository::delete_data_by_user_and_year(&mut *tx, &user.id, year).await?;
+    let deleted_documents = TaxRepository::delete_documents_by_user_and_year(&mut *tx, &user.id, year).await?;
+    CarryforwardRepository::delete_by_user_and_source_year(&mut *tx, &user.id, year).await?;
+    AnomalyRepository::delete_findings_by_user_and_year(&mut *tx, &user.id, year).await?;
+
+    if deleted_data + deleted_documents == 0 {
+        return Err(ErrorBag::NotFound("Tax year".into()));
+    }
+    tx.commit().await?;
+
+    Ok(JsonResponse::success(json!({ "message": "Tax year deleted successfully" })))
+}
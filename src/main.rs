@@ -6,6 +6,7 @@ mod models;
 mod controllers;
 mod middlewares;
 mod repositories;
+mod tax_engine;
 
 use std::io;
 use std::sync::{LazyLock};
@@ -23,6 +24,7 @@ use tracing::info;
 use tracing_actix_web::TracingLogger;
 use crate::config::ENV;
 use crate::services::log_service;
+use crate::tax_engine::rules::TaxRules;
 use crate::utilities::error_bag::ErrorBag;
 
 #[derive(Clone)]
@@ -58,6 +60,13 @@ async fn main() -> io::Result<()> {
     // Capture and log panics for better observability
     log_service::install_panic_hook();
 
+    // Load and validate the federal rule tables before accepting any request
+    let tax_years = TaxRules::load(ENV.tax_rules_dir.as_deref()).map_err(|e| {
+        tracing::error!("Invalid tax rule tables: {}", e);
+        io::Error::new(io::ErrorKind::InvalidData, "Tax rule tables failed validation")
+    })?;
+    info!("Loaded tax rule tables for {} years", tax_years);
+
     // Create PostgreSQL connection pool
     let pool = PgPoolOptions::new()
         .max_connections(64)
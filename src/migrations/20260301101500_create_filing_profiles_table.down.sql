@@ -0,0 +1,2 @@
+-- Down migration: create_filing_profiles_table
+DROP TABLE IF EXISTS "filing_profiles";
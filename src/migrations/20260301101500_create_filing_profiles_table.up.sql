@@ -0,0 +1,51 @@
+-- Up migration: create_filing_profiles_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE IF NOT EXISTS "filing_profiles"
+(
+    id                       UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id                  UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year                     INT NOT NULL,
+    filing_status            VARCHAR(40) NOT NULL, -- 'single', 'married_filing_jointly', ...
+    -- Taxpayer
+    taxpayer_first_name      VARCHAR(100) NOT NULL,
+    taxpayer_last_name       VARCHAR(100) NOT NULL,
+    taxpayer_dob             DATE NULL,
+    taxpayer_ssn_encrypted   TEXT NULL,
+    taxpayer_ssn_last4       VARCHAR(4) NULL,
+    taxpayer_is_blind        BOOLEAN NOT NULL DEFAULT FALSE,
+    taxpayer_is_65_or_older  BOOLEAN NOT NULL DEFAULT FALSE,
+    taxpayer_occupation      VARCHAR(100) NULL,
+    -- Spouse (required for joint and separate returns)
+    spouse_first_name        VARCHAR(100) NULL,
+    spouse_last_name         VARCHAR(100) NULL,
+    spouse_dob               DATE NULL,
+    spouse_ssn_encrypted     TEXT NULL,
+    spouse_ssn_last4         VARCHAR(4) NULL,
+    spouse_is_blind          BOOLEAN NOT NULL DEFAULT FALSE,
+    spouse_is_65_or_older    BOOLEAN NOT NULL DEFAULT FALSE,
+    spouse_occupation        VARCHAR(100) NULL,
+    -- Home address
+    address_id               UUID NULL REFERENCES addresses(id) ON DELETE SET NULL,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_filing_profiles_filing_status CHECK (filing_status IN (
+        'single',
+        'married_filing_jointly',
+        'married_filing_separately',
+        'head_of_household',
+        'qualifying_surviving_spouse'
+    ))
+);
+
+CREATE INDEX IF NOT EXISTS idx_filing_profiles_id_active
+    ON filing_profiles (id)
+    WHERE deleted_at IS NULL;
+
+-- One active profile per user and year
+CREATE UNIQUE INDEX IF NOT EXISTS uq_filing_profiles_user_year_active
+    ON filing_profiles (user_id, year)
+    WHERE deleted_at IS NULL;
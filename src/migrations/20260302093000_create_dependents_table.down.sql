@@ -0,0 +1,2 @@
+-- Down migration: create_dependents_table
+DROP TABLE IF EXISTS "dependents";
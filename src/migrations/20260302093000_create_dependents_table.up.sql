@@ -0,0 +1,38 @@
+-- Up migration: create_dependents_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE IF NOT EXISTS "dependents"
+(
+    id                              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id                         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year                            INT NOT NULL,
+    first_name                      VARCHAR(100) NOT NULL,
+    last_name                       VARCHAR(100) NOT NULL,
+    relationship                    VARCHAR(40) NOT NULL, -- 'son', 'daughter', 'parent', ...
+    dob                             DATE NOT NULL,
+    ssn_encrypted                   TEXT NULL,
+    ssn_last4                       VARCHAR(4) NULL,
+    months_lived_with_taxpayer      SMALLINT NOT NULL DEFAULT 12,
+    is_student                      BOOLEAN NOT NULL DEFAULT FALSE,
+    is_permanently_disabled         BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Share of the dependent's total support paid by the taxpayer (0-100)
+    support_percentage              NUMERIC(5, 2) NOT NULL DEFAULT 100,
+    provided_over_half_own_support  BOOLEAN NOT NULL DEFAULT FALSE,
+    files_joint_return              BOOLEAN NOT NULL DEFAULT FALSE,
+    gross_income                    NUMERIC(12, 2) NOT NULL DEFAULT 0,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_dependents_months_lived CHECK (months_lived_with_taxpayer BETWEEN 0 AND 12),
+    CONSTRAINT chk_dependents_support_percentage CHECK (support_percentage BETWEEN 0 AND 100)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dependents_id_active
+    ON dependents (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_dependents_user_year_active
+    ON dependents (user_id, year)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+-- Down migration: create_businesses_table
+DROP TABLE IF EXISTS "business_expenses";
+DROP TABLE IF EXISTS "businesses";
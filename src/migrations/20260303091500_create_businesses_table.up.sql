@@ -0,0 +1,59 @@
+-- Up migration: create_businesses_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- One Schedule C business per row; a user may run several in the same year
+CREATE TABLE IF NOT EXISTS "businesses"
+(
+    id                       UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id                  UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year                     INT NOT NULL,
+    name                     VARCHAR(150) NOT NULL,
+    owner                    VARCHAR(20) NOT NULL DEFAULT 'taxpayer', -- 'taxpayer' or 'spouse'
+    principal_business       VARCHAR(150) NULL,
+    principal_business_code  VARCHAR(6) NULL, -- NAICS code from the Schedule C instructions
+    accounting_method        VARCHAR(20) NOT NULL DEFAULT 'cash',
+    -- Gross receipts not reported on a 1099-NEC or 1099-K
+    other_receipts           NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    returns_and_allowances   NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    cost_of_goods_sold       NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    other_income             NUMERIC(12, 2) NOT NULL DEFAULT 0,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_businesses_owner CHECK (owner IN ('taxpayer', 'spouse')),
+    CONSTRAINT chk_businesses_accounting_method CHECK (accounting_method IN ('cash', 'accrual', 'other'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_businesses_id_active
+    ON businesses (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_businesses_user_year_active
+    ON businesses (user_id, year)
+    WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS "business_expenses"
+(
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id  UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    category     VARCHAR(40) NOT NULL, -- Schedule C part II line, e.g. 'advertising', 'meals'
+    description  VARCHAR(255) NULL,
+    amount       NUMERIC(12, 2) NOT NULL,
+    incurred_on  DATE NULL,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_business_expenses_amount CHECK (amount >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_business_expenses_id_active
+    ON business_expenses (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_business_expenses_business_active
+    ON business_expenses (business_id)
+    WHERE deleted_at IS NULL;
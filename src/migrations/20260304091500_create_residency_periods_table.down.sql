@@ -0,0 +1,2 @@
+-- Down migration: create_residency_periods_table
+DROP TABLE IF EXISTS "residency_periods";
@@ -0,0 +1,28 @@
+-- Up migration: create_residency_periods_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Where the taxpayer lived during a year; several rows describe a move
+CREATE TABLE IF NOT EXISTS "residency_periods"
+(
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year        INT NOT NULL,
+    state_code  VARCHAR(2) NOT NULL, -- e.g. CA, NY, TX
+    address_id  UUID NULL REFERENCES addresses(id) ON DELETE SET NULL,
+    start_date  DATE NOT NULL,
+    end_date    DATE NOT NULL,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_residency_periods_dates CHECK (start_date <= end_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_residency_periods_id_active
+    ON residency_periods (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_residency_periods_user_year_active
+    ON residency_periods (user_id, year)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+-- Down migration: create_tax_scenarios_table
+DROP TABLE IF EXISTS "tax_scenarios";
+ALTER TABLE "tax_data" DROP COLUMN IF EXISTS revision;
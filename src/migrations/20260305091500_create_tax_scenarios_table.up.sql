@@ -0,0 +1,30 @@
+-- Up migration: create_tax_scenarios_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Bumped on every save so scenarios can tell when the data they were forked from changed
+ALTER TABLE "tax_data" ADD COLUMN IF NOT EXISTS revision INT NOT NULL DEFAULT 1;
+
+-- What-if copies of a year's tax_data; the real row is never touched
+CREATE TABLE IF NOT EXISTS "tax_scenarios"
+(
+    id             UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year           INT NOT NULL,
+    tax_data_id    UUID NOT NULL REFERENCES tax_data(id) ON DELETE CASCADE,
+    base_revision  INT NOT NULL, -- tax_data.revision when forked
+    name           VARCHAR(150) NOT NULL,
+    filing_status  VARCHAR(40) NULL, -- overrides the filing profile when set
+    data           JSONB NOT NULL,
+
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at     TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tax_scenarios_id_active
+    ON tax_scenarios (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tax_scenarios_user_year_active
+    ON tax_scenarios (user_id, year)
+    WHERE deleted_at IS NULL;
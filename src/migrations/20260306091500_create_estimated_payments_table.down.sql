@@ -0,0 +1,2 @@
+-- Down migration: create_estimated_payments_table
+DROP TABLE IF EXISTS "estimated_payments";
@@ -0,0 +1,28 @@
+-- Up migration: create_estimated_payments_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Form 1040-ES payments as reported by the user; a fourth-quarter payment may be made the next January
+CREATE TABLE IF NOT EXISTS "estimated_payments"
+(
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year        INT NOT NULL, -- tax year the payment is for
+    quarter     SMALLINT NOT NULL, -- 1040-ES voucher 1 through 4
+    amount      NUMERIC(12, 2) NOT NULL,
+    paid_on     DATE NOT NULL,
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_estimated_payments_quarter CHECK (quarter BETWEEN 1 AND 4),
+    CONSTRAINT chk_estimated_payments_amount CHECK (amount > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_estimated_payments_id_active
+    ON estimated_payments (id)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_estimated_payments_user_year_active
+    ON estimated_payments (user_id, year)
+    WHERE deleted_at IS NULL;
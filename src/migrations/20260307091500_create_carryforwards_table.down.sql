@@ -0,0 +1,2 @@
+-- Down migration: create_carryforwards_table
+DROP TABLE IF EXISTS "carryforwards";
@@ -0,0 +1,32 @@
+-- Up migration: create_carryforwards_table
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Amounts one computed year hands to the next. Rows are replaced whenever their source year is
+-- rolled forward again; source_revision is the tax_data revision they were computed from
+CREATE TABLE IF NOT EXISTS "carryforwards"
+(
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source_year     INT NOT NULL, -- year whose return produced the amount
+    target_year     INT NOT NULL, -- year the amount is used in
+    kind            VARCHAR(40) NOT NULL,
+    state_code      VARCHAR(2) NULL, -- state refunds only
+    amount          NUMERIC(12, 2) NOT NULL,
+    taxable_amount  NUMERIC(12, 2) NULL, -- part of a state refund that is income in target_year
+    source_revision INT NOT NULL,
+
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at      TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_carryforwards_kind CHECK (kind IN ('capital_loss_short_term', 'capital_loss_long_term', 'charitable_contributions', 'net_operating_loss', 'prior_year_agi', 'state_refund')),
+    CONSTRAINT chk_carryforwards_years CHECK (target_year = source_year + 1)
+);
+
+CREATE INDEX IF NOT EXISTS idx_carryforwards_user_source_year_active
+    ON carryforwards (user_id, source_year)
+    WHERE deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_carryforwards_user_target_year_active
+    ON carryforwards (user_id, target_year)
+    WHERE deleted_at IS NULL;
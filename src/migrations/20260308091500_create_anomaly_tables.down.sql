@@ -0,0 +1,3 @@
+-- Down migration: create_anomaly_tables
+DROP TABLE IF EXISTS "anomaly_findings";
+DROP TABLE IF EXISTS "anomaly_rule_settings";
@@ -0,0 +1,50 @@
+-- Up migration: create_anomaly_tables
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- A user's overrides of the built-in anomaly rules; rules without a row use their defaults
+CREATE TABLE IF NOT EXISTS "anomaly_rule_settings"
+(
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rule        VARCHAR(40) NOT NULL,
+    enabled     BOOLEAN NOT NULL DEFAULT TRUE,
+    threshold   NUMERIC(12, 2) NULL, -- percent or dollars depending on the rule; NULL keeps the default
+
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at  TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_anomaly_rule_settings_rule CHECK (rule IN ('withholding_above_wages', 'wage_jump', 'ein_name_conflict', 'social_security_wages_above_base', 'round_amount', 'form_1099_below_threshold'))
+);
+
+-- One active setting per user and rule
+CREATE UNIQUE INDEX IF NOT EXISTS uq_anomaly_rule_settings_user_rule_active
+    ON anomaly_rule_settings (user_id, rule)
+    WHERE deleted_at IS NULL;
+
+-- Suspicious values found in a year's tax data. Rows are replaced every time the year is
+-- checked; data_revision is the tax_data revision they were found in
+CREATE TABLE IF NOT EXISTS "anomaly_findings"
+(
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    year            INT NOT NULL,
+    rule            VARCHAR(40) NOT NULL,
+    severity        VARCHAR(10) NOT NULL,
+    record_path     VARCHAR(100) NULL, -- path into tax_data.data, e.g. 'documents[0].records[1]'
+    document_id     UUID NULL, -- upload the record was extracted from
+    message         TEXT NOT NULL,
+    amount          NUMERIC(12, 2) NULL,
+    data_revision   INT NOT NULL,
+
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at      TIMESTAMPTZ NULL,
+
+    CONSTRAINT chk_anomaly_findings_rule CHECK (rule IN ('withholding_above_wages', 'wage_jump', 'ein_name_conflict', 'social_security_wages_above_base', 'round_amount', 'form_1099_below_threshold')),
+    CONSTRAINT chk_anomaly_findings_severity CHECK (severity IN ('warning', 'error'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomaly_findings_user_year_active
+    ON anomaly_findings (user_id, year)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,5 @@
+-- Down migration: add_minimum_tax_credit_carryforward
+DELETE FROM "carryforwards" WHERE kind = 'minimum_tax_credit';
+ALTER TABLE "carryforwards" DROP CONSTRAINT IF EXISTS chk_carryforwards_kind;
+ALTER TABLE "carryforwards" ADD CONSTRAINT chk_carryforwards_kind
+    CHECK (kind IN ('capital_loss_short_term', 'capital_loss_long_term', 'charitable_contributions', 'net_operating_loss', 'prior_year_agi', 'state_refund'));
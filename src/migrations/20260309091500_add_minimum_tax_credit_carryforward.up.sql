@@ -0,0 +1,5 @@
+-- Up migration: add_minimum_tax_credit_carryforward
+-- Form 8801 credit for AMT paid on deferral items, carried until the regular tax exceeds the tentative minimum tax
+ALTER TABLE "carryforwards" DROP CONSTRAINT IF EXISTS chk_carryforwards_kind;
+ALTER TABLE "carryforwards" ADD CONSTRAINT chk_carryforwards_kind
+    CHECK (kind IN ('capital_loss_short_term', 'capital_loss_long_term', 'charitable_contributions', 'minimum_tax_credit', 'net_operating_loss', 'prior_year_agi', 'state_refund'));
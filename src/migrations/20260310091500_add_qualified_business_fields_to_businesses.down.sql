@@ -0,0 +1,5 @@
+-- Down migration: add_qualified_business_fields_to_businesses
+ALTER TABLE "businesses" DROP CONSTRAINT IF EXISTS chk_businesses_qualified_property;
+ALTER TABLE "businesses"
+    DROP COLUMN IF EXISTS qualified_property,
+    DROP COLUMN IF EXISTS specified_service;
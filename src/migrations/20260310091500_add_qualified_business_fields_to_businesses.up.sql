@@ -0,0 +1,8 @@
+-- Up migration: add_qualified_business_fields_to_businesses
+-- Form 8995-A inputs: whether the business is a specified service trade or business, and the
+-- unadjusted basis immediately after acquisition of its qualified property
+ALTER TABLE "businesses"
+    ADD COLUMN IF NOT EXISTS specified_service  BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS qualified_property NUMERIC(12,2) NOT NULL DEFAULT 0;
+
+ALTER TABLE "businesses" ADD CONSTRAINT chk_businesses_qualified_property CHECK (qualified_property >= 0);
@@ -0,0 +1,5 @@
+-- Down migration: add_qualified_business_loss_carryforward
+DELETE FROM "carryforwards" WHERE kind = 'qualified_business_loss';
+ALTER TABLE "carryforwards" DROP CONSTRAINT IF EXISTS chk_carryforwards_kind;
+ALTER TABLE "carryforwards" ADD CONSTRAINT chk_carryforwards_kind
+    CHECK (kind IN ('capital_loss_short_term', 'capital_loss_long_term', 'charitable_contributions', 'minimum_tax_credit', 'net_operating_loss', 'prior_year_agi', 'state_refund'));
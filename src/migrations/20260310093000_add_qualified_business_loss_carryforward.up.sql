@@ -0,0 +1,5 @@
+-- Up migration: add_qualified_business_loss_carryforward
+-- Form 8995 line 16 / 8995-A line 35, a negative total QBI carried to reduce next year's QBI
+ALTER TABLE "carryforwards" DROP CONSTRAINT IF EXISTS chk_carryforwards_kind;
+ALTER TABLE "carryforwards" ADD CONSTRAINT chk_carryforwards_kind
+    CHECK (kind IN ('capital_loss_short_term', 'capital_loss_long_term', 'charitable_contributions', 'minimum_tax_credit', 'net_operating_loss', 'prior_year_agi', 'qualified_business_loss', 'state_refund'));
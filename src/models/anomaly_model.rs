@@ -0,0 +1,179 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::Model;
+
+/// A check run over a year's tax data, stored as its snake_case name in `anomaly_rule_settings.rule`
+/// and `anomaly_findings.rule`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyRule {
+    /// W-2 box 2 above box 1, or 1099 withholding above the income
+    WithholdingAboveWages,
+    /// Wages from an employer up more than `threshold` percent on the prior year
+    WageJump,
+    /// One EIN on W-2s or 1099s under different names
+    EinNameConflict,
+    /// W-2 box 3 above the Social Security wage base
+    SocialSecurityWagesAboveBase,
+    /// Extracted amount that is an exact multiple of `threshold` dollars
+    RoundAmount,
+    /// 1099 income under `threshold` dollars, below what payers have to report
+    #[serde(rename = "form_1099_below_threshold")]
+    Form1099BelowThreshold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalySeverity {
+    /// Possibly right, worth a look
+    Warning,
+    /// Cannot be right as entered
+    Error,
+}
+
+impl AnomalyRule {
+    pub const ALL: [AnomalyRule; 6] = [
+        AnomalyRule::WithholdingAboveWages,
+        AnomalyRule::WageJump,
+        AnomalyRule::EinNameConflict,
+        AnomalyRule::SocialSecurityWagesAboveBase,
+        AnomalyRule::RoundAmount,
+        AnomalyRule::Form1099BelowThreshold,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyRule::WithholdingAboveWages => "withholding_above_wages",
+            AnomalyRule::WageJump => "wage_jump",
+            AnomalyRule::EinNameConflict => "ein_name_conflict",
+            AnomalyRule::SocialSecurityWagesAboveBase => "social_security_wages_above_base",
+            AnomalyRule::RoundAmount => "round_amount",
+            AnomalyRule::Form1099BelowThreshold => "form_1099_below_threshold",
+        }
+    }
+
+    pub fn severity(&self) -> AnomalySeverity {
+        match self {
+            AnomalyRule::WithholdingAboveWages | AnomalyRule::SocialSecurityWagesAboveBase => AnomalySeverity::Error,
+            _ => AnomalySeverity::Warning,
+        }
+    }
+
+    /// Percent for `WageJump`, dollars for `RoundAmount` and `Form1099BelowThreshold`;
+    /// `None` for rules without a threshold.
+    pub fn default_threshold(&self) -> Option<Decimal> {
+        match self {
+            AnomalyRule::WageJump => Some(Decimal::from(50)),
+            AnomalyRule::RoundAmount => Some(Decimal::from(100)),
+            AnomalyRule::Form1099BelowThreshold => Some(Decimal::from(10)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AnomalyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AnomalyRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AnomalyRule::ALL
+            .into_iter()
+            .find(|rule| rule.as_str() == s)
+            .ok_or_else(|| format!("Unknown anomaly rule: {s}"))
+    }
+}
+
+impl AnomalySeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalySeverity::Warning => "warning",
+            AnomalySeverity::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for AnomalySeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AnomalySeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [AnomalySeverity::Warning, AnomalySeverity::Error]
+            .into_iter()
+            .find(|severity| severity.as_str() == s)
+            .ok_or_else(|| format!("Unknown anomaly severity: {s}"))
+    }
+}
+
+/// Anomaly rule setting domain model reflecting the `anomaly_rule_settings` table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AnomalyRuleSettingModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub rule: String,
+    pub enabled: bool,
+    /// `None` keeps the rule's default
+    pub threshold: Option<Decimal>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(AnomalyRuleSettingModel, "anomaly_rule_settings", id);
+
+impl AnomalyRuleSettingModel {
+    pub fn rule(&self) -> Result<AnomalyRule, String> {
+        self.rule.parse()
+    }
+}
+
+/// Anomaly rule setting payload.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveAnomalyRuleSettingSchema {
+    pub enabled: bool,
+    pub threshold: Option<Decimal>,
+}
+
+/// Anomaly finding domain model reflecting the `anomaly_findings` table.
+///
+/// Findings are replaced whenever the year is checked again, which happens on every save of
+/// its `tax_data`; `data_revision` is the revision they were found in.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AnomalyFindingModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub rule: String,
+    pub severity: String,
+    /// Path into `tax_data.data` of the record the finding is about
+    pub record_path: Option<String>,
+    /// Upload the record was extracted from
+    pub document_id: Option<Uuid>,
+    pub message: String,
+    /// The suspicious amount, when there is one
+    pub amount: Option<Decimal>,
+    pub data_revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(AnomalyFindingModel, "anomaly_findings", id);
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::filing_profile_model::Owner;
+use crate::models::Model;
+
+/// Schedule C part II expense line, stored as its snake_case name in `business_expenses.category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpenseCategory {
+    Advertising,
+    CarAndTruck,
+    CommissionsAndFees,
+    ContractLabor,
+    Depletion,
+    Depreciation,
+    EmployeeBenefitPrograms,
+    Insurance,
+    MortgageInterest,
+    OtherInterest,
+    LegalAndProfessional,
+    OfficeExpense,
+    PensionAndProfitSharing,
+    RentVehiclesAndEquipment,
+    RentOtherProperty,
+    RepairsAndMaintenance,
+    Supplies,
+    TaxesAndLicenses,
+    Travel,
+    Meals,
+    Utilities,
+    Wages,
+    Other,
+    /// Form 8829 or the simplified method; deducted after tentative profit
+    BusinessUseOfHome,
+}
+
+impl ExpenseCategory {
+    pub const ALL: [ExpenseCategory; 24] = [
+        ExpenseCategory::Advertising,
+        ExpenseCategory::CarAndTruck,
+        ExpenseCategory::CommissionsAndFees,
+        ExpenseCategory::ContractLabor,
+        ExpenseCategory::Depletion,
+        ExpenseCategory::Depreciation,
+        ExpenseCategory::EmployeeBenefitPrograms,
+        ExpenseCategory::Insurance,
+        ExpenseCategory::MortgageInterest,
+        ExpenseCategory::OtherInterest,
+        ExpenseCategory::LegalAndProfessional,
+        ExpenseCategory::OfficeExpense,
+        ExpenseCategory::PensionAndProfitSharing,
+        ExpenseCategory::RentVehiclesAndEquipment,
+        ExpenseCategory::RentOtherProperty,
+        ExpenseCategory::RepairsAndMaintenance,
+        ExpenseCategory::Supplies,
+        ExpenseCategory::TaxesAndLicenses,
+        ExpenseCategory::Travel,
+        ExpenseCategory::Meals,
+        ExpenseCategory::Utilities,
+        ExpenseCategory::Wages,
+        ExpenseCategory::Other,
+        ExpenseCategory::BusinessUseOfHome,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpenseCategory::Advertising => "advertising",
+            ExpenseCategory::CarAndTruck => "car_and_truck",
+            ExpenseCategory::CommissionsAndFees => "commissions_and_fees",
+            ExpenseCategory::ContractLabor => "contract_labor",
+            ExpenseCategory::Depletion => "depletion",
+            ExpenseCategory::Depreciation => "depreciation",
+            ExpenseCategory::EmployeeBenefitPrograms => "employee_benefit_programs",
+            ExpenseCategory::Insurance => "insurance",
+            ExpenseCategory::MortgageInterest => "mortgage_interest",
+            ExpenseCategory::OtherInterest => "other_interest",
+            ExpenseCategory::LegalAndProfessional => "legal_and_professional",
+            ExpenseCategory::OfficeExpense => "office_expense",
+            ExpenseCategory::PensionAndProfitSharing => "pension_and_profit_sharing",
+            ExpenseCategory::RentVehiclesAndEquipment => "rent_vehicles_and_equipment",
+            ExpenseCategory::RentOtherProperty => "rent_other_property",
+            ExpenseCategory::RepairsAndMaintenance => "repairs_and_maintenance",
+            ExpenseCategory::Supplies => "supplies",
+            ExpenseCategory::TaxesAndLicenses => "taxes_and_licenses",
+            ExpenseCategory::Travel => "travel",
+            ExpenseCategory::Meals => "meals",
+            ExpenseCategory::Utilities => "utilities",
+            ExpenseCategory::Wages => "wages",
+            ExpenseCategory::Other => "other",
+            ExpenseCategory::BusinessUseOfHome => "business_use_of_home",
+        }
+    }
+
+    /// Schedule C line the category is reported on.
+    pub fn line(&self) -> &'static str {
+        match self {
+            ExpenseCategory::Advertising => "8",
+            ExpenseCategory::CarAndTruck => "9",
+            ExpenseCategory::CommissionsAndFees => "10",
+            ExpenseCategory::ContractLabor => "11",
+            ExpenseCategory::Depletion => "12",
+            ExpenseCategory::Depreciation => "13",
+            ExpenseCategory::EmployeeBenefitPrograms => "14",
+            ExpenseCategory::Insurance => "15",
+            ExpenseCategory::MortgageInterest => "16a",
+            ExpenseCategory::OtherInterest => "16b",
+            ExpenseCategory::LegalAndProfessional => "17",
+            ExpenseCategory::OfficeExpense => "18",
+            ExpenseCategory::PensionAndProfitSharing => "19",
+            ExpenseCategory::RentVehiclesAndEquipment => "20a",
+            ExpenseCategory::RentOtherProperty => "20b",
+            ExpenseCategory::RepairsAndMaintenance => "21",
+            ExpenseCategory::Supplies => "22",
+            ExpenseCategory::TaxesAndLicenses => "23",
+            ExpenseCategory::Travel => "24a",
+            ExpenseCategory::Meals => "24b",
+            ExpenseCategory::Utilities => "25",
+            ExpenseCategory::Wages => "26",
+            ExpenseCategory::Other => "27a",
+            ExpenseCategory::BusinessUseOfHome => "30",
+        }
+    }
+
+    /// Share of the amount paid that is deductible. Business meals are limited to 50%.
+    pub fn deductible_share(&self) -> Decimal {
+        match self {
+            ExpenseCategory::Meals => Decimal::new(5, 1),
+            _ => Decimal::ONE,
+        }
+    }
+}
+
+impl fmt::Display for ExpenseCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ExpenseCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExpenseCategory::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("Unknown expense category: {s}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountingMethod {
+    #[default]
+    Cash,
+    Accrual,
+    Other,
+}
+
+impl AccountingMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountingMethod::Cash => "cash",
+            AccountingMethod::Accrual => "accrual",
+            AccountingMethod::Other => "other",
+        }
+    }
+}
+
+/// Schedule C business domain model reflecting the `businesses` table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BusinessModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub name: String,
+    pub owner: String,
+    pub principal_business: Option<String>,
+    pub principal_business_code: Option<String>,
+    pub accounting_method: String,
+    pub other_receipts: Decimal,
+    pub returns_and_allowances: Decimal,
+    pub cost_of_goods_sold: Decimal,
+    pub other_income: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub specified_service: bool,
+    pub qualified_property: Decimal,
+}
+
+impl_model!(BusinessModel, "businesses", id);
+
+impl BusinessModel {
+    pub fn owner(&self) -> Result<Owner, String> {
+        self.owner.parse()
+    }
+}
+
+/// Business expense domain model reflecting the `business_expenses` table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BusinessExpenseModel {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub category: String,
+    pub description: Option<String>,
+    pub amount: Decimal,
+    pub incurred_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(BusinessExpenseModel, "business_expenses", id);
+
+impl BusinessExpenseModel {
+    pub fn category(&self) -> Result<ExpenseCategory, String> {
+        self.category.parse()
+    }
+}
+
+/// Business payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveBusinessSchema {
+    #[validate(length(min = 1, max = 150, message = "Name must be between 1 to 150"))]
+    pub name: String,
+    #[serde(default)]
+    pub owner: Owner,
+    #[validate(length(max = 150, message = "Principal business must be at most 150"))]
+    pub principal_business: Option<String>,
+    #[validate(length(equal = 6, message = "Principal business code must be 6 digits"))]
+    pub principal_business_code: Option<String>,
+    #[serde(default)]
+    pub accounting_method: AccountingMethod,
+    /// Gross receipts not reported on a 1099-NEC or 1099-K
+    #[serde(default)]
+    pub other_receipts: Decimal,
+    #[serde(default)]
+    pub returns_and_allowances: Decimal,
+    #[serde(default)]
+    pub cost_of_goods_sold: Decimal,
+    #[serde(default)]
+    pub other_income: Decimal,
+    /// Specified service trade or business (health, law, consulting, ...), whose QBI is phased
+    /// out above the Form 8995 threshold
+    #[serde(default)]
+    pub specified_service: bool,
+    /// Unadjusted basis immediately after acquisition of depreciable property still in service
+    #[serde(default)]
+    pub qualified_property: Decimal,
+}
+
+/// Business expense payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveBusinessExpenseSchema {
+    pub category: ExpenseCategory,
+    #[validate(length(max = 255, message = "Description must be at most 255"))]
+    pub description: Option<String>,
+    pub amount: Decimal,
+    pub incurred_on: Option<NaiveDate>,
+}
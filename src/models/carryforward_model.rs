@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::impl_model;
+use crate::models::Model;
+
+/// What a carried amount is, stored as its snake_case name in `carryforwards.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CarryforwardKind {
+    /// Schedule D line 6
+    CapitalLossShortTerm,
+    /// Schedule D line 14
+    CapitalLossLongTerm,
+    /// Schedule A line 13
+    CharitableContributions,
+    /// Form 8801 line 19
+    MinimumTaxCredit,
+    /// Schedule 1 line 8a
+    NetOperatingLoss,
+    /// Verifies the e-file signature; not used in the calculation
+    PriorYearAgi,
+    /// Form 8995 line 16
+    QualifiedBusinessLoss,
+    /// Schedule 1 line 1
+    StateRefund,
+}
+
+impl CarryforwardKind {
+    pub const ALL: [CarryforwardKind; 8] = [
+        CarryforwardKind::CapitalLossShortTerm,
+        CarryforwardKind::CapitalLossLongTerm,
+        CarryforwardKind::CharitableContributions,
+        CarryforwardKind::MinimumTaxCredit,
+        CarryforwardKind::NetOperatingLoss,
+        CarryforwardKind::PriorYearAgi,
+        CarryforwardKind::QualifiedBusinessLoss,
+        CarryforwardKind::StateRefund,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CarryforwardKind::CapitalLossShortTerm => "capital_loss_short_term",
+            CarryforwardKind::CapitalLossLongTerm => "capital_loss_long_term",
+            CarryforwardKind::CharitableContributions => "charitable_contributions",
+            CarryforwardKind::MinimumTaxCredit => "minimum_tax_credit",
+            CarryforwardKind::NetOperatingLoss => "net_operating_loss",
+            CarryforwardKind::PriorYearAgi => "prior_year_agi",
+            CarryforwardKind::QualifiedBusinessLoss => "qualified_business_loss",
+            CarryforwardKind::StateRefund => "state_refund",
+        }
+    }
+}
+
+impl fmt::Display for CarryforwardKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CarryforwardKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CarryforwardKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("Unknown carryforward kind: {s}"))
+    }
+}
+
+/// Carryforward domain model reflecting the `carryforwards` table.
+///
+/// Each row traces an amount used in `target_year` back to the return for `source_year`,
+/// as computed from `source_revision` of that year's `tax_data`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CarryforwardModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub source_year: i32,
+    pub target_year: i32,
+    pub kind: String,
+    /// State that issues a refund
+    pub state_code: Option<String>,
+    pub amount: Decimal,
+    /// Part of a state refund that is income in `target_year`
+    pub taxable_amount: Option<Decimal>,
+    pub source_revision: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(CarryforwardModel, "carryforwards", id);
+
+impl CarryforwardModel {
+    pub fn kind(&self) -> Result<CarryforwardKind, String> {
+        self.kind.parse()
+    }
+}
+
+/// One year's view of the ledger returned by `GET /api/tax/years/{year}/carryforwards`.
+#[derive(Debug, Serialize)]
+pub struct CarryforwardLedger {
+    pub year: i32,
+    /// Amounts from the prior year's return used in this one
+    pub carried_in: Vec<CarryforwardModel>,
+    /// The prior year's tax data changed after it was rolled forward
+    pub carried_in_stale: bool,
+    /// Amounts this year's return hands to the next
+    pub carried_out: Vec<CarryforwardModel>,
+    pub carried_out_stale: bool,
+}
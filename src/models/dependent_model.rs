@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::filing_profile_model::{age_at_year_end, validate_ssn};
+use crate::models::Model;
+
+/// Relationship of a dependent to the taxpayer, stored as its snake_case name in `dependents.relationship`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relationship {
+    Son,
+    Daughter,
+    Stepchild,
+    FosterChild,
+    Grandchild,
+    Brother,
+    Sister,
+    HalfBrother,
+    HalfSister,
+    Stepbrother,
+    Stepsister,
+    Niece,
+    Nephew,
+    Parent,
+    Stepparent,
+    Grandparent,
+    Aunt,
+    Uncle,
+    InLaw,
+    /// Unrelated member of the household
+    Other,
+}
+
+impl Relationship {
+    pub const ALL: [Relationship; 20] = [
+        Relationship::Son,
+        Relationship::Daughter,
+        Relationship::Stepchild,
+        Relationship::FosterChild,
+        Relationship::Grandchild,
+        Relationship::Brother,
+        Relationship::Sister,
+        Relationship::HalfBrother,
+        Relationship::HalfSister,
+        Relationship::Stepbrother,
+        Relationship::Stepsister,
+        Relationship::Niece,
+        Relationship::Nephew,
+        Relationship::Parent,
+        Relationship::Stepparent,
+        Relationship::Grandparent,
+        Relationship::Aunt,
+        Relationship::Uncle,
+        Relationship::InLaw,
+        Relationship::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relationship::Son => "son",
+            Relationship::Daughter => "daughter",
+            Relationship::Stepchild => "stepchild",
+            Relationship::FosterChild => "foster_child",
+            Relationship::Grandchild => "grandchild",
+            Relationship::Brother => "brother",
+            Relationship::Sister => "sister",
+            Relationship::HalfBrother => "half_brother",
+            Relationship::HalfSister => "half_sister",
+            Relationship::Stepbrother => "stepbrother",
+            Relationship::Stepsister => "stepsister",
+            Relationship::Niece => "niece",
+            Relationship::Nephew => "nephew",
+            Relationship::Parent => "parent",
+            Relationship::Stepparent => "stepparent",
+            Relationship::Grandparent => "grandparent",
+            Relationship::Aunt => "aunt",
+            Relationship::Uncle => "uncle",
+            Relationship::InLaw => "in_law",
+            Relationship::Other => "other",
+        }
+    }
+
+    /// Relationship test for a qualifying child: children, siblings and their descendants.
+    pub fn is_qualifying_child_relation(&self) -> bool {
+        matches!(
+            self,
+            Relationship::Son
+                | Relationship::Daughter
+                | Relationship::Stepchild
+                | Relationship::FosterChild
+                | Relationship::Grandchild
+                | Relationship::Brother
+                | Relationship::Sister
+                | Relationship::HalfBrother
+                | Relationship::HalfSister
+                | Relationship::Stepbrother
+                | Relationship::Stepsister
+                | Relationship::Niece
+                | Relationship::Nephew
+        )
+    }
+
+    /// Relatives who qualify without living with the taxpayer all year (Pub. 501).
+    pub fn is_qualifying_relative_relation(&self) -> bool {
+        !matches!(self, Relationship::Other)
+    }
+}
+
+impl fmt::Display for Relationship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Relationship {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Relationship::ALL
+            .into_iter()
+            .find(|relationship| relationship.as_str() == s)
+            .ok_or_else(|| format!("Unknown relationship: {s}"))
+    }
+}
+
+/// Dependent domain model reflecting the `dependents` table.
+///
+/// Like filing profiles, the SSN is stored encrypted and only its last four digits are serialized.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DependentModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub relationship: String,
+    pub dob: NaiveDate,
+    #[serde(skip_serializing)]
+    pub ssn_encrypted: Option<String>,
+    pub ssn_last4: Option<String>,
+    pub months_lived_with_taxpayer: i16,
+    pub is_student: bool,
+    pub is_permanently_disabled: bool,
+    pub support_percentage: Decimal,
+    pub provided_over_half_own_support: bool,
+    pub files_joint_return: bool,
+    pub gross_income: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(DependentModel, "dependents", id);
+
+impl DependentModel {
+    pub fn relationship(&self) -> Result<Relationship, String> {
+        self.relationship.parse()
+    }
+
+    /// Age at the end of the dependent's tax year.
+    pub fn age_at_year_end(&self) -> i32 {
+        age_at_year_end(self.dob, self.year)
+    }
+}
+
+/// Dependent payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveDependentSchema {
+    #[validate(length(min = 1, max = 100, message = "First name must be between 1 to 100"))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = 100, message = "Last name must be between 1 to 100"))]
+    pub last_name: String,
+    pub relationship: Relationship,
+    pub dob: NaiveDate,
+    /// Omitted on update to keep the stored SSN
+    #[validate(custom(function = "validate_ssn"))]
+    pub ssn: Option<String>,
+    #[validate(range(min = 0, max = 12, message = "Months lived with taxpayer must be between 0 to 12"))]
+    pub months_lived_with_taxpayer: i16,
+    #[serde(default)]
+    pub is_student: bool,
+    #[serde(default)]
+    pub is_permanently_disabled: bool,
+    /// Share of the dependent's total support paid by the taxpayer (0-100)
+    pub support_percentage: Decimal,
+    #[serde(default)]
+    pub provided_over_half_own_support: bool,
+    #[serde(default)]
+    pub files_joint_return: bool,
+    #[serde(default)]
+    pub gross_income: Decimal,
+}
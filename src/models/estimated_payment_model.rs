@@ -0,0 +1,37 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::Model;
+
+/// Estimated tax payment domain model reflecting the `estimated_payments` table.
+///
+/// `year` is the tax year the payment is for, which the fourth installment's `paid_on` may follow.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EstimatedPaymentModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    /// 1040-ES voucher the payment was sent with
+    pub quarter: i16,
+    pub amount: Decimal,
+    pub paid_on: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(EstimatedPaymentModel, "estimated_payments", id);
+
+/// Estimated payment payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveEstimatedPaymentSchema {
+    #[validate(range(min = 1, max = 4, message = "Quarter must be between 1 to 4"))]
+    pub quarter: i16,
+    pub amount: Decimal,
+    pub paid_on: NaiveDate,
+}
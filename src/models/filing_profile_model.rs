@@ -0,0 +1,214 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+use crate::impl_model;
+use crate::models::Model;
+
+/// IRS filing status, stored as its snake_case name in `filing_profiles.filing_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilingStatus {
+    #[default]
+    Single,
+    MarriedFilingJointly,
+    MarriedFilingSeparately,
+    HeadOfHousehold,
+    QualifyingSurvivingSpouse,
+}
+
+impl FilingStatus {
+    pub const ALL: [FilingStatus; 5] = [
+        FilingStatus::Single,
+        FilingStatus::MarriedFilingJointly,
+        FilingStatus::MarriedFilingSeparately,
+        FilingStatus::HeadOfHousehold,
+        FilingStatus::QualifyingSurvivingSpouse,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilingStatus::Single => "single",
+            FilingStatus::MarriedFilingJointly => "married_filing_jointly",
+            FilingStatus::MarriedFilingSeparately => "married_filing_separately",
+            FilingStatus::HeadOfHousehold => "head_of_household",
+            FilingStatus::QualifyingSurvivingSpouse => "qualifying_surviving_spouse",
+        }
+    }
+
+    /// Joint and separate returns both have to identify the spouse.
+    pub fn requires_spouse(&self) -> bool {
+        matches!(self, FilingStatus::MarriedFilingJointly | FilingStatus::MarriedFilingSeparately)
+    }
+}
+
+impl fmt::Display for FilingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for FilingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FilingStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("Unknown filing status: {s}"))
+    }
+}
+
+/// Whose income or document something is on a joint return. Defaults to the taxpayer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Owner {
+    #[default]
+    Taxpayer,
+    Spouse,
+}
+
+impl Owner {
+    pub const ALL: [Owner; 2] = [Owner::Taxpayer, Owner::Spouse];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Owner::Taxpayer => "taxpayer",
+            Owner::Spouse => "spouse",
+        }
+    }
+}
+
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Owner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Owner::ALL
+            .into_iter()
+            .find(|owner| owner.as_str() == s)
+            .ok_or_else(|| format!("Unknown owner: {s}"))
+    }
+}
+
+/// Filing profile domain model reflecting the `filing_profiles` table.
+///
+/// SSNs are stored encrypted (see `CryptoService::encrypt`) and never serialized;
+/// only the last four digits are exposed to clients.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FilingProfileModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub filing_status: String,
+    pub taxpayer_first_name: String,
+    pub taxpayer_last_name: String,
+    pub taxpayer_dob: Option<NaiveDate>,
+    #[serde(skip_serializing)]
+    pub taxpayer_ssn_encrypted: Option<String>,
+    pub taxpayer_ssn_last4: Option<String>,
+    pub taxpayer_is_blind: bool,
+    pub taxpayer_is_65_or_older: bool,
+    pub taxpayer_occupation: Option<String>,
+    pub spouse_first_name: Option<String>,
+    pub spouse_last_name: Option<String>,
+    pub spouse_dob: Option<NaiveDate>,
+    #[serde(skip_serializing)]
+    pub spouse_ssn_encrypted: Option<String>,
+    pub spouse_ssn_last4: Option<String>,
+    pub spouse_is_blind: bool,
+    pub spouse_is_65_or_older: bool,
+    pub spouse_occupation: Option<String>,
+    pub address_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(FilingProfileModel, "filing_profiles", id);
+
+impl FilingProfileModel {
+    /// Parsed filing status. The column is constrained in SQL, so this only fails on a corrupt row.
+    pub fn status(&self) -> Result<FilingStatus, String> {
+        self.filing_status.parse()
+    }
+}
+
+/// Age on December 31st of `year`.
+///
+/// The IRS treats a person as attaining an age on the day before the birthday,
+/// so someone born on January 1st is a year older on December 31st.
+pub fn age_at_year_end(dob: NaiveDate, year: i32) -> i32 {
+    let age = year - dob.year();
+    if dob.month() == 1 && dob.day() == 1 { age + 1 } else { age }
+}
+
+/// Accepts `123-45-6789` or `123456789`.
+pub fn validate_ssn(ssn: &str) -> Result<(), ValidationError> {
+    let digits: String = ssn.chars().filter(|c| *c != '-').collect();
+    if digits.len() != 9 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ValidationError::new("ssn").with_message("SSN must be 9 digits".into()));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AddressSchema {
+    #[validate(length(min = 1, max = 500, message = "Address must be between 1 to 500"))]
+    pub address_raw: String,
+    pub street_address: Option<String>,
+    pub locality: Option<String>,
+    /// State or province code, e.g. "CA"
+    #[validate(length(min = 2, max = 3, message = "State code must be between 2 to 3"))]
+    pub state_code: Option<String>,
+    pub zip: Option<String>,
+    /// ISO2 or ISO3 country code, defaults to "US"
+    #[validate(length(min = 2, max = 3, message = "Country code must be between 2 to 3"))]
+    pub country_code: Option<String>,
+}
+
+/// Filing profile payload for `PUT /api/tax/years/{year}/profile`.
+///
+/// Omitted SSNs keep the value already stored, so clients never need to resend them.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveFilingProfileSchema {
+    pub filing_status: FilingStatus,
+    #[validate(length(min = 1, max = 100, message = "First name must be between 1 to 100"))]
+    pub taxpayer_first_name: String,
+    #[validate(length(min = 1, max = 100, message = "Last name must be between 1 to 100"))]
+    pub taxpayer_last_name: String,
+    pub taxpayer_dob: Option<NaiveDate>,
+    #[validate(custom(function = "validate_ssn"))]
+    pub taxpayer_ssn: Option<String>,
+    #[serde(default)]
+    pub taxpayer_is_blind: bool,
+    #[serde(default)]
+    pub taxpayer_is_65_or_older: bool,
+    #[validate(length(max = 100, message = "Occupation must be at most 100"))]
+    pub taxpayer_occupation: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "Spouse first name must be between 1 to 100"))]
+    pub spouse_first_name: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "Spouse last name must be between 1 to 100"))]
+    pub spouse_last_name: Option<String>,
+    pub spouse_dob: Option<NaiveDate>,
+    #[validate(custom(function = "validate_ssn"))]
+    pub spouse_ssn: Option<String>,
+    #[serde(default)]
+    pub spouse_is_blind: bool,
+    #[serde(default)]
+    pub spouse_is_65_or_older: bool,
+    #[validate(length(max = 100, message = "Spouse occupation must be at most 100"))]
+    pub spouse_occupation: Option<String>,
+    #[validate(nested)]
+    pub address: Option<AddressSchema>,
+}
@@ -4,6 +4,14 @@ use erased_serde::serialize_trait_object;
 pub mod user_model;
 pub mod address_model;
 pub mod tax_model;
+pub mod filing_profile_model;
+pub mod dependent_model;
+pub mod business_model;
+pub mod residency_period_model;
+pub mod tax_scenario_model;
+pub mod estimated_payment_model;
+pub mod carryforward_model;
+pub mod anomaly_model;
 mod macros;
 
 pub trait Model: erased_serde::Serialize + Any + Sync + Send {
@@ -0,0 +1,41 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::filing_profile_model::AddressSchema;
+use crate::models::Model;
+
+/// Residency period domain model reflecting the `residency_periods` table.
+///
+/// Periods are inclusive of both dates. A full-year resident has a single period covering the year.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ResidencyPeriodModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub state_code: String,
+    pub address_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(ResidencyPeriodModel, "residency_periods", id);
+
+/// Residency period payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveResidencyPeriodSchema {
+    /// US postal code, e.g. "CA"
+    #[validate(length(equal = 2, message = "State code must be 2 letters"))]
+    pub state_code: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Home address during the period; its state defaults to `state_code`
+    #[validate(nested)]
+    pub address: Option<AddressSchema>,
+}
@@ -26,6 +26,8 @@ pub struct TaxDataModel {
     pub user_id: Uuid,
     pub year: i32,
     pub data: serde_json::Value,
+    /// Incremented on every save; scenarios record the revision they were forked from
+    pub revision: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -33,6 +35,20 @@ pub struct TaxDataModel {
 
 impl_model!(TaxDataModel, "tax_data", id);
 
+/// One row of the per-year overview returned by `GET /api/tax/years`.
+///
+/// `status` is derived in SQL from the stored `tax_data` payload:
+/// - `no_data`: documents were uploaded but nothing was extracted or entered yet
+/// - `needs_review`: at least one extracted record carries an `error`
+/// - `ready`: data exists and every record parsed cleanly
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TaxYearSummary {
+    pub year: i32,
+    pub document_count: i64,
+    pub last_updated: DateTime<Utc>,
+    pub status: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SaveTaxDataSchema {
     pub year: i32,
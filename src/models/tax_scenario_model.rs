@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use crate::impl_model;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::models::Model;
+
+/// What-if scenario domain model reflecting the `tax_scenarios` table.
+///
+/// `data` starts as a copy of the year's `tax_data` payload at `base_revision` and is only
+/// ever changed through the scenario, so the real return is never touched.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaxScenarioModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub year: i32,
+    pub tax_data_id: Uuid,
+    pub base_revision: i32,
+    pub name: String,
+    /// Overrides the filing profile's status when set
+    pub filing_status: Option<String>,
+    pub data: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl_model!(TaxScenarioModel, "tax_scenarios", id);
+
+impl TaxScenarioModel {
+    pub fn status(&self) -> Result<Option<FilingStatus>, String> {
+        self.filing_status.as_deref().map(str::parse).transpose()
+    }
+}
+
+/// Scenario payload for create and update.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveTaxScenarioSchema {
+    #[validate(length(min = 1, max = 150, message = "Name must be between 1 to 150"))]
+    pub name: String,
+    /// Filing status to compute the scenario with; the filing profile's when omitted
+    pub filing_status: Option<FilingStatus>,
+    /// JSON merge patch (RFC 7396) applied to the scenario's copy of `tax_data`, e.g.
+    /// `{"adjustments": {"ira_deduction": 7000}}`. Arrays are replaced as a whole.
+    pub changes: Option<serde_json::Value>,
+}
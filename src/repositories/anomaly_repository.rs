@@ -0,0 +1,127 @@
+use rust_decimal::Decimal;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::anomaly_model::{AnomalyFindingModel, AnomalyRuleSettingModel};
+use crate::tax_engine::anomalies::AnomalyFinding;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct AnomalyRepository;
+
+impl AnomalyRepository {
+    pub async fn find_settings_by_user<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+    ) -> Result<Vec<AnomalyRuleSettingModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            AnomalyRuleSettingModel,
+            "SELECT * FROM anomaly_rule_settings WHERE user_id = $1 AND deleted_at IS NULL ORDER BY rule",
+            user_id
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    pub async fn upsert_setting<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        rule: &str,
+        enabled: bool,
+        threshold: Option<Decimal>,
+    ) -> Result<AnomalyRuleSettingModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            AnomalyRuleSettingModel,
+            r#"
+            INSERT INTO anomaly_rule_settings (user_id, rule, enabled, threshold)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, rule)
+            WHERE deleted_at IS NULL
+            DO UPDATE SET enabled = EXCLUDED.enabled, threshold = EXCLUDED.threshold, updated_at = NOW()
+            RETURNING *
+            "#,
+            user_id,
+            rule,
+            enabled,
+            threshold
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Soft deletes a rule override so the rule goes back to its defaults. Returns the number of affected rows.
+    pub async fn delete_setting<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, rule: &str) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE anomaly_rule_settings SET deleted_at = NOW() WHERE user_id = $1 AND rule = $2 AND deleted_at IS NULL",
+            user_id,
+            rule
+        ).execute(db).await?.rows_affected())
+    }
+
+    /// Findings across the user's years, newest year first. Each filter is skipped when `None`.
+    pub async fn find_findings_by_user<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: Option<i32>,
+        rule: Option<&str>,
+        severity: Option<&str>,
+    ) -> Result<Vec<AnomalyFindingModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            AnomalyFindingModel,
+            r#"
+            SELECT * FROM anomaly_findings
+            WHERE user_id = $1
+              AND ($2::INT IS NULL OR year = $2)
+              AND ($3::VARCHAR IS NULL OR rule = $3)
+              AND ($4::VARCHAR IS NULL OR severity = $4)
+              AND deleted_at IS NULL
+            ORDER BY year DESC, severity, rule, created_at
+            "#,
+            user_id,
+            year,
+            rule,
+            severity
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    pub async fn create_finding<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        data_revision: i32,
+        finding: &AnomalyFinding,
+    ) -> Result<AnomalyFindingModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            AnomalyFindingModel,
+            r#"
+            INSERT INTO anomaly_findings (user_id, year, rule, severity, record_path, document_id, message, amount, data_revision)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            finding.rule.as_str(),
+            finding.severity.as_str(),
+            finding.source.as_ref().map(|source| source.path.clone()),
+            finding.source.as_ref().and_then(|source| source.document_id),
+            finding.message,
+            finding.amount,
+            data_revision
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Soft deletes a year's findings. Returns the number of affected rows.
+    pub async fn delete_findings_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE anomaly_findings SET deleted_at = NOW() WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL",
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -0,0 +1,260 @@
+use rust_decimal::Decimal;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::business_model::{BusinessExpenseModel, BusinessModel};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `BusinessRepository::create` and `update`.
+pub struct BusinessValues<'a> {
+    pub name: &'a str,
+    pub owner: &'a str,
+    pub principal_business: Option<&'a str>,
+    pub principal_business_code: Option<&'a str>,
+    pub accounting_method: &'a str,
+    pub other_receipts: Decimal,
+    pub returns_and_allowances: Decimal,
+    pub cost_of_goods_sold: Decimal,
+    pub other_income: Decimal,
+    pub specified_service: bool,
+    pub qualified_property: Decimal,
+}
+
+/// Fields written by `BusinessRepository::create_expense` and `update_expense`.
+pub struct BusinessExpenseValues<'a> {
+    pub category: &'a str,
+    pub description: Option<&'a str>,
+    pub amount: Decimal,
+    pub incurred_on: Option<chrono::NaiveDate>,
+}
+
+pub struct BusinessRepository;
+
+impl BusinessRepository {
+    pub async fn find_all_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<BusinessModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            BusinessModel,
+            "SELECT * FROM businesses WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY created_at",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Finds a business owned by the user; other users' businesses are reported as not found.
+    pub async fn find_by_id<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<BusinessModel, ErrorBag> {
+        sqlx::query_as!(
+            BusinessModel,
+            "SELECT * FROM businesses WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Business".into()))
+    }
+
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        values: &BusinessValues<'_>,
+    ) -> Result<BusinessModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            BusinessModel,
+            r#"
+            INSERT INTO businesses (
+                user_id, year, name, owner, principal_business, principal_business_code, accounting_method,
+                other_receipts, returns_and_allowances, cost_of_goods_sold, other_income, specified_service,
+                qualified_property
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            values.name,
+            values.owner,
+            values.principal_business,
+            values.principal_business_code,
+            values.accounting_method,
+            values.other_receipts,
+            values.returns_and_allowances,
+            values.cost_of_goods_sold,
+            values.other_income,
+            values.specified_service,
+            values.qualified_property
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    pub async fn update<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+        values: &BusinessValues<'_>,
+    ) -> Result<BusinessModel, ErrorBag> {
+        sqlx::query_as!(
+            BusinessModel,
+            r#"
+            UPDATE businesses
+            SET
+                name = $4,
+                owner = $5,
+                principal_business = $6,
+                principal_business_code = $7,
+                accounting_method = $8,
+                other_receipts = $9,
+                returns_and_allowances = $10,
+                cost_of_goods_sold = $11,
+                other_income = $12,
+                specified_service = $13,
+                qualified_property = $14,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            user_id,
+            year,
+            values.name,
+            values.owner,
+            values.principal_business,
+            values.principal_business_code,
+            values.accounting_method,
+            values.other_receipts,
+            values.returns_and_allowances,
+            values.cost_of_goods_sold,
+            values.other_income,
+            values.specified_service,
+            values.qualified_property
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Business".into()))
+    }
+
+    /// Soft deletes a business and its expenses. Returns the number of businesses affected.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            r#"
+            WITH deleted AS (
+                UPDATE businesses SET deleted_at = NOW()
+                WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+                RETURNING id
+            ), expenses AS (
+                UPDATE business_expenses SET deleted_at = NOW()
+                WHERE business_id IN (SELECT id FROM deleted) AND deleted_at IS NULL
+            )
+            SELECT id FROM deleted
+            "#,
+            id,
+            user_id,
+            year
+        ).fetch_all(db).await?.len() as u64)
+    }
+
+    /// Every active expense across the user's businesses for the year.
+    pub async fn find_expenses_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<BusinessExpenseModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            BusinessExpenseModel,
+            r#"
+            SELECT e.*
+            FROM business_expenses e
+            JOIN businesses b ON b.id = e.business_id
+            WHERE b.user_id = $1 AND b.year = $2 AND b.deleted_at IS NULL AND e.deleted_at IS NULL
+            ORDER BY e.created_at
+            "#,
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    pub async fn find_expenses_by_business<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        business_id: &Uuid,
+    ) -> Result<Vec<BusinessExpenseModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            BusinessExpenseModel,
+            "SELECT * FROM business_expenses WHERE business_id = $1 AND deleted_at IS NULL ORDER BY created_at",
+            business_id
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    pub async fn create_expense<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        business_id: &Uuid,
+        values: &BusinessExpenseValues<'_>,
+    ) -> Result<BusinessExpenseModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            BusinessExpenseModel,
+            r#"
+            INSERT INTO business_expenses (business_id, category, description, amount, incurred_on)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+            business_id,
+            values.category,
+            values.description,
+            values.amount,
+            values.incurred_on
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    pub async fn update_expense<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        business_id: &Uuid,
+        id: &Uuid,
+        values: &BusinessExpenseValues<'_>,
+    ) -> Result<BusinessExpenseModel, ErrorBag> {
+        sqlx::query_as!(
+            BusinessExpenseModel,
+            r#"
+            UPDATE business_expenses
+            SET category = $3, description = $4, amount = $5, incurred_on = $6, updated_at = NOW()
+            WHERE id = $1 AND business_id = $2 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            business_id,
+            values.category,
+            values.description,
+            values.amount,
+            values.incurred_on
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Business expense".into()))
+    }
+
+    /// Soft deletes an expense. Returns the number of affected rows.
+    pub async fn delete_expense<'e, E: Executor<'e, Database = Postgres>>(db: E, business_id: &Uuid, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE business_expenses SET deleted_at = NOW() WHERE id = $1 AND business_id = $2 AND deleted_at IS NULL",
+            id,
+            business_id
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -0,0 +1,80 @@
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::carryforward_model::CarryforwardModel;
+use crate::tax_engine::carryforward::CarriedAmount;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct CarryforwardRepository;
+
+impl CarryforwardRepository {
+    /// Amounts carried into `target_year`, oldest source first.
+    pub async fn find_all_by_user_and_target_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        target_year: i32,
+    ) -> Result<Vec<CarryforwardModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            CarryforwardModel,
+            "SELECT * FROM carryforwards WHERE user_id = $1 AND target_year = $2 AND deleted_at IS NULL ORDER BY source_year, kind, state_code",
+            user_id,
+            target_year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Amounts `source_year` carries into the following year.
+    pub async fn find_all_by_user_and_source_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        source_year: i32,
+    ) -> Result<Vec<CarryforwardModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            CarryforwardModel,
+            "SELECT * FROM carryforwards WHERE user_id = $1 AND source_year = $2 AND deleted_at IS NULL ORDER BY kind, state_code",
+            user_id,
+            source_year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        source_year: i32,
+        source_revision: i32,
+        carried: &CarriedAmount,
+    ) -> Result<CarryforwardModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            CarryforwardModel,
+            r#"
+            INSERT INTO carryforwards (user_id, source_year, target_year, kind, state_code, amount, taxable_amount, source_revision)
+            VALUES ($1, $2, $2 + 1, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+            user_id,
+            source_year,
+            carried.kind.as_str(),
+            carried.state,
+            carried.amount,
+            carried.taxable_amount,
+            source_revision
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Soft deletes everything `source_year` carries forward. Returns the number of affected rows.
+    pub async fn delete_by_user_and_source_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        source_year: i32,
+    ) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE carryforwards SET deleted_at = NOW() WHERE user_id = $1 AND source_year = $2 AND deleted_at IS NULL",
+            user_id,
+            source_year
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -0,0 +1,159 @@
+use rust_decimal::Decimal;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::dependent_model::DependentModel;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `DependentRepository::create` and `update`, already encrypted and normalized.
+pub struct DependentValues<'a> {
+    pub first_name: &'a str,
+    pub last_name: &'a str,
+    pub relationship: &'a str,
+    pub dob: chrono::NaiveDate,
+    pub ssn_encrypted: Option<String>,
+    pub ssn_last4: Option<String>,
+    pub months_lived_with_taxpayer: i16,
+    pub is_student: bool,
+    pub is_permanently_disabled: bool,
+    pub support_percentage: Decimal,
+    pub provided_over_half_own_support: bool,
+    pub files_joint_return: bool,
+    pub gross_income: Decimal,
+}
+
+pub struct DependentRepository;
+
+impl DependentRepository {
+    pub async fn find_all_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<DependentModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            DependentModel,
+            "SELECT * FROM dependents WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY dob, created_at",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Finds a dependent owned by the user; other users' dependents are reported as not found.
+    pub async fn find_by_id<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<DependentModel, ErrorBag> {
+        sqlx::query_as!(
+            DependentModel,
+            "SELECT * FROM dependents WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Dependent".into()))
+    }
+
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        values: &DependentValues<'_>,
+    ) -> Result<DependentModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            DependentModel,
+            r#"
+            INSERT INTO dependents (
+                user_id, year, first_name, last_name, relationship, dob, ssn_encrypted, ssn_last4,
+                months_lived_with_taxpayer, is_student, is_permanently_disabled, support_percentage,
+                provided_over_half_own_support, files_joint_return, gross_income
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            values.first_name,
+            values.last_name,
+            values.relationship,
+            values.dob,
+            values.ssn_encrypted,
+            values.ssn_last4,
+            values.months_lived_with_taxpayer,
+            values.is_student,
+            values.is_permanently_disabled,
+            values.support_percentage,
+            values.provided_over_half_own_support,
+            values.files_joint_return,
+            values.gross_income
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Updates a dependent. A `None` SSN keeps the stored one.
+    pub async fn update<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+        values: &DependentValues<'_>,
+    ) -> Result<DependentModel, ErrorBag> {
+        sqlx::query_as!(
+            DependentModel,
+            r#"
+            UPDATE dependents
+            SET
+                first_name = $4,
+                last_name = $5,
+                relationship = $6,
+                dob = $7,
+                ssn_encrypted = COALESCE($8, ssn_encrypted),
+                ssn_last4 = COALESCE($9, ssn_last4),
+                months_lived_with_taxpayer = $10,
+                is_student = $11,
+                is_permanently_disabled = $12,
+                support_percentage = $13,
+                provided_over_half_own_support = $14,
+                files_joint_return = $15,
+                gross_income = $16,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            user_id,
+            year,
+            values.first_name,
+            values.last_name,
+            values.relationship,
+            values.dob,
+            values.ssn_encrypted,
+            values.ssn_last4,
+            values.months_lived_with_taxpayer,
+            values.is_student,
+            values.is_permanently_disabled,
+            values.support_percentage,
+            values.provided_over_half_own_support,
+            values.files_joint_return,
+            values.gross_income
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Dependent".into()))
+    }
+
+    /// Soft deletes a dependent. Returns the number of affected rows.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE dependents SET deleted_at = NOW() WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -0,0 +1,115 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::estimated_payment_model::EstimatedPaymentModel;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `EstimatedPaymentRepository::create` and `update`, already validated.
+pub struct EstimatedPaymentValues {
+    pub quarter: i16,
+    pub amount: Decimal,
+    pub paid_on: NaiveDate,
+}
+
+pub struct EstimatedPaymentRepository;
+
+impl EstimatedPaymentRepository {
+    pub async fn find_all_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<EstimatedPaymentModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            EstimatedPaymentModel,
+            "SELECT * FROM estimated_payments WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY paid_on, created_at",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Finds a payment owned by the user; other users' payments are reported as not found.
+    pub async fn find_by_id<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<EstimatedPaymentModel, ErrorBag> {
+        sqlx::query_as!(
+            EstimatedPaymentModel,
+            "SELECT * FROM estimated_payments WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Estimated payment".into()))
+    }
+
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        values: &EstimatedPaymentValues,
+    ) -> Result<EstimatedPaymentModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            EstimatedPaymentModel,
+            r#"
+            INSERT INTO estimated_payments (user_id, year, quarter, amount, paid_on)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            values.quarter,
+            values.amount,
+            values.paid_on
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    pub async fn update<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+        values: &EstimatedPaymentValues,
+    ) -> Result<EstimatedPaymentModel, ErrorBag> {
+        sqlx::query_as!(
+            EstimatedPaymentModel,
+            r#"
+            UPDATE estimated_payments
+            SET
+                quarter = $4,
+                amount = $5,
+                paid_on = $6,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            user_id,
+            year,
+            values.quarter,
+            values.amount,
+            values.paid_on
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Estimated payment".into()))
+    }
+
+    /// Soft deletes a payment. Returns the number of affected rows.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE estimated_payments SET deleted_at = NOW() WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -0,0 +1,140 @@
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::filing_profile_model::FilingProfileModel;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `FilingProfileRepository::upsert`, already encrypted and normalized.
+pub struct FilingProfileValues<'a> {
+    pub filing_status: &'a str,
+    pub taxpayer_first_name: &'a str,
+    pub taxpayer_last_name: &'a str,
+    pub taxpayer_dob: Option<chrono::NaiveDate>,
+    pub taxpayer_ssn_encrypted: Option<String>,
+    pub taxpayer_ssn_last4: Option<String>,
+    pub taxpayer_is_blind: bool,
+    pub taxpayer_is_65_or_older: bool,
+    pub taxpayer_occupation: Option<&'a str>,
+    pub spouse_first_name: Option<&'a str>,
+    pub spouse_last_name: Option<&'a str>,
+    pub spouse_dob: Option<chrono::NaiveDate>,
+    pub spouse_ssn_encrypted: Option<String>,
+    pub spouse_ssn_last4: Option<String>,
+    pub spouse_is_blind: bool,
+    pub spouse_is_65_or_older: bool,
+    pub spouse_occupation: Option<&'a str>,
+    pub address_id: Option<Uuid>,
+}
+
+pub struct FilingProfileRepository;
+
+impl FilingProfileRepository {
+    pub async fn find_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Option<FilingProfileModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            FilingProfileModel,
+            "SELECT * FROM filing_profiles WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL",
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?)
+    }
+
+    pub async fn find_all_by_user<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+    ) -> Result<Vec<FilingProfileModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            FilingProfileModel,
+            "SELECT * FROM filing_profiles WHERE user_id = $1 AND deleted_at IS NULL ORDER BY year DESC",
+            user_id
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Creates or replaces the active profile for a user and year.
+    ///
+    /// A `None` SSN keeps whatever is already stored so clients never have to resend it.
+    /// Dropping the spouse (no `spouse_first_name`) also clears the stored spouse SSN.
+    pub async fn upsert<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        values: &FilingProfileValues<'_>,
+    ) -> Result<FilingProfileModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            FilingProfileModel,
+            r#"
+            INSERT INTO filing_profiles (
+                user_id, year, filing_status,
+                taxpayer_first_name, taxpayer_last_name, taxpayer_dob, taxpayer_ssn_encrypted, taxpayer_ssn_last4,
+                taxpayer_is_blind, taxpayer_is_65_or_older, taxpayer_occupation,
+                spouse_first_name, spouse_last_name, spouse_dob, spouse_ssn_encrypted, spouse_ssn_last4,
+                spouse_is_blind, spouse_is_65_or_older, spouse_occupation,
+                address_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            ON CONFLICT (user_id, year)
+            WHERE deleted_at IS NULL
+            DO UPDATE SET
+                filing_status = EXCLUDED.filing_status,
+                taxpayer_first_name = EXCLUDED.taxpayer_first_name,
+                taxpayer_last_name = EXCLUDED.taxpayer_last_name,
+                taxpayer_dob = EXCLUDED.taxpayer_dob,
+                taxpayer_ssn_encrypted = COALESCE(EXCLUDED.taxpayer_ssn_encrypted, filing_profiles.taxpayer_ssn_encrypted),
+                taxpayer_ssn_last4 = COALESCE(EXCLUDED.taxpayer_ssn_last4, filing_profiles.taxpayer_ssn_last4),
+                taxpayer_is_blind = EXCLUDED.taxpayer_is_blind,
+                taxpayer_is_65_or_older = EXCLUDED.taxpayer_is_65_or_older,
+                taxpayer_occupation = EXCLUDED.taxpayer_occupation,
+                spouse_first_name = EXCLUDED.spouse_first_name,
+                spouse_last_name = EXCLUDED.spouse_last_name,
+                spouse_dob = EXCLUDED.spouse_dob,
+                spouse_ssn_encrypted = CASE WHEN EXCLUDED.spouse_first_name IS NULL THEN NULL
+                    ELSE COALESCE(EXCLUDED.spouse_ssn_encrypted, filing_profiles.spouse_ssn_encrypted) END,
+                spouse_ssn_last4 = CASE WHEN EXCLUDED.spouse_first_name IS NULL THEN NULL
+                    ELSE COALESCE(EXCLUDED.spouse_ssn_last4, filing_profiles.spouse_ssn_last4) END,
+                spouse_is_blind = EXCLUDED.spouse_is_blind,
+                spouse_is_65_or_older = EXCLUDED.spouse_is_65_or_older,
+                spouse_occupation = EXCLUDED.spouse_occupation,
+                address_id = EXCLUDED.address_id,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            values.filing_status,
+            values.taxpayer_first_name,
+            values.taxpayer_last_name,
+            values.taxpayer_dob,
+            values.taxpayer_ssn_encrypted,
+            values.taxpayer_ssn_last4,
+            values.taxpayer_is_blind,
+            values.taxpayer_is_65_or_older,
+            values.taxpayer_occupation,
+            values.spouse_first_name,
+            values.spouse_last_name,
+            values.spouse_dob,
+            values.spouse_ssn_encrypted,
+            values.spouse_ssn_last4,
+            values.spouse_is_blind,
+            values.spouse_is_65_or_older,
+            values.spouse_occupation,
+            values.address_id
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Soft deletes the profile for a user and year. Returns the number of affected rows.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE filing_profiles SET deleted_at = NOW() WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL",
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
@@ -1,3 +1,12 @@
 pub mod user_repository;
 pub mod address_repository;
 pub mod tax_repository;
+pub mod filing_profile_repository;
+pub mod dependent_repository;
+pub mod business_repository;
+pub mod state_repository;
+pub mod residency_repository;
+pub mod tax_scenario_repository;
+pub mod estimated_payment_repository;
+pub mod carryforward_repository;
+pub mod anomaly_repository;
@@ -0,0 +1,118 @@
+use chrono::NaiveDate;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::residency_period_model::ResidencyPeriodModel;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `ResidencyRepository::create` and `update`, already normalized.
+pub struct ResidencyPeriodValues<'a> {
+    pub state_code: &'a str,
+    pub address_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+pub struct ResidencyRepository;
+
+impl ResidencyRepository {
+    pub async fn find_all_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<ResidencyPeriodModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            ResidencyPeriodModel,
+            "SELECT * FROM residency_periods WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY start_date",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Finds a period owned by the user; other users' periods are reported as not found.
+    pub async fn find_by_id<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<ResidencyPeriodModel, ErrorBag> {
+        sqlx::query_as!(
+            ResidencyPeriodModel,
+            "SELECT * FROM residency_periods WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Residency period".into()))
+    }
+
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        values: &ResidencyPeriodValues<'_>,
+    ) -> Result<ResidencyPeriodModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            ResidencyPeriodModel,
+            r#"
+            INSERT INTO residency_periods (user_id, year, state_code, address_id, start_date, end_date)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            user_id,
+            year,
+            values.state_code,
+            values.address_id,
+            values.start_date,
+            values.end_date
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    pub async fn update<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+        values: &ResidencyPeriodValues<'_>,
+    ) -> Result<ResidencyPeriodModel, ErrorBag> {
+        sqlx::query_as!(
+            ResidencyPeriodModel,
+            r#"
+            UPDATE residency_periods
+            SET
+                state_code = $4,
+                address_id = $5,
+                start_date = $6,
+                end_date = $7,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            user_id,
+            year,
+            values.state_code,
+            values.address_id,
+            values.start_date,
+            values.end_date
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Residency period".into()))
+    }
+
+    /// Soft deletes a period. Returns the number of affected rows.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE residency_periods SET deleted_at = NOW() WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
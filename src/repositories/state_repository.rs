@@ -0,0 +1,28 @@
+use sqlx::{Executor, Postgres};
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct StateRepository;
+
+impl StateRepository {
+    /// Postal codes of a country's states, looked up by ISO2 or ISO3 code.
+    pub async fn find_codes_by_country<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        country_code: &str,
+    ) -> Result<Vec<String>, ErrorBag> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT s.code
+            FROM states s
+            JOIN countries c ON s.country_id = c.id
+            WHERE c.iso2_code = $1 OR c.iso3_code = $1
+            ORDER BY s.code
+            "#,
+            country_code
+        )
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.code)
+        .collect())
+    }
+}
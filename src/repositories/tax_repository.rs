@@ -1,6 +1,6 @@
 use sqlx::{Executor, Postgres};
 use uuid::Uuid;
-use crate::models::tax_model::{TaxDocumentModel, TaxDataModel};
+use crate::models::tax_model::{TaxDocumentModel, TaxDataModel, TaxYearSummary};
 use crate::utilities::error_bag::ErrorBag;
 
 pub struct TaxRepository;
@@ -80,7 +80,7 @@ impl TaxRepository {
             INSERT INTO tax_data (user_id, year, data)
             VALUES ($1, $2, $3)
             ON CONFLICT (user_id, year)
-            DO UPDATE SET data = EXCLUDED.data, updated_at = NOW()
+            DO UPDATE SET data = EXCLUDED.data, revision = tax_data.revision + 1, updated_at = NOW(), deleted_at = NULL
             RETURNING *
             "#,
             user_id,
@@ -103,4 +103,84 @@ impl TaxRepository {
         .fetch_one(db)
         .await?)
     }
+
+    pub async fn find_documents_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<TaxDocumentModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            TaxDocumentModel,
+            "SELECT * FROM tax_documents WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY created_at DESC",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Lists every year the user has data or documents for, newest first.
+    pub async fn find_year_summaries_by_user<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+    ) -> Result<Vec<TaxYearSummary>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            TaxYearSummary,
+            r#"
+            WITH years AS (
+                SELECT year FROM tax_data WHERE user_id = $1 AND deleted_at IS NULL
+                UNION
+                SELECT year FROM tax_documents WHERE user_id = $1 AND deleted_at IS NULL
+            ),
+            documents AS (
+                SELECT year, COUNT(*) AS document_count, MAX(updated_at) AS updated_at
+                FROM tax_documents
+                WHERE user_id = $1 AND deleted_at IS NULL
+                GROUP BY year
+            )
+            SELECT
+                y.year AS "year!",
+                COALESCE(d.document_count, 0) AS "document_count!",
+                GREATEST(t.updated_at, d.updated_at) AS "last_updated!",
+                CASE
+                    WHEN t.id IS NULL THEN 'no_data'
+                    WHEN jsonb_path_exists(t.data, '$.documents[*].records[*].error') THEN 'needs_review'
+                    ELSE 'ready'
+                END AS "status!"
+            FROM years y
+            LEFT JOIN tax_data t ON t.user_id = $1 AND t.year = y.year AND t.deleted_at IS NULL
+            LEFT JOIN documents d ON d.year = y.year
+            ORDER BY y.year DESC
+            "#,
+            user_id
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Soft deletes a year's `tax_data` row. Returns the number of affected rows.
+    pub async fn delete_data_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE tax_data SET deleted_at = NOW() WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL",
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+
+    /// Soft deletes every document uploaded for a year. Returns the number of affected rows.
+    pub async fn delete_documents_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE tax_documents SET deleted_at = NOW() WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL",
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
 }
@@ -0,0 +1,117 @@
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+use crate::models::tax_model::TaxDataModel;
+use crate::models::tax_scenario_model::TaxScenarioModel;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Fields written by `TaxScenarioRepository::create` and `update`, already normalized.
+pub struct TaxScenarioValues<'a> {
+    pub name: &'a str,
+    pub filing_status: Option<&'a str>,
+    pub data: &'a serde_json::Value,
+}
+
+pub struct TaxScenarioRepository;
+
+impl TaxScenarioRepository {
+    pub async fn find_all_by_user_and_year<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+    ) -> Result<Vec<TaxScenarioModel>, ErrorBag> {
+        Ok(sqlx::query_as!(
+            TaxScenarioModel,
+            "SELECT * FROM tax_scenarios WHERE user_id = $1 AND year = $2 AND deleted_at IS NULL ORDER BY created_at",
+            user_id,
+            year
+        )
+        .fetch_all(db)
+        .await?)
+    }
+
+    /// Finds a scenario owned by the user; other users' scenarios are reported as not found.
+    pub async fn find_by_id<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<TaxScenarioModel, ErrorBag> {
+        sqlx::query_as!(
+            TaxScenarioModel,
+            "SELECT * FROM tax_scenarios WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Tax scenario".into()))
+    }
+
+    /// Creates a scenario forked from `base` at its current revision.
+    pub async fn create<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        base: &TaxDataModel,
+        values: &TaxScenarioValues<'_>,
+    ) -> Result<TaxScenarioModel, ErrorBag> {
+        Ok(sqlx::query_as!(
+            TaxScenarioModel,
+            r#"
+            INSERT INTO tax_scenarios (user_id, year, tax_data_id, base_revision, name, filing_status, data)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+            base.user_id,
+            base.year,
+            base.id,
+            base.revision,
+            values.name,
+            values.filing_status,
+            values.data
+        )
+        .fetch_one(db)
+        .await?)
+    }
+
+    /// Saves the scenario's name, status and data. The base it was forked from never changes.
+    pub async fn update<'e, E: Executor<'e, Database = Postgres>>(
+        db: E,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+        values: &TaxScenarioValues<'_>,
+    ) -> Result<TaxScenarioModel, ErrorBag> {
+        sqlx::query_as!(
+            TaxScenarioModel,
+            r#"
+            UPDATE tax_scenarios
+            SET
+                name = $4,
+                filing_status = $5,
+                data = $6,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+            id,
+            user_id,
+            year,
+            values.name,
+            values.filing_status,
+            values.data
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ErrorBag::NotFound("Tax scenario".into()))
+    }
+
+    /// Soft deletes a scenario. Returns the number of affected rows.
+    pub async fn delete<'e, E: Executor<'e, Database = Postgres>>(db: E, user_id: &Uuid, year: i32, id: &Uuid) -> Result<u64, ErrorBag> {
+        Ok(sqlx::query!(
+            "UPDATE tax_scenarios SET deleted_at = NOW() WHERE id = $1 AND user_id = $2 AND year = $3 AND deleted_at IS NULL",
+            id,
+            user_id,
+            year
+        ).execute(db).await?.rows_affected())
+    }
+}
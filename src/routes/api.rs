@@ -1,6 +1,6 @@
 use actix_web::web;
 use actix_web::web::{ServiceConfig};
-use crate::controllers::api::{api_auth_controller, default_controller, user_controller, tax_controller};
+use crate::controllers::api::{api_auth_controller, default_controller, user_controller, tax_controller, tax_year_controller, filing_profile_controller, dependent_controller, business_controller, residency_controller, tax_scenario_controller, estimated_tax_controller, carryforward_controller, anomaly_controller, penalty_controller};
 use crate::middlewares::auth_middleware::AuthMiddleware;
 
 fn authenticated(cfg: &mut ServiceConfig) {
@@ -16,6 +16,63 @@ fn authenticated(cfg: &mut ServiceConfig) {
                 .route("/data", get!(tax_controller::get_tax_data))
                 .route("/ai-helper", post!(tax_controller::ai_helper))
                 .route("/download/{id}", get!(tax_controller::download_document))
+                .route("/anomalies", get!(anomaly_controller::index))
+                .route("/anomalies/rules", get!(anomaly_controller::rules))
+                .route("/anomalies/rules/{rule}", put!(anomaly_controller::update_rule))
+                .route("/anomalies/rules/{rule}", delete!(anomaly_controller::reset_rule))
+                .route("/years", get!(tax_year_controller::index))
+                .route("/years/supported", get!(tax_year_controller::supported))
+                .route("/years/{year}", get!(tax_year_controller::show))
+                .route("/years/{year}", delete!(tax_year_controller::delete))
+                .route("/years/{year}/documents", get!(tax_year_controller::documents))
+                .route("/years/{year}/documents/checklist", get!(tax_year_controller::document_checklist))
+                .route("/years/{year}/calculation", get!(tax_year_controller::calculation))
+                .route("/years/{year}/calculation/trace", get!(tax_year_controller::trace))
+                .route("/years/{year}/summary", get!(tax_year_controller::summary))
+                .route("/years/{year}/payroll", get!(tax_year_controller::payroll))
+                .route("/years/{year}/itemized", get!(tax_year_controller::itemized))
+                .route("/years/{year}/states", get!(tax_year_controller::states))
+                .route("/years/{year}/filing-status", get!(tax_year_controller::filing_status))
+                .route("/years/{year}/analytics", get!(tax_year_controller::analytics))
+                .route("/profiles", get!(filing_profile_controller::index))
+                .route("/years/{year}/profile", get!(filing_profile_controller::show))
+                .route("/years/{year}/profile", put!(filing_profile_controller::update))
+                .route("/years/{year}/profile", delete!(filing_profile_controller::delete))
+                .route("/years/{year}/dependents", get!(dependent_controller::index))
+                .route("/years/{year}/dependents", post!(dependent_controller::create))
+                .route("/years/{year}/dependents/{id}", get!(dependent_controller::show))
+                .route("/years/{year}/dependents/{id}", put!(dependent_controller::update))
+                .route("/years/{year}/dependents/{id}", delete!(dependent_controller::delete))
+                .route("/years/{year}/residency", get!(residency_controller::index))
+                .route("/years/{year}/residency", post!(residency_controller::create))
+                .route("/years/{year}/residency/{id}", get!(residency_controller::show))
+                .route("/years/{year}/residency/{id}", put!(residency_controller::update))
+                .route("/years/{year}/residency/{id}", delete!(residency_controller::delete))
+                .route("/years/{year}/scenarios", get!(tax_scenario_controller::index))
+                .route("/years/{year}/scenarios", post!(tax_scenario_controller::create))
+                .route("/years/{year}/scenarios/{id}", get!(tax_scenario_controller::show))
+                .route("/years/{year}/scenarios/{id}", put!(tax_scenario_controller::update))
+                .route("/years/{year}/scenarios/{id}", delete!(tax_scenario_controller::delete))
+                .route("/years/{year}/scenarios/{id}/comparison", get!(tax_scenario_controller::comparison))
+                .route("/years/{year}/estimated-tax", get!(estimated_tax_controller::plan))
+                .route("/years/{year}/estimated-tax/vouchers", get!(estimated_tax_controller::vouchers))
+                .route("/years/{year}/estimated-payments", get!(estimated_tax_controller::payments))
+                .route("/years/{year}/estimated-payments", post!(estimated_tax_controller::create_payment))
+                .route("/years/{year}/estimated-payments/{id}", get!(estimated_tax_controller::show_payment))
+                .route("/years/{year}/estimated-payments/{id}", put!(estimated_tax_controller::update_payment))
+                .route("/years/{year}/estimated-payments/{id}", delete!(estimated_tax_controller::delete_payment))
+                .route("/years/{year}/penalties", get!(penalty_controller::show))
+                .route("/years/{year}/carryforwards", get!(carryforward_controller::index))
+                .route("/years/{year}/carryforwards/roll", post!(carryforward_controller::roll_forward))
+                .route("/years/{year}/anomalies/check", post!(anomaly_controller::check))
+                .route("/years/{year}/businesses", get!(business_controller::index))
+                .route("/years/{year}/businesses", post!(business_controller::create))
+                .route("/years/{year}/businesses/{id}", get!(business_controller::show))
+                .route("/years/{year}/businesses/{id}", put!(business_controller::update))
+                .route("/years/{year}/businesses/{id}", delete!(business_controller::delete))
+                .route("/years/{year}/businesses/{id}/expenses", post!(business_controller::create_expense))
+                .route("/years/{year}/businesses/{id}/expenses/{expense_id}", put!(business_controller::update_expense))
+                .route("/years/{year}/businesses/{id}/expenses/{expense_id}", delete!(business_controller::delete_expense))
         )
         .service(
             web::scope("/users")
@@ -0,0 +1,116 @@
+use rust_decimal::Decimal;
+use sqlx::{Acquire, PgConnection, Postgres};
+use uuid::Uuid;
+use crate::models::anomaly_model::{AnomalyFindingModel, AnomalyRule, AnomalySeverity, SaveAnomalyRuleSettingSchema};
+use crate::repositories::anomaly_repository::AnomalyRepository;
+use crate::repositories::tax_repository::TaxRepository;
+use crate::tax_engine::anomalies::{AnomalyDetector, RuleSetting};
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::rules::TaxRules;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct AnomalyService;
+
+impl AnomalyService {
+    /// Every rule with the user's overrides applied.
+    pub async fn settings<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid) -> Result<Vec<RuleSetting>, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        let overrides = AnomalyRepository::find_settings_by_user(&mut *conn, user_id).await?;
+
+        Ok(AnomalyRule::ALL.into_iter()
+            .map(|rule| {
+                let mut setting = RuleSetting::default_for(rule);
+                if let Some(row) = overrides.iter().find(|row| row.rule() == Ok(rule)) {
+                    setting.enabled = row.enabled;
+                    setting.threshold = row.threshold.or(setting.default_threshold);
+                }
+                setting
+            })
+            .collect())
+    }
+
+    pub async fn update_setting<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        rule: AnomalyRule,
+        body: &SaveAnomalyRuleSettingSchema,
+    ) -> Result<RuleSetting, ErrorBag> {
+        if let Some(threshold) = body.threshold {
+            if rule.default_threshold().is_none() {
+                return Err(ErrorBag::Validation { field: "threshold".into(), message: format!("The {rule} rule has no threshold") });
+            }
+            if threshold <= Decimal::ZERO {
+                return Err(ErrorBag::Validation { field: "threshold".into(), message: "Threshold must be positive".into() });
+            }
+        }
+
+        let mut conn = db.acquire().await?;
+        let row = AnomalyRepository::upsert_setting(&mut *conn, user_id, rule.as_str(), body.enabled, body.threshold).await?;
+
+        let mut setting = RuleSetting::default_for(rule);
+        setting.enabled = row.enabled;
+        setting.threshold = row.threshold.or(setting.default_threshold);
+        Ok(setting)
+    }
+
+    /// Drops the user's override; the rule runs with its defaults again.
+    pub async fn reset_setting<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, rule: AnomalyRule) -> Result<RuleSetting, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        AnomalyRepository::delete_setting(&mut *conn, user_id, rule.as_str()).await?;
+
+        Ok(RuleSetting::default_for(rule))
+    }
+
+    /// Checks `year` and replaces its findings. The following year compares its wages with
+    /// this one, so it is checked again too when it has tax data.
+    pub async fn check<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<Vec<AnomalyFindingModel>, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        let settings = Self::settings(&mut *tx, user_id).await?;
+        let findings = Self::check_year(&mut tx, user_id, year, &settings).await?
+            .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+        Self::check_year(&mut tx, user_id, year + 1, &settings).await?;
+        tx.commit().await?;
+
+        Ok(findings)
+    }
+
+    /// Checks one year; `None` when the year has no tax data.
+    async fn check_year(conn: &mut PgConnection, user_id: &Uuid, year: i32, settings: &[RuleSetting]) -> Result<Option<Vec<AnomalyFindingModel>>, ErrorBag> {
+        let Some(data) = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await? else {
+            return Ok(None);
+        };
+        let input = TaxInput::from_tax_data(year, &data.data, None)?;
+        let prior = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year - 1).await?
+            .and_then(|prior| TaxInput::from_tax_data(prior.year, &prior.data, None).ok());
+        let rules = TaxRules::for_year(year).ok();
+
+        AnomalyRepository::delete_findings_by_user_and_year(&mut *conn, user_id, year).await?;
+        let mut findings = Vec::new();
+        for finding in AnomalyDetector::detect(&input, prior.as_ref(), rules, settings) {
+            findings.push(AnomalyRepository::create_finding(&mut *conn, user_id, year, data.revision, &finding).await?);
+        }
+
+        Ok(Some(findings))
+    }
+
+    /// Stored findings, optionally narrowed to a year, a rule and a severity.
+    pub async fn findings<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: Option<i32>,
+        rule: Option<AnomalyRule>,
+        severity: Option<AnomalySeverity>,
+    ) -> Result<Vec<AnomalyFindingModel>, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        AnomalyRepository::find_findings_by_user(
+            &mut *conn,
+            user_id,
+            year,
+            rule.as_ref().map(AnomalyRule::as_str),
+            severity.as_ref().map(AnomalySeverity::as_str),
+        ).await
+    }
+}
@@ -0,0 +1,93 @@
+use rust_decimal::Decimal;
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::models::business_model::{BusinessExpenseModel, BusinessModel, SaveBusinessExpenseSchema, SaveBusinessSchema};
+use crate::repositories::business_repository::{BusinessExpenseValues, BusinessRepository, BusinessValues};
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct BusinessService;
+
+impl BusinessService {
+    /// Creates a business, or updates `id` when given.
+    pub async fn save<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: Option<&Uuid>,
+        body: &SaveBusinessSchema,
+    ) -> Result<BusinessModel, ErrorBag> {
+        let amounts = [
+            ("other_receipts", body.other_receipts),
+            ("returns_and_allowances", body.returns_and_allowances),
+            ("cost_of_goods_sold", body.cost_of_goods_sold),
+            ("qualified_property", body.qualified_property),
+        ];
+        if let Some((field, _)) = amounts.iter().find(|(_, amount)| *amount < Decimal::ZERO) {
+            return Err(ErrorBag::Validation { field: (*field).into(), message: "Amount cannot be negative".into() });
+        }
+        if body.principal_business_code.as_deref().is_some_and(|code| !code.chars().all(|c| c.is_ascii_digit())) {
+            return Err(ErrorBag::Validation { field: "principal_business_code".into(), message: "Principal business code must be 6 digits".into() });
+        }
+
+        let values = BusinessValues {
+            name: body.name.trim(),
+            owner: body.owner.as_str(),
+            principal_business: body.principal_business.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+            principal_business_code: body.principal_business_code.as_deref(),
+            accounting_method: body.accounting_method.as_str(),
+            other_receipts: body.other_receipts,
+            returns_and_allowances: body.returns_and_allowances,
+            cost_of_goods_sold: body.cost_of_goods_sold,
+            other_income: body.other_income,
+            specified_service: body.specified_service,
+            qualified_property: body.qualified_property,
+        };
+
+        let mut conn = db.acquire().await?;
+        match id {
+            Some(id) => BusinessRepository::update(&mut *conn, user_id, year, id, &values).await,
+            None => BusinessRepository::create(&mut *conn, user_id, year, &values).await,
+        }
+    }
+
+    /// Adds an expense to one of the user's businesses, or updates `expense_id` when given.
+    pub async fn save_expense<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        business_id: &Uuid,
+        expense_id: Option<&Uuid>,
+        body: &SaveBusinessExpenseSchema,
+    ) -> Result<BusinessExpenseModel, ErrorBag> {
+        if body.amount < Decimal::ZERO {
+            return Err(ErrorBag::Validation { field: "amount".into(), message: "Amount cannot be negative".into() });
+        }
+
+        let values = BusinessExpenseValues {
+            category: body.category.as_str(),
+            description: body.description.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+            amount: body.amount,
+            incurred_on: body.incurred_on,
+        };
+
+        let mut conn = db.acquire().await?;
+        let business = BusinessRepository::find_by_id(&mut *conn, user_id, year, business_id).await?;
+        match expense_id {
+            Some(id) => BusinessRepository::update_expense(&mut *conn, &business.id, id, &values).await,
+            None => BusinessRepository::create_expense(&mut *conn, &business.id, &values).await,
+        }
+    }
+
+    pub async fn delete_expense<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        business_id: &Uuid,
+        expense_id: &Uuid,
+    ) -> Result<u64, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        let business = BusinessRepository::find_by_id(&mut *conn, user_id, year, business_id).await?;
+
+        BusinessRepository::delete_expense(&mut *conn, &business.id, expense_id).await
+    }
+}
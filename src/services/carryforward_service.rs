@@ -0,0 +1,107 @@
+use rust_decimal::Decimal;
+use sqlx::{Acquire, PgConnection, Postgres};
+use uuid::Uuid;
+use crate::models::carryforward_model::{CarryforwardKind, CarryforwardLedger, CarryforwardModel};
+use crate::repositories::carryforward_repository::CarryforwardRepository;
+use crate::repositories::tax_repository::TaxRepository;
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::tax_engine::carryforward::Carryforwards;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::TaxEngine;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct CarryforwardService;
+
+impl CarryforwardService {
+    /// Computes `year` and replaces what it carries into the next year. Because the next
+    /// year's return uses those amounts, every following year with tax data is rolled in turn.
+    /// Returns the amounts `year` carries forward.
+    pub async fn roll_forward<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<Vec<CarryforwardModel>, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        let carried = Self::roll_year(&mut tx, user_id, year).await?
+            .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+        let mut next = year + 1;
+        while TaxRules::for_year(next).is_ok() && Self::roll_year(&mut tx, user_id, next).await?.is_some() {
+            next += 1;
+        }
+        tx.commit().await?;
+
+        Ok(carried)
+    }
+
+    /// Rolls one year forward; `None` when the year has no tax data.
+    async fn roll_year(conn: &mut PgConnection, user_id: &Uuid, year: i32) -> Result<Option<Vec<CarryforwardModel>>, ErrorBag> {
+        let Some(data) = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await? else {
+            return Ok(None);
+        };
+        let input = TaxCalculationService::load_input(conn, user_id, year, &data.data).await?;
+        let calculation = TaxEngine::calculate(&input)?;
+
+        CarryforwardRepository::delete_by_user_and_source_year(&mut *conn, user_id, year).await?;
+        let mut carried = Vec::new();
+        for amount in Carryforwards::from_calculation(&input, &calculation) {
+            carried.push(CarryforwardRepository::create(&mut *conn, user_id, year, data.revision, &amount).await?);
+        }
+
+        Ok(Some(carried))
+    }
+
+    /// What the prior year carries into `year` and what `year` carries on. Either side is stale
+    /// when its source year's tax data has been saved since it was rolled forward.
+    pub async fn ledger<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<CarryforwardLedger, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let carried_in = CarryforwardRepository::find_all_by_user_and_target_year(&mut *conn, user_id, year).await?;
+        let carried_out = CarryforwardRepository::find_all_by_user_and_source_year(&mut *conn, user_id, year).await?;
+        let prior_revision = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year - 1).await?.map(|data| data.revision);
+        let revision = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?.map(|data| data.revision);
+
+        Ok(CarryforwardLedger {
+            year,
+            carried_in_stale: Carryforwards::stale(&carried_in, prior_revision),
+            carried_out_stale: Carryforwards::stale(&carried_out, revision),
+            carried_in,
+            carried_out,
+        })
+    }
+
+    /// Uses the amounts carried into the input's year in place of the ones entered in its tax
+    /// data. Entered amounts of a kind the prior year did not carry are kept.
+    pub(crate) fn apply(input: &mut TaxInput, carryforwards: &[CarryforwardModel]) -> Result<(), ErrorBag> {
+        let carryforwards = carryforwards.iter()
+            .map(|row| Ok((row.kind().map_err(ErrorBag::InternalServerError)?, row)))
+            .collect::<Result<Vec<_>, ErrorBag>>()?;
+
+        for kind in CarryforwardKind::ALL {
+            let rows: Vec<&CarryforwardModel> = carryforwards.iter().filter(|(k, _)| *k == kind).map(|(_, row)| *row).collect();
+            let Some(source_year) = rows.first().map(|row| row.source_year) else {
+                continue;
+            };
+            let carried = rows.iter()
+                .map(|row| match kind {
+                    CarryforwardKind::StateRefund => row.taxable_amount.unwrap_or_default(),
+                    _ => row.amount,
+                })
+                .sum::<Decimal>();
+            let (entered, label) = match kind {
+                CarryforwardKind::CapitalLossShortTerm => (&mut input.capital_loss_carryover.short_term, "Short-term capital loss carryover"),
+                CarryforwardKind::CapitalLossLongTerm => (&mut input.capital_loss_carryover.long_term, "Long-term capital loss carryover"),
+                CarryforwardKind::CharitableContributions => (&mut input.itemized.charitable_carryover, "Charitable contribution carryover"),
+                CarryforwardKind::NetOperatingLoss => (&mut input.net_operating_loss_carryover, "Net operating loss carryover"),
+                CarryforwardKind::MinimumTaxCredit => (&mut input.minimum_tax_credit_carryover, "Minimum tax credit carryover"),
+                CarryforwardKind::QualifiedBusinessLoss => (&mut input.qualified_business_loss_carryover, "Qualified business loss carryover"),
+                CarryforwardKind::StateRefund => (&mut input.taxable_state_refund, "Taxable state refund"),
+                CarryforwardKind::PriorYearAgi => continue,
+            };
+            if !entered.is_zero() && *entered != carried {
+                input.warnings.push(format!("{label} of ${entered} in the tax data is replaced by the ${carried} carried from {source_year}"));
+            }
+            *entered = carried;
+        }
+
+        Ok(())
+    }
+}
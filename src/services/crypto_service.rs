@@ -1,5 +1,25 @@
+use std::sync::LazyLock;
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Error as CipherError};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use argon2::password_hash::{rand_core, SaltString, Error as PasswordHashError};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use crate::config::ENV;
+
+/// AES-256 key derived once from APP_SECRET. Rotating APP_SECRET makes stored ciphertexts unreadable.
+static ENCRYPTION_KEY: LazyLock<[u8; 32]> = LazyLock::new(|| {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(ENV.app_secret.as_bytes(), b"tax-file.field-encryption", &mut key)
+        .unwrap_or_else(|e| {
+            eprintln!("ERROR: Could not derive encryption key from APP_SECRET: {:?}", e);
+            std::process::exit(1);
+        });
+    key
+});
+
+const NONCE_LENGTH: usize = 12;
 
 pub struct CryptoService<'a> {
     argon2: Argon2<'a>,
@@ -26,4 +46,31 @@ impl<'a> CryptoService<'a> {
 
         self.argon2.verify_password(new_password.as_bytes(), &parsed_hash).is_ok()
     }
+
+    /// Encrypts a sensitive field (e.g. an SSN) for storage.
+    ///
+    /// Output is base64(nonce || ciphertext); a fresh nonce is generated per call.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*ENCRYPTION_KEY));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes())?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(payload))
+    }
+
+    /// Reverses `encrypt`.
+    pub fn decrypt(&self, encoded: &str) -> Result<String, CipherError> {
+        let payload = BASE64.decode(encoded).map_err(|_| CipherError)?;
+        if payload.len() <= NONCE_LENGTH {
+            return Err(CipherError);
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*ENCRYPTION_KEY));
+        let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext)?;
+
+        String::from_utf8(plaintext).map_err(|_| CipherError)
+    }
 }
\ No newline at end of file
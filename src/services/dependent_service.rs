@@ -0,0 +1,503 @@
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::models::dependent_model::{DependentModel, Relationship, SaveDependentSchema};
+use crate::models::filing_profile_model::{FilingProfileModel, FilingStatus};
+use crate::repositories::dependent_repository::{DependentRepository, DependentValues};
+use crate::services::crypto_service::CryptoService;
+use crate::services::filing_profile_service::FilingProfileService;
+use crate::tax_engine::rules::TaxRules;
+use crate::utilities::error_bag::ErrorBag;
+
+/// Outcome of a single IRS test, e.g. the age test for a qualifying child.
+#[derive(Debug, Clone, Serialize)]
+pub struct EligibilityTest {
+    pub test: &'static str,
+    pub passed: bool,
+    pub reason: String,
+}
+
+/// Qualifying child or qualifying relative outcome with every test that was applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyTestResult {
+    pub qualifies: bool,
+    pub tests: Vec<EligibilityTest>,
+}
+
+impl DependencyTestResult {
+    fn from_tests(tests: Vec<EligibilityTest>) -> Self {
+        Self { qualifies: tests.iter().all(|t| t.passed), tests }
+    }
+
+    fn failures(&self) -> String {
+        self.tests.iter().filter(|t| !t.passed).map(|t| t.reason.as_str()).collect::<Vec<_>>().join("; ")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreditEligibility {
+    pub credit: &'static str,
+    pub eligible: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependentEligibility {
+    pub dependent_id: Uuid,
+    pub is_dependent: bool,
+    pub qualifying_child: DependencyTestResult,
+    pub qualifying_relative: DependencyTestResult,
+    pub credits: Vec<CreditEligibility>,
+}
+
+pub struct DependentService;
+
+impl DependentService {
+    /// Creates a dependent, or updates `id` when given. SSNs are encrypted like filing profile SSNs.
+    pub async fn save<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: Option<&Uuid>,
+        body: &SaveDependentSchema,
+    ) -> Result<DependentModel, ErrorBag> {
+        if body.support_percentage < Decimal::ZERO || body.support_percentage > Decimal::ONE_HUNDRED {
+            return Err(ErrorBag::Validation { field: "support_percentage".into(), message: "Support percentage must be between 0 to 100".into() });
+        }
+        if body.gross_income < Decimal::ZERO {
+            return Err(ErrorBag::Validation { field: "gross_income".into(), message: "Gross income cannot be negative".into() });
+        }
+        if body.dob.year() > year {
+            return Err(ErrorBag::Validation { field: "dob".into(), message: format!("Dependent must be born by the end of {year}") });
+        }
+
+        let crypto = CryptoService::new();
+        let (ssn_encrypted, ssn_last4) = FilingProfileService::protect_ssn(&crypto, body.ssn.as_deref())?;
+        let values = DependentValues {
+            first_name: body.first_name.trim(),
+            last_name: body.last_name.trim(),
+            relationship: body.relationship.as_str(),
+            dob: body.dob,
+            ssn_encrypted,
+            ssn_last4,
+            months_lived_with_taxpayer: body.months_lived_with_taxpayer,
+            is_student: body.is_student,
+            is_permanently_disabled: body.is_permanently_disabled,
+            support_percentage: body.support_percentage,
+            provided_over_half_own_support: body.provided_over_half_own_support,
+            files_joint_return: body.files_joint_return,
+            gross_income: body.gross_income,
+        };
+
+        let mut conn = db.acquire().await?;
+        match id {
+            Some(id) => DependentRepository::update(&mut *conn, user_id, year, id, &values).await,
+            None => DependentRepository::create(&mut *conn, user_id, year, &values).await,
+        }
+    }
+
+    /// Runs the qualifying child (IRC §152(c)) and qualifying relative (§152(d)) tests and derives
+    /// which dependent-based credits the dependent can support.
+    ///
+    /// `profile` is optional; without it the "younger than taxpayer" test cannot be verified and is assumed to pass.
+    pub fn evaluate(dependent: &DependentModel, profile: Option<&FilingProfileModel>) -> Result<DependentEligibility, ErrorBag> {
+        let relationship = dependent.relationship().map_err(ErrorBag::InternalServerError)?;
+        let age = dependent.age_at_year_end();
+        let has_ssn = dependent.ssn_last4.is_some();
+        let lived_over_half_year = dependent.months_lived_with_taxpayer > 6 || dependent.dob.year() == dependent.year;
+
+        let relationship_test = EligibilityTest {
+            test: "relationship",
+            passed: relationship.is_qualifying_child_relation(),
+            reason: format!("{} {} a qualifying child relationship", relationship, if relationship.is_qualifying_child_relation() { "is" } else { "is not" }),
+        };
+        let age_test = Self::age_test(dependent, age, profile);
+        let residency_test = EligibilityTest {
+            test: "residency",
+            passed: lived_over_half_year,
+            reason: format!("lived with taxpayer {} of 12 months (more than half the year required)", dependent.months_lived_with_taxpayer),
+        };
+        let joint_return_test = EligibilityTest {
+            test: "joint_return",
+            passed: !dependent.files_joint_return,
+            reason: if dependent.files_joint_return { "files a joint return".into() } else { "does not file a joint return".into() },
+        };
+
+        let mut child_tests = vec![relationship_test.clone(), age_test.clone(), residency_test.clone()];
+        child_tests.push(EligibilityTest {
+            test: "support",
+            passed: !dependent.provided_over_half_own_support,
+            reason: if dependent.provided_over_half_own_support { "provided over half of own support".into() } else { "did not provide over half of own support".into() },
+        });
+        child_tests.push(joint_return_test.clone());
+        let qualifying_child = DependencyTestResult::from_tests(child_tests);
+
+        // Gross income must be below the exemption amount for a qualifying relative
+        let income_limit = TaxRules::for_year(dependent.year).ok().map(|rules| rules.dependent_gross_income_limit);
+        let mut relative_tests = vec![EligibilityTest {
+            test: "not_a_qualifying_child",
+            passed: !qualifying_child.qualifies,
+            reason: if qualifying_child.qualifies { "is already a qualifying child".into() } else { "is not a qualifying child".into() },
+        }];
+        relative_tests.push(EligibilityTest {
+            test: "relationship_or_household",
+            passed: relationship.is_qualifying_relative_relation() || dependent.months_lived_with_taxpayer == 12,
+            reason: match relationship.is_qualifying_relative_relation() {
+                true => format!("{} is a qualifying relative relationship", relationship),
+                false => format!("unrelated dependents must live with taxpayer all year, lived {} months", dependent.months_lived_with_taxpayer),
+            },
+        });
+        relative_tests.push(match income_limit {
+            Some(limit) => EligibilityTest {
+                test: "gross_income",
+                passed: dependent.gross_income < limit,
+                reason: format!("gross income ${} must be less than ${} for {}", dependent.gross_income.round_dp(2), limit, dependent.year),
+            },
+            None => EligibilityTest {
+                test: "gross_income",
+                passed: false,
+                reason: format!("no gross income limit known for {}", dependent.year),
+            },
+        });
+        relative_tests.push(EligibilityTest {
+            test: "support",
+            passed: dependent.support_percentage > Decimal::from(50),
+            reason: format!("taxpayer provided {}% of support (more than 50% required)", dependent.support_percentage.normalize()),
+        });
+        relative_tests.push(joint_return_test.clone());
+        let qualifying_relative = DependencyTestResult::from_tests(relative_tests);
+
+        let is_dependent = qualifying_child.qualifies || qualifying_relative.qualifies;
+        let not_a_dependent = || format!("not a dependent: {}", qualifying_child.failures());
+
+        let mut credits = Vec::new();
+
+        credits.push(match (qualifying_child.qualifies, age < 17, has_ssn) {
+            (true, true, true) => CreditEligibility { credit: "child_tax_credit", eligible: true, reason: format!("qualifying child under 17 (age {age}) with an SSN") },
+            (true, false, _) => CreditEligibility { credit: "child_tax_credit", eligible: false, reason: format!("age {age} at year end, must be under 17") },
+            (true, true, false) => CreditEligibility { credit: "child_tax_credit", eligible: false, reason: "an SSN valid for employment is required".into() },
+            (false, _, _) => CreditEligibility { credit: "child_tax_credit", eligible: false, reason: not_a_dependent() },
+        });
+
+        let ctc_eligible = credits[0].eligible;
+        credits.push(CreditEligibility {
+            credit: "credit_for_other_dependents",
+            eligible: is_dependent && !ctc_eligible,
+            reason: match (is_dependent, ctc_eligible) {
+                (true, false) => "dependent who does not qualify for the child tax credit".into(),
+                (true, true) => "already claimed for the child tax credit".into(),
+                (false, _) => format!("not a dependent: {}", qualifying_relative.failures()),
+            },
+        });
+
+        // EITC uses the qualifying child relationship, age, residency and joint return tests but no support test.
+        let eitc_tests = [&relationship_test, &age_test, &residency_test, &joint_return_test];
+        let eitc_failures: Vec<&str> = eitc_tests.iter().filter(|t| !t.passed).map(|t| t.reason.as_str()).collect();
+        credits.push(CreditEligibility {
+            credit: "earned_income_credit",
+            eligible: eitc_failures.is_empty() && has_ssn,
+            reason: match (eitc_failures.is_empty(), has_ssn) {
+                (true, true) => "qualifying child for the earned income credit".into(),
+                (true, false) => "an SSN valid for employment is required".into(),
+                (false, _) => eitc_failures.join("; "),
+            },
+        });
+
+        let under_13 = age < 13;
+        let incapable_of_self_care = dependent.is_permanently_disabled && lived_over_half_year;
+        credits.push(CreditEligibility {
+            credit: "child_and_dependent_care_credit",
+            eligible: (qualifying_child.qualifies && under_13) || (is_dependent && incapable_of_self_care),
+            reason: match (qualifying_child.qualifies && under_13, is_dependent && incapable_of_self_care) {
+                (true, _) => format!("qualifying child under 13 (age {age})"),
+                (false, true) => "dependent incapable of self-care who lived with taxpayer over half the year".into(),
+                (false, false) => "must be a qualifying child under 13 or a disabled dependent living with taxpayer".into(),
+            },
+        });
+
+        // A parent can make the taxpayer head of household without living with them; other relatives cannot.
+        let hoh_relative = qualifying_relative.qualifies
+            && relationship != Relationship::Other
+            && (relationship == Relationship::Parent || lived_over_half_year);
+        credits.push(CreditEligibility {
+            credit: "head_of_household_qualifying_person",
+            eligible: qualifying_child.qualifies || hoh_relative,
+            reason: match (qualifying_child.qualifies, hoh_relative) {
+                (true, _) => "qualifying child".into(),
+                (false, true) => "related qualifying relative".into(),
+                (false, false) => "must be a qualifying child or a related qualifying relative".into(),
+            },
+        });
+
+        Ok(DependentEligibility { dependent_id: dependent.id, is_dependent, qualifying_child, qualifying_relative, credits })
+    }
+
+    fn age_test(dependent: &DependentModel, age: i32, profile: Option<&FilingProfileModel>) -> EligibilityTest {
+        if dependent.is_permanently_disabled {
+            return EligibilityTest { test: "age", passed: true, reason: "permanently and totally disabled, any age".into() };
+        }
+
+        let age_ok = age < 19 || (age < 24 && dependent.is_student);
+        if !age_ok {
+            let reason = match dependent.is_student {
+                true => format!("age {age} at year end, full-time students must be under 24"),
+                false => format!("age {age} at year end, must be under 19 or a full-time student under 24"),
+            };
+            return EligibilityTest { test: "age", passed: false, reason };
+        }
+
+        // The child must also be younger than the taxpayer, or the spouse on a joint return.
+        let mut elders: Vec<NaiveDate> = Vec::new();
+        if let Some(profile) = profile {
+            elders.extend(profile.taxpayer_dob);
+            if profile.status() == Ok(FilingStatus::MarriedFilingJointly) {
+                elders.extend(profile.spouse_dob);
+            }
+        }
+        if !elders.is_empty() && !elders.iter().any(|dob| *dob < dependent.dob) {
+            return EligibilityTest { test: "age", passed: false, reason: "must be younger than the taxpayer (or spouse if filing jointly)".into() };
+        }
+
+        EligibilityTest { test: "age", passed: true, reason: format!("age {age} at year end") }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+    use super::{CreditEligibility, DependencyTestResult, DependentEligibility, DependentService, EligibilityTest};
+    use crate::models::dependent_model::{DependentModel, Relationship};
+    use crate::models::filing_profile_model::{FilingProfileModel, FilingStatus};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// A 2024 dependent who lived with the taxpayer all year, was fully supported and has an SSN.
+    fn dependent(relationship: Relationship, dob: NaiveDate) -> DependentModel {
+        DependentModel {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            year: 2024,
+            first_name: "Sam".into(),
+            last_name: "Doe".into(),
+            relationship: relationship.as_str().into(),
+            dob,
+            ssn_encrypted: None,
+            ssn_last4: Some("1234".into()),
+            months_lived_with_taxpayer: 12,
+            is_student: false,
+            is_permanently_disabled: false,
+            support_percentage: Decimal::ONE_HUNDRED,
+            provided_over_half_own_support: false,
+            files_joint_return: false,
+            gross_income: Decimal::ZERO,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn profile(status: FilingStatus, taxpayer_dob: NaiveDate, spouse_dob: Option<NaiveDate>) -> FilingProfileModel {
+        FilingProfileModel {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            year: 2024,
+            filing_status: status.as_str().into(),
+            taxpayer_first_name: "Alex".into(),
+            taxpayer_last_name: "Doe".into(),
+            taxpayer_dob: Some(taxpayer_dob),
+            taxpayer_ssn_encrypted: None,
+            taxpayer_ssn_last4: None,
+            taxpayer_is_blind: false,
+            taxpayer_is_65_or_older: false,
+            taxpayer_occupation: None,
+            spouse_first_name: None,
+            spouse_last_name: None,
+            spouse_dob,
+            spouse_ssn_encrypted: None,
+            spouse_ssn_last4: None,
+            spouse_is_blind: false,
+            spouse_is_65_or_older: false,
+            spouse_occupation: None,
+            address_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn evaluate(dependent: &DependentModel) -> DependentEligibility {
+        DependentService::evaluate(dependent, None).unwrap()
+    }
+
+    fn test<'a>(result: &'a DependencyTestResult, name: &str) -> &'a EligibilityTest {
+        result.tests.iter().find(|test| test.test == name).unwrap()
+    }
+
+    fn credit<'a>(eligibility: &'a DependentEligibility, name: &str) -> &'a CreditEligibility {
+        eligibility.credits.iter().find(|credit| credit.credit == name).unwrap()
+    }
+
+    fn eligible(eligibility: &DependentEligibility) -> Vec<&'static str> {
+        eligibility.credits.iter().filter(|credit| credit.eligible).map(|credit| credit.credit).collect()
+    }
+
+    #[test]
+    fn young_child_with_an_ssn_supports_every_child_credit() {
+        let eligibility = evaluate(&dependent(Relationship::Son, date(2015, 5, 1)));
+
+        assert!(eligibility.is_dependent);
+        assert!(eligibility.qualifying_child.qualifies);
+        assert!(!eligibility.qualifying_relative.qualifies);
+        assert_eq!(eligibility.credits[0].credit, "child_tax_credit");
+        assert_eq!(eligible(&eligibility), ["child_tax_credit", "earned_income_credit", "child_and_dependent_care_credit", "head_of_household_qualifying_person"]);
+        assert_eq!(credit(&eligibility, "credit_for_other_dependents").reason, "already claimed for the child tax credit");
+    }
+
+    #[test]
+    fn child_aged_17_gets_the_credit_for_other_dependents_instead() {
+        let eligibility = evaluate(&dependent(Relationship::Daughter, date(2007, 3, 1)));
+
+        assert!(eligibility.qualifying_child.qualifies);
+        assert!(!eligibility.credits[0].eligible);
+        assert_eq!(eligibility.credits[0].reason, "age 17 at year end, must be under 17");
+        assert!(credit(&eligibility, "credit_for_other_dependents").eligible);
+        assert!(credit(&eligibility, "earned_income_credit").eligible);
+        assert!(!credit(&eligibility, "child_and_dependent_care_credit").eligible);
+    }
+
+    #[test]
+    fn child_without_an_ssn_gets_the_credit_for_other_dependents_but_not_the_eic() {
+        let eligibility = evaluate(&DependentModel { ssn_last4: None, ..dependent(Relationship::Son, date(2016, 8, 20)) });
+
+        assert!(!eligibility.credits[0].eligible);
+        assert_eq!(eligibility.credits[0].reason, "an SSN valid for employment is required");
+        assert!(credit(&eligibility, "credit_for_other_dependents").eligible);
+        assert!(!credit(&eligibility, "earned_income_credit").eligible);
+        assert_eq!(credit(&eligibility, "earned_income_credit").reason, "an SSN valid for employment is required");
+    }
+
+    #[test]
+    fn age_test_allows_students_under_24_and_disabled_dependents_of_any_age() {
+        let nineteen = evaluate(&dependent(Relationship::Son, date(2005, 6, 1)));
+        assert!(!test(&nineteen.qualifying_child, "age").passed);
+        assert_eq!(test(&nineteen.qualifying_child, "age").reason, "age 19 at year end, must be under 19 or a full-time student under 24");
+
+        let student = evaluate(&DependentModel { is_student: true, ..dependent(Relationship::Son, date(2001, 6, 1)) });
+        assert!(test(&student.qualifying_child, "age").passed);
+        assert!(student.qualifying_child.qualifies);
+        assert!(!student.credits[0].eligible);
+        assert!(credit(&student, "credit_for_other_dependents").eligible);
+
+        let older_student = evaluate(&DependentModel { is_student: true, ..dependent(Relationship::Son, date(2000, 6, 1)) });
+        assert_eq!(test(&older_student.qualifying_child, "age").reason, "age 24 at year end, full-time students must be under 24");
+
+        let disabled = evaluate(&DependentModel { is_permanently_disabled: true, ..dependent(Relationship::Daughter, date(1994, 2, 1)) });
+        assert!(disabled.qualifying_child.qualifies);
+        assert!(credit(&disabled, "child_and_dependent_care_credit").eligible);
+        assert_eq!(credit(&disabled, "child_and_dependent_care_credit").reason, "dependent incapable of self-care who lived with taxpayer over half the year");
+    }
+
+    #[test]
+    fn qualifying_child_must_be_younger_than_the_taxpayer_or_joint_spouse() {
+        let sister = dependent(Relationship::Sister, date(2006, 1, 10));
+
+        let single = profile(FilingStatus::Single, date(2006, 5, 1), None);
+        let eligibility = DependentService::evaluate(&sister, Some(&single)).unwrap();
+        assert!(!test(&eligibility.qualifying_child, "age").passed);
+
+        let joint = profile(FilingStatus::MarriedFilingJointly, date(2006, 5, 1), Some(date(1990, 1, 1)));
+        assert!(DependentService::evaluate(&sister, Some(&joint)).unwrap().qualifying_child.qualifies);
+
+        let separate = profile(FilingStatus::MarriedFilingSeparately, date(2006, 5, 1), Some(date(1990, 1, 1)));
+        assert!(!DependentService::evaluate(&sister, Some(&separate)).unwrap().qualifying_child.qualifies);
+    }
+
+    #[test]
+    fn residency_needs_more_than_half_the_year_unless_born_in_it() {
+        let six_months = evaluate(&DependentModel { months_lived_with_taxpayer: 6, ..dependent(Relationship::Son, date(2015, 5, 1)) });
+        assert!(!test(&six_months.qualifying_child, "residency").passed);
+        assert!(!credit(&six_months, "earned_income_credit").eligible);
+        assert!(!credit(&six_months, "head_of_household_qualifying_person").eligible);
+
+        let newborn = evaluate(&DependentModel { months_lived_with_taxpayer: 1, ..dependent(Relationship::Daughter, date(2024, 11, 20)) });
+        assert!(test(&newborn.qualifying_child, "residency").passed);
+        assert!(newborn.credits[0].eligible);
+    }
+
+    #[test]
+    fn child_who_supports_themselves_can_still_be_a_qualifying_relative() {
+        let eligibility = evaluate(&DependentModel { provided_over_half_own_support: true, ..dependent(Relationship::Son, date(2008, 4, 1)) });
+
+        assert!(!test(&eligibility.qualifying_child, "support").passed);
+        assert!(eligibility.qualifying_relative.qualifies);
+        assert!(eligibility.is_dependent);
+        assert!(!eligibility.credits[0].eligible);
+        assert!(eligibility.credits[0].reason.starts_with("not a dependent: provided over half of own support"));
+        assert!(credit(&eligibility, "credit_for_other_dependents").eligible);
+        // The EIC has no support test
+        assert!(credit(&eligibility, "earned_income_credit").eligible);
+    }
+
+    #[test]
+    fn qualifying_relative_tests_gross_income_and_support() {
+        let parent = DependentModel {
+            months_lived_with_taxpayer: 0,
+            support_percentage: Decimal::from(60),
+            gross_income: Decimal::from(3000),
+            ..dependent(Relationship::Parent, date(1954, 9, 1))
+        };
+        let eligibility = evaluate(&parent);
+        assert!(!eligibility.qualifying_child.qualifies);
+        assert!(eligibility.qualifying_relative.qualifies);
+        assert_eq!(eligible(&eligibility), ["credit_for_other_dependents", "head_of_household_qualifying_person"]);
+
+        let over_limit = evaluate(&DependentModel { gross_income: Decimal::from(5050), ..parent.clone() });
+        assert!(!test(&over_limit.qualifying_relative, "gross_income").passed);
+        assert_eq!(test(&over_limit.qualifying_relative, "gross_income").reason, "gross income $5050 must be less than $5050 for 2024");
+        assert!(eligible(&over_limit).is_empty());
+
+        let half_support = evaluate(&DependentModel { support_percentage: Decimal::from(50), ..parent });
+        assert!(!test(&half_support.qualifying_relative, "support").passed);
+        assert!(!half_support.is_dependent);
+    }
+
+    #[test]
+    fn only_a_parent_makes_head_of_household_without_living_together() {
+        let grandparent = DependentModel { months_lived_with_taxpayer: 3, ..dependent(Relationship::Grandparent, date(1940, 1, 5)) };
+        let eligibility = evaluate(&grandparent);
+        assert!(eligibility.qualifying_relative.qualifies);
+        assert!(!credit(&eligibility, "head_of_household_qualifying_person").eligible);
+
+        let at_home = evaluate(&DependentModel { months_lived_with_taxpayer: 7, ..grandparent });
+        assert!(credit(&at_home, "head_of_household_qualifying_person").eligible);
+    }
+
+    #[test]
+    fn unrelated_member_of_household_must_live_there_all_year() {
+        let friend = dependent(Relationship::Other, date(1980, 7, 7));
+        let eligibility = evaluate(&friend);
+        assert!(eligibility.qualifying_relative.qualifies);
+        assert!(credit(&eligibility, "credit_for_other_dependents").eligible);
+        assert!(!credit(&eligibility, "head_of_household_qualifying_person").eligible);
+
+        let eleven_months = evaluate(&DependentModel { months_lived_with_taxpayer: 11, ..friend });
+        assert!(!test(&eleven_months.qualifying_relative, "relationship_or_household").passed);
+        assert!(!eleven_months.is_dependent);
+    }
+
+    #[test]
+    fn joint_return_disqualifies_a_child_and_a_relative() {
+        let eligibility = evaluate(&DependentModel { files_joint_return: true, ..dependent(Relationship::Daughter, date(2005, 2, 2)) });
+
+        assert!(!test(&eligibility.qualifying_child, "joint_return").passed);
+        assert!(!test(&eligibility.qualifying_relative, "joint_return").passed);
+        assert!(!eligibility.is_dependent);
+        assert!(eligible(&eligibility).is_empty());
+    }
+}
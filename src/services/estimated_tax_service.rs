@@ -0,0 +1,93 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::{Acquire, PgConnection, Postgres};
+use uuid::Uuid;
+use crate::models::estimated_payment_model::{EstimatedPaymentModel, SaveEstimatedPaymentSchema};
+use crate::repositories::estimated_payment_repository::{EstimatedPaymentRepository, EstimatedPaymentValues};
+use crate::repositories::tax_repository::TaxRepository;
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::tax_engine::estimated_tax::{EstimatedPayment, EstimatedTaxPlan, EstimatedTaxPlanner, PriorYearTax};
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::TaxEngine;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct EstimatedTaxService;
+
+impl EstimatedTaxService {
+    /// Plans the year's 1040-ES installments.
+    ///
+    /// - The year's `tax_data` is taken as year-to-date through `as_of` (today by default) and
+    ///   projected to the full year; pass the last day of the year when it already holds full-year figures.
+    /// - The prior year's stored `tax_data` sets the prior-year safe harbor when it exists.
+    /// - Recorded estimated payments are applied to the installments.
+    pub async fn plan<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        as_of: Option<NaiveDate>,
+    ) -> Result<EstimatedTaxPlan, ErrorBag> {
+        let rules = TaxRules::for_year(year)?;
+        let as_of = as_of.unwrap_or_else(|| Utc::now().date_naive());
+        if as_of.year() < year {
+            return Err(ErrorBag::Validation { field: "as_of".into(), message: format!("Year-to-date figures cannot be as of a date before {year}") });
+        }
+
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.data)
+            .unwrap_or_else(|| json!({}));
+        let input = TaxCalculationService::load_input(&mut conn, user_id, year, &data).await?;
+        let factor = EstimatedTaxPlanner::annualization_factor(year, as_of);
+        let projected = TaxEngine::calculate(&EstimatedTaxPlanner::project(&input, factor)?)?;
+
+        let prior_year = Self::prior_year(&mut conn, user_id, year).await?;
+        let payments = Self::payments(&mut conn, user_id, year).await?;
+
+        Ok(EstimatedTaxPlanner::plan(as_of, factor, &projected, prior_year, &payments, rules))
+    }
+
+    /// The prior year's return from its stored `tax_data`, when there is one and a rule table for it.
+    pub(crate) async fn prior_year(conn: &mut PgConnection, user_id: &Uuid, year: i32) -> Result<Option<PriorYearTax>, ErrorBag> {
+        match (TaxRules::for_year(year - 1), TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year - 1).await?) {
+            (Ok(_), Some(prior)) => {
+                let prior_input = TaxCalculationService::load_input(conn, user_id, year - 1, &prior.data).await?;
+                Ok(Some(PriorYearTax::from_calculation(&TaxEngine::calculate(&prior_input)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) async fn payments(conn: &mut PgConnection, user_id: &Uuid, year: i32) -> Result<Vec<EstimatedPayment>, ErrorBag> {
+        Ok(EstimatedPaymentRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?
+            .into_iter()
+            .map(|payment| EstimatedPayment { quarter: payment.quarter, amount: payment.amount, paid_on: payment.paid_on })
+            .collect())
+    }
+
+    /// Records an estimated payment, or updates `id` when given. Payments for a year can be
+    /// made during it or, for the last installment, in the following year.
+    pub async fn save_payment<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: Option<&Uuid>,
+        body: &SaveEstimatedPaymentSchema,
+    ) -> Result<EstimatedPaymentModel, ErrorBag> {
+        if body.amount <= Decimal::ZERO {
+            return Err(ErrorBag::Validation { field: "amount".into(), message: "Amount must be positive".into() });
+        }
+        if body.paid_on.year() != year && body.paid_on.year() != year + 1 {
+            return Err(ErrorBag::Validation { field: "paid_on".into(), message: format!("Payments for {year} are made in {year} or {}", year + 1) });
+        }
+
+        let mut conn = db.acquire().await?;
+        let values = EstimatedPaymentValues { quarter: body.quarter, amount: body.amount, paid_on: body.paid_on };
+
+        match id {
+            Some(id) => EstimatedPaymentRepository::update(&mut *conn, user_id, year, id, &values).await,
+            None => EstimatedPaymentRepository::create(&mut *conn, user_id, year, &values).await,
+        }
+    }
+}
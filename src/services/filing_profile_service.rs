@@ -0,0 +1,93 @@
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::models::filing_profile_model::{FilingProfileModel, SaveFilingProfileSchema};
+use crate::repositories::address_repository::AddressRepository;
+use crate::repositories::filing_profile_repository::{FilingProfileRepository, FilingProfileValues};
+use crate::services::crypto_service::CryptoService;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::str::FilterEmptyString;
+
+pub struct FilingProfileService;
+
+impl FilingProfileService {
+    /// Creates or replaces a user's filing profile for a year.
+    ///
+    /// - Joint and separate returns must name the spouse; other statuses drop any spouse fields.
+    /// - SSNs are encrypted before they reach the repository; only the last four digits are kept in clear.
+    /// - The home address goes through `AddressRepository::find_or_create` so identical addresses are shared.
+    pub async fn save<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        body: &SaveFilingProfileSchema,
+    ) -> Result<FilingProfileModel, ErrorBag> {
+        let has_spouse = body.filing_status.requires_spouse();
+        if has_spouse && (body.spouse_first_name.as_deref().empty_as_none().is_none() || body.spouse_last_name.as_deref().empty_as_none().is_none()) {
+            return Err(ErrorBag::Validation {
+                field: "spouse_first_name".into(),
+                message: format!("Spouse name is required when filing as {}", body.filing_status),
+            });
+        }
+
+        let crypto = CryptoService::new();
+        let (taxpayer_ssn_encrypted, taxpayer_ssn_last4) = Self::protect_ssn(&crypto, body.taxpayer_ssn.as_deref())?;
+        let (spouse_ssn_encrypted, spouse_ssn_last4) = match has_spouse {
+            true => Self::protect_ssn(&crypto, body.spouse_ssn.as_deref())?,
+            false => (None, None),
+        };
+
+        let mut conn = db.acquire().await?;
+        let address_id = match &body.address {
+            Some(address) => Some(
+                AddressRepository::find_or_create(
+                    &mut *conn,
+                    address.address_raw.trim(),
+                    address.street_address.as_deref().empty_as_none(),
+                    address.locality.as_deref().empty_as_none(),
+                    address.state_code.as_deref().empty_as_none(),
+                    address.zip.as_deref().empty_as_none(),
+                    address.country_code.as_deref().empty_as_none().unwrap_or("US"),
+                    None,
+                    None,
+                ).await?.id
+            ),
+            None => None,
+        };
+
+        let values = FilingProfileValues {
+            filing_status: body.filing_status.as_str(),
+            taxpayer_first_name: body.taxpayer_first_name.trim(),
+            taxpayer_last_name: body.taxpayer_last_name.trim(),
+            taxpayer_dob: body.taxpayer_dob,
+            taxpayer_ssn_encrypted,
+            taxpayer_ssn_last4,
+            taxpayer_is_blind: body.taxpayer_is_blind,
+            taxpayer_is_65_or_older: body.taxpayer_is_65_or_older,
+            taxpayer_occupation: body.taxpayer_occupation.as_deref().empty_as_none(),
+            spouse_first_name: body.spouse_first_name.as_deref().filter(|_| has_spouse).empty_as_none(),
+            spouse_last_name: body.spouse_last_name.as_deref().filter(|_| has_spouse).empty_as_none(),
+            spouse_dob: body.spouse_dob.filter(|_| has_spouse),
+            spouse_ssn_encrypted,
+            spouse_ssn_last4,
+            spouse_is_blind: has_spouse && body.spouse_is_blind,
+            spouse_is_65_or_older: has_spouse && body.spouse_is_65_or_older,
+            spouse_occupation: body.spouse_occupation.as_deref().filter(|_| has_spouse).empty_as_none(),
+            address_id,
+        };
+
+        FilingProfileRepository::upsert(&mut *conn, user_id, year, &values).await
+    }
+
+    /// Returns (ciphertext, last four digits) for a validated SSN.
+    pub(crate) fn protect_ssn(crypto: &CryptoService, ssn: Option<&str>) -> Result<(Option<String>, Option<String>), ErrorBag> {
+        let Some(ssn) = ssn.empty_as_none() else {
+            return Ok((None, None));
+        };
+
+        let digits: String = ssn.chars().filter(|c| c.is_ascii_digit()).collect();
+        let encrypted = crypto.encrypt(&digits)
+            .map_err(|e| ErrorBag::InternalServerError(format!("FilingProfileService::protect_ssn failed: {:?}", e)))?;
+
+        Ok((Some(encrypted), Some(digits[digits.len() - 4..].to_string())))
+    }
+}
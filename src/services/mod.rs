@@ -2,3 +2,13 @@ pub mod log_service;
 pub mod crypto_service;
 pub mod jwt_service;
 pub mod user_service;
+pub mod filing_profile_service;
+pub mod dependent_service;
+pub mod tax_calculation_service;
+pub mod business_service;
+pub mod residency_service;
+pub mod tax_scenario_service;
+pub mod estimated_tax_service;
+pub mod carryforward_service;
+pub mod anomaly_service;
+pub mod penalty_service;
@@ -0,0 +1,44 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::repositories::tax_repository::TaxRepository;
+use crate::services::estimated_tax_service::EstimatedTaxService;
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::tax_engine::penalties::{PenaltyCalculator, PenaltyEstimate};
+use crate::tax_engine::TaxEngine;
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct PenaltyService;
+
+impl PenaltyService {
+    /// Form 2210 underpayment penalty for the year, plus the failure-to-file and failure-to-pay
+    /// penalties and interest for a return filed on `filed_on` with the balance paid on
+    /// `paid_on`. Filing defaults to today and payment to the filing date.
+    ///
+    /// Recorded estimated payments are applied on the dates they were made, and the prior
+    /// year's stored `tax_data` sets the prior-year safe harbor when it exists.
+    pub async fn estimate<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        filed_on: Option<NaiveDate>,
+        paid_on: Option<NaiveDate>,
+    ) -> Result<PenaltyEstimate, ErrorBag> {
+        let filed_on = filed_on.unwrap_or_else(|| Utc::now().date_naive());
+        let paid_on = paid_on.unwrap_or(filed_on);
+        if filed_on.year() <= year {
+            return Err(ErrorBag::Validation { field: "filed_on".into(), message: format!("A {year} return is filed after {year}") });
+        }
+
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+        let input = TaxCalculationService::load_input(&mut conn, user_id, year, &data.data).await?;
+        let calculation = TaxEngine::calculate(&input)?;
+        let prior_year = EstimatedTaxService::prior_year(&mut conn, user_id, year).await?;
+        let payments = EstimatedTaxService::payments(&mut conn, user_id, year).await?;
+
+        PenaltyCalculator::estimate(&input, &calculation, prior_year, &payments, filed_on, paid_on)
+    }
+}
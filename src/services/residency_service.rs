@@ -0,0 +1,79 @@
+use chrono::Datelike;
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::models::residency_period_model::{ResidencyPeriodModel, SaveResidencyPeriodSchema};
+use crate::repositories::address_repository::AddressRepository;
+use crate::repositories::residency_repository::{ResidencyPeriodValues, ResidencyRepository};
+use crate::repositories::state_repository::StateRepository;
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::str::FilterEmptyString;
+
+pub struct ResidencyService;
+
+impl ResidencyService {
+    /// Creates a residency period, or updates `id` when given.
+    ///
+    /// - The state must be a US state and the dates must fall within the year.
+    /// - Periods cannot overlap, since income is allocated by the days lived in each state.
+    /// - The address goes through `AddressRepository::find_or_create`, like the filing profile's.
+    pub async fn save<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: Option<&Uuid>,
+        body: &SaveResidencyPeriodSchema,
+    ) -> Result<ResidencyPeriodModel, ErrorBag> {
+        if body.start_date > body.end_date {
+            return Err(ErrorBag::Validation { field: "end_date".into(), message: "End date cannot be before the start date".into() });
+        }
+        if body.start_date.year() != year || body.end_date.year() != year {
+            return Err(ErrorBag::Validation { field: "start_date".into(), message: format!("Residency dates must fall within {year}") });
+        }
+
+        let mut conn = db.acquire().await?;
+        let state_code = body.state_code.trim().to_uppercase();
+        let state_codes = StateRepository::find_codes_by_country(&mut *conn, "US").await?;
+        if !state_codes.contains(&state_code) {
+            return Err(ErrorBag::Validation { field: "state_code".into(), message: format!("{state_code} is not a US state") });
+        }
+
+        let periods = ResidencyRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        if let Some(overlap) = periods.iter()
+            .filter(|period| Some(&period.id) != id)
+            .find(|period| period.start_date <= body.end_date && body.start_date <= period.end_date) {
+            return Err(ErrorBag::Validation {
+                field: "start_date".into(),
+                message: format!("Overlaps the {} period from {} to {}", overlap.state_code, overlap.start_date, overlap.end_date),
+            });
+        }
+
+        let address_id = match &body.address {
+            Some(address) => Some(
+                AddressRepository::find_or_create(
+                    &mut *conn,
+                    address.address_raw.trim(),
+                    address.street_address.as_deref().empty_as_none(),
+                    address.locality.as_deref().empty_as_none(),
+                    Some(address.state_code.as_deref().empty_as_none().unwrap_or(&state_code)),
+                    address.zip.as_deref().empty_as_none(),
+                    address.country_code.as_deref().empty_as_none().unwrap_or("US"),
+                    None,
+                    None,
+                ).await?.id
+            ),
+            None => None,
+        };
+
+        let values = ResidencyPeriodValues {
+            state_code: &state_code,
+            address_id,
+            start_date: body.start_date,
+            end_date: body.end_date,
+        };
+
+        match id {
+            Some(id) => ResidencyRepository::update(&mut *conn, user_id, year, id, &values).await,
+            None => ResidencyRepository::create(&mut *conn, user_id, year, &values).await,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use sqlx::{Acquire, PgConnection, Postgres};
+use uuid::Uuid;
+use crate::models::filing_profile_model::FilingProfileModel;
+use crate::repositories::business_repository::BusinessRepository;
+use crate::repositories::carryforward_repository::CarryforwardRepository;
+use crate::repositories::dependent_repository::DependentRepository;
+use crate::repositories::estimated_payment_repository::EstimatedPaymentRepository;
+use crate::repositories::filing_profile_repository::FilingProfileRepository;
+use crate::repositories::residency_repository::ResidencyRepository;
+use crate::repositories::state_repository::StateRepository;
+use crate::repositories::tax_repository::TaxRepository;
+use crate::services::carryforward_service::CarryforwardService;
+use crate::services::dependent_service::DependentService;
+use crate::tax_engine::analytics::{TaxAnalytics, TaxAnalyzer};
+use crate::tax_engine::checklist::DocumentChecklist;
+use crate::tax_engine::filing_status::{FilingStatusComparison, FilingStatusOptimizer};
+use crate::tax_engine::input::{Business, Dependent, ResidencyPeriod, TaxInput};
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::payroll::PayrollReconciliation;
+use crate::tax_engine::self_employment::ScheduleC;
+use crate::tax_engine::state::StateReturn;
+use crate::tax_engine::summary::{Completeness, TaxSummary};
+use crate::tax_engine::trace::CalculationTrace;
+use crate::tax_engine::{DeductionSummary, TaxCalculation, TaxEngine};
+use crate::utilities::error_bag::ErrorBag;
+
+pub struct TaxCalculationService;
+
+impl TaxCalculationService {
+    /// Loads everything the engine needs for a user's year and normalizes it into a `TaxInput`.
+    pub async fn build_input<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<TaxInput, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+
+        Self::load_input(&mut conn, user_id, year, &data.data).await
+    }
+
+    /// Schedule C for each of the year's businesses. Works before any tax data is saved,
+    /// in which case only manually entered receipts count.
+    pub async fn schedule_c<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<(Vec<ScheduleC>, Vec<String>), ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.data)
+            .unwrap_or_else(|| json!({}));
+        let input = Self::load_input(&mut conn, user_id, year, &data).await?;
+
+        Ok(ScheduleC::for_input(&input))
+    }
+
+    pub(crate) async fn load_input(conn: &mut PgConnection, user_id: &Uuid, year: i32, data: &Value) -> Result<TaxInput, ErrorBag> {
+        let profile = FilingProfileRepository::find_by_user_and_year(&mut *conn, user_id, year).await?;
+        Self::load_input_with_profile(conn, user_id, year, data, profile.as_ref()).await
+    }
+
+    /// `load_input` for a caller that has already loaded the year's filing profile.
+    async fn load_input_with_profile(conn: &mut PgConnection, user_id: &Uuid, year: i32, data: &Value, profile: Option<&FilingProfileModel>) -> Result<TaxInput, ErrorBag> {
+        let businesses = BusinessRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        let expenses = BusinessRepository::find_expenses_by_user_and_year(&mut *conn, user_id, year).await?;
+        let dependents = DependentRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        let state_codes = StateRepository::find_codes_by_country(&mut *conn, "US").await?;
+        let residency = ResidencyRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        let estimated_payments = EstimatedPaymentRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        let carryforwards = CarryforwardRepository::find_all_by_user_and_target_year(&mut *conn, user_id, year).await?;
+
+        let mut input = TaxInput::from_tax_data(year, data, profile)?;
+        for w2 in &mut input.w2s {
+            if let Some(state) = &w2.state
+                && !state_codes.iter().any(|code| code.eq_ignore_ascii_case(state.trim())) {
+                input.warnings.push(format!("W-2 from {} lists {state} in box 15, which is not a US state; it is left off the state returns", w2.employer));
+                w2.state = None;
+            }
+        }
+        if !estimated_payments.is_empty() {
+            let paid = estimated_payments.iter().map(|payment| payment.amount).sum::<Decimal>();
+            if !input.estimated_payments.is_zero() && input.estimated_payments != paid {
+                input.warnings.push(format!("Estimated payments of ${} in the tax data are replaced by the ${paid} of recorded payments", input.estimated_payments));
+            }
+            input.estimated_payments = paid;
+        }
+        CarryforwardService::apply(&mut input, &carryforwards)?;
+        input.residency = residency.iter()
+            .map(|period| ResidencyPeriod { state: period.state_code.clone(), start_date: period.start_date, end_date: period.end_date })
+            .collect();
+        input.businesses = businesses.iter()
+            .map(|business| Business::from_model(business, &expenses))
+            .collect::<Result<Vec<_>, ErrorBag>>()?;
+        for dependent in &dependents {
+            let eligibility = DependentService::evaluate(dependent, profile)?;
+            let eligible = |credit: &str| eligibility.credits.iter().any(|c| c.credit == credit && c.eligible);
+            input.dependents.push(Dependent {
+                name: format!("{} {}", dependent.first_name, dependent.last_name),
+                age: dependent.age_at_year_end(),
+                student: dependent.is_student,
+                child_tax_credit: eligible("child_tax_credit"),
+                credit_for_other_dependents: eligible("credit_for_other_dependents"),
+                earned_income_credit: eligible("earned_income_credit"),
+                child_and_dependent_care_credit: eligible("child_and_dependent_care_credit"),
+                head_of_household_qualifying_person: eligible("head_of_household_qualifying_person"),
+            });
+        }
+
+        Ok(input)
+    }
+
+    pub async fn calculate<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<TaxCalculation, ErrorBag> {
+        let input = Self::build_input(db, user_id, year).await?;
+
+        TaxEngine::calculate(&input)
+    }
+
+    /// The calculation with every line traced back to its inputs, records and rule table entries.
+    pub async fn trace<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<CalculationTrace, ErrorBag> {
+        let input = Self::build_input(db, user_id, year).await?;
+
+        CalculationTrace::new(&input, &TaxEngine::calculate(&input)?)
+    }
+
+    /// Payroll reconciliation needs Schedule SE for Form 8959 part II, so it runs the full calculation.
+    pub async fn reconcile_payroll<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<PayrollReconciliation, ErrorBag> {
+        Ok(Self::calculate(db, user_id, year).await?.payroll)
+    }
+
+    /// Schedule A and the itemize-or-standard recommendation. The medical floor and charitable
+    /// limits depend on AGI, so this runs the full calculation.
+    pub async fn itemized_deductions<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<(ScheduleA, DeductionSummary), ErrorBag> {
+        let calculation = Self::calculate(db, user_id, year).await?;
+
+        Ok((calculation.schedule_a, calculation.deduction))
+    }
+
+    /// State returns build on federal AGI and Schedule A, so this runs the full calculation.
+    /// Residency periods decide which returns are resident, part-year or nonresident.
+    pub async fn state_returns<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<Vec<StateReturn>, ErrorBag> {
+        Ok(Self::calculate(db, user_id, year).await?.states)
+    }
+
+    /// Refund or balance due with a completeness checklist. Works before any tax data is saved,
+    /// in which case the checklist asks for income.
+    pub async fn summary<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<TaxSummary, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.data)
+            .unwrap_or_else(|| json!({}));
+        let profile = FilingProfileRepository::find_by_user_and_year(&mut *conn, user_id, year).await?;
+        let input = Self::load_input_with_profile(&mut conn, user_id, year, &data, profile.as_ref()).await?;
+        let calculation = TaxEngine::calculate(&input)?;
+
+        let mut completeness = Completeness::for_input(&input);
+        if profile.is_none() {
+            completeness.add("filing_profile", "Add a filing profile; the return is figured as single until then");
+        }
+        let documents = data.get("documents").and_then(|d| d.as_array()).map(Vec::as_slice).unwrap_or_default();
+        for document in documents {
+            let records = document.get("records").and_then(|r| r.as_array()).map(Vec::as_slice).unwrap_or_default();
+            if records.iter().any(|record| record.get("error").is_some()) {
+                let doc_type = document.get("type").and_then(|t| t.as_str()).unwrap_or("document");
+                completeness.add("documents", format!("A {doc_type} upload could not be read; enter it manually"));
+            }
+        }
+        for expected in Self::checklist(&mut conn, user_id, &input, &data).await?.missing() {
+            completeness.add("expected_documents", expected.action.clone());
+        }
+
+        Ok(TaxSummary::new(&calculation, completeness))
+    }
+
+    /// Documents expected for the year from the prior year's issuers and the profile, and which
+    /// of them are still missing. Works before any tax data is saved.
+    pub async fn document_checklist<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<DocumentChecklist, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.data)
+            .unwrap_or_else(|| json!({}));
+        let input = Self::load_input(&mut conn, user_id, year, &data).await?;
+
+        Self::checklist(&mut conn, user_id, &input, &data).await
+    }
+
+    /// Compares `input` with the most recent earlier year that has tax data. An earlier year
+    /// whose data no longer parses is skipped rather than failing this one.
+    async fn checklist(conn: &mut PgConnection, user_id: &Uuid, input: &TaxInput, data: &Value) -> Result<DocumentChecklist, ErrorBag> {
+        let uploaded: Vec<String> = TaxRepository::find_documents_by_user_and_year(&mut *conn, user_id, input.year).await?
+            .into_iter()
+            .map(|document| document.document_type)
+            .collect();
+        let prior = TaxRepository::find_all_data_by_user(&mut *conn, user_id).await?
+            .into_iter()
+            .find(|prior| prior.year < input.year)
+            .and_then(|prior| TaxInput::from_tax_data(prior.year, &prior.data, None).ok());
+        let dismissed: Vec<String> = data.get("dismissed_documents").and_then(|d| d.as_array()).map(Vec::as_slice).unwrap_or_default()
+            .iter()
+            .filter_map(|key| key.as_str().map(str::to_string))
+            .collect();
+
+        Ok(DocumentChecklist::build(input, &uploaded, prior.as_ref(), &dismissed))
+    }
+
+    /// Ranks every filing status the profile allows. Needs a filing profile to know whether
+    /// the taxpayer is married; works before any tax data is saved.
+    pub async fn filing_status_comparison<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<FilingStatusComparison, ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        if FilingProfileRepository::find_by_user_and_year(&mut *conn, user_id, year).await?.is_none() {
+            return Err(ErrorBag::NotFound("Filing profile".into()));
+        }
+        let data = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.data)
+            .unwrap_or_else(|| json!({}));
+        let input = Self::load_input(&mut conn, user_id, year, &data).await?;
+
+        FilingStatusOptimizer::compare(&input)
+    }
+
+    /// Marginal rates, nearby phase-outs and contribution suggestions for a year with tax data.
+    pub async fn analytics<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<TaxAnalytics, ErrorBag> {
+        let input = Self::build_input(db, user_id, year).await?;
+        TaxAnalyzer::analyze(&input)
+    }
+}
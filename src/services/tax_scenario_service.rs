@@ -0,0 +1,131 @@
+use serde_json::Value;
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::models::tax_scenario_model::{SaveTaxScenarioSchema, TaxScenarioModel};
+use crate::repositories::tax_repository::TaxRepository;
+use crate::repositories::tax_scenario_repository::{TaxScenarioRepository, TaxScenarioValues};
+use crate::services::tax_calculation_service::TaxCalculationService;
+use crate::tax_engine::filing_status::FilingStatusOptimizer;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::scenario::ScenarioComparison;
+use crate::tax_engine::{TaxCalculation, TaxEngine};
+use crate::utilities::error_bag::ErrorBag;
+use crate::utilities::json::merge_patch;
+
+pub struct TaxScenarioService;
+
+impl TaxScenarioService {
+    /// Creates a scenario from the year's current `tax_data`, or updates `id` when given.
+    ///
+    /// - A new scenario copies the data and records the revision it was forked from.
+    /// - `changes` are merged into the scenario's copy; the real `tax_data` row is never written.
+    /// - The merged data must still parse as tax data, so a bad patch fails here rather than on compare.
+    pub async fn save<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: Option<&Uuid>,
+        body: &SaveTaxScenarioSchema,
+    ) -> Result<TaxScenarioModel, ErrorBag> {
+        let mut conn = db.acquire().await?;
+        let filing_status = body.filing_status.map(|status| status.as_str());
+
+        match id {
+            Some(id) => {
+                let scenario = TaxScenarioRepository::find_by_id(&mut *conn, user_id, year, id).await?;
+                let data = Self::apply_changes(year, scenario.data, body.changes.as_ref())?;
+                let values = TaxScenarioValues { name: body.name.trim(), filing_status, data: &data };
+
+                TaxScenarioRepository::update(&mut *conn, user_id, year, id, &values).await
+            }
+            None => {
+                let base = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+                    .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+                let data = Self::apply_changes(year, base.data.clone(), body.changes.as_ref())?;
+                let values = TaxScenarioValues { name: body.name.trim(), filing_status, data: &data };
+
+                TaxScenarioRepository::create(&mut *conn, &base, &values).await
+            }
+        }
+    }
+
+    fn apply_changes(year: i32, mut data: Value, changes: Option<&Value>) -> Result<Value, ErrorBag> {
+        if let Some(changes) = changes {
+            if !changes.is_object() {
+                return Err(ErrorBag::Validation { field: "changes".into(), message: "Changes must be a JSON object".into() });
+            }
+            merge_patch(&mut data, changes);
+        }
+        TaxInput::from_tax_data(year, &data, None).map_err(|e| match e {
+            ErrorBag::Validation { message, .. } => ErrorBag::Validation { field: "changes".into(), message },
+            e => e,
+        })?;
+
+        Ok(data)
+    }
+
+    /// Calculates the year's return and the scenario side by side. Also returns the current
+    /// `tax_data` revision; when it differs from the scenario's `base_revision` the real
+    /// return has changed since the fork and the scenario does not reflect it.
+    pub async fn compare<'e, A: Acquire<'e, Database = Postgres>>(
+        db: A,
+        user_id: &Uuid,
+        year: i32,
+        id: &Uuid,
+    ) -> Result<(TaxScenarioModel, i32, ScenarioComparison), ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let scenario = TaxScenarioRepository::find_by_id(&mut *conn, user_id, year, id).await?;
+        let base = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .ok_or_else(|| ErrorBag::NotFound("Tax data".into()))?;
+
+        let base_input = TaxCalculationService::load_input(&mut conn, user_id, year, &base.data).await?;
+        let scenario_input = TaxCalculationService::load_input(&mut conn, user_id, year, &scenario.data).await?;
+        let (scenario_return, spouse_return) = Self::scenario_returns(&scenario_input, &scenario)?;
+
+        let comparison = ScenarioComparison::new(TaxEngine::calculate(&base_input)?, scenario_return, spouse_return);
+
+        Ok((scenario, base.revision, comparison))
+    }
+
+    /// Figures the scenario under its filing status. A joint return filed another way is split
+    /// between the spouses the way the filing status optimizer splits it, and the spouse's
+    /// return is figured too; filing separately also makes both spouses itemize when one does.
+    /// How the split was made is added to the scenario's warnings.
+    fn scenario_returns(input: &TaxInput, scenario: &TaxScenarioModel) -> Result<(TaxCalculation, Option<TaxCalculation>), ErrorBag> {
+        let Some(status) = scenario.status().map_err(ErrorBag::InternalServerError)? else {
+            return Ok((TaxEngine::calculate(input)?, None));
+        };
+        if input.filing_status != FilingStatus::MarriedFilingJointly || status == FilingStatus::MarriedFilingJointly {
+            return Ok((TaxEngine::calculate(&TaxInput { filing_status: status, ..input.clone() })?, None));
+        }
+
+        let (mut taxpayer, spouse, notes) = match status {
+            FilingStatus::MarriedFilingSeparately => FilingStatusOptimizer::separate_returns(input)?,
+            _ => {
+                let (taxpayer, spouse, mut notes) = FilingStatusOptimizer::split(input);
+                notes.push(format!("Filing as {status} splits the joint return as for married filing separately; the spouse's return is figured as married filing separately"));
+                (
+                    TaxEngine::calculate(&TaxInput { filing_status: status, ..taxpayer })?,
+                    TaxEngine::calculate(&spouse)?,
+                    notes,
+                )
+            }
+        };
+        taxpayer.warnings.extend(notes);
+
+        Ok((taxpayer, Some(spouse)))
+    }
+
+    /// Scenarios are listed with the current `tax_data` revision so stale ones can be flagged.
+    pub async fn list<'e, A: Acquire<'e, Database = Postgres>>(db: A, user_id: &Uuid, year: i32) -> Result<(Vec<TaxScenarioModel>, Option<i32>), ErrorBag> {
+        let mut conn = db.acquire().await?;
+
+        let scenarios = TaxScenarioRepository::find_all_by_user_and_year(&mut *conn, user_id, year).await?;
+        let revision = TaxRepository::find_data_by_user_and_year(&mut *conn, user_id, year).await?
+            .map(|data| data.revision);
+
+        Ok((scenarios, revision))
+    }
+}
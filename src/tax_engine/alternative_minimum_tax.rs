@@ -0,0 +1,256 @@
+//! Alternative minimum tax (Form 6251) and the minimum tax credit (Form 8801).
+//!
+//! AMTI starts from taxable income and adds back what the minimum tax does not allow: state
+//! and local taxes (or the standard deduction), the regular NOL deduction in place of the
+//! alternative tax NOL deduction, private activity bond interest and the bargain element of
+//! incentive stock options exercised and held. Qualified dividends and net capital gain keep
+//! their preferential rates through Part III.
+//!
+//! AMT caused by deferral items (incentive stock options and the adjustments entered as
+//! `other`) only moves income between years, so it becomes a credit against the regular tax
+//! of a later year in which that tax is above the tentative minimum tax. AMT caused by
+//! exclusion items (deductions and bond interest) is never recovered.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::capital_gains::QualifiedDividendsWorksheet;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::{DeductionKind, DeductionSummary, IncomeSummary};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Form 6251 Part III: the tentative minimum tax with qualified dividends and net capital gain
+/// taxed at their preferential rates.
+#[derive(Debug, Clone, Serialize)]
+pub struct AmtCapitalGainsTax {
+    /// Line 12
+    pub taxable_excess: Decimal,
+    /// Line 13, from line 4 of the qualified dividends and capital gain tax worksheet
+    pub preferential_income: Decimal,
+    /// Line 18, tax on the rest at the AMT rates
+    pub ordinary_tax: Decimal,
+    /// Line 23
+    pub zero_rate_amount: Decimal,
+    /// Line 30
+    pub fifteen_rate_amount: Decimal,
+    /// Line 33
+    pub twenty_rate_amount: Decimal,
+    /// Line 38
+    pub tax: Decimal,
+    /// Line 39, tax on line 12 at the AMT rates
+    pub tax_without_preferential_rates: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlternativeMinimumTax {
+    /// Line 1, Form 1040 line 11 less line 14 (the deduction and the QBI deduction); negative when the deduction is larger
+    pub taxable_income: Decimal,
+    /// Line 2a, Schedule A line 7 when itemizing, otherwise the standard deduction
+    pub taxes_or_standard_deduction: Decimal,
+    /// Line 2b, as a negative amount
+    pub tax_refund: Decimal,
+    /// Line 2e
+    pub net_operating_loss_deduction: Decimal,
+    /// Line 2f, as a negative amount
+    pub alternative_net_operating_loss_deduction: Decimal,
+    /// Line 2g
+    pub private_activity_bond_interest: Decimal,
+    /// Line 2i
+    pub incentive_stock_options: Decimal,
+    /// Line 3
+    pub other_adjustments: Decimal,
+    /// Line 4
+    pub alternative_minimum_taxable_income: Decimal,
+    /// Line 5, after the phase-out
+    pub exemption: Decimal,
+    /// Line 6
+    pub taxable_excess: Decimal,
+    pub capital_gains_tax: Option<AmtCapitalGainsTax>,
+    /// Line 9
+    pub tentative_minimum_tax: Decimal,
+    /// Line 10
+    pub regular_tax: Decimal,
+    /// Line 11, Form 1040 line 17
+    pub tax: Decimal,
+    /// Form 8801 credit from earlier years
+    pub credit_carryover: Decimal,
+    /// Credit used this year, Schedule 3 line 6b
+    pub credit_allowed: Decimal,
+    /// Part of this year's AMT caused by deferral items, usable from next year
+    pub credit_generated: Decimal,
+    pub credit_carryforward: Decimal,
+}
+
+/// Lines 5 through 9 for one AMTI.
+struct MinimumTax {
+    exemption: Decimal,
+    taxable_excess: Decimal,
+    capital_gains_tax: Option<AmtCapitalGainsTax>,
+    tentative_minimum_tax: Decimal,
+}
+
+impl AlternativeMinimumTax {
+    /// Lines 1 through 4. The tax itself depends on the regular tax, figured in `compute_tax`.
+    pub fn compute(
+        input: &TaxInput,
+        rules: &TaxRules,
+        income: &IncomeSummary,
+        adjusted_gross_income: Decimal,
+        deduction: &DeductionSummary,
+        schedule_a: &ScheduleA,
+        qualified_business_income_deduction: Decimal,
+    ) -> Result<AlternativeMinimumTax, ErrorBag> {
+        let taxable_income = adjusted_gross_income - deduction.amount - qualified_business_income_deduction;
+        let taxes_or_standard_deduction = match deduction.kind {
+            DeductionKind::Itemized => schedule_a.salt_deduction,
+            DeductionKind::Standard => deduction.amount,
+        };
+        let tax_refund = -income.taxable_refunds;
+        let private_activity_bond_interest = input.form_1099s.iter().map(|form| form.private_activity_bond_interest).sum::<Decimal>()
+            + input.amt_adjustments.private_activity_bond_interest;
+        let incentive_stock_options = input.form_3921s.iter()
+            .map(|form| form.bargain_element().map(round_cents))
+            .sum::<Result<Decimal, ErrorBag>>()?
+            + input.amt_adjustments.incentive_stock_options;
+        let other_adjustments = input.amt_adjustments.other;
+
+        // The alternative tax NOL deduction may offset 90% of AMTI figured without it
+        let before_net_operating_loss = taxable_income + taxes_or_standard_deduction + tax_refund + income.net_operating_loss
+            + private_activity_bond_interest + incentive_stock_options + other_adjustments;
+        let alternative_net_operating_loss_deduction = -input.net_operating_loss_carryover.max(Decimal::ZERO)
+            .min(round_cents(before_net_operating_loss.max(Decimal::ZERO) * rules.alternative_minimum_tax.net_operating_loss_limit));
+        let alternative_minimum_taxable_income = Self::separate_return_increase(
+            input.filing_status,
+            before_net_operating_loss + alternative_net_operating_loss_deduction,
+            rules,
+        );
+        let credit_carryover = input.minimum_tax_credit_carryover.max(Decimal::ZERO);
+
+        Ok(AlternativeMinimumTax {
+            taxable_income,
+            taxes_or_standard_deduction,
+            tax_refund,
+            net_operating_loss_deduction: income.net_operating_loss,
+            alternative_net_operating_loss_deduction,
+            private_activity_bond_interest,
+            incentive_stock_options,
+            other_adjustments,
+            alternative_minimum_taxable_income,
+            exemption: Decimal::ZERO,
+            taxable_excess: Decimal::ZERO,
+            capital_gains_tax: None,
+            tentative_minimum_tax: Decimal::ZERO,
+            regular_tax: Decimal::ZERO,
+            tax: Decimal::ZERO,
+            credit_carryover,
+            credit_allowed: Decimal::ZERO,
+            credit_generated: Decimal::ZERO,
+            credit_carryforward: credit_carryover,
+        })
+    }
+
+    /// Lines 5 through 11 against the regular `tax` on Form 1040 line 16, and the part of the
+    /// AMT that becomes a credit. Returns the AMT.
+    pub fn compute_tax(&mut self, status: FilingStatus, rules: &TaxRules, worksheet: Option<&QualifiedDividendsWorksheet>, tax: Decimal) -> Decimal {
+        let minimum_tax = Self::minimum_tax(status, self.alternative_minimum_taxable_income, worksheet, rules);
+        self.exemption = minimum_tax.exemption;
+        self.taxable_excess = minimum_tax.taxable_excess;
+        self.capital_gains_tax = minimum_tax.capital_gains_tax;
+        self.tentative_minimum_tax = minimum_tax.tentative_minimum_tax;
+        self.regular_tax = tax;
+        self.tax = (self.tentative_minimum_tax - tax).max(Decimal::ZERO);
+
+        // Form 8801 Part I: the AMT that exclusion items alone would have caused
+        let exclusion_income = self.alternative_minimum_taxable_income - self.incentive_stock_options - self.other_adjustments;
+        let exclusion_tax = Self::minimum_tax(status, exclusion_income, worksheet, rules).tentative_minimum_tax;
+        self.credit_generated = (self.tax - (exclusion_tax - tax).max(Decimal::ZERO)).max(Decimal::ZERO);
+        self.credit_carryforward = self.credit_carryover + self.credit_generated;
+        self.tax
+    }
+
+    /// Form 8801 lines 23 through 26: the credit carried in is limited to the regular tax, less
+    /// the other nonrefundable credits, that is above the tentative minimum tax. Returns the credit used.
+    pub fn apply_credit(&mut self, tax_after_credits: Decimal) -> Decimal {
+        let room = (tax_after_credits - self.tentative_minimum_tax).max(Decimal::ZERO);
+        self.credit_allowed = self.credit_carryover.min(room);
+        self.credit_carryforward = self.credit_carryover - self.credit_allowed + self.credit_generated;
+        self.credit_allowed
+    }
+
+    /// Line 4 for a separate return: the exemption phase-out of a joint return is mirrored by
+    /// adding 25% of AMTI over the point where a separate exemption would be gone, up to the exemption.
+    fn separate_return_increase(status: FilingStatus, income: Decimal, rules: &TaxRules) -> Decimal {
+        if status != FilingStatus::MarriedFilingSeparately {
+            return income;
+        }
+        let amt = &rules.alternative_minimum_tax;
+        let exemption = *amt.exemption.get(status);
+        let threshold = amt.exemption_phase_out_start.get(status) + exemption / amt.exemption_phase_out_rate;
+        if income <= threshold {
+            return income;
+        }
+
+        income + round_cents((income - threshold) * amt.exemption_phase_out_rate).min(exemption)
+    }
+
+    fn minimum_tax(status: FilingStatus, income: Decimal, worksheet: Option<&QualifiedDividendsWorksheet>, rules: &TaxRules) -> MinimumTax {
+        let amt = &rules.alternative_minimum_tax;
+        let reduction = round_cents((income - amt.exemption_phase_out_start.get(status)).max(Decimal::ZERO) * amt.exemption_phase_out_rate);
+        let exemption = (amt.exemption.get(status) - reduction).max(Decimal::ZERO);
+        let taxable_excess = (income - exemption).max(Decimal::ZERO);
+        if taxable_excess.is_zero() {
+            return MinimumTax { exemption, taxable_excess, capital_gains_tax: None, tentative_minimum_tax: Decimal::ZERO };
+        }
+
+        let capital_gains_tax = worksheet.map(|worksheet| Self::capital_gains_tax(status, taxable_excess, worksheet, rules));
+        let tentative_minimum_tax = match &capital_gains_tax {
+            Some(part) => part.tax.min(part.tax_without_preferential_rates),
+            None => Self::rate_tax(status, taxable_excess, rules),
+        };
+
+        MinimumTax { exemption, taxable_excess, capital_gains_tax, tentative_minimum_tax }
+    }
+
+    /// 26% up to the rate threshold and 28% above it (lines 7, 18 and 39).
+    fn rate_tax(status: FilingStatus, amount: Decimal, rules: &TaxRules) -> Decimal {
+        let amt = &rules.alternative_minimum_tax;
+        let threshold = *amt.rate_threshold.get(status);
+        let tax = match amount <= threshold {
+            true => amount * amt.lower_rate,
+            false => amount * amt.upper_rate - threshold * (amt.upper_rate - amt.lower_rate),
+        };
+
+        round_cents(tax.max(Decimal::ZERO))
+    }
+
+    /// Lines 12 through 40. Unrecaptured section 1250 gain is not modeled, so lines 14 and 35-37 are zero.
+    fn capital_gains_tax(status: FilingStatus, taxable_excess: Decimal, worksheet: &QualifiedDividendsWorksheet, rules: &TaxRules) -> AmtCapitalGainsTax {
+        let gains = &rules.capital_gains;
+        let preferential_income = worksheet.qualified_dividends + worksheet.net_capital_gain;
+        let preferential_taxed = taxable_excess.min(preferential_income);
+        let ordinary_tax = Self::rate_tax(status, taxable_excess - preferential_taxed, rules);
+
+        let ordinary_income = worksheet.ordinary_income.max(Decimal::ZERO);
+        let zero_rate_room = (gains.zero_rate_maximum.get(status) - ordinary_income).max(Decimal::ZERO);
+        let zero_rate_amount = zero_rate_room.min(preferential_taxed);
+        let fifteen_rate_room = (gains.fifteen_rate_maximum.get(status) - (zero_rate_room + ordinary_income)).max(Decimal::ZERO);
+        let fifteen_rate_amount = (preferential_taxed - zero_rate_amount).min(fifteen_rate_room);
+        let twenty_rate_amount = preferential_taxed - zero_rate_amount - fifteen_rate_amount;
+
+        let tax = ordinary_tax + round_cents(fifteen_rate_amount * gains.fifteen_rate) + round_cents(twenty_rate_amount * gains.twenty_rate);
+
+        AmtCapitalGainsTax {
+            taxable_excess,
+            preferential_income,
+            ordinary_tax,
+            zero_rate_amount,
+            fifteen_rate_amount,
+            twenty_rate_amount,
+            tax,
+            tax_without_preferential_rates: Self::rate_tax(status, taxable_excess, rules),
+        }
+    }
+}
@@ -0,0 +1,708 @@
+//! Marginal-rate analytics and tax-saving suggestions.
+//!
+//! Everything here is figured by running the engine again on a changed copy of the input:
+//! marginal rates add $1,000 of wages, long-term gain or deductions, and each suggestion makes
+//! a contribution the return has room for and reports the scenario comparison. Tax is Form 1040
+//! line 24 less refundable credits plus state tax, as in the filing status optimizer; payroll
+//! taxes are not part of the return and are left out.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::credits::CreditKind;
+use crate::tax_engine::input::{CapitalLot, Term, TaxInput, W2, W2Box12};
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::scenario::{LineDelta, ScenarioComparison, StateDelta};
+use crate::tax_engine::{round_cents, TaxCalculation, TaxEngine};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Income or deduction added to figure each marginal rate.
+const MARGINAL_AMOUNT: Decimal = Decimal::ONE_THOUSAND;
+/// Income this close below a phase-out is reported as approaching it.
+const PROXIMITY: i64 = 5000;
+/// Suggestions saving less than this are not worth making.
+const MINIMUM_SAVINGS: Decimal = Decimal::ONE;
+/// Ages from which catch-up contributions are allowed.
+const ELECTIVE_DEFERRAL_CATCH_UP_AGE: i32 = 50;
+const IRA_CATCH_UP_AGE: i32 = 50;
+const HSA_CATCH_UP_AGE: i32 = 55;
+/// W-2 box 12 codes for deferrals under the 402(g) limit, pre-tax and Roth.
+const ELECTIVE_DEFERRAL_CODES: [&str; 6] = ["D", "E", "F", "S", "AA", "BB"];
+/// W-2 box 12 codes that show the employee is an active participant in a workplace plan.
+const RETIREMENT_PLAN_CODES: [&str; 9] = ["D", "E", "F", "G", "H", "S", "AA", "BB", "EE"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginalItem {
+    Wages,
+    LongTermCapitalGain,
+    /// Schedule 1 adjustment, such as a deductible contribution
+    AboveTheLineDeduction,
+    /// Schedule A line 16, which only helps when itemizing
+    ItemizedDeduction,
+}
+
+/// Effect of the next $1,000 of one kind of income or deduction.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarginalRate {
+    pub item: MarginalItem,
+    pub amount: Decimal,
+    /// Change in Form 1040 line 24 less refundable credits
+    pub federal_tax_change: Decimal,
+    pub state_tax_change: Decimal,
+    /// Tax added per dollar of income, or saved per dollar of deduction
+    pub rate: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseOutProgram {
+    EarnedIncomeCredit,
+    ChildTaxCredit,
+    /// Traditional IRA deduction for someone covered by a workplace plan
+    IraDeduction,
+    /// Net investment income tax, which phases in rather than out
+    NetInvestmentIncomeTax,
+}
+
+impl PhaseOutProgram {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhaseOutProgram::EarnedIncomeCredit => "earned income credit",
+            PhaseOutProgram::ChildTaxCredit => "child tax credit",
+            PhaseOutProgram::IraDeduction => "IRA deduction",
+            PhaseOutProgram::NetInvestmentIncomeTax => "net investment income tax",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseOutPosition {
+    Below,
+    /// Within $5,000 of the start
+    Approaching,
+    /// Past the start; for the net investment income tax, which has no end, the tax applies
+    Within,
+    Above,
+}
+
+/// Where income sits against one phase-out range.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseOutProximity {
+    pub program: PhaseOutProgram,
+    /// What the range is measured against, e.g. `modified_adjusted_gross_income`
+    pub measure: &'static str,
+    pub income: Decimal,
+    pub start: Decimal,
+    /// Where the benefit is gone; `None` for the net investment income tax
+    pub end: Option<Decimal>,
+    /// Start less income; negative once income is past the start
+    pub distance: Decimal,
+    pub position: PhaseOutPosition,
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// Pre-tax 401(k), 403(b) or similar deferral through payroll
+    ElectiveDeferral,
+    TraditionalIra,
+    HealthSavingsAccount,
+}
+
+/// A contribution the return has room for, with the scenario run that quantifies it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    /// Whose limit the contribution uses; `None` when it is split between spouses
+    pub owner: Option<Owner>,
+    pub amount: Decimal,
+    /// Federal and state tax saved
+    pub savings: Decimal,
+    /// Savings per dollar contributed
+    pub rate: Decimal,
+    /// Set when the amount is what brings income back to the start of this phase-out
+    pub phase_out: Option<PhaseOutProgram>,
+    pub description: String,
+    /// Federal lines of the scenario against the return as filed
+    pub deltas: Vec<LineDelta>,
+    pub states: Vec<StateDelta>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxAnalytics {
+    pub year: i32,
+    pub filing_status: FilingStatus,
+    /// Form 1040 line 24 less refundable credits
+    pub federal_tax: Decimal,
+    pub state_tax: Decimal,
+    /// Rate of the ordinary income bracket taxable income ends in
+    pub bracket_rate: Decimal,
+    pub marginal_rates: Vec<MarginalRate>,
+    pub phase_outs: Vec<PhaseOutProximity>,
+    /// Largest savings first
+    pub suggestions: Vec<Suggestion>,
+    /// Simplifications the suggestions rely on
+    pub assumptions: Vec<String>,
+}
+
+pub struct TaxAnalyzer;
+
+impl TaxAnalyzer {
+    /// Figures the marginal rates, phase-outs and suggestions for a return, failing for years without a rule table.
+    pub fn analyze(input: &TaxInput) -> Result<TaxAnalytics, ErrorBag> {
+        let rules = TaxRules::for_year(input.year)?;
+        let base = TaxEngine::calculate(input)?;
+        let (federal_tax, state_tax) = Self::tax(&base);
+
+        let marginal_rates = [
+            MarginalItem::Wages,
+            MarginalItem::LongTermCapitalGain,
+            MarginalItem::AboveTheLineDeduction,
+            MarginalItem::ItemizedDeduction,
+        ]
+            .into_iter()
+            .map(|item| Self::marginal_rate(input, &base, item))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let phase_outs = Self::phase_outs(input, rules, &base);
+
+        let mut assumptions = Vec::new();
+        let mut suggestions = Vec::new();
+        Self::elective_deferrals(input, rules, &base, &phase_outs, &mut suggestions, &mut assumptions)?;
+        Self::traditional_ira(input, rules, &base, &mut suggestions, &mut assumptions)?;
+        Self::health_savings_account(input, rules, &base, &mut suggestions, &mut assumptions)?;
+        suggestions.retain(|suggestion| suggestion.savings >= MINIMUM_SAVINGS);
+        suggestions.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.savings));
+
+        Ok(TaxAnalytics {
+            year: input.year,
+            filing_status: input.filing_status,
+            federal_tax,
+            state_tax,
+            bracket_rate: base.marginal_rate,
+            marginal_rates,
+            phase_outs,
+            suggestions,
+            assumptions,
+        })
+    }
+
+    /// Federal tax less refundable credits, and state tax.
+    fn tax(calculation: &TaxCalculation) -> (Decimal, Decimal) {
+        let federal = calculation.total_tax - calculation.refundable_credits;
+        let state = calculation.states.iter().map(|state| state.total_tax).sum();
+        (federal, state)
+    }
+
+    fn marginal_rate(input: &TaxInput, base: &TaxCalculation, item: MarginalItem) -> Result<MarginalRate, ErrorBag> {
+        let mut changed = input.clone();
+        match item {
+            MarginalItem::Wages => match changed.w2s.first_mut() {
+                Some(w2) => {
+                    w2.wages += MARGINAL_AMOUNT;
+                    for wages in [&mut w2.social_security_wages, &mut w2.medicare_wages, &mut w2.state_wages].into_iter().flatten() {
+                        *wages += MARGINAL_AMOUNT;
+                    }
+                }
+                None => changed.w2s.push(W2 { employer: "Additional wages".into(), wages: MARGINAL_AMOUNT, ..W2::default() }),
+            },
+            MarginalItem::LongTermCapitalGain => changed.capital_lots.push(CapitalLot {
+                description: "Additional long-term gain".into(),
+                date_acquired: NaiveDate::from_ymd_opt(input.year - 2, 1, 1),
+                date_sold: NaiveDate::from_ymd_opt(input.year, 12, 31),
+                proceeds: MARGINAL_AMOUNT,
+                term: Some(Term::LongTerm),
+                ..CapitalLot::default()
+            }),
+            MarginalItem::AboveTheLineDeduction => changed.adjustments.other += MARGINAL_AMOUNT,
+            MarginalItem::ItemizedDeduction => changed.itemized.other += MARGINAL_AMOUNT,
+        }
+
+        let (base_federal, base_state) = Self::tax(base);
+        let (federal, state) = Self::tax(&TaxEngine::calculate(&changed)?);
+        let federal_tax_change = federal - base_federal;
+        let state_tax_change = state - base_state;
+        let change = federal_tax_change + state_tax_change;
+        let rate = match item {
+            MarginalItem::Wages | MarginalItem::LongTermCapitalGain => change / MARGINAL_AMOUNT,
+            MarginalItem::AboveTheLineDeduction | MarginalItem::ItemizedDeduction => -change / MARGINAL_AMOUNT,
+        };
+
+        Ok(MarginalRate { item, amount: MARGINAL_AMOUNT, federal_tax_change, state_tax_change, rate: rate.round_dp(4) })
+    }
+
+    /// The phase-outs that apply to the return. The earned income credit is only reported
+    /// while it is allowed, the child tax credit with a dependent who qualifies for it or the
+    /// credit for other dependents, the IRA deduction when a W-2 shows plan participation, and
+    /// the net investment income tax with investment income.
+    fn phase_outs(input: &TaxInput, rules: &TaxRules, base: &TaxCalculation) -> Vec<PhaseOutProximity> {
+        let status = input.filing_status;
+        let agi = base.adjusted_gross_income;
+        let allowed = |kind: CreditKind| base.credits.credits.iter()
+            .filter(|credit| credit.credit == kind)
+            .map(|credit| credit.allowed)
+            .sum::<Decimal>();
+        let mut phase_outs = Vec::new();
+
+        if allowed(CreditKind::EarnedIncome) > Decimal::ZERO {
+            let eic = &rules.credits.earned_income;
+            let children = input.dependents.iter().filter(|d| d.earned_income_credit).count();
+            let schedule = &eic.schedules[children.min(eic.schedules.len() - 1)];
+            let start = *schedule.phase_out_start.get(status);
+            let end = start + schedule.maximum_credit / schedule.phase_out_rate;
+            let income = Self::earned_income(base).max(agi);
+            phase_outs.push(Self::proximity(PhaseOutProgram::EarnedIncomeCredit, "greater_of_earned_income_and_agi", income, start, Some(round_cents(end)), format!(
+                "the credit shrinks by {}% of income over ${start}", (schedule.phase_out_rate * Decimal::ONE_HUNDRED).normalize(),
+            )));
+        }
+
+        let credits = &rules.credits;
+        let children = input.dependents.iter().filter(|d| d.child_tax_credit).count();
+        let others = input.dependents.iter().filter(|d| d.credit_for_other_dependents).count();
+        let amount = credits.child_tax_credit * Decimal::from(children) + credits.other_dependent_credit * Decimal::from(others);
+        if amount > Decimal::ZERO {
+            let start = *credits.child_tax_credit_phase_out_start.get(status);
+            let end = start + (amount / credits.child_tax_credit_reduction).ceil() * Decimal::ONE_THOUSAND;
+            phase_outs.push(Self::proximity(PhaseOutProgram::ChildTaxCredit, "modified_adjusted_gross_income", agi, start, Some(end), format!(
+                "${} of credit is lost for each $1,000 (or part) of income over ${start}", credits.child_tax_credit_reduction,
+            )));
+        }
+
+        if Self::covered_by_plan(input) {
+            let range = rules.phase_outs.ira_deduction_covered.get(status);
+            // Modified AGI for the IRA deduction is figured without it.
+            let income = agi + input.adjustments.ira_deduction;
+            phase_outs.push(Self::proximity(PhaseOutProgram::IraDeduction, "modified_adjusted_gross_income", income, range.start, Some(range.end), format!(
+                "a W-2 shows workplace plan participation, so traditional IRA contributions stop being deductible between ${} and ${}", range.start, range.end,
+            )));
+        }
+
+        let niit = &base.net_investment_income_tax;
+        if niit.net_investment_income > Decimal::ZERO {
+            phase_outs.push(Self::proximity(PhaseOutProgram::NetInvestmentIncomeTax, "modified_adjusted_gross_income", niit.modified_adjusted_gross_income, niit.threshold, None, format!(
+                "{}% applies to the smaller of ${} of net investment income and income over ${}",
+                (rules.net_investment_income_tax.rate * Decimal::ONE_HUNDRED).normalize(), niit.net_investment_income, niit.threshold,
+            )));
+        }
+
+        phase_outs
+    }
+
+    fn proximity(program: PhaseOutProgram, measure: &'static str, income: Decimal, start: Decimal, end: Option<Decimal>, explanation: String) -> PhaseOutProximity {
+        let distance = start - income;
+        let position = match distance {
+            distance if distance > Decimal::from(PROXIMITY) => PhaseOutPosition::Below,
+            distance if distance >= Decimal::ZERO => PhaseOutPosition::Approaching,
+            _ if end.is_none_or(|end| income < end) => PhaseOutPosition::Within,
+            _ => PhaseOutPosition::Above,
+        };
+        PhaseOutProximity { program, measure, income, start, end, distance, position, explanation }
+    }
+
+    /// Wages plus Schedule C profit less the deductible half of self-employment tax.
+    fn earned_income(base: &TaxCalculation) -> Decimal {
+        base.income.wages
+            + base.schedule_c.iter().map(|schedule| schedule.net_profit).sum::<Decimal>()
+            - base.schedule_se.iter().map(|se| se.deduction).sum::<Decimal>()
+    }
+
+    fn covered_by_plan(input: &TaxInput) -> bool {
+        input.w2s.iter().any(|w2| RETIREMENT_PLAN_CODES.iter().any(|code| w2.box_12_amount(code) > Decimal::ZERO))
+    }
+
+    /// Spouses on a joint return each have their own limits; otherwise everything is the taxpayer's.
+    fn owners(input: &TaxInput) -> Vec<Owner> {
+        match input.filing_status {
+            FilingStatus::MarriedFilingJointly => Owner::ALL.to_vec(),
+            _ => vec![Owner::Taxpayer],
+        }
+    }
+
+    fn owner_of(input: &TaxInput, owner: Owner) -> Owner {
+        match input.filing_status {
+            FilingStatus::MarriedFilingJointly => owner,
+            _ => Owner::Taxpayer,
+        }
+    }
+
+    fn age(input: &TaxInput, owner: Owner) -> Option<i32> {
+        match owner {
+            Owner::Taxpayer => input.taxpayer_age,
+            Owner::Spouse => input.spouse_age,
+        }
+    }
+
+    fn possessive(owner: Owner) -> &'static str {
+        match owner {
+            Owner::Taxpayer => "your",
+            Owner::Spouse => "your spouse's",
+        }
+    }
+
+    /// Runs `changed` against the return as filed and turns it into a suggestion.
+    fn suggestion(
+        base: &TaxCalculation,
+        changed: &TaxInput,
+        kind: SuggestionKind,
+        owner: Option<Owner>,
+        amount: Decimal,
+        phase_out: Option<PhaseOutProgram>,
+        describe: impl FnOnce(Decimal) -> String,
+    ) -> Result<Suggestion, ErrorBag> {
+        let comparison = ScenarioComparison::new(base.clone(), TaxEngine::calculate(changed)?, None);
+        let (base_federal, base_state) = Self::tax(base);
+        let (federal, state) = Self::tax(&comparison.scenario);
+        let savings = round_cents(base_federal + base_state - federal - state);
+
+        Ok(Suggestion {
+            kind,
+            owner,
+            amount,
+            savings,
+            rate: (savings / amount).round_dp(4),
+            phase_out,
+            description: describe(savings.normalize()),
+            deltas: comparison.deltas,
+            states: comparison.states,
+        })
+    }
+
+    /// Remaining 402(g) room for each person with W-2 wages, limited to those wages.
+    fn deferral_room(input: &TaxInput, rules: &TaxRules) -> Vec<(Owner, Decimal)> {
+        let limits = &rules.contribution_limits;
+        Self::owners(input).into_iter()
+            .filter_map(|owner| {
+                let w2s: Vec<&W2> = input.w2s.iter().filter(|w2| Self::owner_of(input, w2.owner) == owner).collect();
+                let wages = w2s.iter().map(|w2| w2.wages).sum::<Decimal>();
+                let deferred = w2s.iter()
+                    .map(|w2| ELECTIVE_DEFERRAL_CODES.iter().map(|code| w2.box_12_amount(code)).sum::<Decimal>())
+                    .sum::<Decimal>();
+                let catch_up = match Self::age(input, owner).is_some_and(|age| age >= ELECTIVE_DEFERRAL_CATCH_UP_AGE) {
+                    true => limits.elective_deferral_catch_up,
+                    false => Decimal::ZERO,
+                };
+                let room = (limits.elective_deferral + catch_up - deferred).min(wages).max(Decimal::ZERO).floor();
+                (room > Decimal::ZERO).then_some((owner, room))
+            })
+            .collect()
+    }
+
+    /// Moves `amount` of `owner`'s box 1 (and box 16) wages into a box 12 code D deferral,
+    /// W-2 by W-2. Boxes 3 and 5 keep the deferral, since it is still subject to payroll tax.
+    /// The deferral also counts toward the saver's credit.
+    fn defer(input: &TaxInput, owner: Owner, amount: Decimal) -> TaxInput {
+        let mut changed = input.clone();
+        let mut remaining = amount;
+        for w2 in changed.w2s.iter_mut().filter(|w2| Self::owner_of(input, w2.owner) == owner) {
+            let deferred = remaining.min(w2.wages.max(Decimal::ZERO));
+            if deferred.is_zero() {
+                continue;
+            }
+            w2.social_security_wages.get_or_insert(w2.wages);
+            w2.medicare_wages.get_or_insert(w2.wages);
+            w2.wages -= deferred;
+            if let Some(state_wages) = w2.state_wages.as_mut() {
+                *state_wages = (*state_wages - deferred).max(Decimal::ZERO);
+            }
+            w2.box_12.push(W2Box12 { code: "D".into(), amount: deferred });
+            remaining -= deferred;
+        }
+        match owner {
+            Owner::Taxpayer => changed.credits.taxpayer_retirement_contributions += amount,
+            Owner::Spouse => changed.credits.spouse_retirement_contributions += amount,
+        }
+        changed
+    }
+
+    /// Deferring the rest of each person's 402(g) limit, and, for a phase-out income is
+    /// inside of, deferring just enough to bring income back to its start when the room allows.
+    fn elective_deferrals(
+        input: &TaxInput,
+        rules: &TaxRules,
+        base: &TaxCalculation,
+        phase_outs: &[PhaseOutProximity],
+        suggestions: &mut Vec<Suggestion>,
+        assumptions: &mut Vec<String>,
+    ) -> Result<(), ErrorBag> {
+        let room = Self::deferral_room(input, rules);
+        if room.is_empty() {
+            return Ok(());
+        }
+        assumptions.push(format!(
+            "401(k) room is the ${} limit (plus ${} catch-up at {ELECTIVE_DEFERRAL_CATCH_UP_AGE} or older) less W-2 box 12 deferrals; it assumes the employer offers a plan and the state excludes deferrals from box 16 wages",
+            rules.contribution_limits.elective_deferral, rules.contribution_limits.elective_deferral_catch_up,
+        ));
+
+        for &(owner, amount) in &room {
+            let whose = Self::possessive(owner);
+            suggestions.push(Self::suggestion(base, &Self::defer(input, owner, amount), SuggestionKind::ElectiveDeferral, Some(owner), amount, None, |savings| format!(
+                "Contributing ${amount} more to {whose} 401(k) saves ${savings}",
+            ))?);
+        }
+
+        for phase_out in phase_outs.iter().filter(|phase_out| phase_out.position == PhaseOutPosition::Within) {
+            let needed = (-phase_out.distance).ceil();
+            let Some(&(owner, available)) = room.iter().find(|(_, available)| *available >= needed) else {
+                continue;
+            };
+            if needed == available {
+                continue;
+            }
+            let whose = Self::possessive(owner);
+            let program = phase_out.program;
+            suggestions.push(Self::suggestion(base, &Self::defer(input, owner, needed), SuggestionKind::ElectiveDeferral, Some(owner), needed, Some(program), |savings| format!(
+                "Contributing ${needed} more to {whose} 401(k) brings income back to the start of the {} phase-out and saves ${savings}", program.name(),
+            ))?);
+        }
+
+        Ok(())
+    }
+
+    /// The rest of the traditional IRA limit, limited to compensation and, when a W-2 shows plan
+    /// participation, to the deductible part.
+    fn traditional_ira(
+        input: &TaxInput,
+        rules: &TaxRules,
+        base: &TaxCalculation,
+        suggestions: &mut Vec<Suggestion>,
+        assumptions: &mut Vec<String>,
+    ) -> Result<(), ErrorBag> {
+        let limits = &rules.contribution_limits;
+        let limit = Self::owners(input).into_iter()
+            .map(|owner| match Self::age(input, owner).is_some_and(|age| age >= IRA_CATCH_UP_AGE) {
+                true => limits.ira + limits.ira_catch_up,
+                false => limits.ira,
+            })
+            .sum::<Decimal>();
+        let mut amount = (limit - input.adjustments.ira_deduction).min(Self::earned_income(base)).max(Decimal::ZERO);
+
+        if Self::covered_by_plan(input) {
+            let range = rules.phase_outs.ira_deduction_covered.get(input.filing_status);
+            let income = base.adjusted_gross_income + input.adjustments.ira_deduction;
+            amount -= amount * range.reduction_fraction(income);
+            assumptions.push(format!(
+                "The IRA deduction is phased out over ${} to ${} because a W-2 shows plan participation; a spouse who is not covered has a higher range",
+                range.start, range.end,
+            ));
+        }
+        let amount = amount.floor();
+        if amount <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        let mut changed = input.clone();
+        changed.adjustments.ira_deduction += amount;
+        changed.credits.taxpayer_retirement_contributions += amount;
+        let owner = (Self::owners(input).len() == 1).then_some(Owner::Taxpayer);
+        suggestions.push(Self::suggestion(base, &changed, SuggestionKind::TraditionalIra, owner, amount, None, |savings| format!(
+            "Contributing ${amount} to a traditional IRA before the filing deadline saves ${savings}",
+        ))?);
+
+        Ok(())
+    }
+
+    /// Only suggested when the return already shows HSA contributions, since eligibility needs
+    /// a high-deductible health plan. The self-only limit is used because coverage is not recorded.
+    fn health_savings_account(
+        input: &TaxInput,
+        rules: &TaxRules,
+        base: &TaxCalculation,
+        suggestions: &mut Vec<Suggestion>,
+        assumptions: &mut Vec<String>,
+    ) -> Result<(), ErrorBag> {
+        let limits = &rules.contribution_limits;
+        let employer = input.w2s.iter().map(|w2| w2.box_12_amount("W")).sum::<Decimal>();
+        let contributed = employer + input.adjustments.hsa_deduction;
+        if contributed <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        let catch_up = match input.taxpayer_age.is_some_and(|age| age >= HSA_CATCH_UP_AGE) {
+            true => limits.hsa_catch_up,
+            false => Decimal::ZERO,
+        };
+        let amount = (limits.hsa_self_only + catch_up - contributed).max(Decimal::ZERO).floor();
+        if amount <= Decimal::ZERO {
+            return Ok(());
+        }
+        assumptions.push(format!(
+            "HSA room assumes self-only coverage (${}); family coverage allows ${}",
+            limits.hsa_self_only, limits.hsa_family,
+        ));
+
+        let mut changed = input.clone();
+        changed.adjustments.hsa_deduction += amount;
+        suggestions.push(Self::suggestion(base, &changed, SuggestionKind::HealthSavingsAccount, None, amount, None, |savings| format!(
+            "Contributing ${amount} more to your HSA before the filing deadline saves ${savings}",
+        ))?);
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use super::{MarginalItem, PhaseOutPosition, PhaseOutProgram, SuggestionKind, TaxAnalytics, TaxAnalyzer};
+    use crate::models::filing_profile_model::Owner;
+    use crate::tax_engine::input::TaxInput;
+
+    fn analyze(input: Value) -> TaxAnalytics {
+        TaxAnalyzer::analyze(&serde_json::from_value::<TaxInput>(input).unwrap()).unwrap()
+    }
+
+    fn rate(analytics: &TaxAnalytics, item: MarginalItem) -> Decimal {
+        analytics.marginal_rates.iter().find(|rate| rate.item == item).unwrap().rate
+    }
+
+    /// Head of household with one child and $215,000 of wages: $15,000 into the child tax
+    /// credit phase-out, in the 32% bracket and over the additional Medicare tax threshold.
+    fn child_tax_credit_phase_out() -> TaxAnalytics {
+        analyze(json!({
+            "year": 2024,
+            "filing_status": "head_of_household",
+            "dependents": [{ "name": "Ava", "age": 10, "child_tax_credit": true, "earned_income_credit": true }],
+            "w2s": [{ "employer": "Acme Corp", "wages": 215000, "federal_tax_withheld": 40000 }]
+        }))
+    }
+
+    #[test]
+    fn marginal_rates_include_phase_outs_and_surtaxes() {
+        let analytics = child_tax_credit_phase_out();
+
+        assert_eq!(analytics.bracket_rate, Decimal::new(32, 2));
+        // 32% bracket, $50 of child tax credit per $1,000 and 0.9% additional Medicare tax
+        assert_eq!(rate(&analytics, MarginalItem::Wages), Decimal::new(379, 3));
+        // 15% capital gain rate, 3.8% net investment income tax and the lost credit
+        assert_eq!(rate(&analytics, MarginalItem::LongTermCapitalGain), Decimal::new(238, 3));
+        assert_eq!(rate(&analytics, MarginalItem::AboveTheLineDeduction), Decimal::new(37, 2));
+        // $1,000 of itemized deductions is still under the $21,900 standard deduction
+        assert_eq!(rate(&analytics, MarginalItem::ItemizedDeduction), Decimal::ZERO);
+    }
+
+    #[test]
+    fn deferral_back_to_the_child_tax_credit_phase_out_start() {
+        let analytics = child_tax_credit_phase_out();
+
+        let phase_out = &analytics.phase_outs[0];
+        assert_eq!(analytics.phase_outs.len(), 1);
+        assert_eq!(phase_out.program, PhaseOutProgram::ChildTaxCredit);
+        assert_eq!(phase_out.position, PhaseOutPosition::Within);
+        assert_eq!((phase_out.start, phase_out.end, phase_out.distance), (200000.into(), Some(240000.into()), Decimal::from(-15000)));
+
+        // $1,150 at 32% and $13,850 at 24%, plus $750 of credit
+        let needed = analytics.suggestions.iter().find(|suggestion| suggestion.phase_out == Some(PhaseOutProgram::ChildTaxCredit)).unwrap();
+        assert_eq!(needed.kind, SuggestionKind::ElectiveDeferral);
+        assert_eq!(needed.amount, Decimal::from(15000));
+        assert_eq!(needed.savings, Decimal::from(4442));
+
+        // The full limit: $1,150 at 32% and $21,850 at 24%, plus $750 of credit; Medicare
+        // wages keep the deferral, so the additional Medicare tax is unchanged
+        let full = &analytics.suggestions[0];
+        assert_eq!((full.kind, full.phase_out, full.owner), (SuggestionKind::ElectiveDeferral, None, Some(Owner::Taxpayer)));
+        assert_eq!(full.amount, Decimal::from(23000));
+        assert_eq!(full.savings, Decimal::from(6362));
+
+        // $1,150 at 32% and $5,850 at 24%, plus $350 of credit
+        let ira = analytics.suggestions.iter().find(|suggestion| suggestion.kind == SuggestionKind::TraditionalIra).unwrap();
+        assert_eq!(ira.amount, Decimal::from(7000));
+        assert_eq!(ira.savings, Decimal::from(2122));
+    }
+
+    #[test]
+    fn deferral_back_to_the_earned_income_credit_phase_out_start() {
+        let analytics = analyze(json!({
+            "year": 2024,
+            "filing_status": "head_of_household",
+            "taxpayer_age": 35,
+            "dependents": [
+                { "name": "Ava", "age": 8, "child_tax_credit": true, "earned_income_credit": true, "child_and_dependent_care_credit": true },
+                { "name": "Ben", "age": 14, "child_tax_credit": true, "earned_income_credit": true }
+            ],
+            "w2s": [{ "employer": "Acme Corp", "wages": 30000, "federal_tax_withheld": 1000 }],
+            "credits": { "dependent_care_expenses": 4000 }
+        }));
+
+        let eic = analytics.phase_outs.iter().find(|phase_out| phase_out.program == PhaseOutProgram::EarnedIncomeCredit).unwrap();
+        assert_eq!(eic.position, PhaseOutPosition::Within);
+        assert_eq!((eic.start, eic.end, eic.distance), (22720.into(), Some(Decimal::new(5576843, 2)), Decimal::from(-7280)));
+        let ctc = analytics.phase_outs.iter().find(|phase_out| phase_out.program == PhaseOutProgram::ChildTaxCredit).unwrap();
+        assert_eq!(ctc.position, PhaseOutPosition::Below);
+
+        // The credit goes from $5,422 to $6,959 and the additional child tax credit from
+        // $3,400 to 15% of $20,220, $3,033
+        let needed = analytics.suggestions.iter().find(|suggestion| suggestion.phase_out == Some(PhaseOutProgram::EarnedIncomeCredit)).unwrap();
+        assert_eq!(needed.amount, Decimal::from(7280));
+        assert_eq!(needed.savings, Decimal::from(1170));
+        // Deferring the full limit costs more in credits than it saves
+        assert!(!analytics.suggestions.iter().any(|suggestion| suggestion.kind == SuggestionKind::ElectiveDeferral && suggestion.amount == Decimal::from(23000)));
+    }
+
+    #[test]
+    fn phase_out_positions() {
+        let position = |income: i64, end: Option<i64>| {
+            TaxAnalyzer::proximity(PhaseOutProgram::IraDeduction, "modified_adjusted_gross_income", income.into(), 77000.into(), end.map(Decimal::from), String::new()).position
+        };
+
+        assert_eq!(position(71999, Some(87000)), PhaseOutPosition::Below);
+        assert_eq!(position(72000, Some(87000)), PhaseOutPosition::Approaching);
+        assert_eq!(position(77000, Some(87000)), PhaseOutPosition::Approaching);
+        assert_eq!(position(77001, Some(87000)), PhaseOutPosition::Within);
+        assert_eq!(position(87000, Some(87000)), PhaseOutPosition::Above);
+        assert_eq!(position(500000, None), PhaseOutPosition::Within);
+    }
+
+    #[test]
+    fn ira_deduction_is_limited_by_plan_participation() {
+        let analytics = analyze(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 82000, "box_12": [{ "code": "D", "amount": 5000 }] }]
+        }));
+
+        let ira = analytics.phase_outs.iter().find(|phase_out| phase_out.program == PhaseOutProgram::IraDeduction).unwrap();
+        assert_eq!(ira.position, PhaseOutPosition::Within);
+        let amount = |kind| analytics.suggestions.iter().find(|suggestion| suggestion.kind == kind).unwrap().amount;
+        // Halfway through the $77,000 to $87,000 range
+        assert_eq!(amount(SuggestionKind::TraditionalIra), Decimal::from(3500));
+        assert_eq!(amount(SuggestionKind::ElectiveDeferral), Decimal::from(18000));
+    }
+
+    #[test]
+    fn catch_up_contributions_at_55_with_an_hsa() {
+        let analytics = analyze(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "taxpayer_age": 56,
+            "w2s": [{ "employer": "Acme", "wages": 80000, "box_12": [{ "code": "W", "amount": 2000 }] }]
+        }));
+        let suggestion = |kind| analytics.suggestions.iter().find(|suggestion| suggestion.kind == kind).unwrap();
+
+        // $65,400 taxable stays in the 22% bracket for both
+        let hsa = suggestion(SuggestionKind::HealthSavingsAccount);
+        assert_eq!(hsa.amount, Decimal::from(3150));
+        assert_eq!(hsa.savings, Decimal::from(693));
+        let ira = suggestion(SuggestionKind::TraditionalIra);
+        assert_eq!(ira.amount, Decimal::from(8000));
+        assert_eq!(ira.savings, Decimal::from(1760));
+        assert_eq!(suggestion(SuggestionKind::ElectiveDeferral).amount, Decimal::from(30500));
+        assert!(analytics.suggestions.windows(2).all(|pair| pair[0].savings >= pair[1].savings));
+    }
+
+    #[test]
+    fn no_hsa_suggestion_without_hsa_contributions() {
+        let analytics = analyze(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 80000 }]
+        }));
+
+        assert!(!analytics.suggestions.iter().any(|suggestion| suggestion.kind == SuggestionKind::HealthSavingsAccount));
+    }
+}
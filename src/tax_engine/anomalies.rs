@@ -0,0 +1,363 @@
+//! Checks for values in a year's tax data that are probably wrong: misread by the OCR
+//! extraction or mistyped. Each `AnomalyRule` can be turned off or given its own threshold;
+//! findings point back to the record they are about.
+//!
+//! Round amounts are only looked for in extracted records. A truncated read ("52,000" for
+//! "52,000.37") is the OCR failure they catch, and manually entered amounts are often rounded
+//! on purpose.
+
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::anomaly_model::{AnomalyRule, AnomalySeverity};
+use crate::tax_engine::checklist::same_name;
+use crate::tax_engine::input::{RecordSource, TaxInput};
+use crate::tax_engine::rules::TaxRules;
+
+/// Whether a rule runs and with which threshold, after the user's overrides.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleSetting {
+    pub rule: AnomalyRule,
+    pub severity: AnomalySeverity,
+    pub enabled: bool,
+    pub threshold: Option<Decimal>,
+    pub default_threshold: Option<Decimal>,
+}
+
+impl RuleSetting {
+    pub fn default_for(rule: AnomalyRule) -> RuleSetting {
+        RuleSetting {
+            rule,
+            severity: rule.severity(),
+            enabled: true,
+            threshold: rule.default_threshold(),
+            default_threshold: rule.default_threshold(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyFinding {
+    pub rule: AnomalyRule,
+    pub severity: AnomalySeverity,
+    pub source: Option<RecordSource>,
+    pub message: String,
+    pub amount: Option<Decimal>,
+}
+
+pub struct AnomalyDetector<'a> {
+    input: &'a TaxInput,
+    settings: &'a [RuleSetting],
+    findings: Vec<AnomalyFinding>,
+}
+
+impl<'a> AnomalyDetector<'a> {
+    /// Runs every enabled rule over `input`. `prior` is the year before, for wage jumps; `rules`
+    /// is the year's rule table, for the Social Security wage base. Rules missing from
+    /// `settings` run with their defaults.
+    pub fn detect(input: &TaxInput, prior: Option<&TaxInput>, rules: Option<&TaxRules>, settings: &[RuleSetting]) -> Vec<AnomalyFinding> {
+        let mut detector = AnomalyDetector { input, settings, findings: Vec::new() };
+
+        for rule in AnomalyRule::ALL {
+            let Some(threshold) = detector.enabled(rule) else {
+                continue;
+            };
+            match rule {
+                AnomalyRule::WithholdingAboveWages => detector.withholding_above_wages(),
+                AnomalyRule::WageJump => if let Some(prior) = prior {
+                    detector.wage_jump(prior, threshold.unwrap_or_default());
+                },
+                AnomalyRule::EinNameConflict => detector.ein_name_conflict(),
+                AnomalyRule::SocialSecurityWagesAboveBase => if let Some(rules) = rules {
+                    detector.social_security_wages_above_base(rules.social_security_wage_base);
+                },
+                AnomalyRule::RoundAmount => detector.round_amount(threshold.unwrap_or_default()),
+                AnomalyRule::Form1099BelowThreshold => detector.form_1099_below_threshold(threshold.unwrap_or_default()),
+            }
+        }
+
+        detector.findings
+    }
+
+    /// The rule's threshold when it is enabled.
+    fn enabled(&self, rule: AnomalyRule) -> Option<Option<Decimal>> {
+        let setting = self.settings.iter()
+            .find(|setting| setting.rule == rule)
+            .cloned()
+            .unwrap_or_else(|| RuleSetting::default_for(rule));
+        setting.enabled.then_some(setting.threshold)
+    }
+
+    fn flag(&mut self, rule: AnomalyRule, source: Option<&RecordSource>, amount: Option<Decimal>, message: String) {
+        self.findings.push(AnomalyFinding { rule, severity: rule.severity(), source: source.cloned(), message, amount });
+    }
+
+    fn withholding_above_wages(&mut self) {
+        let input = self.input;
+        for w2 in input.w2s.iter().filter(|w2| w2.federal_tax_withheld > w2.wages) {
+            self.flag(AnomalyRule::WithholdingAboveWages, w2.source.as_ref(), Some(w2.federal_tax_withheld), format!(
+                "W-2 from {} shows ${} of federal tax withheld (box 2) on ${} of wages (box 1); check both boxes",
+                w2.employer, w2.federal_tax_withheld, w2.wages,
+            ));
+        }
+        for form in input.form_1099s.iter().filter(|form| form.federal_tax_withheld > form.income) {
+            self.flag(AnomalyRule::WithholdingAboveWages, form.source.as_ref(), Some(form.federal_tax_withheld), format!(
+                "{} from {} shows ${} of federal tax withheld on ${} of income; check both amounts",
+                form.kind().form_name(), form.payer, form.federal_tax_withheld, form.income,
+            ));
+        }
+    }
+
+    /// Wages are totalled per employer in both years, so an employer's W-2s for several
+    /// states count together. `threshold` is in percent.
+    fn wage_jump(&mut self, prior: &TaxInput, threshold: Decimal) {
+        let input = self.input;
+        for (employer, wages) in wages_by_employer(input) {
+            let prior_wages = wages_by_employer(prior).into_iter()
+                .filter(|(prior_employer, _)| same_name(prior_employer, employer))
+                .map(|(_, wages)| wages)
+                .sum::<Decimal>();
+            if prior_wages <= Decimal::ZERO {
+                continue;
+            }
+            let increase = (wages - prior_wages) / prior_wages * Decimal::ONE_HUNDRED;
+            if increase > threshold {
+                let source = input.w2s.iter().find(|w2| w2.employer.trim() == employer).and_then(|w2| w2.source.as_ref());
+                self.flag(AnomalyRule::WageJump, source, Some(wages), format!(
+                    "Wages from {employer} rose {}% from ${prior_wages} in {} to ${wages}; check for a misread digit",
+                    increase.round_dp(0), prior.year,
+                ));
+            }
+        }
+    }
+
+    fn ein_name_conflict(&mut self) {
+        let input = self.input;
+        let issuers = input.w2s.iter()
+            .map(|w2| (w2.employer_ein.as_deref(), w2.employer.as_str(), w2.source.as_ref()))
+            .chain(input.form_1099s.iter().map(|form| (form.payer_tin.as_deref(), form.payer.as_str(), form.source.as_ref())));
+
+        let mut by_ein: BTreeMap<String, Vec<(&str, Option<&RecordSource>)>> = BTreeMap::new();
+        for (ein, name, source) in issuers {
+            let Some(ein) = ein.map(|ein| ein.chars().filter(char::is_ascii_digit).collect::<String>()).filter(|ein| !ein.is_empty()) else {
+                continue;
+            };
+            by_ein.entry(ein).or_default().push((name.trim(), source));
+        }
+
+        for (digits, issuers) in by_ein {
+            let ein = match digits.len() {
+                9 => format!("{}-{}", &digits[..2], &digits[2..]),
+                _ => digits,
+            };
+            let Some((first, _)) = issuers.first().copied() else {
+                continue;
+            };
+            for (name, source) in issuers.iter().skip(1).filter(|(name, _)| !same_name(first, name)) {
+                self.flag(AnomalyRule::EinNameConflict, *source, None, format!(
+                    "EIN {ein} appears as both {first} and {name}; one of the names or numbers was probably misread",
+                ));
+            }
+        }
+    }
+
+    fn social_security_wages_above_base(&mut self, wage_base: Decimal) {
+        let input = self.input;
+        for w2 in input.w2s.iter() {
+            let Some(wages) = w2.social_security_wages.filter(|wages| *wages > wage_base) else {
+                continue;
+            };
+            self.flag(AnomalyRule::SocialSecurityWagesAboveBase, w2.source.as_ref(), Some(wages), format!(
+                "W-2 from {} shows ${wages} of Social Security wages (box 3), more than the {} wage base of ${wage_base}",
+                w2.employer, input.year,
+            ));
+        }
+    }
+
+    /// `threshold` is in dollars; zero turns the rule off.
+    fn round_amount(&mut self, threshold: Decimal) {
+        if threshold <= Decimal::ZERO {
+            return;
+        }
+        let input = self.input;
+        let round = |amount: Decimal| amount >= threshold && (amount % threshold).is_zero();
+        let extracted = |source: Option<&RecordSource>| source.is_some_and(|source| source.document_type.is_some());
+
+        let mut amounts: Vec<(Option<&RecordSource>, String, Decimal)> = Vec::new();
+        for w2 in input.w2s.iter().filter(|w2| extracted(w2.source.as_ref())) {
+            amounts.push((w2.source.as_ref(), format!("W-2 from {} box 1", w2.employer), w2.wages));
+            amounts.push((w2.source.as_ref(), format!("W-2 from {} box 2", w2.employer), w2.federal_tax_withheld));
+        }
+        for form in input.form_1099s.iter().filter(|form| extracted(form.source.as_ref())) {
+            amounts.push((form.source.as_ref(), format!("{} from {}", form.kind().form_name(), form.payer), form.income));
+        }
+        for form in input.form_1098s.iter().filter(|form| extracted(form.source.as_ref())) {
+            amounts.push((form.source.as_ref(), format!("1098 from {} box 1", form.lender), form.mortgage_interest));
+        }
+
+        for (source, label, amount) in amounts.into_iter().filter(|(_, _, amount)| round(*amount)) {
+            self.flag(AnomalyRule::RoundAmount, source, Some(amount), format!(
+                "{label} was read as exactly ${amount}; compare it with the form in case cents or digits were cut off",
+            ));
+        }
+    }
+
+    /// `threshold` is in dollars.
+    fn form_1099_below_threshold(&mut self, threshold: Decimal) {
+        let input = self.input;
+        for form in input.form_1099s.iter().filter(|form| form.income > Decimal::ZERO && form.income < threshold) {
+            self.flag(AnomalyRule::Form1099BelowThreshold, form.source.as_ref(), Some(form.income), format!(
+                "{} from {} shows ${}, below the ${threshold} payers have to report; check the amount was read correctly",
+                form.kind().form_name(), form.payer, form.income,
+            ));
+        }
+    }
+}
+
+/// Box 1 wages per employer name, in the order the employers first appear.
+fn wages_by_employer(input: &TaxInput) -> Vec<(&str, Decimal)> {
+    let mut totals: Vec<(&str, Decimal)> = Vec::new();
+    for w2 in &input.w2s {
+        let employer = w2.employer.trim();
+        match totals.iter_mut().find(|(name, _)| same_name(name, employer)) {
+            Some((_, wages)) => *wages += w2.wages,
+            None => totals.push((employer, w2.wages)),
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use super::{AnomalyDetector, AnomalyFinding, RuleSetting};
+    use crate::models::anomaly_model::AnomalyRule;
+    use crate::tax_engine::input::{RecordSource, TaxInput};
+    use crate::tax_engine::rules::TaxRules;
+
+    fn input(data: Value) -> TaxInput {
+        let mut data = data;
+        data["year"] = json!(2024);
+        data["filing_status"] = json!("single");
+        serde_json::from_value(data).unwrap()
+    }
+
+    /// A record extracted from an uploaded form.
+    fn extracted(path: &str) -> Option<RecordSource> {
+        Some(RecordSource { path: path.into(), document_id: None, document_type: Some("W2".into()) })
+    }
+
+    fn override_threshold(rule: AnomalyRule, threshold: i64) -> Vec<RuleSetting> {
+        vec![RuleSetting { threshold: Some(threshold.into()), ..RuleSetting::default_for(rule) }]
+    }
+
+    fn findings(input: &TaxInput, prior: Option<&TaxInput>, settings: &[RuleSetting], rule: AnomalyRule) -> Vec<AnomalyFinding> {
+        let rules = TaxRules::for_year(2024).unwrap();
+        AnomalyDetector::detect(input, prior, Some(rules), settings).into_iter().filter(|finding| finding.rule == rule).collect()
+    }
+
+    #[test]
+    fn withholding_above_wages() {
+        let flagged = input(json!({
+            "w2s": [{ "employer": "Acme", "wages": 1000, "federal_tax_withheld": 5000 }],
+            "form_1099s": [{ "payer": "Bank", "form": "INT", "income": 100, "federal_tax_withheld": 200 }]
+        }));
+        let clean = input(json!({
+            "w2s": [{ "employer": "Acme", "wages": 50000, "federal_tax_withheld": 5000 }],
+            "form_1099s": [{ "payer": "Bank", "form": "INT", "income": 100, "federal_tax_withheld": 24 }]
+        }));
+
+        let found = findings(&flagged, None, &[], AnomalyRule::WithholdingAboveWages);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].amount, Some(Decimal::from(5000)));
+        assert!(findings(&clean, None, &[], AnomalyRule::WithholdingAboveWages).is_empty());
+    }
+
+    #[test]
+    fn wage_jump_from_the_same_employer() {
+        let prior = input(json!({ "w2s": [{ "employer": "Acme Corp", "wages": 50000 }] }));
+        // Two W-2s from one employer count together, under either spelling of the name
+        let jumped = input(json!({ "w2s": [{ "employer": "ACME CORP.", "wages": 50000 }, { "employer": "Acme Corp", "wages": 30000 }] }));
+        let raised = input(json!({ "w2s": [{ "employer": "Acme Corp", "wages": 70000 }] }));
+
+        let found = findings(&jumped, Some(&prior), &[], AnomalyRule::WageJump);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].amount, Some(Decimal::from(80000)));
+        assert!(found[0].message.contains("rose 60%"));
+        assert!(findings(&raised, Some(&prior), &[], AnomalyRule::WageJump).is_empty());
+        assert!(findings(&jumped, None, &[], AnomalyRule::WageJump).is_empty());
+        assert!(findings(&jumped, Some(&prior), &override_threshold(AnomalyRule::WageJump, 75), AnomalyRule::WageJump).is_empty());
+    }
+
+    #[test]
+    fn ein_with_different_names() {
+        let conflict = input(json!({
+            "w2s": [{ "employer": "Acme Corp", "employer_ein": "12-3456789", "wages": 50000 }],
+            "form_1099s": [{ "payer": "Globex", "payer_tin": "123456789", "form": "INT", "income": 100 }]
+        }));
+        let same = input(json!({
+            "w2s": [{ "employer": "Acme Corp", "employer_ein": "12-3456789", "wages": 50000 }],
+            "form_1099s": [{ "payer": "ACME CORP INC", "payer_tin": "123456789", "form": "INT", "income": 100 }]
+        }));
+
+        let found = findings(&conflict, None, &[], AnomalyRule::EinNameConflict);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.starts_with("EIN 12-3456789 appears as both Acme Corp and Globex"));
+        assert!(findings(&same, None, &[], AnomalyRule::EinNameConflict).is_empty());
+    }
+
+    #[test]
+    fn social_security_wages_over_the_wage_base() {
+        let over = input(json!({ "w2s": [{ "employer": "Acme", "wages": 170000, "social_security_wages": 170000 }] }));
+        let at = input(json!({ "w2s": [{ "employer": "Acme", "wages": 170000, "social_security_wages": 168600 }] }));
+
+        let found = findings(&over, None, &[], AnomalyRule::SocialSecurityWagesAboveBase);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("2024 wage base of $168600"));
+        assert!(findings(&at, None, &[], AnomalyRule::SocialSecurityWagesAboveBase).is_empty());
+        assert!(AnomalyDetector::detect(&over, None, None, &[]).iter().all(|finding| finding.rule != AnomalyRule::SocialSecurityWagesAboveBase));
+    }
+
+    #[test]
+    fn round_amounts_on_extracted_records() {
+        let mut round = input(json!({ "w2s": [{ "employer": "Acme", "wages": 52500, "federal_tax_withheld": "5000.37" }] }));
+        round.w2s[0].source = extracted("documents[0].records[0]");
+        let mut cents = input(json!({ "w2s": [{ "employer": "Acme", "wages": "52500.37", "federal_tax_withheld": "5000.37" }] }));
+        cents.w2s[0].source = extracted("documents[0].records[0]");
+        let manual = input(json!({ "w2s": [{ "employer": "Acme", "wages": 52500 }] }));
+
+        let found = findings(&round, None, &[], AnomalyRule::RoundAmount);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.starts_with("W-2 from Acme box 1"));
+        assert_eq!(found[0].source.as_ref().map(|source| source.path.as_str()), Some("documents[0].records[0]"));
+        assert!(findings(&cents, None, &[], AnomalyRule::RoundAmount).is_empty());
+        assert!(findings(&manual, None, &[], AnomalyRule::RoundAmount).is_empty());
+        assert!(findings(&round, None, &override_threshold(AnomalyRule::RoundAmount, 1000), AnomalyRule::RoundAmount).is_empty());
+        assert!(findings(&round, None, &override_threshold(AnomalyRule::RoundAmount, 0), AnomalyRule::RoundAmount).is_empty());
+    }
+
+    #[test]
+    fn form_1099_under_the_reporting_threshold() {
+        let data = input(json!({
+            "form_1099s": [
+                { "payer": "Bank", "form": "INT", "income": 5 },
+                { "payer": "Credit Union", "form": "INT", "income": 15 },
+                { "payer": "Broker", "form": "DIV", "income": 0 }
+            ]
+        }));
+
+        let found = findings(&data, None, &[], AnomalyRule::Form1099BelowThreshold);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].amount, Some(Decimal::from(5)));
+        assert_eq!(findings(&data, None, &override_threshold(AnomalyRule::Form1099BelowThreshold, 20), AnomalyRule::Form1099BelowThreshold).len(), 2);
+    }
+
+    #[test]
+    fn disabled_rules_do_not_run() {
+        let data = input(json!({ "w2s": [{ "employer": "Acme", "wages": 1000, "federal_tax_withheld": 5000 }] }));
+        let settings = [RuleSetting { enabled: false, ..RuleSetting::default_for(AnomalyRule::WithholdingAboveWages) }];
+
+        assert!(findings(&data, None, &settings, AnomalyRule::WithholdingAboveWages).is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+//! Capital gains: Form 8949, Schedule D and the qualified dividends and capital gain tax worksheet.
+//!
+//! Manually entered lots are checked for wash sales before they are classified: a loss sale
+//! with shares of the same security bought within 30 days either side has the loss disallowed
+//! (code W) and added to the replacement shares' basis. 1099-B lots already carry the broker's
+//! box 1g adjustment and are not matched again.
+
+use chrono::{Days, Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::input::{CapitalLossCarryover, CapitalLot, Form1099Kind, TaxInput, Term};
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+
+/// Days before and after a loss sale in which buying the same security makes it a wash sale.
+const WASH_SALE_WINDOW_DAYS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Form8949Box {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+}
+
+impl Form8949Box {
+    fn for_lot(lot: &CapitalLot, term: Term) -> Form8949Box {
+        match (term, lot.reported_on_1099b, lot.basis_reported_to_irs) {
+            (Term::ShortTerm, true, true) => Form8949Box::A,
+            (Term::ShortTerm, true, false) => Form8949Box::B,
+            (Term::ShortTerm, false, _) => Form8949Box::C,
+            (Term::LongTerm, true, true) => Form8949Box::D,
+            (Term::LongTerm, true, false) => Form8949Box::E,
+            (Term::LongTerm, false, _) => Form8949Box::F,
+        }
+    }
+}
+
+/// One Form 8949 line, columns (a) through (h).
+#[derive(Debug, Clone, Serialize)]
+pub struct Form8949Row {
+    pub description: String,
+    pub date_acquired: Option<NaiveDate>,
+    pub date_sold: NaiveDate,
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub code: Option<&'static str>,
+    pub adjustment: Decimal,
+    pub gain: Decimal,
+}
+
+/// All rows reported under one Form 8949 checkbox, with the totals carried to Schedule D.
+#[derive(Debug, Clone, Serialize)]
+pub struct Form8949Part {
+    pub form_box: Form8949Box,
+    pub term: Term,
+    pub rows: Vec<Form8949Row>,
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub adjustment: Decimal,
+    pub gain: Decimal,
+}
+
+/// A loss disallowed because replacement shares were bought inside the wash-sale window.
+#[derive(Debug, Clone, Serialize)]
+pub struct WashSale {
+    pub security: String,
+    pub sold_lot: String,
+    pub date_sold: NaiveDate,
+    pub replacement_lot: String,
+    pub replacement_acquired: Option<NaiveDate>,
+    pub disallowed_loss: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScheduleD {
+    /// Lines 1b-3 column (h)
+    pub short_term_transactions: Decimal,
+    /// Line 6
+    pub short_term_carryover: Decimal,
+    /// Line 7
+    pub net_short_term: Decimal,
+    /// Lines 8b-10 column (h)
+    pub long_term_transactions: Decimal,
+    /// Line 13
+    pub capital_gain_distributions: Decimal,
+    /// Line 14
+    pub long_term_carryover: Decimal,
+    /// Line 15
+    pub net_long_term: Decimal,
+    /// Line 16
+    pub net_gain: Decimal,
+    /// Line 16, or the line 21 loss limited to $3,000 ($1,500 separate); Form 1040 line 7
+    pub capital_gain: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapitalGains {
+    pub form_8949: Vec<Form8949Part>,
+    pub wash_sales: Vec<WashSale>,
+    pub schedule_d: ScheduleD,
+    /// Unused loss carried to next year (Schedule D capital loss carryover worksheet)
+    pub carryover: CapitalLossCarryover,
+    pub warnings: Vec<String>,
+}
+
+/// Form 1040 line 16 qualified dividends and capital gain tax worksheet.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualifiedDividendsWorksheet {
+    /// Line 1
+    pub taxable_income: Decimal,
+    /// Line 2
+    pub qualified_dividends: Decimal,
+    /// Line 3
+    pub net_capital_gain: Decimal,
+    /// Line 5, taxed at ordinary rates
+    pub ordinary_income: Decimal,
+    /// Line 9
+    pub zero_rate_amount: Decimal,
+    /// Line 17
+    pub fifteen_rate_amount: Decimal,
+    /// Line 20
+    pub twenty_rate_amount: Decimal,
+    /// Line 22
+    pub ordinary_tax: Decimal,
+    /// Line 24, tax without the preferential rates
+    pub regular_tax: Decimal,
+    /// Line 25
+    pub tax: Decimal,
+}
+
+impl CapitalGains {
+    /// Returns `None` when the return has no sales, distributions or carryover.
+    pub fn compute(input: &TaxInput, rules: &TaxRules) -> Option<CapitalGains> {
+        let distributions: Decimal = input.form_1099s.iter()
+            .filter(|form| form.kind() == Form1099Kind::Div)
+            .map(|form| form.capital_gain_distributions)
+            .sum();
+        let carryover = &input.capital_loss_carryover;
+        if input.capital_lots.is_empty() && distributions.is_zero() && carryover.short_term.is_zero() && carryover.long_term.is_zero() {
+            return None;
+        }
+
+        let mut lots = input.capital_lots.clone();
+        let wash_sales = apply_wash_sales(&mut lots);
+
+        let mut warnings = Vec::new();
+        let mut form_8949: Vec<Form8949Part> = Vec::new();
+        let year_end = NaiveDate::from_ymd_opt(input.year, 12, 31);
+        for lot in &lots {
+            // A 1099-B always reports a sale, so a missing date is an extraction gap rather than an open position.
+            let date_sold = match (lot.date_sold, year_end) {
+                (Some(date_sold), _) => date_sold,
+                (None, Some(year_end)) if lot.reported_on_1099b => {
+                    warnings.push(format!("{}: the 1099-B has no sale date; treated as sold on {year_end}", lot.description));
+                    year_end
+                }
+                (None, _) => {
+                    warnings.push(format!("{}: no sale date; treated as still held and left off Form 8949", lot.description));
+                    continue;
+                }
+            };
+            let term = holding_term(lot, date_sold).unwrap_or_else(|| {
+                warnings.push(format!("{}: no acquisition date or term; treated as short-term", lot.description));
+                Term::ShortTerm
+            });
+
+            let adjustment = lot.wash_sale_loss_disallowed;
+            let row = Form8949Row {
+                description: lot.description.clone(),
+                date_acquired: lot.date_acquired,
+                date_sold,
+                proceeds: lot.proceeds,
+                cost_basis: lot.cost_basis,
+                code: (!adjustment.is_zero()).then_some("W"),
+                adjustment,
+                gain: lot.proceeds - lot.cost_basis + adjustment,
+            };
+
+            let form_box = Form8949Box::for_lot(lot, term);
+            match form_8949.iter_mut().find(|part| part.form_box == form_box) {
+                Some(part) => part.rows.push(row),
+                None => form_8949.push(Form8949Part {
+                    form_box,
+                    term,
+                    rows: vec![row],
+                    proceeds: Decimal::ZERO,
+                    cost_basis: Decimal::ZERO,
+                    adjustment: Decimal::ZERO,
+                    gain: Decimal::ZERO,
+                }),
+            }
+        }
+        form_8949.sort_by_key(|part| part.form_box);
+        for part in &mut form_8949 {
+            part.proceeds = part.rows.iter().map(|row| row.proceeds).sum();
+            part.cost_basis = part.rows.iter().map(|row| row.cost_basis).sum();
+            part.adjustment = part.rows.iter().map(|row| row.adjustment).sum();
+            part.gain = part.rows.iter().map(|row| row.gain).sum();
+        }
+
+        let total = |term: Term| form_8949.iter().filter(|part| part.term == term).map(|part| part.gain).sum::<Decimal>();
+        let short_term_transactions = total(Term::ShortTerm);
+        let long_term_transactions = total(Term::LongTerm);
+        let net_short_term = short_term_transactions - carryover.short_term;
+        let net_long_term = long_term_transactions + distributions - carryover.long_term;
+        let net_gain = net_short_term + net_long_term;
+        let loss_limit = *rules.capital_gains.loss_limit.get(input.filing_status);
+
+        Some(CapitalGains {
+            form_8949,
+            wash_sales,
+            schedule_d: ScheduleD {
+                short_term_transactions,
+                short_term_carryover: carryover.short_term,
+                net_short_term,
+                long_term_transactions,
+                capital_gain_distributions: distributions,
+                long_term_carryover: carryover.long_term,
+                net_long_term,
+                net_gain,
+                capital_gain: net_gain.max(-loss_limit),
+            },
+            carryover: CapitalLossCarryover::default(),
+            warnings,
+        })
+    }
+
+    /// Capital loss carryover worksheet. `taxable_income` is Form 1040 line 15 before it is
+    /// floored at zero, so a loss that produced no tax benefit is carried over in full.
+    pub fn compute_carryover(&mut self, taxable_income: Decimal) {
+        let schedule = &self.schedule_d;
+        if schedule.net_gain >= Decimal::ZERO {
+            self.carryover = CapitalLossCarryover::default();
+            return;
+        }
+
+        let deducted = -schedule.capital_gain;
+        let used = deducted.min((taxable_income + deducted).max(Decimal::ZERO));
+        let short_term_loss = (-schedule.net_short_term).max(Decimal::ZERO);
+        let long_term_loss = (-schedule.net_long_term).max(Decimal::ZERO);
+
+        let short_term = (short_term_loss - (used + schedule.net_long_term.max(Decimal::ZERO))).max(Decimal::ZERO);
+        let short_term_absorbed = (used - short_term_loss).max(Decimal::ZERO);
+        let long_term = (long_term_loss - (schedule.net_short_term.max(Decimal::ZERO) + short_term_absorbed)).max(Decimal::ZERO);
+
+        self.carryover = CapitalLossCarryover { short_term, long_term };
+    }
+
+    /// Schedule D line 15 or 16, whichever is smaller, when both are gains.
+    pub fn net_capital_gain(&self) -> Decimal {
+        let schedule = &self.schedule_d;
+        if schedule.net_long_term <= Decimal::ZERO || schedule.net_gain <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        schedule.net_long_term.min(schedule.net_gain)
+    }
+}
+
+impl QualifiedDividendsWorksheet {
+    pub fn compute(
+        status: FilingStatus,
+        taxable_income: Decimal,
+        qualified_dividends: Decimal,
+        net_capital_gain: Decimal,
+        rules: &TaxRules,
+    ) -> QualifiedDividendsWorksheet {
+        let gains = &rules.capital_gains;
+        let preferential = qualified_dividends + net_capital_gain;
+        let ordinary_income = (taxable_income - preferential).max(Decimal::ZERO);
+
+        let zero_rate_ceiling = taxable_income.min(*gains.zero_rate_maximum.get(status));
+        let zero_rate_amount = zero_rate_ceiling - ordinary_income.min(zero_rate_ceiling);
+        let preferential_taxed = taxable_income.min(preferential);
+        let fifteen_rate_room = (taxable_income.min(*gains.fifteen_rate_maximum.get(status)) - (ordinary_income + zero_rate_amount)).max(Decimal::ZERO);
+        let fifteen_rate_amount = (preferential_taxed - zero_rate_amount).min(fifteen_rate_room);
+        let twenty_rate_amount = preferential_taxed - zero_rate_amount - fifteen_rate_amount;
+
+        let ordinary_tax = rules.income_tax(status, ordinary_income);
+        let preferential_tax = round_cents(fifteen_rate_amount * gains.fifteen_rate) + round_cents(twenty_rate_amount * gains.twenty_rate);
+        let regular_tax = rules.income_tax(status, taxable_income);
+
+        QualifiedDividendsWorksheet {
+            taxable_income,
+            qualified_dividends,
+            net_capital_gain,
+            ordinary_income,
+            zero_rate_amount,
+            fifteen_rate_amount,
+            twenty_rate_amount,
+            ordinary_tax,
+            regular_tax,
+            tax: (ordinary_tax + preferential_tax).min(regular_tax),
+        }
+    }
+}
+
+/// Long-term means held more than one year: sold after the anniversary of the purchase.
+fn holding_term(lot: &CapitalLot, date_sold: NaiveDate) -> Option<Term> {
+    if lot.inherited {
+        return Some(Term::LongTerm);
+    }
+    if let Some(term) = lot.term {
+        return Some(term);
+    }
+
+    let anniversary = lot.date_acquired?.checked_add_months(Months::new(12))?;
+    Some(if date_sold > anniversary { Term::LongTerm } else { Term::ShortTerm })
+}
+
+/// Matches manually entered loss sales against purchases of the same security within
+/// 30 days before or after, oldest sale first. Each replacement share absorbs at most one
+/// sold share's loss; lots without a quantity count as one share, and lots with a zero or
+/// negative quantity are not matched. The disallowed loss is
+/// added to the replacement's basis and its holding period is extended by the sold lot's.
+fn apply_wash_sales(lots: &mut [CapitalLot]) -> Vec<WashSale> {
+    let window = Days::new(WASH_SALE_WINDOW_DAYS);
+    let mut used = vec![Decimal::ZERO; lots.len()];
+    let mut wash_sales = Vec::new();
+
+    let mut losses: Vec<usize> = (0..lots.len())
+        .filter(|&i| !lots[i].reported_on_1099b && lots[i].security.is_some() && lots[i].date_sold.is_some())
+        .filter(|&i| lots[i].quantity.is_none_or(|quantity| quantity > Decimal::ZERO))
+        .collect();
+    losses.sort_by_key(|&i| lots[i].date_sold);
+
+    for i in losses {
+        let loss = lots[i].cost_basis - lots[i].proceeds;
+        let (Some(security), Some(date_sold)) = (lots[i].security.clone(), lots[i].date_sold) else { continue };
+        if loss <= Decimal::ZERO {
+            continue;
+        }
+
+        let (Some(window_start), Some(window_end)) = (date_sold.checked_sub_days(window), date_sold.checked_add_days(window)) else { continue };
+        let mut candidates: Vec<usize> = (0..lots.len())
+            .filter(|&j| j != i)
+            .filter(|&j| lots[j].security.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(&security)))
+            .filter(|&j| lots[j].date_acquired.is_some_and(|acquired| acquired >= window_start && acquired <= window_end))
+            .filter(|&j| lots[j].date_sold.is_none_or(|sold| sold > date_sold))
+            .collect();
+        candidates.sort_by_key(|&j| lots[j].date_acquired);
+
+        let sold_quantity = lots[i].quantity.unwrap_or(Decimal::ONE);
+        let mut remaining = sold_quantity;
+        for j in candidates {
+            let available = lots[j].quantity.unwrap_or(Decimal::ONE) - used[j];
+            if available <= Decimal::ZERO {
+                continue;
+            }
+
+            let replaced = available.min(remaining);
+            let disallowed = round_cents(loss * (replaced / sold_quantity));
+            used[j] += replaced;
+            remaining -= replaced;
+
+            let replacement_acquired = lots[j].date_acquired;
+            lots[i].wash_sale_loss_disallowed += disallowed;
+            lots[j].cost_basis += disallowed;
+            if let (Some(sold_acquired), Some(replacement_acquired)) = (lots[i].date_acquired, replacement_acquired) {
+                let held = date_sold.signed_duration_since(sold_acquired).num_days().max(0) as u64;
+                lots[j].date_acquired = replacement_acquired.checked_sub_days(Days::new(held));
+            }
+
+            wash_sales.push(WashSale {
+                security: security.clone(),
+                sold_lot: lots[i].description.clone(),
+                date_sold,
+                replacement_lot: lots[j].description.clone(),
+                replacement_acquired,
+                disallowed_loss: disallowed,
+            });
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+        }
+    }
+
+    wash_sales
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use super::{CapitalGains, Form8949Box};
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::rules::TaxRules;
+
+    fn capital_gains(lots: Value) -> CapitalGains {
+        let input: TaxInput = serde_json::from_value(json!({ "year": 2024, "filing_status": "single", "capital_lots": lots })).unwrap();
+        CapitalGains::compute(&input, TaxRules::for_year(2024).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn skips_wash_sale_matching_for_a_loss_sale_without_a_quantity() {
+        let gains = capital_gains(json!([
+            { "description": "10 sh AAA", "security": "AAA", "quantity": 0, "date_acquired": "2023-06-01", "date_sold": "2024-03-01", "proceeds": 500, "cost_basis": 1500 },
+            { "description": "10 sh AAA replacement", "security": "AAA", "quantity": 10, "date_acquired": "2024-03-15", "proceeds": 0, "cost_basis": 1400 }
+        ]));
+
+        assert!(gains.wash_sales.is_empty());
+        assert_eq!(gains.form_8949.len(), 1);
+        let row = &gains.form_8949[0].rows[0];
+        assert_eq!((row.code, row.gain), (None, Decimal::from(-1000)));
+        assert_eq!(gains.warnings, ["10 sh AAA replacement: no sale date; treated as still held and left off Form 8949"]);
+    }
+
+    #[test]
+    fn treats_a_1099b_lot_without_a_sale_date_as_sold_at_year_end() {
+        let gains = capital_gains(json!([
+            { "description": "20 sh BBB", "security": "BBB", "quantity": 20, "reported_on_1099b": true, "date_acquired": "2020-01-02", "proceeds": 3000, "cost_basis": 1000 }
+        ]));
+
+        let part = &gains.form_8949[0];
+        assert!(matches!(part.form_box, Form8949Box::D));
+        assert_eq!(part.rows[0].date_sold, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert_eq!(gains.schedule_d.net_long_term, Decimal::from(2000));
+        assert_eq!(gains.warnings, ["20 sh BBB: the 1099-B has no sale date; treated as sold on 2024-12-31"]);
+    }
+
+    #[test]
+    fn disallows_the_share_of_a_loss_the_replacement_shares_cover() {
+        let gains = capital_gains(json!([
+            { "description": "30 sh CCC", "security": "CCC", "quantity": 30, "date_acquired": "2023-01-10", "date_sold": "2024-05-01", "proceeds": 2000, "cost_basis": 3000 },
+            { "description": "10 sh CCC", "security": "CCC", "quantity": 10, "date_acquired": "2024-05-20", "proceeds": 0, "cost_basis": 700 }
+        ]));
+
+        assert_eq!(gains.wash_sales.len(), 1);
+        assert_eq!(gains.wash_sales[0].disallowed_loss, "333.33".parse::<Decimal>().unwrap());
+        assert_eq!(gains.schedule_d.net_long_term, "-666.67".parse::<Decimal>().unwrap());
+    }
+}
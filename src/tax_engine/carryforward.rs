@@ -0,0 +1,207 @@
+//! Amounts one year's return hands to the next: unused capital losses, charitable
+//! contributions over the AGI limits, net operating losses, the minimum tax credit, AGI (which the next year's e-file
+//! signature is verified against) and state income tax refunds.
+//!
+//! A state refund is only income to the extent the deduction for the tax it refunds lowered
+//! the federal tax, which is the State and Local Income Tax Refund Worksheet (Schedule 1 line 1):
+//! nothing when the standard deduction was taken or sales tax was deducted instead, and
+//! otherwise no more than the SALT deduction it added or the amount by which itemizing beat
+//! the standard deduction.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::carryforward_model::{CarryforwardKind, CarryforwardModel};
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::{round_cents, DeductionKind, TaxCalculation};
+
+/// An amount carried from one year's calculation into the next year.
+#[derive(Debug, Clone, Serialize)]
+pub struct CarriedAmount {
+    pub kind: CarryforwardKind,
+    /// State that issues a refund
+    pub state: Option<String>,
+    pub amount: Decimal,
+    /// Part of a state refund that is income next year
+    pub taxable_amount: Option<Decimal>,
+}
+
+pub struct Carryforwards;
+
+impl Carryforwards {
+    /// Everything `calculation` carries into the following year. AGI is always carried;
+    /// other amounts only when they are not zero.
+    pub fn from_calculation(input: &TaxInput, calculation: &TaxCalculation) -> Vec<CarriedAmount> {
+        let carried = |kind: CarryforwardKind, amount: Decimal| CarriedAmount { kind, state: None, amount, taxable_amount: None };
+        let mut amounts = vec![carried(CarryforwardKind::PriorYearAgi, round_cents(calculation.adjusted_gross_income))];
+
+        if let Some(gains) = &calculation.capital_gains {
+            amounts.push(carried(CarryforwardKind::CapitalLossShortTerm, gains.carryover.short_term));
+            amounts.push(carried(CarryforwardKind::CapitalLossLongTerm, gains.carryover.long_term));
+        }
+        amounts.push(carried(CarryforwardKind::CharitableContributions, calculation.schedule_a.charitable_carryforward));
+        amounts.push(carried(CarryforwardKind::NetOperatingLoss, Self::net_operating_loss(input, calculation)));
+        amounts.push(carried(CarryforwardKind::MinimumTaxCredit, calculation.alternative_minimum_tax.credit_carryforward));
+        if let Some(qbi) = &calculation.qualified_business_income {
+            amounts.push(carried(CarryforwardKind::QualifiedBusinessLoss, qbi.loss_carryforward));
+        }
+        amounts.retain(|amount| amount.kind == CarryforwardKind::PriorYearAgi || !amount.amount.is_zero());
+
+        let mut taxable = Self::taxable_state_refunds(calculation);
+        for state in calculation.states.iter().filter(|state| state.supported && state.refund > Decimal::ZERO) {
+            let taxable_amount = state.refund.min(taxable);
+            taxable -= taxable_amount;
+            amounts.push(CarriedAmount {
+                kind: CarryforwardKind::StateRefund,
+                state: Some(state.state.clone()),
+                amount: state.refund,
+                taxable_amount: Some(taxable_amount),
+            });
+        }
+
+        amounts
+    }
+
+    /// Whether rows carried from a year are out of date: that year's tax data, now at
+    /// `revision`, has been saved since they were rolled forward, or was never rolled forward.
+    pub fn stale(rows: &[CarryforwardModel], revision: Option<i32>) -> bool {
+        revision.is_some_and(|revision| rows.iter().all(|row| row.source_revision != revision))
+    }
+
+    /// Losses carried in and not used this year, plus this year's own loss. Following Form 172,
+    /// only a business loss creates one: the personal deductions, capital losses and the
+    /// deduction for earlier losses do not add to it.
+    fn net_operating_loss(input: &TaxInput, calculation: &TaxCalculation) -> Decimal {
+        let income = &calculation.income;
+        let unused = (input.net_operating_loss_carryover - income.net_operating_loss).max(Decimal::ZERO);
+
+        let business_loss = (-income.business_income).max(Decimal::ZERO);
+        let capital_loss = (-income.capital_gain).max(Decimal::ZERO);
+        let loss = (-(calculation.adjusted_gross_income + income.net_operating_loss + capital_loss)).max(Decimal::ZERO);
+
+        unused + loss.min(business_loss)
+    }
+
+    /// Total of the state refunds that will be income next year, under the tax benefit rule.
+    fn taxable_state_refunds(calculation: &TaxCalculation) -> Decimal {
+        let schedule_a = &calculation.schedule_a;
+        let deduction = &calculation.deduction;
+        if deduction.kind != DeductionKind::Itemized || schedule_a.sales_tax_elected {
+            return Decimal::ZERO;
+        }
+
+        let refunds = calculation.states.iter()
+            .filter(|state| state.supported)
+            .map(|state| state.refund)
+            .sum::<Decimal>();
+        let refunded_taxes = (schedule_a.income_taxes - refunds).max(schedule_a.general_sales_tax)
+            + schedule_a.real_estate_taxes
+            + schedule_a.personal_property_taxes;
+        let deduction_added = schedule_a.salt_deduction - refunded_taxes.min(schedule_a.salt_cap);
+        let itemizing_advantage = deduction.itemized - deduction.standard;
+
+        refunds.min(deduction_added).min(itemizing_advantage).max(Decimal::ZERO)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+    use super::{CarriedAmount, Carryforwards};
+    use crate::models::carryforward_model::{CarryforwardKind, CarryforwardModel};
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::TaxEngine;
+
+    fn carried(input: Value) -> Vec<CarriedAmount> {
+        let input: TaxInput = serde_json::from_value(input).unwrap();
+        let calculation = TaxEngine::calculate(&input).unwrap();
+        Carryforwards::from_calculation(&input, &calculation)
+    }
+
+    fn amount(carried: &[CarriedAmount], kind: CarryforwardKind) -> Option<&CarriedAmount> {
+        carried.iter().find(|amount| amount.kind == kind)
+    }
+
+    #[test]
+    fn carries_the_unused_part_of_a_net_operating_loss() {
+        let carried = carried(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 40000 }],
+            "net_operating_loss_carryover": 50000
+        }));
+
+        // 80% of the $25,400 taxable income figured without the deduction is used
+        assert_eq!(amount(&carried, CarryforwardKind::NetOperatingLoss).unwrap().amount, Decimal::from(29680));
+        assert_eq!(amount(&carried, CarryforwardKind::PriorYearAgi).unwrap().amount, Decimal::from(19680));
+    }
+
+    #[test]
+    fn state_refund_is_not_income_after_the_standard_deduction() {
+        let carried = carried(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 60000, "state": "IL", "state_wages": 60000, "state_tax_withheld": 4000 }]
+        }));
+
+        let refund = amount(&carried, CarryforwardKind::StateRefund).unwrap();
+        assert_eq!(refund.state.as_deref(), Some("IL"));
+        assert_eq!(refund.amount, "1167.36".parse::<Decimal>().unwrap());
+        assert_eq!(refund.taxable_amount, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn state_refund_is_income_only_up_to_the_salt_deduction_it_added() {
+        let carried = carried(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 150000, "state": "IL", "state_wages": 150000, "state_tax_withheld": 9000 }],
+            "itemized": { "real_estate_taxes": 2500, "mortgage_interest": 20000 }
+        }));
+
+        // $11,500 of taxes was capped at $10,000; without the refund they would have been
+        // $9,787.64, so only $212.36 of the $1,712.36 refund lowered the tax
+        let refund = amount(&carried, CarryforwardKind::StateRefund).unwrap();
+        assert_eq!(refund.amount, "1712.36".parse::<Decimal>().unwrap());
+        assert_eq!(refund.taxable_amount, Some("212.36".parse().unwrap()));
+    }
+
+    #[test]
+    fn carries_charitable_contributions_over_the_agi_limit() {
+        let carried = carried(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 50000 }],
+            "itemized": { "charitable_cash": 40000 }
+        }));
+
+        assert_eq!(amount(&carried, CarryforwardKind::CharitableContributions).unwrap().amount, Decimal::from(10000));
+        assert!(amount(&carried, CarryforwardKind::NetOperatingLoss).is_none());
+    }
+
+    #[test]
+    fn rows_are_stale_once_the_source_year_changes() {
+        let row = CarryforwardModel {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            source_year: 2023,
+            target_year: 2024,
+            kind: CarryforwardKind::NetOperatingLoss.as_str().into(),
+            state_code: None,
+            amount: Decimal::from(5000),
+            taxable_amount: None,
+            source_revision: 3,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+
+        assert!(!Carryforwards::stale(std::slice::from_ref(&row), Some(3)));
+        assert!(Carryforwards::stale(std::slice::from_ref(&row), Some(4)));
+        assert!(Carryforwards::stale(&[], Some(4)));
+        assert!(!Carryforwards::stale(&[row], None));
+    }
+}
@@ -0,0 +1,364 @@
+//! Documents a year is expected to have but does not yet.
+//!
+//! Most information returns come back every year, so each employer, payer, lender and school
+//! in the most recent prior year with data is expected again. The profile adds more: a student
+//! in college should get a 1098-T, and HSA contributions (W-2 box 12 code W or an HSA deduction)
+//! mean a 5498-SA is on its way and a 1099-SA if money was taken out.
+//!
+//! Issuers are matched by name with case, punctuation and suffixes such as "Inc" ignored, and a
+//! shorter name matches a longer one it begins ("Chase" matches "Chase Bank"). An expectation
+//! the user knows does not apply (a job that ended, a paid-off mortgage) is dismissed by adding
+//! its `key` to `tax_data.dismissed_documents`.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::tax_engine::input::{Form1099Kind, TaxInput};
+
+/// Words dropped from issuer names before they are compared.
+const NAME_SUFFIXES: [&str; 10] = ["the", "inc", "llc", "llp", "ltd", "co", "corp", "corporation", "company", "na"];
+
+/// Students this old or older are taken to be in college rather than high school.
+const COLLEGE_AGE: i32 = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectationBasis {
+    /// The issuer sent the same form for the prior year
+    PriorYear,
+    /// A dependent, the taxpayer or the spouse is a student
+    Student,
+    /// HSA contributions were made
+    HealthSavingsAccount,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpectedDocument {
+    /// Stable key for dismissing the expectation, e.g. `w-2:acme`
+    pub key: String,
+    /// Form name, e.g. "W-2" or "1099-INT"
+    pub form: String,
+    /// Employer, payer, lender or school, when known
+    pub issuer: Option<String>,
+    /// Student a 1098-T is for
+    pub student: Option<String>,
+    pub basis: ExpectationBasis,
+    /// Counted as missing when not received; forms that may legitimately not exist are only suggested
+    pub required: bool,
+    pub received: bool,
+    pub dismissed: bool,
+    /// What to do about it
+    pub action: String,
+}
+
+impl ExpectedDocument {
+    pub fn is_missing(&self) -> bool {
+        self.required && !self.received && !self.dismissed
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentChecklist {
+    pub year: i32,
+    /// Prior year the issuers were carried over from
+    pub prior_year: Option<i32>,
+    pub expected: Vec<ExpectedDocument>,
+    /// Required documents neither received nor dismissed
+    pub missing: usize,
+}
+
+impl DocumentChecklist {
+    /// Builds the checklist for `current`. `uploaded_types` are the document types uploaded for
+    /// the year, which is the only trace of forms the engine does not read (5498-SA, 1099-SA).
+    /// `dismissed` holds the keys the user has dismissed.
+    pub fn build(current: &TaxInput, uploaded_types: &[String], prior: Option<&TaxInput>, dismissed: &[String]) -> DocumentChecklist {
+        let mut checklist = Checklist { current, dismissed, expected: Vec::new() };
+
+        if let Some(prior) = prior {
+            checklist.prior_year(prior);
+        }
+        checklist.students();
+        checklist.health_savings_account(uploaded_types);
+
+        let expected = checklist.expected;
+        DocumentChecklist {
+            year: current.year,
+            prior_year: prior.map(|prior| prior.year),
+            missing: expected.iter().filter(|document| document.is_missing()).count(),
+            expected,
+        }
+    }
+
+    pub fn missing(&self) -> impl Iterator<Item = &ExpectedDocument> {
+        self.expected.iter().filter(|document| document.is_missing())
+    }
+}
+
+struct Checklist<'a> {
+    current: &'a TaxInput,
+    dismissed: &'a [String],
+    expected: Vec<ExpectedDocument>,
+}
+
+impl Checklist<'_> {
+    fn prior_year(&mut self, prior: &TaxInput) {
+        let year = self.current.year;
+        let prior_year = prior.year;
+
+        for employer in unique(prior.w2s.iter().map(|w2| w2.employer.as_str())) {
+            let received = self.current.w2s.iter().any(|w2| same_name(&w2.employer, employer));
+            self.push("W-2", Some(employer), None, ExpectationBasis::PriorYear, received, format!(
+                "{employer} sent a W-2 for {prior_year}; upload the {year} W-2, or dismiss it if you no longer work there",
+            ));
+        }
+        for kind in [Form1099Kind::Int, Form1099Kind::Div, Form1099Kind::Nec, Form1099Kind::Misc, Form1099Kind::K] {
+            let form = kind.form_name();
+            let payers = prior.form_1099s.iter().filter(|f| f.kind() == kind).map(|f| f.payer.as_str());
+            for payer in unique(payers) {
+                let received = self.current.form_1099s.iter().any(|f| f.kind() == kind && same_name(&f.payer, payer));
+                self.push(form, Some(payer), None, ExpectationBasis::PriorYear, received, format!(
+                    "{payer} sent a {form} for {prior_year}; upload the {year} {form}, or dismiss it if the account was closed",
+                ));
+            }
+        }
+        if prior.capital_lots.iter().any(|lot| lot.reported_on_1099b) {
+            let received = self.current.capital_lots.iter().any(|lot| lot.reported_on_1099b);
+            self.push("1099-B", None, None, ExpectationBasis::PriorYear, received, format!(
+                "Sales were reported on a 1099-B for {prior_year}; upload the {year} 1099-B, or dismiss it if nothing was sold",
+            ));
+        }
+        for lender in unique(prior.form_1098s.iter().map(|form| form.lender.as_str())) {
+            let received = self.current.form_1098s.iter().any(|form| same_name(&form.lender, lender));
+            self.push("1098", Some(lender), None, ExpectationBasis::PriorYear, received, format!(
+                "{lender} sent a 1098 for {prior_year}; upload the {year} 1098, or dismiss it if the loan was paid off or sold",
+            ));
+        }
+        for form in &prior.form_1098ts {
+            let institution = form.institution.trim();
+            if institution.is_empty() || self.expected.iter().any(|e| e.form == "1098-T" && e.issuer.as_deref().is_some_and(|i| same_name(i, institution))) {
+                continue;
+            }
+            let student = Some(form.student.trim()).filter(|student| !student.is_empty());
+            let received = self.current.form_1098ts.iter().any(|f| same_name(&f.institution, institution));
+            self.push("1098-T", Some(institution), student, ExpectationBasis::PriorYear, received, format!(
+                "{institution} sent a 1098-T for {prior_year}; upload the {year} 1098-T, or dismiss it if the student is no longer enrolled",
+            ));
+        }
+    }
+
+    /// A 1098-T for each student dependent of college age and each student filer, unless a
+    /// prior-year 1098-T already covers them.
+    fn students(&mut self) {
+        let year = self.current.year;
+        let credits = &self.current.credits;
+        let mut students: Vec<String> = self.current.dependents.iter()
+            .filter(|dependent| dependent.student && dependent.age >= COLLEGE_AGE)
+            .map(|dependent| dependent.name.clone())
+            .collect();
+        if credits.taxpayer_full_time_student {
+            students.push("Taxpayer".to_string());
+        }
+        if credits.spouse_full_time_student {
+            students.push("Spouse".to_string());
+        }
+
+        for student in students {
+            let covered = self.expected.iter().any(|e| e.form == "1098-T" && e.student.as_deref().is_some_and(|s| same_person(s, &student)));
+            if covered {
+                continue;
+            }
+            let received = self.current.form_1098ts.iter().any(|form| form.student.trim().is_empty() || same_person(&form.student, &student));
+            self.push("1098-T", None, Some(&student), ExpectationBasis::Student, received, format!(
+                "{student} is listed as a student; upload the {year} 1098-T from the school for the education credits, or dismiss it if no tuition was billed",
+            ));
+        }
+    }
+
+    /// HSA contributions through payroll (W-2 box 12 code W) or made directly (the deduction).
+    fn health_savings_account(&mut self, uploaded_types: &[String]) {
+        let year = self.current.year;
+        let contributions = self.current.w2s.iter().map(|w2| w2.box_12_amount("W")).sum::<Decimal>()
+            + self.current.adjustments.hsa_deduction;
+        if contributions <= Decimal::ZERO {
+            return;
+        }
+        let uploaded = |form: &str| uploaded_types.iter().any(|t| t.trim().eq_ignore_ascii_case(form));
+
+        self.push("5498-SA", None, None, ExpectationBasis::HealthSavingsAccount, uploaded("5498-SA"), format!(
+            "HSA contributions of ${contributions} were made for {year}; the trustee's 5498-SA (sent by May 31) confirms them for Form 8889",
+        ));
+        self.push("1099-SA", None, None, ExpectationBasis::HealthSavingsAccount, uploaded("1099-SA"), format!(
+            "If you took money out of the HSA in {year}, upload the 1099-SA; Form 8889 needs the distributions",
+        ));
+    }
+
+    /// HSA forms are only suggested: the contributions are already on the W-2 and there may
+    /// have been no distributions.
+    fn push(&mut self, form: &str, issuer: Option<&str>, student: Option<&str>, basis: ExpectationBasis, received: bool, action: String) {
+        let subject = issuer.or(student).map(normalize);
+        let key = match subject {
+            Some(subject) => format!("{}:{subject}", form.to_lowercase()),
+            None => form.to_lowercase(),
+        };
+        let dismissed = self.dismissed.iter().any(|dismissed| dismissed.eq_ignore_ascii_case(&key));
+
+        self.expected.push(ExpectedDocument {
+            key,
+            form: form.to_string(),
+            issuer: issuer.map(str::to_string),
+            student: student.map(str::to_string),
+            basis,
+            required: basis != ExpectationBasis::HealthSavingsAccount,
+            received,
+            dismissed,
+            action,
+        });
+    }
+}
+
+/// Non-blank names, first spelling of each.
+fn unique<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut unique: Vec<&str> = Vec::new();
+    for name in names.map(str::trim).filter(|name| !name.is_empty()) {
+        if !unique.iter().any(|seen| same_name(seen, name)) {
+            unique.push(name);
+        }
+    }
+    unique
+}
+
+/// Lowercase words of a name without punctuation or company suffixes.
+fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .replace('.', "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !NAME_SUFFIXES.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Issuer names match when the words of one begin the other.
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize(a), normalize(b));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    !shorter.is_empty() && (longer == shorter || longer.starts_with(&format!("{shorter} ")))
+}
+
+/// People match on their first name, which is what 1098-T extractions reliably capture.
+fn same_person(a: &str, b: &str) -> bool {
+    let first = |name: &str| normalize(name).split(' ').next().map(str::to_string).unwrap_or_default();
+    let (a, b) = (first(a), first(b));
+    !a.is_empty() && a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use super::{DocumentChecklist, ExpectationBasis, ExpectedDocument};
+    use crate::tax_engine::input::TaxInput;
+
+    fn input(year: i32, data: Value) -> TaxInput {
+        let mut data = data;
+        data["year"] = json!(year);
+        data["filing_status"] = json!("single");
+        serde_json::from_value(data).unwrap()
+    }
+
+    fn expected<'a>(checklist: &'a DocumentChecklist, key: &str) -> &'a ExpectedDocument {
+        checklist.expected.iter().find(|document| document.key == key).unwrap()
+    }
+
+    fn prior() -> TaxInput {
+        input(2023, json!({
+            "w2s": [{ "employer": "Acme Corp", "wages": 50000 }, { "employer": "Initech LLC", "wages": 10000 }],
+            "form_1099s": [
+                { "payer": "Chase", "form": "INT", "income": 40 },
+                { "payer": "Vanguard", "form": "DIV", "income": 300 }
+            ],
+            "capital_lots": [{ "description": "Fund", "date_acquired": "2020-01-02", "date_sold": "2023-06-01", "proceeds": 1000, "cost_basis": 800, "reported_on_1099b": true }],
+            "form_1098s": [{ "lender": "Wells Fargo Bank, N.A.", "mortgage_interest": 9000 }],
+            "form_1098ts": [{ "institution": "State University", "student": "Ben Smith", "qualified_tuition": 8000 }]
+        }))
+    }
+
+    #[test]
+    fn prior_year_issuers_are_matched_by_name() {
+        let current = input(2024, json!({
+            "w2s": [{ "employer": "ACME CORPORATION", "wages": 52000 }],
+            "form_1099s": [
+                { "payer": "Chase Bank", "form": "INT", "income": 55 },
+                { "payer": "Vanguard", "form": "INT", "income": 10 }
+            ],
+            "form_1098s": [{ "lender": "Wells Fargo", "mortgage_interest": 8800 }],
+            "form_1098ts": [{ "institution": "State University", "student": "Ben", "qualified_tuition": 8200 }]
+        }));
+        let checklist = DocumentChecklist::build(&current, &[], Some(&prior()), &[]);
+
+        assert_eq!(checklist.prior_year, Some(2023));
+        assert!(checklist.expected.iter().all(|document| document.basis == ExpectationBasis::PriorYear));
+        assert!(expected(&checklist, "w-2:acme").received);
+        assert!(expected(&checklist, "1099-int:chase").received);
+        assert!(expected(&checklist, "1098:wells fargo bank").received);
+        assert!(expected(&checklist, "1098-t:state university").received);
+        assert_eq!(expected(&checklist, "1098-t:state university").student.as_deref(), Some("Ben Smith"));
+
+        // A 1099-INT from Vanguard does not stand in for last year's 1099-DIV
+        let missing: Vec<&str> = checklist.missing().map(|document| document.key.as_str()).collect();
+        assert_eq!(missing, ["w-2:initech", "1099-div:vanguard", "1099-b"]);
+        assert_eq!(checklist.missing, 3);
+    }
+
+    #[test]
+    fn dismissed_expectations_are_not_missing() {
+        let current = input(2024, json!({ "w2s": [{ "employer": "Acme Corp", "wages": 52000 }] }));
+        let dismissed = ["W-2:initech".to_string(), "1099-b".to_string()];
+        let checklist = DocumentChecklist::build(&current, &[], Some(&prior()), &dismissed);
+
+        assert!(expected(&checklist, "w-2:initech").dismissed);
+        assert!(!expected(&checklist, "w-2:initech").is_missing());
+        assert_eq!(checklist.missing().map(|document| document.form.as_str()).collect::<Vec<_>>(), ["1099-INT", "1099-DIV", "1098", "1098-T"]);
+    }
+
+    #[test]
+    fn students_of_college_age_expect_a_1098_t() {
+        let current = input(2024, json!({
+            "dependents": [
+                { "name": "Ben Smith", "age": 19, "student": true },
+                { "name": "Cal Smith", "age": 20, "student": true },
+                { "name": "Dee Smith", "age": 15, "student": true }
+            ],
+            "credits": { "taxpayer_full_time_student": true },
+            "form_1098ts": [{ "institution": "City College", "student": "Cal", "qualified_tuition": 3000 }]
+        }));
+
+        let without_prior = DocumentChecklist::build(&current, &[], None, &[]);
+        let students: Vec<(&str, bool)> = without_prior.expected.iter()
+            .map(|document| (document.student.as_deref().unwrap_or_default(), document.received))
+            .collect();
+        assert_eq!(students, [("Ben Smith", false), ("Cal Smith", true), ("Taxpayer", false)]);
+        assert!(without_prior.expected.iter().all(|document| document.basis == ExpectationBasis::Student && document.required));
+
+        // Last year's 1098-T for Ben already covers him
+        let with_prior = DocumentChecklist::build(&current, &[], Some(&prior()), &[]);
+        let ben: Vec<&ExpectedDocument> = with_prior.expected.iter()
+            .filter(|document| document.form == "1098-T" && document.student.as_deref().is_some_and(|student| student.starts_with("Ben")))
+            .collect();
+        assert_eq!(ben.len(), 1);
+        assert_eq!(ben[0].basis, ExpectationBasis::PriorYear);
+    }
+
+    #[test]
+    fn hsa_contributions_suggest_the_hsa_forms() {
+        let payroll = input(2024, json!({ "w2s": [{ "employer": "Acme", "wages": 52000, "box_12": [{ "code": "W", "amount": 2000 }] }] }));
+        let direct = input(2024, json!({ "adjustments": { "hsa_deduction": 1500 } }));
+        let none = input(2024, json!({ "w2s": [{ "employer": "Acme", "wages": 52000, "box_12": [{ "code": "D", "amount": 2000 }] }] }));
+
+        let checklist = DocumentChecklist::build(&payroll, &[" 5498-sa ".to_string()], None, &[]);
+        assert!(expected(&checklist, "5498-sa").received);
+        assert!(!expected(&checklist, "1099-sa").received);
+        assert!(checklist.expected.iter().all(|document| document.basis == ExpectationBasis::HealthSavingsAccount && !document.required));
+        assert_eq!(checklist.missing, 0);
+
+        assert_eq!(DocumentChecklist::build(&direct, &[], None, &[]).expected.len(), 2);
+        assert!(DocumentChecklist::build(&none, &[], None, &[]).expected.is_empty());
+    }
+}
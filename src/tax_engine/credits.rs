@@ -0,0 +1,489 @@
+//! Credits against tax (Form 1040 lines 19-21) and refundable credits (lines 27-29).
+//!
+//! Nonrefundable credits are applied in the order the credit limit worksheets assume:
+//! dependent care (Form 2441), education (Form 8863), saver's (Form 8880), then the child tax
+//! credit and credit for other dependents (Schedule 8812), each limited to the tax the earlier
+//! ones left. The refundable part of the child tax credit, the earned income credit and the
+//! refundable American Opportunity credit are payments and are not limited by tax.
+//!
+//! Every credit is reported, with an explanation of why it was allowed, reduced or denied.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::rules::{EarnedIncomeSchedule, TaxRules};
+use crate::tax_engine::self_employment::{ScheduleC, ScheduleSe};
+use crate::tax_engine::{round_cents, round_dollars, IncomeSummary};
+
+/// Ages between which a taxpayer without a qualifying child can claim the earned income credit.
+const EARNED_INCOME_CREDIT_AGES: (i32, i32) = (25, 64);
+/// Minimum age for the saver's credit.
+const SAVERS_CREDIT_MINIMUM_AGE: i32 = 18;
+/// Width of the EIC table rows.
+const EARNED_INCOME_TABLE_ROW: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditKind {
+    ChildAndDependentCare,
+    AmericanOpportunity,
+    LifetimeLearning,
+    Savers,
+    ChildTax,
+    OtherDependents,
+    AdditionalChildTax,
+    EarnedIncome,
+    RefundableAmericanOpportunity,
+}
+
+impl CreditKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditKind::ChildAndDependentCare => "child_and_dependent_care",
+            CreditKind::AmericanOpportunity => "american_opportunity",
+            CreditKind::LifetimeLearning => "lifetime_learning",
+            CreditKind::Savers => "savers",
+            CreditKind::ChildTax => "child_tax",
+            CreditKind::OtherDependents => "other_dependents",
+            CreditKind::AdditionalChildTax => "additional_child_tax",
+            CreditKind::EarnedIncome => "earned_income",
+            CreditKind::RefundableAmericanOpportunity => "refundable_american_opportunity",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CreditKind::ChildAndDependentCare => "child and dependent care credit",
+            CreditKind::AmericanOpportunity => "American Opportunity credit",
+            CreditKind::LifetimeLearning => "Lifetime Learning credit",
+            CreditKind::Savers => "saver's credit",
+            CreditKind::ChildTax => "child tax credit",
+            CreditKind::OtherDependents => "credit for other dependents",
+            CreditKind::AdditionalChildTax => "additional child tax credit",
+            CreditKind::EarnedIncome => "earned income credit",
+            CreditKind::RefundableAmericanOpportunity => "refundable American Opportunity credit",
+        }
+    }
+}
+
+/// One credit with how it was figured.
+#[derive(Debug, Clone, Serialize)]
+pub struct Credit {
+    pub credit: CreditKind,
+    /// Form and line the credit is reported on
+    pub line: &'static str,
+    pub refundable: bool,
+    /// After phase-outs, before the tax liability limit
+    pub tentative: Decimal,
+    pub allowed: Decimal,
+    pub explanation: Vec<String>,
+}
+
+impl Credit {
+    fn new(credit: CreditKind, line: &'static str, refundable: bool) -> Credit {
+        Credit { credit, line, refundable, tentative: Decimal::ZERO, allowed: Decimal::ZERO, explanation: Vec::new() }
+    }
+
+    fn denied(mut self, reason: impl Into<String>) -> Credit {
+        self.explanation.push(reason.into());
+        self
+    }
+
+    /// Allows up to the tax still unused by earlier credits and reduces it.
+    fn limit_to(&mut self, remaining_tax: &mut Decimal) {
+        self.allowed = self.tentative.min(*remaining_tax);
+        if self.allowed < self.tentative {
+            self.explanation.push(format!("limited to ${} of remaining tax", self.allowed));
+        }
+        *remaining_tax -= self.allowed;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Credits {
+    pub credits: Vec<Credit>,
+    /// Form 1040 line 21
+    pub nonrefundable: Decimal,
+    /// Form 1040 lines 27 through 29
+    pub refundable: Decimal,
+}
+
+/// Amounts the credits are figured from, all taken from the return as computed so far.
+struct CreditBase {
+    status: FilingStatus,
+    adjusted_gross_income: Decimal,
+    earned_income: Decimal,
+    /// Earned income of the lower-earning spouse on a joint return, otherwise the taxpayer's
+    lower_earned_income: Decimal,
+    investment_income: Decimal,
+}
+
+impl Credits {
+    pub fn compute(
+        input: &TaxInput,
+        rules: &TaxRules,
+        income: &IncomeSummary,
+        schedule_c: &[ScheduleC],
+        schedule_se: &[ScheduleSe],
+        adjusted_gross_income: Decimal,
+        tax: Decimal,
+    ) -> Credits {
+        let joint = input.filing_status == FilingStatus::MarriedFilingJointly;
+        let owner_of = |owner: Owner| if joint { owner } else { Owner::Taxpayer };
+        // Earned income counts self-employment profit less the deductible half of SE tax.
+        let earned_by = |owner: Owner| -> Decimal {
+            input.w2s.iter().filter(|w2| owner_of(w2.owner) == owner).map(|w2| w2.wages).sum::<Decimal>()
+                + schedule_c.iter().filter(|s| owner_of(s.owner) == owner).map(|s| s.net_profit).sum::<Decimal>()
+                - schedule_se.iter().filter(|se| owner_of(se.owner) == owner).map(|se| se.deduction).sum::<Decimal>()
+        };
+        let taxpayer_earned = earned_by(Owner::Taxpayer);
+        let spouse_earned = earned_by(Owner::Spouse);
+
+        let base = CreditBase {
+            status: input.filing_status,
+            adjusted_gross_income,
+            earned_income: taxpayer_earned + spouse_earned,
+            lower_earned_income: if joint { taxpayer_earned.min(spouse_earned) } else { taxpayer_earned },
+            investment_income: income.taxable_interest + income.ordinary_dividends + income.capital_gain.max(Decimal::ZERO)
+                + income.rental_income.max(Decimal::ZERO),
+        };
+
+        // Form 1040 line 18, used up by each nonrefundable credit in turn
+        let mut remaining_tax = tax.max(Decimal::ZERO);
+        let mut care = Self::dependent_care(input, rules, &base);
+        care.limit_to(&mut remaining_tax);
+
+        let (mut american_opportunity, mut lifetime_learning, refundable_american_opportunity) = Self::education(input, rules, &base);
+        american_opportunity.limit_to(&mut remaining_tax);
+        lifetime_learning.limit_to(&mut remaining_tax);
+
+        let mut savers = Self::savers(input, rules, &base);
+        savers.limit_to(&mut remaining_tax);
+
+        let (child_tax, other_dependents, additional_child_tax) = Self::child_tax_credit(input, rules, &base, &mut remaining_tax);
+        let earned_income = Self::earned_income(input, rules, &base);
+
+        let credits = vec![
+            care,
+            american_opportunity,
+            lifetime_learning,
+            savers,
+            child_tax,
+            other_dependents,
+            earned_income,
+            additional_child_tax,
+            refundable_american_opportunity,
+        ];
+        let nonrefundable = credits.iter().filter(|c| !c.refundable).map(|c| c.allowed).sum();
+        let refundable = credits.iter().filter(|c| c.refundable).map(|c| c.allowed).sum();
+
+        Credits { credits, nonrefundable, refundable }
+    }
+
+    /// Form 2441. The expense limit is reduced by excluded employer benefits and by the
+    /// earned income of the lower-earning spouse.
+    fn dependent_care(input: &TaxInput, rules: &TaxRules, base: &CreditBase) -> Credit {
+        let credit = Credit::new(CreditKind::ChildAndDependentCare, "Schedule 3 line 2", false);
+        let care = &rules.credits.dependent_care;
+        let entries = &input.credits;
+
+        if base.status == FilingStatus::MarriedFilingSeparately {
+            return credit.denied("not available when married filing separately");
+        }
+        let persons = input.dependents.iter().filter(|d| d.child_and_dependent_care_credit).count();
+        if persons == 0 {
+            return credit.denied("no qualifying person: a child under 13 or a dependent unable to care for themselves");
+        }
+        if entries.dependent_care_expenses <= Decimal::ZERO {
+            return credit.denied("no care expenses entered");
+        }
+
+        let mut credit = credit;
+        let limit = if persons == 1 { care.one_person_expense_limit } else { care.two_or_more_expense_limit };
+        let limit = (limit - entries.dependent_care_benefits).max(Decimal::ZERO);
+        let mut expenses = entries.dependent_care_expenses.min(limit);
+        credit.explanation.push(format!("{persons} qualifying person(s); ${expenses} of ${} expenses within the limit", entries.dependent_care_expenses));
+        if base.lower_earned_income < expenses {
+            expenses = base.lower_earned_income.max(Decimal::ZERO);
+            credit.explanation.push(format!("limited to earned income of ${expenses}"));
+        }
+
+        let rate = care.rate(base.adjusted_gross_income);
+        credit.tentative = round_cents(expenses * rate);
+        credit.explanation.push(format!("{}% of expenses at AGI ${}", rate * Decimal::ONE_HUNDRED, base.adjusted_gross_income));
+        credit
+    }
+
+    /// Form 8863. Each student gets the American Opportunity credit when eligible (it is never
+    /// smaller for the same expenses), otherwise their expenses count toward the Lifetime
+    /// Learning credit. Both phase out over the same MAGI range.
+    fn education(input: &TaxInput, rules: &TaxRules, base: &CreditBase) -> (Credit, Credit, Credit) {
+        let mut aotc = Credit::new(CreditKind::AmericanOpportunity, "Schedule 3 line 3", false);
+        let mut llc = Credit::new(CreditKind::LifetimeLearning, "Schedule 3 line 3", false);
+        let mut refundable = Credit::new(CreditKind::RefundableAmericanOpportunity, "Form 1040 line 29", true);
+        let american_opportunity = &rules.credits.american_opportunity;
+        let lifetime_learning = &rules.credits.lifetime_learning;
+
+        let denial = if input.form_1098ts.is_empty() {
+            Some("no 1098-T tuition statements")
+        } else if base.status == FilingStatus::MarriedFilingSeparately {
+            Some("not available when married filing separately")
+        } else if input.credits.claimed_as_dependent {
+            Some("not available to someone claimed as a dependent")
+        } else {
+            None
+        };
+        if let Some(reason) = denial {
+            return (aotc.denied(reason), llc.denied(reason), refundable.denied(reason));
+        }
+
+        let mut students: Vec<&str> = input.form_1098ts.iter().map(|form| form.student.as_str()).collect();
+        students.sort_unstable();
+        students.dedup();
+
+        let mut aotc_total = Decimal::ZERO;
+        let mut llc_expenses = Decimal::ZERO;
+        for student in students {
+            let forms: Vec<_> = input.form_1098ts.iter().filter(|form| form.student == student).collect();
+            let tuition = forms.iter().map(|form| form.qualified_tuition - form.scholarships).sum::<Decimal>().max(Decimal::ZERO);
+            let name = if student.is_empty() { "student" } else { student };
+
+            let ineligible = if !forms.iter().any(|form| form.at_least_half_time) {
+                Some("not enrolled at least half-time")
+            } else if forms.iter().any(|form| form.graduate_student) {
+                Some("graduate student")
+            } else if forms.iter().map(|form| form.american_opportunity_years_claimed).max().unwrap_or_default() >= american_opportunity.maximum_years {
+                Some("credit already claimed for the maximum number of years")
+            } else {
+                None
+            };
+
+            match ineligible {
+                None => {
+                    let expenses = tuition + forms.iter().map(|form| form.course_materials).sum::<Decimal>();
+                    let first_tier = expenses.min(american_opportunity.first_tier_expenses);
+                    let second_tier = (expenses - first_tier).min(american_opportunity.second_tier_expenses);
+                    let amount = first_tier + round_cents(second_tier * american_opportunity.second_tier_rate);
+                    aotc.explanation.push(format!("{name}: ${amount} on ${expenses} of expenses"));
+                    aotc_total += amount;
+                }
+                Some(reason) => {
+                    llc.explanation.push(format!("{name}: ${tuition} of tuition ({reason}, so not eligible for the American Opportunity credit)"));
+                    llc_expenses += tuition;
+                }
+            }
+        }
+        if llc_expenses > lifetime_learning.expense_limit {
+            llc.explanation.push(format!("expenses limited to ${} per return", lifetime_learning.expense_limit));
+        }
+        let llc_total = round_cents(llc_expenses.min(lifetime_learning.expense_limit) * lifetime_learning.rate);
+
+        // Form 8863 lines 3-6 and 10-17 round the allowed share to three decimal places.
+        let phase_out = rules.phase_outs.education.get(base.status);
+        let allowed_share = (Decimal::ONE - phase_out.reduction_fraction(base.adjusted_gross_income)).round_dp(3);
+        if allowed_share < Decimal::ONE {
+            let message = format!("{}% allowed at MAGI ${} (phase-out ${} to ${})",
+                allowed_share * Decimal::ONE_HUNDRED, base.adjusted_gross_income, phase_out.start, phase_out.end);
+            aotc.explanation.push(message.clone());
+            llc.explanation.push(message);
+        }
+
+        let aotc_allowed = round_cents(aotc_total * allowed_share);
+        refundable.tentative = round_cents(aotc_allowed * american_opportunity.refundable_share);
+        refundable.allowed = refundable.tentative;
+        aotc.tentative = aotc_allowed - refundable.tentative;
+        llc.tentative = round_cents(llc_total * allowed_share);
+
+        if aotc_total.is_zero() {
+            aotc = aotc.denied("no eligible students");
+            refundable = refundable.denied("no eligible students");
+        } else {
+            refundable.explanation.push(format!("{}% of the ${aotc_allowed} American Opportunity credit", american_opportunity.refundable_share * Decimal::ONE_HUNDRED));
+        }
+        if llc_expenses.is_zero() {
+            llc = llc.denied("no expenses outside the American Opportunity credit");
+        }
+
+        (aotc, llc, refundable)
+    }
+
+    /// Form 8880. Contributions count up to the per-person limit at the rate for the AGI tier.
+    fn savers(input: &TaxInput, rules: &TaxRules, base: &CreditBase) -> Credit {
+        let mut credit = Credit::new(CreditKind::Savers, "Schedule 3 line 4", false);
+        let savers = &rules.credits.savers;
+        let entries = &input.credits;
+
+        if entries.claimed_as_dependent {
+            return credit.denied("not available to someone claimed as a dependent");
+        }
+        let rate = savers.rate(base.status, base.adjusted_gross_income);
+        if rate.is_zero() {
+            return credit.denied(format!("AGI ${} is above the limit for {}", base.adjusted_gross_income, base.status));
+        }
+
+        let mut people = vec![("taxpayer", input.taxpayer_age, entries.taxpayer_full_time_student, entries.taxpayer_retirement_contributions)];
+        if base.status == FilingStatus::MarriedFilingJointly {
+            people.push(("spouse", input.spouse_age, entries.spouse_full_time_student, entries.spouse_retirement_contributions));
+        }
+
+        let mut contributions = Decimal::ZERO;
+        for (person, age, student, contributed) in people {
+            if contributed <= Decimal::ZERO {
+                continue;
+            }
+            if age.is_some_and(|age| age < SAVERS_CREDIT_MINIMUM_AGE) {
+                credit.explanation.push(format!("{person}: under {SAVERS_CREDIT_MINIMUM_AGE}"));
+            } else if student {
+                credit.explanation.push(format!("{person}: full-time student"));
+            } else {
+                let counted = contributed.min(savers.contribution_limit);
+                credit.explanation.push(format!("{person}: ${counted} of ${contributed} contributions counted"));
+                contributions += counted;
+            }
+        }
+        if contributions.is_zero() {
+            return credit.denied("no eligible retirement contributions");
+        }
+
+        credit.tentative = round_cents(contributions * rate);
+        credit.explanation.push(format!("{}% rate at AGI ${}", rate * Decimal::ONE_HUNDRED, base.adjusted_gross_income));
+        credit
+    }
+
+    /// Schedule 8812. The phase-out and the tax limit apply to the combined child tax credit and
+    /// credit for other dependents; what is allowed goes to the child tax credit first. The
+    /// unused child amount is refundable up to the per-child maximum and 15% of earned income
+    /// over the threshold (the alternative method for three or more children is not applied).
+    fn child_tax_credit(input: &TaxInput, rules: &TaxRules, base: &CreditBase, remaining_tax: &mut Decimal) -> (Credit, Credit, Credit) {
+        let mut ctc = Credit::new(CreditKind::ChildTax, "Form 1040 line 19", false);
+        let mut odc = Credit::new(CreditKind::OtherDependents, "Form 1040 line 19", false);
+        let mut actc = Credit::new(CreditKind::AdditionalChildTax, "Form 1040 line 28", true);
+        let credits = &rules.credits;
+
+        let children = input.dependents.iter().filter(|d| d.child_tax_credit).count() as u32;
+        let others = input.dependents.iter().filter(|d| d.credit_for_other_dependents).count() as u32;
+        let child_amount = credits.child_tax_credit * Decimal::from(children);
+        let other_amount = credits.other_dependent_credit * Decimal::from(others);
+
+        let start = *credits.child_tax_credit_phase_out_start.get(base.status);
+        let excess = (base.adjusted_gross_income - start).max(Decimal::ZERO);
+        let reduction = (excess / Decimal::ONE_THOUSAND).ceil() * credits.child_tax_credit_reduction;
+        let combined = (child_amount + other_amount - reduction).max(Decimal::ZERO);
+        let allowed = combined.min(*remaining_tax);
+        *remaining_tax -= allowed;
+
+        let phase_out_message = (!reduction.is_zero() && !(child_amount + other_amount).is_zero())
+            .then(|| format!("reduced by ${reduction}: MAGI ${} is over ${start}", base.adjusted_gross_income));
+        let limit_message = (allowed < combined).then(|| format!("limited to ${allowed} of remaining tax"));
+
+        if children == 0 {
+            ctc = ctc.denied("no qualifying children under 17 with an SSN");
+            actc = actc.denied("no qualifying children under 17 with an SSN");
+        } else {
+            ctc.explanation.push(format!("{children} qualifying child(ren) × ${}", credits.child_tax_credit));
+            ctc.tentative = child_amount.min(combined);
+            ctc.allowed = ctc.tentative.min(allowed);
+            ctc.explanation.extend(phase_out_message.clone());
+            ctc.explanation.extend(limit_message.clone());
+        }
+        if others == 0 {
+            odc = odc.denied("no other dependents");
+        } else {
+            odc.explanation.push(format!("{others} other dependent(s) × ${}", credits.other_dependent_credit));
+            odc.tentative = combined - ctc.tentative;
+            odc.allowed = allowed - ctc.allowed;
+            odc.explanation.extend(phase_out_message);
+            odc.explanation.extend(limit_message);
+        }
+
+        if children > 0 {
+            let unused = combined - allowed;
+            let per_child_limit = credits.additional_child_tax_credit_max * Decimal::from(children);
+            let earned = round_cents((base.earned_income - credits.additional_child_tax_credit_earned_income_threshold).max(Decimal::ZERO)
+                * credits.additional_child_tax_credit_rate);
+            actc.tentative = unused.min(per_child_limit);
+            actc.allowed = actc.tentative.min(earned);
+            actc.explanation.push(match unused.is_zero() {
+                true => "child tax credit fully used against tax".into(),
+                false => format!("${unused} unused, up to ${per_child_limit} for {children} child(ren)"),
+            });
+            if actc.allowed < actc.tentative {
+                actc.explanation.push(format!("limited to {}% of earned income over ${}: ${earned}",
+                    credits.additional_child_tax_credit_rate * Decimal::ONE_HUNDRED, credits.additional_child_tax_credit_earned_income_threshold));
+            }
+        }
+
+        (ctc, odc, actc)
+    }
+
+    /// EIC worksheet A. The credit is looked up for earned income and, when AGI differs and is
+    /// in the phase-out range, for AGI too; the smaller applies.
+    fn earned_income(input: &TaxInput, rules: &TaxRules, base: &CreditBase) -> Credit {
+        let mut credit = Credit::new(CreditKind::EarnedIncome, "Form 1040 line 27", true);
+        let eic = &rules.credits.earned_income;
+
+        if base.status == FilingStatus::MarriedFilingSeparately {
+            return credit.denied("not available when married filing separately");
+        }
+        if input.credits.claimed_as_dependent {
+            return credit.denied("not available to someone claimed as a dependent");
+        }
+        if base.investment_income > eic.investment_income_limit {
+            return credit.denied(format!("investment income ${} is over ${}", base.investment_income, eic.investment_income_limit));
+        }
+        if base.earned_income <= Decimal::ZERO {
+            return credit.denied("no earned income");
+        }
+
+        let children = input.dependents.iter().filter(|d| d.earned_income_credit).count();
+        if children == 0 {
+            let (minimum, maximum) = EARNED_INCOME_CREDIT_AGES;
+            let mut ages = vec![input.taxpayer_age];
+            if base.status == FilingStatus::MarriedFilingJointly {
+                ages.push(input.spouse_age);
+            }
+            let known: Vec<i32> = ages.into_iter().flatten().collect();
+            if known.is_empty() {
+                return credit.denied(format!("without a qualifying child a date of birth is needed to confirm age {minimum} to {maximum}"));
+            }
+            if !known.iter().any(|age| (minimum..=maximum).contains(age)) {
+                return credit.denied(format!("without a qualifying child the taxpayer or spouse must be {minimum} to {maximum}"));
+            }
+        }
+
+        let schedule = &eic.schedules[children.min(eic.schedules.len() - 1)];
+        let by_earned_income = earned_income_table(schedule, base.status, base.earned_income);
+        let start = *schedule.phase_out_start.get(base.status);
+        credit.explanation.push(format!("{children} qualifying child(ren); ${by_earned_income} for earned income ${}", base.earned_income));
+
+        credit.tentative = by_earned_income;
+        if base.adjusted_gross_income != base.earned_income && base.adjusted_gross_income >= start {
+            let by_agi = earned_income_table(schedule, base.status, base.adjusted_gross_income);
+            if by_agi < by_earned_income {
+                credit.explanation.push(format!("${by_agi} for AGI ${}, which is smaller", base.adjusted_gross_income));
+                credit.tentative = by_agi;
+            }
+        }
+        if credit.tentative.is_zero() {
+            credit.explanation.push(format!("income is above the phase-out range starting at ${start}"));
+        }
+
+        credit.allowed = credit.tentative;
+        credit
+    }
+}
+
+/// The EIC table figures the credit at the midpoint of each $50 row and rounds to whole dollars.
+fn earned_income_table(schedule: &EarnedIncomeSchedule, status: FilingStatus, income: Decimal) -> Decimal {
+    if income < Decimal::ONE {
+        return Decimal::ZERO;
+    }
+
+    let width = Decimal::from(EARNED_INCOME_TABLE_ROW);
+    let midpoint = (income / width).floor() * width + width / Decimal::TWO;
+    let phase_in = (midpoint * schedule.credit_rate).min(schedule.maximum_credit);
+    let phase_out = (midpoint - schedule.phase_out_start.get(status)).max(Decimal::ZERO) * schedule.phase_out_rate;
+
+    round_dollars((phase_in - phase_out).max(Decimal::ZERO))
+}
@@ -0,0 +1,401 @@
+//! Form 1040-ES planner: required installments under the Form 2210 safe harbors, with
+//! the payments made so far applied to them.
+//!
+//! The required annual payment is the smaller of `current_year_rate` of the projected
+//! current-year tax and `prior_year_rate` (or the high-income rate) of the prior year's tax.
+//! Withholding counts as paid evenly through the year, so each installment is a quarter of
+//! what withholding leaves. Payments go to the earliest installment still owed, whenever
+//! they were made, as on Form 2210.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::{round_cents, TaxCalculation};
+use crate::utilities::error_bag::ErrorBag;
+
+/// A payment as reported by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedPayment {
+    pub quarter: i16,
+    pub amount: Decimal,
+    pub paid_on: NaiveDate,
+}
+
+/// The prior year's return, as far as the safe harbor needs it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PriorYearTax {
+    pub year: i32,
+    /// Filing status on the prior return, which sets the high-income threshold
+    pub filing_status: FilingStatus,
+    pub adjusted_gross_income: Decimal,
+    /// Form 1040 line 24 less refundable credits
+    pub tax: Decimal,
+}
+
+impl PriorYearTax {
+    pub fn from_calculation(calculation: &TaxCalculation) -> PriorYearTax {
+        PriorYearTax {
+            year: calculation.year,
+            filing_status: calculation.filing_status,
+            adjusted_gross_income: calculation.adjusted_gross_income,
+            tax: (calculation.total_tax - calculation.refundable_credits).max(Decimal::ZERO),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeHarborRule {
+    CurrentYear,
+    PriorYear,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeHarbor {
+    pub rule: SafeHarborRule,
+    pub rate: Decimal,
+    /// The tax the rate applies to
+    pub tax: Decimal,
+    pub required: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Installment {
+    pub quarter: u32,
+    pub due_date: NaiveDate,
+    pub required: Decimal,
+    /// Payments applied to this installment
+    pub paid: Decimal,
+    pub remaining: Decimal,
+    /// Due before the plan's `as_of` date with something still owed
+    pub past_due: bool,
+    /// Amount for the 1040-ES voucher: what is still owed on this installment
+    pub voucher_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimatedTaxPlan {
+    pub year: i32,
+    /// Date the year-to-date inputs run through
+    pub as_of: NaiveDate,
+    /// Year-to-date income is multiplied by this to project the full year
+    pub annualization_factor: Decimal,
+    /// Projected Form 1040 line 24 less refundable credits
+    pub projected_tax: Decimal,
+    pub projected_withholding: Decimal,
+    pub prior_year: Option<PriorYearTax>,
+    pub safe_harbors: Vec<SafeHarbor>,
+    /// Smallest of the safe harbors
+    pub required_annual_payment: Decimal,
+    /// What withholding leaves for the four installments
+    pub required_estimated_payments: Decimal,
+    pub total_paid: Decimal,
+    pub installments: Vec<Installment>,
+    pub notes: Vec<String>,
+}
+
+pub struct EstimatedTaxPlanner;
+
+impl EstimatedTaxPlanner {
+    /// Share of the year through `as_of`, inverted: 365 / days elapsed, to four decimal places.
+    /// Dates after the year end give 1.
+    pub fn annualization_factor(year: i32, as_of: NaiveDate) -> Decimal {
+        let (Some(first), Some(last)) = (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) else {
+            return Decimal::ONE;
+        };
+        if as_of >= last {
+            return Decimal::ONE;
+        }
+        let elapsed = (as_of.max(first) - first).num_days() + 1;
+        let days = (last - first).num_days() + 1;
+
+        (Decimal::from(days) / Decimal::from(elapsed)).round_dp(4)
+    }
+
+    /// Scales year-to-date wages, 1099 income, business activity, other income and the
+    /// withholding on them by `factor`. Capital gains and deductions are left as entered,
+    /// since they do not accrue evenly. Fails when a scaled amount does not fit.
+    pub fn project(input: &TaxInput, factor: Decimal) -> Result<TaxInput, ErrorBag> {
+        let mut projected = input.clone();
+        if factor == Decimal::ONE {
+            return Ok(projected);
+        }
+        let mut overflowed = false;
+        let mut scale = |amount: &mut Decimal| match amount.checked_mul(factor) {
+            Some(scaled) => *amount = round_cents(scaled),
+            None => overflowed = true,
+        };
+
+        for w2 in &mut projected.w2s {
+            scale(&mut w2.wages);
+            scale(&mut w2.federal_tax_withheld);
+            scale(&mut w2.state_tax_withheld);
+            scale(&mut w2.local_tax_withheld);
+            for amount in [&mut w2.social_security_wages, &mut w2.social_security_tax_withheld, &mut w2.medicare_wages, &mut w2.medicare_tax_withheld, &mut w2.state_wages] {
+                amount.iter_mut().for_each(&mut scale);
+            }
+        }
+        for form in &mut projected.form_1099s {
+            scale(&mut form.income);
+            scale(&mut form.qualified_dividends);
+            scale(&mut form.capital_gain_distributions);
+            scale(&mut form.federal_tax_withheld);
+        }
+        for business in &mut projected.businesses {
+            scale(&mut business.other_receipts);
+            scale(&mut business.returns_and_allowances);
+            scale(&mut business.cost_of_goods_sold);
+            scale(&mut business.other_income);
+            business.expenses.iter_mut().for_each(|expense| scale(&mut expense.amount));
+        }
+        scale(&mut projected.rental_income);
+        scale(&mut projected.other_income);
+        if overflowed {
+            return Err(ErrorBag::Validation {
+                field: "annualized_income".into(),
+                message: format!("Income projected at {factor} times is too large"),
+            });
+        }
+        projected.warnings.push(format!("Year-to-date income projected to the full year at {factor} times"));
+
+        Ok(projected)
+    }
+
+    /// The current-year safe harbor on `current_tax` and, when there is a prior-year return, the prior-year one.
+    /// The high-income rate turns on the prior return's AGI and filing status.
+    pub fn safe_harbors(
+        current_tax: Decimal,
+        prior_year: Option<&PriorYearTax>,
+        rules: &TaxRules,
+        notes: &mut Vec<String>,
+    ) -> Vec<SafeHarbor> {
+        let estimated = &rules.estimated_tax;
+        let mut safe_harbors = vec![SafeHarbor {
+            rule: SafeHarborRule::CurrentYear,
+            rate: estimated.current_year_rate,
+            tax: current_tax,
+            required: round_cents(current_tax * estimated.current_year_rate),
+        }];
+        match prior_year {
+            Some(prior) => {
+                let rate = estimated.prior_year_rate(prior.filing_status, prior.adjusted_gross_income);
+                if rate > estimated.prior_year_rate {
+                    notes.push(format!("{} AGI of ${} is over ${}, so the prior-year safe harbor is {}% of its tax",
+                        prior.year, prior.adjusted_gross_income, estimated.high_income_threshold.get(prior.filing_status), rate * Decimal::ONE_HUNDRED));
+                }
+                safe_harbors.push(SafeHarbor { rule: SafeHarborRule::PriorYear, rate, tax: prior.tax, required: round_cents(prior.tax * rate) });
+            }
+            None => notes.push(format!("No {} return on file, so only the current-year safe harbor applies", rules.year - 1)),
+        }
+
+        safe_harbors
+    }
+
+    /// Plans the year from the projected calculation and, when there is one, the prior year's return.
+    pub fn plan(
+        as_of: NaiveDate,
+        factor: Decimal,
+        projected: &TaxCalculation,
+        prior_year: Option<PriorYearTax>,
+        payments: &[EstimatedPayment],
+        rules: &TaxRules,
+    ) -> EstimatedTaxPlan {
+        let estimated = &rules.estimated_tax;
+        let projected_tax = (projected.total_tax - projected.refundable_credits).max(Decimal::ZERO);
+        let projected_withholding = projected.withholding;
+        let mut notes = Vec::new();
+
+        let safe_harbors = Self::safe_harbors(projected_tax, prior_year.as_ref(), rules, &mut notes);
+        let required_annual_payment = safe_harbors.iter().map(|harbor| harbor.required).min().unwrap_or_default();
+
+        let mut required_estimated_payments = (required_annual_payment - projected_withholding).max(Decimal::ZERO);
+        if projected_tax - projected_withholding < estimated.minimum_balance_due {
+            notes.push(format!("Withholding leaves less than ${} of tax, so no estimated payments are required", estimated.minimum_balance_due));
+            required_estimated_payments = Decimal::ZERO;
+        } else if prior_year.as_ref().is_some_and(|prior| prior.tax.is_zero()) {
+            notes.push(format!("There was no tax for {}, so no estimated payments are required", rules.year - 1));
+            required_estimated_payments = Decimal::ZERO;
+        }
+
+        let quarter_amount = round_cents(required_estimated_payments / Decimal::from(4));
+        let mut installments: Vec<Installment> = estimated.due_dates.iter().enumerate()
+            .map(|(index, due_date)| Installment {
+                quarter: index as u32 + 1,
+                due_date: *due_date,
+                required: match index {
+                    3 => required_estimated_payments - quarter_amount * Decimal::from(3),
+                    _ => quarter_amount,
+                },
+                paid: Decimal::ZERO,
+                remaining: Decimal::ZERO,
+                past_due: false,
+                voucher_amount: Decimal::ZERO,
+            })
+            .collect();
+
+        let mut payments = payments.to_vec();
+        payments.sort_by_key(|payment| payment.paid_on);
+        for payment in &payments {
+            let mut amount = payment.amount;
+            for installment in installments.iter_mut() {
+                let applied = amount.min((installment.required - installment.paid).max(Decimal::ZERO));
+                installment.paid += applied;
+                amount -= applied;
+            }
+            if let Some(last) = installments.last_mut() {
+                last.paid += amount;
+            }
+            if payment.paid_on > estimated.due_dates[3] {
+                notes.push(format!("Payment of ${} on {} was made after the last installment was due", payment.amount, payment.paid_on));
+            }
+        }
+        for installment in &mut installments {
+            installment.remaining = (installment.required - installment.paid).max(Decimal::ZERO);
+            installment.past_due = installment.due_date < as_of && !installment.remaining.is_zero();
+            installment.voucher_amount = installment.remaining;
+        }
+        if as_of > estimated.due_dates[3] {
+            notes.push("Every installment date has passed; any shortfall is figured on Form 2210 with the return".into());
+        }
+
+        EstimatedTaxPlan {
+            year: projected.year,
+            as_of,
+            annualization_factor: factor,
+            projected_tax,
+            projected_withholding,
+            prior_year,
+            safe_harbors,
+            required_annual_payment,
+            required_estimated_payments,
+            total_paid: payments.iter().map(|payment| payment.amount).sum(),
+            installments,
+            notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+    use super::{EstimatedPayment, EstimatedTaxPlanner, PriorYearTax, SafeHarborRule};
+    use crate::models::filing_profile_model::FilingStatus;
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::rules::TaxRules;
+    use crate::tax_engine::TaxEngine;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn prior(filing_status: FilingStatus, adjusted_gross_income: i64, tax: i64) -> PriorYearTax {
+        PriorYearTax { year: 2023, filing_status, adjusted_gross_income: adjusted_gross_income.into(), tax: tax.into() }
+    }
+
+    /// Single, $100,000 of wages: $85,400 taxable and $13,847 of tax from the tax table.
+    fn input(withheld: i64) -> TaxInput {
+        serde_json::from_value(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 100000, "federal_tax_withheld": withheld }]
+        })).unwrap()
+    }
+
+    #[test]
+    fn annualization_factor_inverts_the_share_of_the_year() {
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2024, 3, 31)), Decimal::new(40220, 4));
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2024, 6, 30)), Decimal::new(20110, 4));
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2024, 1, 1)), Decimal::from(366));
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2023, 12, 1)), Decimal::from(366));
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2024, 12, 31)), Decimal::ONE);
+        assert_eq!(EstimatedTaxPlanner::annualization_factor(2024, date(2025, 2, 1)), Decimal::ONE);
+    }
+
+    #[test]
+    fn prior_year_safe_harbor_is_100_percent_below_the_threshold() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let mut notes = Vec::new();
+        let harbors = EstimatedTaxPlanner::safe_harbors(10000.into(), Some(&prior(FilingStatus::Single, 150000, 8000)), rules, &mut notes);
+
+        assert_eq!(harbors[0].rule, SafeHarborRule::CurrentYear);
+        assert_eq!(harbors[0].required, Decimal::from(9000));
+        assert_eq!(harbors[1].rule, SafeHarborRule::PriorYear);
+        assert_eq!(harbors[1].rate, Decimal::ONE);
+        assert_eq!(harbors[1].required, Decimal::from(8000));
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn prior_year_safe_harbor_is_110_percent_above_the_threshold() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let mut notes = Vec::new();
+        let harbors = EstimatedTaxPlanner::safe_harbors(10000.into(), Some(&prior(FilingStatus::Single, 150001, 8000)), rules, &mut notes);
+
+        assert_eq!(harbors[1].rate, Decimal::new(11, 1));
+        assert_eq!(harbors[1].required, Decimal::from(8800));
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn high_income_threshold_follows_the_prior_year_filing_status() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let rate = |status| {
+            let harbors = EstimatedTaxPlanner::safe_harbors(10000.into(), Some(&prior(status, 80000, 8000)), rules, &mut Vec::new());
+            harbors[1].rate
+        };
+
+        assert_eq!(rate(FilingStatus::MarriedFilingSeparately), Decimal::new(11, 1));
+        assert_eq!(rate(FilingStatus::Single), Decimal::ONE);
+    }
+
+    #[test]
+    fn only_the_current_year_safe_harbor_without_a_prior_return() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let mut notes = Vec::new();
+        let harbors = EstimatedTaxPlanner::safe_harbors(10000.into(), None, rules, &mut notes);
+
+        assert_eq!(harbors.len(), 1);
+        assert_eq!(harbors[0].required, Decimal::from(9000));
+        assert!(notes[0].contains("No 2023 return"));
+    }
+
+    #[test]
+    fn plan_applies_payments_to_the_earliest_installments() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let calculation = TaxEngine::calculate(&input(5000)).unwrap();
+        let payments = [EstimatedPayment { quarter: 1, amount: 2000.into(), paid_on: date(2024, 4, 10) }];
+        let plan = EstimatedTaxPlanner::plan(date(2024, 7, 1), Decimal::ONE, &calculation, Some(prior(FilingStatus::Single, 90000, 12000)), &payments, rules);
+
+        assert_eq!(plan.projected_tax, Decimal::from(13847));
+        assert_eq!(plan.projected_withholding, Decimal::from(5000));
+        // 90% of $13,847 is $12,462.30, so 100% of the prior year's $12,000 is required.
+        assert_eq!(plan.required_annual_payment, Decimal::from(12000));
+        assert_eq!(plan.required_estimated_payments, Decimal::from(7000));
+        assert_eq!(plan.total_paid, Decimal::from(2000));
+
+        let paid: Vec<Decimal> = plan.installments.iter().map(|installment| installment.paid).collect();
+        let vouchers: Vec<Decimal> = plan.installments.iter().map(|installment| installment.voucher_amount).collect();
+        let past_due: Vec<bool> = plan.installments.iter().map(|installment| installment.past_due).collect();
+        assert!(plan.installments.iter().all(|installment| installment.required == Decimal::from(1750)));
+        assert_eq!(paid, [1750, 250, 0, 0].map(Decimal::from));
+        assert_eq!(vouchers, [0, 1500, 1750, 1750].map(Decimal::from));
+        assert_eq!(past_due, [false, true, false, false]);
+        assert_eq!(plan.installments[1].due_date, date(2024, 6, 17));
+    }
+
+    #[test]
+    fn plan_requires_nothing_when_withholding_leaves_under_the_minimum() {
+        let rules = TaxRules::for_year(2024).unwrap();
+        let calculation = TaxEngine::calculate(&input(13000)).unwrap();
+        let plan = EstimatedTaxPlanner::plan(date(2024, 7, 1), Decimal::ONE, &calculation, None, &[], rules);
+
+        assert!(plan.required_estimated_payments.is_zero());
+        assert!(plan.installments.iter().all(|installment| installment.voucher_amount.is_zero() && !installment.past_due));
+        assert!(plan.notes.iter().any(|note| note.contains("less than $1000")));
+    }
+}
@@ -0,0 +1,557 @@
+//! Filing status optimizer: figures the return under every status the household can use and
+//! ranks them by what they cost.
+//!
+//! Married couples are compared as one joint return against two separate returns. Separate
+//! returns split the input by owner, and household amounts by the spouse's part entered in
+//! `spouse_share`; amounts with neither are split evenly, which is an assumption the
+//! comparison reports.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::input::{Adjustments, AmtAdjustments, CapitalLossCarryover, CreditEntries, Form1098, ItemizedEntries, TaxInput};
+use crate::tax_engine::{round_cents, DeductionKind, TaxCalculation, TaxEngine};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Differences smaller than this are not worth explaining.
+const MINIMUM_DRIVER: Decimal = Decimal::ONE;
+/// Drivers listed per option, largest first.
+const MAXIMUM_DRIVERS: usize = 5;
+
+/// A status the household cannot use, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct IneligibleStatus {
+    pub filing_status: FilingStatus,
+    pub reason: String,
+}
+
+/// Something that makes an option cost more or less than the best one.
+#[derive(Debug, Clone, Serialize)]
+pub struct Driver {
+    /// What changed, e.g. `tax`, `state_tax` or a credit such as `earned_income`
+    pub item: String,
+    /// Added tax compared with the best option; negative when this option does better on the item
+    pub tax_effect: Decimal,
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilingStatusOption {
+    pub filing_status: FilingStatus,
+    /// 1 for the cheapest option
+    pub rank: usize,
+    /// Form 1040 line 24 less refundable credits, over every federal return
+    pub federal_tax: Decimal,
+    pub state_tax: Decimal,
+    pub total_tax: Decimal,
+    /// Refunds less balances due, federal and state
+    pub net_refund: Decimal,
+    /// Extra tax compared with the best option
+    pub difference: Decimal,
+    /// Why this option costs more than the best one; empty for the best one
+    pub drivers: Vec<Driver>,
+    /// One return, or the taxpayer's then the spouse's when filing separately
+    pub returns: Vec<TaxCalculation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilingStatusComparison {
+    /// Status on the filing profile
+    pub current: FilingStatus,
+    pub recommended: FilingStatus,
+    /// Tax saved by the recommended status over the current one
+    pub savings: Decimal,
+    /// Cheapest first
+    pub options: Vec<FilingStatusOption>,
+    pub ineligible: Vec<IneligibleStatus>,
+    /// How income and deductions were divided for separate returns
+    pub assumptions: Vec<String>,
+}
+
+pub struct FilingStatusOptimizer;
+
+impl FilingStatusOptimizer {
+    /// Figures every status the filing profile allows. `input.filing_status` is the profile's status.
+    ///
+    /// - Married couples can file jointly or separately. Head of household also needs the
+    ///   spouses to have lived apart for the last six months of the year, which the profile
+    ///   does not record, so it is not offered.
+    /// - Unmarried taxpayers can file single, head of household with a qualifying person, and
+    ///   as a qualifying surviving spouse only when the profile already says so, since the
+    ///   year of the spouse's death is not recorded.
+    pub fn compare(input: &TaxInput) -> Result<FilingStatusComparison, ErrorBag> {
+        let current = input.filing_status;
+        let married = current.requires_spouse();
+        let has_qualifying_person = input.dependents.iter().any(|d| d.head_of_household_qualifying_person);
+
+        let mut eligible = Vec::new();
+        let mut ineligible = Vec::new();
+        for status in FilingStatus::ALL {
+            let reason = match status {
+                FilingStatus::MarriedFilingJointly | FilingStatus::MarriedFilingSeparately if !married => Some("not married according to the filing profile".to_string()),
+                FilingStatus::Single if married => Some("married at the end of the year".to_string()),
+                FilingStatus::HeadOfHousehold if married => Some("married taxpayers must have lived apart from their spouse for the last six months of the year, which the filing profile does not record".to_string()),
+                FilingStatus::HeadOfHousehold if !has_qualifying_person => Some("no dependent is a qualifying person for head of household".to_string()),
+                FilingStatus::QualifyingSurvivingSpouse if current != FilingStatus::QualifyingSurvivingSpouse => Some("only available for two years after the spouse's death with a dependent child; set it on the filing profile".to_string()),
+                _ => None,
+            };
+            match reason {
+                Some(reason) => ineligible.push(IneligibleStatus { filing_status: status, reason }),
+                None => eligible.push(status),
+            }
+        }
+
+        let mut assumptions = Vec::new();
+        let mut options = Vec::new();
+        for status in eligible {
+            let returns = match status {
+                FilingStatus::MarriedFilingSeparately => {
+                    let (taxpayer, spouse, notes) = Self::separate_returns(input)?;
+                    assumptions.extend(notes);
+                    vec![taxpayer, spouse]
+                }
+                _ => vec![TaxEngine::calculate(&TaxInput { filing_status: status, ..input.clone() })?],
+            };
+            options.push(Self::option(status, returns));
+        }
+
+        options.sort_by_key(|option| option.total_tax);
+        let best = options[0].clone();
+        for (index, option) in options.iter_mut().enumerate() {
+            option.rank = index + 1;
+            option.difference = option.total_tax - best.total_tax;
+            if index > 0 {
+                option.drivers = Self::drivers(option, &best);
+            }
+        }
+
+        let savings = options.iter()
+            .find(|option| option.filing_status == current)
+            .map(|option| option.difference)
+            .unwrap_or_default();
+
+        Ok(FilingStatusComparison { current, recommended: best.filing_status, savings, options, ineligible, assumptions })
+    }
+
+    fn option(filing_status: FilingStatus, returns: Vec<TaxCalculation>) -> FilingStatusOption {
+        let federal_tax = returns.iter().map(|r| r.total_tax - r.refundable_credits).sum::<Decimal>();
+        let state_tax = returns.iter().flat_map(|r| &r.states).map(|state| state.total_tax).sum::<Decimal>();
+        let net_refund = returns.iter()
+            .map(|r| r.refund - r.amount_owed + r.states.iter().map(|state| state.refund - state.amount_owed).sum::<Decimal>())
+            .sum();
+
+        FilingStatusOption {
+            filing_status,
+            rank: 0,
+            federal_tax,
+            state_tax,
+            total_tax: federal_tax + state_tax,
+            net_refund,
+            difference: Decimal::ZERO,
+            drivers: Vec::new(),
+            returns,
+        }
+    }
+
+    /// Splits a joint input into the taxpayer's and the spouse's and figures both returns.
+    /// A spouse who itemizes takes away the other's standard deduction, so when only one
+    /// return itemizes the other is figured again itemizing too. Scenarios that file a joint
+    /// return separately use this too, so both give the same answer.
+    pub fn separate_returns(input: &TaxInput) -> Result<(TaxCalculation, TaxCalculation, Vec<String>), ErrorBag> {
+        let (mut taxpayer, mut spouse, mut notes) = Self::split(input);
+        if !input.dependents.is_empty() {
+            notes.push("Separate returns: every dependent is claimed by the taxpayer".into());
+        }
+
+        let mut taxpayer_return = TaxEngine::calculate(&taxpayer)?;
+        let mut spouse_return = TaxEngine::calculate(&spouse)?;
+        match (taxpayer_return.deduction.kind, spouse_return.deduction.kind) {
+            (DeductionKind::Itemized, DeductionKind::Standard) => {
+                spouse.itemized.spouse_itemizes = true;
+                spouse_return = TaxEngine::calculate(&spouse)?;
+                notes.push("Separate returns: the taxpayer itemizes, so the spouse must itemize too".into());
+            }
+            (DeductionKind::Standard, DeductionKind::Itemized) => {
+                taxpayer.itemized.spouse_itemizes = true;
+                taxpayer_return = TaxEngine::calculate(&taxpayer)?;
+                notes.push("Separate returns: the spouse itemizes, so the taxpayer must itemize too".into());
+            }
+            _ => {}
+        }
+
+        Ok((taxpayer_return, spouse_return, notes))
+    }
+
+    /// The taxpayer's and the spouse's separate inputs, and how they were divided. Each is
+    /// written from its filer's point of view, so the spouse's own amounts move to the
+    /// taxpayer fields.
+    pub fn split(input: &TaxInput) -> (TaxInput, TaxInput, Vec<String>) {
+        let business_owner = |id| input.businesses.iter().find(|b| b.id == Some(id)).map(|b| b.owner);
+        let owns = |owner: Owner, spouse: bool| (owner == Owner::Spouse) == spouse;
+        let share = &input.spouse_share;
+
+        let build = |spouse: bool| {
+            let half = |amount: Decimal| match spouse {
+                false => round_cents(amount / Decimal::TWO),
+                true => amount - round_cents(amount / Decimal::TWO),
+            };
+            // The spouse's part when entered, otherwise half
+            let part = |total: Decimal, spouse_part: Option<Decimal>| match (spouse_part, spouse) {
+                (Some(part), true) => part,
+                (Some(part), false) => total - part,
+                (None, _) => half(total),
+            };
+            let adjustment = |field: fn(&Adjustments) -> Decimal| part(field(&input.adjustments), share.adjustments.as_ref().map(field));
+            let itemized = |field: fn(&ItemizedEntries) -> Decimal| part(field(&input.itemized), share.itemized.as_ref().map(field));
+            let amt_adjustment = |field: fn(&AmtAdjustments) -> Decimal| part(field(&input.amt_adjustments), share.amt_adjustments.as_ref().map(field));
+            let capital_loss = |field: fn(&CapitalLossCarryover) -> Decimal| part(field(&input.capital_loss_carryover), share.capital_loss_carryover.as_ref().map(field));
+            let credits = &input.credits;
+
+            let mut filer = TaxInput {
+                year: input.year,
+                filing_status: FilingStatus::MarriedFilingSeparately,
+                taxpayer_65_or_older: if spouse { input.spouse_65_or_older } else { input.taxpayer_65_or_older },
+                taxpayer_blind: if spouse { input.spouse_blind } else { input.taxpayer_blind },
+                taxpayer_age: if spouse { input.spouse_age } else { input.taxpayer_age },
+                dependents: if spouse { Vec::new() } else { input.dependents.clone() },
+                residency: input.residency.clone(),
+                w2s: input.w2s.iter().filter(|w2| owns(w2.owner, spouse)).cloned().collect(),
+                form_1099s: input.form_1099s.iter()
+                    .filter(|form| owns(form.business_id.and_then(business_owner).unwrap_or(form.owner), spouse))
+                    .cloned()
+                    .collect(),
+                businesses: input.businesses.iter().filter(|b| owns(b.owner, spouse)).cloned().collect(),
+                capital_lots: input.capital_lots.iter().filter(|lot| owns(lot.owner, spouse)).cloned().collect(),
+                capital_loss_carryover: CapitalLossCarryover {
+                    short_term: capital_loss(|carryover| carryover.short_term),
+                    long_term: capital_loss(|carryover| carryover.long_term),
+                },
+                form_3921s: input.form_3921s.iter().filter(|form| owns(form.owner, spouse)).cloned().collect(),
+                amt_adjustments: AmtAdjustments {
+                    private_activity_bond_interest: amt_adjustment(|amt| amt.private_activity_bond_interest),
+                    incentive_stock_options: amt_adjustment(|amt| amt.incentive_stock_options),
+                    other: amt_adjustment(|amt| amt.other),
+                },
+                minimum_tax_credit_carryover: part(input.minimum_tax_credit_carryover, share.minimum_tax_credit_carryover),
+                rental_income: part(input.rental_income, share.rental_income),
+                other_income: part(input.other_income, share.other_income),
+                taxable_state_refund: part(input.taxable_state_refund, share.taxable_state_refund),
+                net_operating_loss_carryover: part(input.net_operating_loss_carryover, share.net_operating_loss_carryover),
+                qualified_business_loss_carryover: part(input.qualified_business_loss_carryover, share.qualified_business_loss_carryover),
+                adjustments: Adjustments {
+                    educator_expenses: adjustment(|adjustments| adjustments.educator_expenses),
+                    hsa_deduction: adjustment(|adjustments| adjustments.hsa_deduction),
+                    ira_deduction: adjustment(|adjustments| adjustments.ira_deduction),
+                    student_loan_interest: adjustment(|adjustments| adjustments.student_loan_interest),
+                    other: adjustment(|adjustments| adjustments.other),
+                },
+                form_1098s: input.form_1098s.iter()
+                    .filter(|form| form.owner.is_none_or(|owner| owns(owner, spouse)))
+                    .map(|form| match form.owner {
+                        Some(_) => Form1098 { owner: Some(Owner::Taxpayer), ..form.clone() },
+                        None => Form1098 {
+                            mortgage_interest: half(form.mortgage_interest),
+                            outstanding_principal: form.outstanding_principal.map(half),
+                            points: half(form.points),
+                            real_estate_taxes: half(form.real_estate_taxes),
+                            ..form.clone()
+                        },
+                    })
+                    .collect(),
+                form_1098ts: if spouse { Vec::new() } else { input.form_1098ts.clone() },
+                itemized: ItemizedEntries {
+                    medical_expenses: itemized(|itemized| itemized.medical_expenses),
+                    state_income_tax_paid: itemized(|itemized| itemized.state_income_tax_paid),
+                    state_estimated_payments: itemized(|itemized| itemized.state_estimated_payments),
+                    general_sales_tax: itemized(|itemized| itemized.general_sales_tax),
+                    real_estate_taxes: itemized(|itemized| itemized.real_estate_taxes),
+                    personal_property_taxes: itemized(|itemized| itemized.personal_property_taxes),
+                    mortgage_interest: itemized(|itemized| itemized.mortgage_interest),
+                    points: itemized(|itemized| itemized.points),
+                    charitable_cash: itemized(|itemized| itemized.charitable_cash),
+                    charitable_noncash: itemized(|itemized| itemized.charitable_noncash),
+                    charitable_capital_gain_property: itemized(|itemized| itemized.charitable_capital_gain_property),
+                    charitable_carryover: itemized(|itemized| itemized.charitable_carryover),
+                    other: itemized(|itemized| itemized.other),
+                    elect_to_itemize: input.itemized.elect_to_itemize,
+                    spouse_itemizes: false,
+                },
+                credits: CreditEntries {
+                    dependent_care_expenses: half(credits.dependent_care_expenses),
+                    dependent_care_benefits: half(credits.dependent_care_benefits),
+                    taxpayer_retirement_contributions: if spouse { credits.spouse_retirement_contributions } else { credits.taxpayer_retirement_contributions },
+                    spouse_retirement_contributions: Decimal::ZERO,
+                    taxpayer_full_time_student: if spouse { credits.spouse_full_time_student } else { credits.taxpayer_full_time_student },
+                    spouse_full_time_student: false,
+                    claimed_as_dependent: !spouse && credits.claimed_as_dependent,
+                },
+                estimated_payments: part(input.estimated_payments, share.estimated_payments),
+                warnings: if spouse { Vec::new() } else { input.warnings.clone() },
+                ..TaxInput::default()
+            };
+
+            // Each separate return belongs to one filer
+            filer.w2s.iter_mut().for_each(|w2| w2.owner = Owner::Taxpayer);
+            filer.form_1099s.iter_mut().for_each(|form| form.owner = Owner::Taxpayer);
+            filer.businesses.iter_mut().for_each(|business| business.owner = Owner::Taxpayer);
+            filer.capital_lots.iter_mut().for_each(|lot| lot.owner = Owner::Taxpayer);
+            filer.form_3921s.iter_mut().for_each(|form| form.owner = Owner::Taxpayer);
+            filer
+        };
+
+        let mut notes = vec!["Separate returns: W-2s, businesses, 1099s, 1098s, Forms 3921 and capital lots go to their owner; 1099s routed to a business follow the business".to_string()];
+        let split_evenly = Self::split_evenly(input);
+        if !split_evenly.is_empty() {
+            notes.push(format!("Separate returns: {} split evenly because the spouse's part is not entered", split_evenly.join(", ")));
+        }
+
+        (build(false), build(true), notes)
+    }
+
+    /// Amounts `split` divides in half because nothing says whose they are.
+    fn split_evenly(input: &TaxInput) -> Vec<String> {
+        let share = &input.spouse_share;
+        let entered = |amounts: &[(&str, Decimal)]| amounts.iter().any(|(_, amount)| !amount.is_zero());
+        let mut unknown = Vec::new();
+        for (name, has_amount, known) in [
+            ("adjustments", entered(&input.adjustments.amounts()), share.adjustments.is_some()),
+            ("itemized deductions", entered(&input.itemized.amounts()), share.itemized.is_some()),
+            ("AMT adjustments", entered(&input.amt_adjustments.amounts()), share.amt_adjustments.is_some()),
+            ("the capital loss carryover", entered(&input.capital_loss_carryover.amounts()), share.capital_loss_carryover.is_some()),
+            ("the minimum tax credit carryover", !input.minimum_tax_credit_carryover.is_zero(), share.minimum_tax_credit_carryover.is_some()),
+            ("rental income", !input.rental_income.is_zero(), share.rental_income.is_some()),
+            ("other income", !input.other_income.is_zero(), share.other_income.is_some()),
+            ("the taxable state refund", !input.taxable_state_refund.is_zero(), share.taxable_state_refund.is_some()),
+            ("the net operating loss carryover", !input.net_operating_loss_carryover.is_zero(), share.net_operating_loss_carryover.is_some()),
+            ("the qualified business loss carryover", !input.qualified_business_loss_carryover.is_zero(), share.qualified_business_loss_carryover.is_some()),
+            ("estimated payments", !input.estimated_payments.is_zero(), share.estimated_payments.is_some()),
+        ] {
+            if has_amount && !known {
+                unknown.push(name.to_string());
+            }
+        }
+        unknown.extend(input.form_1098s.iter().filter(|form| form.owner.is_none()).map(|form| format!("the 1098 from {}", form.lender)));
+        unknown
+    }
+
+    /// Explains an option against the best one: regular tax, AMT, each credit that differs,
+    /// other taxes and state tax, largest effect first.
+    fn drivers(option: &FilingStatusOption, best: &FilingStatusOption) -> Vec<Driver> {
+        let total = |option: &FilingStatusOption, line: fn(&TaxCalculation) -> Decimal| option.returns.iter().map(line).sum::<Decimal>();
+        let mut drivers = Vec::new();
+
+        let tax_effect = total(option, |r| r.tax) - total(best, |r| r.tax);
+        drivers.push(Driver {
+            item: "tax".into(),
+            tax_effect,
+            explanation: format!(
+                "Tax on ${} of taxable income after a ${} deduction, against ${} after ${} filing {}",
+                total(option, |r| r.taxable_income), total(option, |r| r.deduction.amount),
+                total(best, |r| r.taxable_income), total(best, |r| r.deduction.amount),
+                best.filing_status,
+            ),
+        });
+
+        let amt = total(option, |r| r.alternative_minimum_tax.tax) - total(best, |r| r.alternative_minimum_tax.tax);
+        drivers.push(Driver {
+            item: "alternative_minimum_tax".into(),
+            tax_effect: amt,
+            explanation: match amt < Decimal::ZERO {
+                true => format!("Alternative minimum tax is ${} lower", -amt),
+                false => format!("Alternative minimum tax is ${amt} higher, on ${} of AMTI", total(option, |r| r.alternative_minimum_tax.alternative_minimum_taxable_income)),
+            },
+        });
+        let minimum_tax_credit = total(option, |r| r.alternative_minimum_tax.credit_allowed) - total(best, |r| r.alternative_minimum_tax.credit_allowed);
+        drivers.push(Driver {
+            item: "minimum_tax_credit".into(),
+            tax_effect: -minimum_tax_credit,
+            explanation: match minimum_tax_credit < Decimal::ZERO {
+                true => format!("Minimum tax credit is ${} lower", -minimum_tax_credit),
+                false => format!("Minimum tax credit is ${minimum_tax_credit} higher"),
+            },
+        });
+
+        let credit_total = |option: &FilingStatusOption, kind| option.returns.iter()
+            .flat_map(|r| &r.credits.credits)
+            .filter(|credit| credit.credit == kind)
+            .map(|credit| credit.allowed)
+            .sum::<Decimal>();
+        for credit in best.returns[0].credits.credits.iter().map(|credit| credit.credit) {
+            let difference = credit_total(option, credit) - credit_total(best, credit);
+            let reason = option.returns.iter()
+                .flat_map(|r| &r.credits.credits)
+                .filter(|c| c.credit == credit)
+                .find_map(|c| c.explanation.last());
+            drivers.push(Driver {
+                item: credit.as_str().into(),
+                tax_effect: -difference,
+                explanation: match reason {
+                    Some(reason) if difference < Decimal::ZERO => format!("{} is ${} lower: {reason}", credit.name(), -difference),
+                    _ if difference < Decimal::ZERO => format!("{} is ${} lower", credit.name(), -difference),
+                    _ => format!("{} is ${difference} higher", credit.name()),
+                },
+            });
+        }
+
+        let other_taxes = total(option, |r| r.other_taxes) - total(best, |r| r.other_taxes);
+        drivers.push(Driver {
+            item: "other_taxes".into(),
+            tax_effect: other_taxes,
+            explanation: match other_taxes < Decimal::ZERO {
+                true => format!("Schedule 2 taxes are ${} lower", -other_taxes),
+                false => format!("Schedule 2 taxes are ${other_taxes} higher"),
+            },
+        });
+        let state_tax = option.state_tax - best.state_tax;
+        drivers.push(Driver {
+            item: "state_tax".into(),
+            tax_effect: state_tax,
+            explanation: format!("State tax of ${} against ${} filing {}", option.state_tax, best.state_tax, best.filing_status),
+        });
+
+        drivers.retain(|driver| driver.tax_effect.abs() >= MINIMUM_DRIVER);
+        drivers.sort_by_key(|driver| std::cmp::Reverse(driver.tax_effect.abs()));
+        drivers.truncate(MAXIMUM_DRIVERS);
+        drivers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use super::FilingStatusOptimizer;
+    use crate::models::filing_profile_model::FilingStatus;
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::DeductionKind;
+
+    fn input(value: Value) -> TaxInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn married(extra: Value) -> TaxInput {
+        let mut value = json!({
+            "year": 2024,
+            "filing_status": "married_filing_jointly",
+            "w2s": [
+                { "employer": "Acme", "owner": "taxpayer", "wages": 150000, "federal_tax_withheld": 20000 },
+                { "employer": "Globex", "owner": "spouse", "wages": 20000, "federal_tax_withheld": 1500 }
+            ]
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        input(value)
+    }
+
+    #[test]
+    fn ranks_options_cheapest_first() {
+        let comparison = FilingStatusOptimizer::compare(&married(json!({}))).unwrap();
+
+        let statuses: Vec<_> = comparison.options.iter().map(|option| option.filing_status).collect();
+        assert_eq!(statuses, [FilingStatus::MarriedFilingJointly, FilingStatus::MarriedFilingSeparately]);
+        assert_eq!(comparison.recommended, FilingStatus::MarriedFilingJointly);
+        assert_eq!(comparison.savings, Decimal::ZERO);
+        let best = &comparison.options[0];
+        for (index, option) in comparison.options.iter().enumerate() {
+            assert_eq!(option.rank, index + 1);
+            assert_eq!(option.difference, option.total_tax - best.total_tax);
+            assert!(option.difference >= Decimal::ZERO);
+        }
+        assert!(best.drivers.is_empty());
+        assert_eq!(comparison.options[1].returns.len(), 2);
+    }
+
+    #[test]
+    fn reports_savings_against_the_current_status() {
+        let mut input = married(json!({}));
+        input.filing_status = FilingStatus::MarriedFilingSeparately;
+        let comparison = FilingStatusOptimizer::compare(&input).unwrap();
+
+        let separate = comparison.options.iter().find(|option| option.filing_status == FilingStatus::MarriedFilingSeparately).unwrap();
+        assert_eq!(comparison.current, FilingStatus::MarriedFilingSeparately);
+        assert_eq!(comparison.savings, separate.difference);
+        assert!(comparison.savings > Decimal::ZERO);
+    }
+
+    #[test]
+    fn leaves_out_statuses_the_profile_does_not_allow() {
+        let single = input(json!({ "year": 2024, "filing_status": "single", "w2s": [{ "employer": "Acme", "wages": 50000 }] }));
+        let comparison = FilingStatusOptimizer::compare(&single).unwrap();
+        let statuses: Vec<_> = comparison.options.iter().map(|option| option.filing_status).collect();
+        assert_eq!(statuses, [FilingStatus::Single]);
+        let ineligible: Vec<_> = comparison.ineligible.iter().map(|status| status.filing_status).collect();
+        assert!(ineligible.contains(&FilingStatus::HeadOfHousehold));
+        assert!(ineligible.contains(&FilingStatus::QualifyingSurvivingSpouse));
+
+        let parent = input(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 50000 }],
+            "dependents": [{ "name": "Kid", "age": 8, "child_tax_credit": true, "head_of_household_qualifying_person": true }]
+        }));
+        let comparison = FilingStatusOptimizer::compare(&parent).unwrap();
+        assert_eq!(comparison.recommended, FilingStatus::HeadOfHousehold);
+        assert!(comparison.ineligible.iter().any(|status| status.filing_status == FilingStatus::QualifyingSurvivingSpouse));
+
+        let comparison = FilingStatusOptimizer::compare(&married(json!({}))).unwrap();
+        assert!(comparison.options.iter().all(|option| option.filing_status.requires_spouse()));
+        assert!(comparison.ineligible.iter().any(|status| status.filing_status == FilingStatus::HeadOfHousehold));
+    }
+
+    #[test]
+    fn both_spouses_itemize_when_one_does() {
+        let input = married(json!({ "form_1098s": [{ "lender": "Bank", "owner": "taxpayer", "mortgage_interest": 30000, "real_estate_taxes": 9000 }] }));
+        let (taxpayer, spouse, notes) = FilingStatusOptimizer::separate_returns(&input).unwrap();
+
+        assert_eq!(taxpayer.deduction.kind, DeductionKind::Itemized);
+        assert_eq!(spouse.deduction.kind, DeductionKind::Itemized);
+        assert_eq!(spouse.deduction.amount, Decimal::ZERO);
+        assert!(notes.iter().any(|note| note.contains("the spouse must itemize too")));
+    }
+
+    #[test]
+    fn splits_household_amounts_by_owner() {
+        let input = married(json!({
+            "form_1098s": [{ "lender": "Bank", "owner": "spouse", "mortgage_interest": 12000 }],
+            "adjustments": { "ira_deduction": 7000, "hsa_deduction": 4000 },
+            "capital_loss_carryover": { "long_term": 5000 },
+            "spouse_share": { "adjustments": { "ira_deduction": 7000 }, "capital_loss_carryover": { "long_term": 0 } }
+        }));
+        let (taxpayer, spouse, notes) = FilingStatusOptimizer::split(&input);
+
+        assert!(taxpayer.form_1098s.is_empty());
+        assert_eq!(spouse.form_1098s[0].mortgage_interest, Decimal::from(12000));
+        assert_eq!((taxpayer.adjustments.ira_deduction, spouse.adjustments.ira_deduction), (Decimal::ZERO, Decimal::from(7000)));
+        assert_eq!((taxpayer.adjustments.hsa_deduction, spouse.adjustments.hsa_deduction), (Decimal::from(4000), Decimal::ZERO));
+        assert_eq!(taxpayer.capital_loss_carryover.long_term, Decimal::from(5000));
+        assert!(notes.iter().all(|note| !note.contains("split evenly")));
+    }
+
+    #[test]
+    fn splits_amounts_evenly_when_the_owner_is_unknown() {
+        let input = married(json!({
+            "form_1098s": [{ "lender": "Bank", "mortgage_interest": 12000.01 }],
+            "itemized": { "charitable_cash": 3000 }
+        }));
+        let (taxpayer, spouse, notes) = FilingStatusOptimizer::split(&input);
+
+        assert_eq!(taxpayer.form_1098s[0].mortgage_interest + spouse.form_1098s[0].mortgage_interest, "12000.01".parse::<Decimal>().unwrap());
+        assert_eq!(spouse.itemized.charitable_cash, Decimal::from(1500));
+        assert!(notes.iter().any(|note| note.contains("itemized deductions, the 1098 from Bank split evenly")));
+    }
+
+    #[test]
+    fn lists_the_largest_drivers_first() {
+        let input = married(json!({
+            "dependents": [{ "name": "Kid", "age": 8, "child_tax_credit": true, "earned_income_credit": true }],
+            "credits": { "dependent_care_expenses": 3000 },
+            "adjustments": { "student_loan_interest": 2500 }
+        }));
+        let comparison = FilingStatusOptimizer::compare(&input).unwrap();
+        let drivers = &comparison.options[1].drivers;
+
+        assert!(drivers.len() >= 2 && drivers.len() <= super::MAXIMUM_DRIVERS);
+        assert!(drivers.windows(2).all(|pair| pair[0].tax_effect.abs() >= pair[1].tax_effect.abs()));
+        assert!(drivers.iter().all(|driver| driver.tax_effect.abs() >= super::MINIMUM_DRIVER));
+        assert!(drivers.iter().any(|driver| driver.item == "tax"));
+    }
+}
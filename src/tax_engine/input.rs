@@ -0,0 +1,924 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::models::business_model::{BusinessExpenseModel, BusinessModel, ExpenseCategory};
+use crate::models::filing_profile_model::{age_at_year_end, FilingProfileModel, FilingStatus, Owner};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Largest amount accepted from `tax_data`. Anything bigger is an entry or extraction error, and
+/// would overflow `Decimal` once the engine adds and multiplies it.
+const MAXIMUM_AMOUNT: i64 = 1_000_000_000_000_000;
+
+/// Where a record in `tax_data` came from, so the calculation trace can point back to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordSource {
+    /// Path into `tax_data.data`, e.g. `w2s[1]` or `documents[2].records[0]`
+    pub path: String,
+    /// Upload the record was extracted from
+    pub document_id: Option<Uuid>,
+    pub document_type: Option<String>,
+}
+
+impl RecordSource {
+    fn manual(list: &str, index: usize) -> RecordSource {
+        RecordSource { path: format!("{list}[{index}]"), document_id: None, document_type: None }
+    }
+}
+
+/// Wage statement. Field names follow the records `parse_tax_data` extracts from W-2 uploads.
+/// Boxes 3-6 are optional because older extractions did not capture them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct W2 {
+    pub employer: String,
+    /// Box b
+    pub employer_ein: Option<String>,
+    pub owner: Owner,
+    /// Box 1
+    pub wages: Decimal,
+    /// Box 2
+    #[serde(alias = "tax_withheld")]
+    pub federal_tax_withheld: Decimal,
+    /// Box 3
+    pub social_security_wages: Option<Decimal>,
+    /// Box 4
+    pub social_security_tax_withheld: Option<Decimal>,
+    /// Box 5
+    pub medicare_wages: Option<Decimal>,
+    /// Box 6
+    pub medicare_tax_withheld: Option<Decimal>,
+    /// Box 15 postal code; W-2s for several states are entered once per state
+    pub state: Option<String>,
+    /// Box 16, when it differs from box 1
+    pub state_wages: Option<Decimal>,
+    /// Box 17, deductible on Schedule A line 5a
+    pub state_tax_withheld: Decimal,
+    /// Box 19
+    pub local_tax_withheld: Decimal,
+    /// Box 12 entries. Incentive stock options have no code here: code V is a nonstatutory
+    /// option, already in box 1, and an ISO exercise is reported on Form 3921 instead
+    pub box_12: Vec<W2Box12>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+impl W2 {
+    /// Total of the box 12 entries with `code`, e.g. `W` for employer HSA contributions.
+    pub fn box_12_amount(&self, code: &str) -> Decimal {
+        self.box_12.iter()
+            .filter(|entry| entry.code.trim().eq_ignore_ascii_case(code))
+            .map(|entry| entry.amount)
+            .sum()
+    }
+}
+
+/// One W-2 box 12 entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct W2Box12 {
+    pub code: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Form1099Kind {
+    #[default]
+    Int,
+    Div,
+    Nec,
+    Misc,
+    K,
+}
+
+impl Form1099Kind {
+    /// Maps a document type such as "1099-NEC" to its form. A bare "1099" is treated as interest,
+    /// which is what the OCR extraction recognizes first. `None` for a 1099-SA: HSA distributions
+    /// spent on medical expenses are not income, so the engine has no place for them.
+    fn from_document_type(doc_type: &str) -> Option<Form1099Kind> {
+        match doc_type.to_uppercase().trim_start_matches("1099").trim_start_matches('-') {
+            "SA" => None,
+            "DIV" => Some(Form1099Kind::Div),
+            "NEC" => Some(Form1099Kind::Nec),
+            "MISC" => Some(Form1099Kind::Misc),
+            "K" => Some(Form1099Kind::K),
+            _ => Some(Form1099Kind::Int),
+        }
+    }
+
+    /// Printed form name, e.g. "1099-INT".
+    pub fn form_name(&self) -> &'static str {
+        match self {
+            Form1099Kind::Int => "1099-INT",
+            Form1099Kind::Div => "1099-DIV",
+            Form1099Kind::Nec => "1099-NEC",
+            Form1099Kind::Misc => "1099-MISC",
+            Form1099Kind::K => "1099-K",
+        }
+    }
+}
+
+/// Information return for non-wage income. Field names follow the extracted 1099 records.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Form1099 {
+    pub payer: String,
+    pub payer_tin: Option<String>,
+    /// Only used to split a joint return; 1099s routed to a business follow its owner
+    pub owner: Owner,
+    pub form: Option<Form1099Kind>,
+    /// Box 1 (1a on 1099-DIV)
+    pub income: Decimal,
+    /// 1099-DIV box 1b
+    pub qualified_dividends: Decimal,
+    /// 1099-DIV box 2a, reported on Schedule D line 13
+    pub capital_gain_distributions: Decimal,
+    /// Box 4
+    pub federal_tax_withheld: Decimal,
+    /// Tax-exempt interest from specified private activity bonds (1099-INT box 9, 1099-DIV box 13),
+    /// an AMT preference item
+    pub private_activity_bond_interest: Decimal,
+    /// Schedule C business a 1099-NEC or 1099-K belongs to
+    pub business_id: Option<Uuid>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+impl Form1099 {
+    pub fn kind(&self) -> Form1099Kind {
+        self.form.unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Term {
+    ShortTerm,
+    LongTerm,
+}
+
+/// One sale (or open position) from a 1099-B or entered manually.
+///
+/// Lots without a `date_sold` are still held; they are only used as wash-sale replacements.
+/// A 1099-B lot always reports a sale, so one without a date is treated as sold at year end.
+/// Manually entered lots are matched for wash sales by `security`; 1099-B lots already carry
+/// the broker's box 1g adjustment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapitalLot {
+    /// Box 1a
+    pub description: String,
+    /// Ticker or CUSIP
+    pub security: Option<String>,
+    pub quantity: Option<Decimal>,
+    /// Box 1b; `None` for "various"
+    pub date_acquired: Option<NaiveDate>,
+    /// Box 1c
+    pub date_sold: Option<NaiveDate>,
+    /// Box 1d
+    pub proceeds: Decimal,
+    /// Box 1e
+    pub cost_basis: Decimal,
+    /// Box 1g
+    pub wash_sale_loss_disallowed: Decimal,
+    /// Box 2, when the broker states it
+    pub term: Option<Term>,
+    /// Inherited property is always long-term
+    pub inherited: bool,
+    pub reported_on_1099b: bool,
+    /// Box 12; noncovered securities go to Form 8949 box B or E
+    pub basis_reported_to_irs: bool,
+    /// Only used to split a joint return
+    pub owner: Owner,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+impl Default for CapitalLot {
+    fn default() -> Self {
+        CapitalLot {
+            description: String::new(),
+            security: None,
+            quantity: None,
+            date_acquired: None,
+            date_sold: None,
+            proceeds: Decimal::ZERO,
+            cost_basis: Decimal::ZERO,
+            wash_sale_loss_disallowed: Decimal::ZERO,
+            term: None,
+            inherited: false,
+            reported_on_1099b: false,
+            basis_reported_to_irs: true,
+            owner: Owner::Taxpayer,
+            source: None,
+        }
+    }
+}
+
+/// Exercise of an incentive stock option. Field names follow the records `parse_tax_data`
+/// extracts from 3921 uploads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Form3921 {
+    pub corporation: String,
+    pub owner: Owner,
+    /// Box 2
+    pub exercise_date: Option<NaiveDate>,
+    /// Box 5
+    pub shares: Decimal,
+    /// Box 3, per share
+    pub exercise_price: Decimal,
+    /// Box 4, per share on the exercise date
+    pub fair_market_value: Decimal,
+    /// Shares sold in the year they were exercised; the sale is ordinary income for both taxes,
+    /// so there is no AMT adjustment
+    pub sold_in_year: bool,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+impl Form3921 {
+    /// Spread at exercise, added to AMTI on Form 6251 line 2i. Fails when the spread times the
+    /// shares does not fit in an amount.
+    pub fn bargain_element(&self) -> Result<Decimal, ErrorBag> {
+        if self.sold_in_year {
+            return Ok(Decimal::ZERO);
+        }
+
+        (self.fair_market_value - self.exercise_price).checked_mul(self.shares)
+            .map(|spread| spread.max(Decimal::ZERO))
+            .ok_or_else(|| ErrorBag::Validation {
+                field: self.source.as_ref().map(|source| source.path.clone()).unwrap_or_else(|| "form_3921s".into()),
+                message: format!("{} shares at a ${} spread is too large", self.shares, self.fair_market_value - self.exercise_price),
+            })
+    }
+}
+
+/// Form 6251 adjustments entered by the user, on top of those the engine derives from the forms.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmtAdjustments {
+    /// Line 2g, for bonds not reported on a 1099
+    pub private_activity_bond_interest: Decimal,
+    /// Line 2i, for exercises not reported on a Form 3921
+    pub incentive_stock_options: Decimal,
+    /// Line 3, every other adjustment and preference (depreciation, passive activities, ...)
+    pub other: Decimal,
+}
+
+impl AmtAdjustments {
+    /// Each amount with its field name.
+    pub(crate) fn amounts(&self) -> [(&'static str, Decimal); 3] {
+        [
+            ("private_activity_bond_interest", self.private_activity_bond_interest),
+            ("incentive_stock_options", self.incentive_stock_options),
+            ("other", self.other),
+        ]
+    }
+}
+
+/// Unused capital losses brought in from the prior year (Schedule D lines 6 and 14).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapitalLossCarryover {
+    pub short_term: Decimal,
+    pub long_term: Decimal,
+}
+
+impl CapitalLossCarryover {
+    /// Each amount with its field name.
+    pub(crate) fn amounts(&self) -> [(&'static str, Decimal); 2] {
+        [("short_term", self.short_term), ("long_term", self.long_term)]
+    }
+}
+
+/// Mortgage interest statement. Field names follow the records `parse_tax_data` extracts from 1098 uploads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Form1098 {
+    pub lender: String,
+    /// Borrower, only used to split a joint return; a 1098 without one is split evenly
+    pub owner: Option<Owner>,
+    /// Box 1
+    pub mortgage_interest: Decimal,
+    /// Box 2
+    pub outstanding_principal: Option<Decimal>,
+    /// Box 3; loans from before December 16, 2017 keep the $1,000,000 limit
+    pub origination_date: Option<NaiveDate>,
+    /// Box 6
+    pub points: Decimal,
+    /// Box 10, when the lender pays property tax from escrow
+    pub real_estate_taxes: Decimal,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+/// Tuition statement. Field names follow the records `parse_tax_data` extracts from 1098-T uploads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Form1098T {
+    pub institution: String,
+    /// Statements for the same student are combined
+    pub student: String,
+    /// Box 1
+    pub qualified_tuition: Decimal,
+    /// Box 5
+    pub scholarships: Decimal,
+    /// Box 8
+    pub at_least_half_time: bool,
+    /// Box 9
+    pub graduate_student: bool,
+    /// Required books and supplies bought elsewhere; only the American Opportunity credit counts them
+    pub course_materials: Decimal,
+    /// Prior years the American Opportunity credit was claimed for this student
+    pub american_opportunity_years_claimed: u32,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RecordSource>,
+}
+
+/// A dependent with the credits their eligibility tests allow, from `DependentService::evaluate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Dependent {
+    pub name: String,
+    pub age: i32,
+    pub student: bool,
+    pub child_tax_credit: bool,
+    pub credit_for_other_dependents: bool,
+    pub earned_income_credit: bool,
+    pub child_and_dependent_care_credit: bool,
+    pub head_of_household_qualifying_person: bool,
+}
+
+/// Credit inputs as entered by the user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreditEntries {
+    /// Form 2441 qualified care expenses paid
+    pub dependent_care_expenses: Decimal,
+    /// W-2 box 10 dependent care benefits, which reduce the expenses
+    pub dependent_care_benefits: Decimal,
+    /// Elective deferrals and IRA contributions (Form 8880 lines 1-2)
+    pub taxpayer_retirement_contributions: Decimal,
+    pub spouse_retirement_contributions: Decimal,
+    pub taxpayer_full_time_student: bool,
+    pub spouse_full_time_student: bool,
+    /// The taxpayer can be claimed on someone else's return
+    pub claimed_as_dependent: bool,
+}
+
+/// Schedule A amounts as entered by the user. Mortgage interest on a 1098 and state tax
+/// withheld on a W-2 are added to these by the engine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemizedEntries {
+    /// Line 1, before the AGI floor
+    pub medical_expenses: Decimal,
+    /// State and local income tax paid other than withholding, such as a prior-year balance
+    pub state_income_tax_paid: Decimal,
+    pub state_estimated_payments: Decimal,
+    /// Used instead of income taxes on line 5a when larger
+    pub general_sales_tax: Decimal,
+    pub real_estate_taxes: Decimal,
+    pub personal_property_taxes: Decimal,
+    /// Line 8b, home mortgage interest not reported on a 1098
+    pub mortgage_interest: Decimal,
+    /// Line 8c, points not reported on a 1098
+    pub points: Decimal,
+    /// Gifts by cash or check, limited to 60% of AGI
+    pub charitable_cash: Decimal,
+    /// Gifts of property other than long-term capital gain property, limited to 50% of AGI
+    pub charitable_noncash: Decimal,
+    /// Long-term capital gain property at fair market value, limited to 30% of AGI
+    pub charitable_capital_gain_property: Decimal,
+    /// Line 13, unused contributions from the prior five years
+    pub charitable_carryover: Decimal,
+    /// Line 16
+    pub other: Decimal,
+    /// Itemize even when the standard deduction is larger (e.g. for a state return)
+    pub elect_to_itemize: bool,
+    /// A married-filing-separately spouse who itemizes takes away the standard deduction
+    pub spouse_itemizes: bool,
+}
+
+impl ItemizedEntries {
+    /// Each amount with its field name.
+    pub(crate) fn amounts(&self) -> [(&'static str, Decimal); 13] {
+        [
+            ("medical_expenses", self.medical_expenses),
+            ("state_income_tax_paid", self.state_income_tax_paid),
+            ("state_estimated_payments", self.state_estimated_payments),
+            ("general_sales_tax", self.general_sales_tax),
+            ("real_estate_taxes", self.real_estate_taxes),
+            ("personal_property_taxes", self.personal_property_taxes),
+            ("mortgage_interest", self.mortgage_interest),
+            ("points", self.points),
+            ("charitable_cash", self.charitable_cash),
+            ("charitable_noncash", self.charitable_noncash),
+            ("charitable_capital_gain_property", self.charitable_capital_gain_property),
+            ("charitable_carryover", self.charitable_carryover),
+            ("other", self.other),
+        ]
+    }
+}
+
+/// Where the taxpayer lived from `start_date` through `end_date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResidencyPeriod {
+    pub state: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl ResidencyPeriod {
+    /// Days of the period within `year`, counting both ends.
+    pub fn days_in(&self, year: i32) -> i64 {
+        let (Some(first), Some(last)) = (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) else {
+            return 0;
+        };
+        let start = self.start_date.max(first);
+        let end = self.end_date.min(last);
+        ((end - start).num_days() + 1).max(0)
+    }
+}
+
+/// Schedule C expense as entered, before the meals limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusinessExpense {
+    pub category: ExpenseCategory,
+    pub amount: Decimal,
+}
+
+/// A Schedule C business. 1099-NEC/1099-K receipts are routed to it by `Form1099::business_id`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Business {
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub owner: Owner,
+    /// Gross receipts not reported on a 1099-NEC or 1099-K
+    pub other_receipts: Decimal,
+    pub returns_and_allowances: Decimal,
+    pub cost_of_goods_sold: Decimal,
+    pub other_income: Decimal,
+    pub expenses: Vec<BusinessExpense>,
+    /// Specified service trade or business for the QBI deduction
+    pub specified_service: bool,
+    /// Unadjusted basis immediately after acquisition of qualified property
+    pub qualified_property: Decimal,
+}
+
+impl Business {
+    pub fn from_model(business: &BusinessModel, expenses: &[BusinessExpenseModel]) -> Result<Business, ErrorBag> {
+        let expenses = expenses.iter()
+            .filter(|expense| expense.business_id == business.id)
+            .map(|expense| Ok(BusinessExpense {
+                category: expense.category().map_err(ErrorBag::InternalServerError)?,
+                amount: expense.amount,
+            }))
+            .collect::<Result<Vec<_>, ErrorBag>>()?;
+
+        Ok(Business {
+            id: Some(business.id),
+            name: business.name.clone(),
+            owner: business.owner().map_err(ErrorBag::InternalServerError)?,
+            other_receipts: business.other_receipts,
+            returns_and_allowances: business.returns_and_allowances,
+            cost_of_goods_sold: business.cost_of_goods_sold,
+            other_income: business.other_income,
+            expenses,
+            specified_service: business.specified_service,
+            qualified_property: business.qualified_property,
+        })
+    }
+}
+
+/// Schedule 1 part II adjustments as entered by the user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    pub educator_expenses: Decimal,
+    pub hsa_deduction: Decimal,
+    pub ira_deduction: Decimal,
+    pub student_loan_interest: Decimal,
+    pub other: Decimal,
+}
+
+impl Adjustments {
+    /// Each amount with its field name.
+    pub(crate) fn amounts(&self) -> [(&'static str, Decimal); 5] {
+        [
+            ("educator_expenses", self.educator_expenses),
+            ("hsa_deduction", self.hsa_deduction),
+            ("ira_deduction", self.ira_deduction),
+            ("student_loan_interest", self.student_loan_interest),
+            ("other", self.other),
+        ]
+    }
+}
+
+/// The spouse's part of amounts entered for the whole household, only used to split a joint
+/// return into separate ones; the taxpayer keeps the rest. Amounts whose spouse's part is not
+/// entered are split evenly. The flags on `itemized` are not used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpouseShare {
+    pub adjustments: Option<Adjustments>,
+    pub itemized: Option<ItemizedEntries>,
+    pub amt_adjustments: Option<AmtAdjustments>,
+    pub capital_loss_carryover: Option<CapitalLossCarryover>,
+    pub minimum_tax_credit_carryover: Option<Decimal>,
+    pub rental_income: Option<Decimal>,
+    pub other_income: Option<Decimal>,
+    pub taxable_state_refund: Option<Decimal>,
+    pub net_operating_loss_carryover: Option<Decimal>,
+    pub qualified_business_loss_carryover: Option<Decimal>,
+    pub estimated_payments: Option<Decimal>,
+}
+
+impl SpouseShare {
+    fn amounts(&self) -> Vec<(String, Decimal)> {
+        let mut amounts = Vec::new();
+        let mut add = |path: &str, fields: &[(&str, Decimal)]| amounts.extend(fields.iter().map(|(field, amount)| (format!("spouse_share.{path}{field}"), *amount)));
+        if let Some(adjustments) = &self.adjustments {
+            add("adjustments.", &adjustments.amounts());
+        }
+        if let Some(itemized) = &self.itemized {
+            add("itemized.", &itemized.amounts());
+        }
+        if let Some(amt_adjustments) = &self.amt_adjustments {
+            add("amt_adjustments.", &amt_adjustments.amounts());
+        }
+        if let Some(carryover) = &self.capital_loss_carryover {
+            add("capital_loss_carryover.", &carryover.amounts());
+        }
+        add("", &[
+            ("minimum_tax_credit_carryover", self.minimum_tax_credit_carryover.unwrap_or_default()),
+            ("rental_income", self.rental_income.unwrap_or_default()),
+            ("other_income", self.other_income.unwrap_or_default()),
+            ("taxable_state_refund", self.taxable_state_refund.unwrap_or_default()),
+            ("net_operating_loss_carryover", self.net_operating_loss_carryover.unwrap_or_default()),
+            ("qualified_business_loss_carryover", self.qualified_business_loss_carryover.unwrap_or_default()),
+            ("estimated_payments", self.estimated_payments.unwrap_or_default()),
+        ]);
+        amounts
+    }
+}
+
+/// Everything the engine needs for one return, normalized from `tax_data` and the filing profile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaxInput {
+    pub year: i32,
+    pub filing_status: FilingStatus,
+    pub taxpayer_65_or_older: bool,
+    pub taxpayer_blind: bool,
+    pub spouse_65_or_older: bool,
+    pub spouse_blind: bool,
+    /// Ages at the end of the year, when the filing profile has dates of birth
+    pub taxpayer_age: Option<i32>,
+    pub spouse_age: Option<i32>,
+    /// Loaded from the `dependents` table rather than `tax_data`
+    pub dependents: Vec<Dependent>,
+    /// Loaded from the `residency_periods` table; empty means every W-2 state is a full-year residence
+    pub residency: Vec<ResidencyPeriod>,
+    pub w2s: Vec<W2>,
+    pub form_1099s: Vec<Form1099>,
+    /// Loaded from the `businesses` tables rather than `tax_data`
+    pub businesses: Vec<Business>,
+    pub capital_lots: Vec<CapitalLot>,
+    pub capital_loss_carryover: CapitalLossCarryover,
+    pub form_3921s: Vec<Form3921>,
+    pub amt_adjustments: AmtAdjustments,
+    /// Unused minimum tax credit from earlier years (Form 8801 line 19)
+    pub minimum_tax_credit_carryover: Decimal,
+    /// Schedule E line 26: net rental real estate income, after the passive activity loss limits
+    pub rental_income: Decimal,
+    pub other_income: Decimal,
+    /// Schedule 1 line 1, the part of a state or local income tax refund that is taxable
+    pub taxable_state_refund: Decimal,
+    /// Net operating losses carried in from earlier years, before the taxable income limit
+    pub net_operating_loss_carryover: Decimal,
+    /// Negative total qualified business income carried in (Form 8995 line 3), as a positive amount
+    pub qualified_business_loss_carryover: Decimal,
+    pub adjustments: Adjustments,
+    pub form_1098s: Vec<Form1098>,
+    pub form_1098ts: Vec<Form1098T>,
+    pub itemized: ItemizedEntries,
+    pub credits: CreditEntries,
+    pub estimated_payments: Decimal,
+    /// Form 2210 Schedule AI line 1: AGI from January 1 through March 31, May 31, August 31 and
+    /// December 31. Empty when the annualized income installment method is not used.
+    pub annualized_income: Vec<Decimal>,
+    pub spouse_share: SpouseShare,
+    /// Assumptions made while building the input, surfaced on the calculation
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+/// Top-level keys of `tax_data.data` that hold manually entered values.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ManualEntries {
+    w2s: Vec<W2>,
+    form_1099s: Vec<Form1099>,
+    capital_lots: Vec<CapitalLot>,
+    capital_loss_carryover: CapitalLossCarryover,
+    form_3921s: Vec<Form3921>,
+    amt_adjustments: AmtAdjustments,
+    minimum_tax_credit_carryover: Decimal,
+    rental_income: Decimal,
+    other_income: Decimal,
+    taxable_state_refund: Decimal,
+    net_operating_loss_carryover: Decimal,
+    qualified_business_loss_carryover: Decimal,
+    adjustments: Adjustments,
+    form_1098s: Vec<Form1098>,
+    form_1098ts: Vec<Form1098T>,
+    itemized: ItemizedEntries,
+    credits: CreditEntries,
+    /// Older payloads stored only an itemized total; it is kept as Schedule A line 16
+    itemized_deductions: Option<Decimal>,
+    estimated_payments: Decimal,
+    annualized_income: Vec<Decimal>,
+    spouse_share: SpouseShare,
+}
+
+impl TaxInput {
+    /// Builds the engine input from a year's `tax_data` payload.
+    ///
+    /// Extracted document records (`documents[].records[]`) and manually entered lists
+    /// (`w2s`, `form_1099s`, `form_1098s`, `form_1098ts`, `form_3921s`, `capital_lots`) are combined; records flagged with an `error` are skipped.
+    /// Without a filing profile the return is computed as single and a warning is recorded.
+    /// Amounts of $1 quadrillion or more are rejected.
+    pub fn from_tax_data(year: i32, data: &Value, profile: Option<&FilingProfileModel>) -> Result<TaxInput, ErrorBag> {
+        let manual: ManualEntries = serde_json::from_value(data.clone())
+            .map_err(|e| ErrorBag::Validation { field: "data".into(), message: format!("Invalid tax data: {e}") })?;
+
+        let mut input = TaxInput {
+            year,
+            w2s: manual.w2s,
+            form_1099s: manual.form_1099s,
+            capital_lots: manual.capital_lots,
+            capital_loss_carryover: manual.capital_loss_carryover,
+            form_3921s: manual.form_3921s,
+            amt_adjustments: manual.amt_adjustments,
+            minimum_tax_credit_carryover: manual.minimum_tax_credit_carryover,
+            rental_income: manual.rental_income,
+            other_income: manual.other_income,
+            taxable_state_refund: manual.taxable_state_refund,
+            net_operating_loss_carryover: manual.net_operating_loss_carryover,
+            qualified_business_loss_carryover: manual.qualified_business_loss_carryover,
+            adjustments: manual.adjustments,
+            form_1098s: manual.form_1098s,
+            form_1098ts: manual.form_1098ts,
+            itemized: manual.itemized,
+            credits: manual.credits,
+            estimated_payments: manual.estimated_payments,
+            annualized_income: manual.annualized_income,
+            spouse_share: manual.spouse_share,
+            ..TaxInput::default()
+        };
+        input.itemized.other += manual.itemized_deductions.unwrap_or_default();
+        input.w2s.iter_mut().enumerate().for_each(|(index, w2)| w2.source = Some(RecordSource::manual("w2s", index)));
+        input.form_1099s.iter_mut().enumerate().for_each(|(index, form)| form.source = Some(RecordSource::manual("form_1099s", index)));
+        input.form_1098s.iter_mut().enumerate().for_each(|(index, form)| form.source = Some(RecordSource::manual("form_1098s", index)));
+        input.form_1098ts.iter_mut().enumerate().for_each(|(index, form)| form.source = Some(RecordSource::manual("form_1098ts", index)));
+        input.capital_lots.iter_mut().enumerate().for_each(|(index, lot)| lot.source = Some(RecordSource::manual("capital_lots", index)));
+        input.form_3921s.iter_mut().enumerate().for_each(|(index, form)| form.source = Some(RecordSource::manual("form_3921s", index)));
+
+        let documents = data.get("documents").and_then(|d| d.as_array()).map(Vec::as_slice).unwrap_or_default();
+        for (document_index, document) in documents.iter().enumerate() {
+            let doc_type = document.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+            let records = document.get("records").and_then(|r| r.as_array()).map(Vec::as_slice).unwrap_or_default();
+            let source = |record_index: usize| Some(RecordSource {
+                path: format!("documents[{document_index}].records[{record_index}]"),
+                document_id: document.get("id").and_then(|id| id.as_str()).and_then(|id| id.parse().ok()),
+                document_type: Some(doc_type.to_string()),
+            });
+
+            for (record_index, record) in records.iter().enumerate().filter(|(_, r)| r.get("error").is_none()) {
+                if doc_type.eq_ignore_ascii_case("W2") {
+                    let mut w2: W2 = Self::record(record, doc_type)?;
+                    w2.source = source(record_index);
+                    input.w2s.push(w2);
+                } else if doc_type.eq_ignore_ascii_case("1098-T") {
+                    let mut form: Form1098T = Self::record(record, doc_type)?;
+                    form.source = source(record_index);
+                    input.form_1098ts.push(form);
+                } else if doc_type.eq_ignore_ascii_case("1098") {
+                    let mut form: Form1098 = Self::record(record, doc_type)?;
+                    form.source = source(record_index);
+                    input.form_1098s.push(form);
+                } else if doc_type.eq_ignore_ascii_case("1099-B") {
+                    let mut lot: CapitalLot = Self::record(record, doc_type)?;
+                    lot.reported_on_1099b = true;
+                    lot.source = source(record_index);
+                    input.capital_lots.push(lot);
+                } else if doc_type == "3921" {
+                    let mut form: Form3921 = Self::record(record, doc_type)?;
+                    form.source = source(record_index);
+                    input.form_3921s.push(form);
+                } else if doc_type.starts_with("1099") {
+                    let Some(kind) = Form1099Kind::from_document_type(doc_type) else {
+                        input.warnings.push(format!("{doc_type} amounts are not included; enter any taxable part as other income"));
+                        break;
+                    };
+                    let mut form: Form1099 = Self::record(record, doc_type)?;
+                    form.form.get_or_insert(kind);
+                    form.source = source(record_index);
+                    input.form_1099s.push(form);
+                }
+            }
+        }
+
+        match profile {
+            Some(profile) => {
+                input.filing_status = profile.status().map_err(ErrorBag::InternalServerError)?;
+                input.taxpayer_65_or_older = profile.taxpayer_is_65_or_older;
+                input.taxpayer_blind = profile.taxpayer_is_blind;
+                input.spouse_65_or_older = profile.spouse_is_65_or_older;
+                input.spouse_blind = profile.spouse_is_blind;
+                input.taxpayer_age = profile.taxpayer_dob.map(|dob| age_at_year_end(dob, year));
+                input.spouse_age = profile.spouse_dob.map(|dob| age_at_year_end(dob, year));
+            }
+            None => input.warnings.push(format!("No filing profile for {year}; computed as single")),
+        }
+
+        input.check_amounts()?;
+
+        Ok(input)
+    }
+
+    /// Rejects an amount of `MAXIMUM_AMOUNT` or more, naming the field by its record's `source`
+    /// path so the error points back into `tax_data`. Text fields such as EINs are not amounts
+    /// and are left alone.
+    fn check_amounts(&self) -> Result<(), ErrorBag> {
+        let mut amounts: Vec<(String, Decimal)> = Vec::new();
+        let mut add = |path: &str, fields: &[(&str, Decimal)]| amounts.extend(fields.iter().map(|(field, amount)| (match path.is_empty() {
+            true => field.to_string(),
+            false => format!("{path}.{field}"),
+        }, *amount)));
+        let path = |source: &Option<RecordSource>, list: &str, index: usize| source.as_ref()
+            .map(|source| source.path.clone())
+            .unwrap_or_else(|| format!("{list}[{index}]"));
+
+        for (index, w2) in self.w2s.iter().enumerate() {
+            let path = path(&w2.source, "w2s", index);
+            add(&path, &[
+                ("wages", w2.wages),
+                ("federal_tax_withheld", w2.federal_tax_withheld),
+                ("social_security_wages", w2.social_security_wages.unwrap_or_default()),
+                ("social_security_tax_withheld", w2.social_security_tax_withheld.unwrap_or_default()),
+                ("medicare_wages", w2.medicare_wages.unwrap_or_default()),
+                ("medicare_tax_withheld", w2.medicare_tax_withheld.unwrap_or_default()),
+                ("state_wages", w2.state_wages.unwrap_or_default()),
+                ("state_tax_withheld", w2.state_tax_withheld),
+                ("local_tax_withheld", w2.local_tax_withheld),
+            ]);
+            for (entry_index, entry) in w2.box_12.iter().enumerate() {
+                add(&format!("{path}.box_12[{entry_index}]"), &[("amount", entry.amount)]);
+            }
+        }
+        for (index, form) in self.form_1099s.iter().enumerate() {
+            add(&path(&form.source, "form_1099s", index), &[
+                ("income", form.income),
+                ("qualified_dividends", form.qualified_dividends),
+                ("capital_gain_distributions", form.capital_gain_distributions),
+                ("federal_tax_withheld", form.federal_tax_withheld),
+                ("private_activity_bond_interest", form.private_activity_bond_interest),
+            ]);
+        }
+        for (index, lot) in self.capital_lots.iter().enumerate() {
+            add(&path(&lot.source, "capital_lots", index), &[
+                ("quantity", lot.quantity.unwrap_or_default()),
+                ("proceeds", lot.proceeds),
+                ("cost_basis", lot.cost_basis),
+                ("wash_sale_loss_disallowed", lot.wash_sale_loss_disallowed),
+            ]);
+        }
+        for (index, form) in self.form_3921s.iter().enumerate() {
+            add(&path(&form.source, "form_3921s", index), &[
+                ("shares", form.shares),
+                ("exercise_price", form.exercise_price),
+                ("fair_market_value", form.fair_market_value),
+            ]);
+        }
+        for (index, form) in self.form_1098s.iter().enumerate() {
+            add(&path(&form.source, "form_1098s", index), &[
+                ("mortgage_interest", form.mortgage_interest),
+                ("outstanding_principal", form.outstanding_principal.unwrap_or_default()),
+                ("points", form.points),
+                ("real_estate_taxes", form.real_estate_taxes),
+            ]);
+        }
+        for (index, form) in self.form_1098ts.iter().enumerate() {
+            add(&path(&form.source, "form_1098ts", index), &[
+                ("qualified_tuition", form.qualified_tuition),
+                ("scholarships", form.scholarships),
+                ("course_materials", form.course_materials),
+            ]);
+        }
+        add("", &[
+            ("minimum_tax_credit_carryover", self.minimum_tax_credit_carryover),
+            ("rental_income", self.rental_income),
+            ("other_income", self.other_income),
+            ("taxable_state_refund", self.taxable_state_refund),
+            ("net_operating_loss_carryover", self.net_operating_loss_carryover),
+            ("qualified_business_loss_carryover", self.qualified_business_loss_carryover),
+            ("estimated_payments", self.estimated_payments),
+        ]);
+        add("capital_loss_carryover", &self.capital_loss_carryover.amounts());
+        add("amt_adjustments", &self.amt_adjustments.amounts());
+        add("adjustments", &self.adjustments.amounts());
+        add("itemized", &self.itemized.amounts());
+        add("credits", &[
+            ("dependent_care_expenses", self.credits.dependent_care_expenses),
+            ("dependent_care_benefits", self.credits.dependent_care_benefits),
+            ("taxpayer_retirement_contributions", self.credits.taxpayer_retirement_contributions),
+            ("spouse_retirement_contributions", self.credits.spouse_retirement_contributions),
+        ]);
+        for (index, amount) in self.annualized_income.iter().enumerate() {
+            add("", &[(&format!("annualized_income[{index}]"), *amount)]);
+        }
+        amounts.extend(self.spouse_share.amounts());
+
+        match amounts.into_iter().find(|(_, amount)| amount.abs() >= Decimal::from(MAXIMUM_AMOUNT)) {
+            Some((field, amount)) => Err(ErrorBag::Validation {
+                field,
+                message: format!("{amount} is too large; amounts must be under ${MAXIMUM_AMOUNT}"),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn record<T: serde::de::DeserializeOwned>(record: &Value, doc_type: &str) -> Result<T, ErrorBag> {
+        serde_json::from_value(record.clone())
+            .map_err(|e| ErrorBag::Validation { field: "data".into(), message: format!("Invalid {doc_type} record: {e}") })
+    }
+
+    /// Number of 65+/blind boxes checked. Spouse boxes only count on joint returns
+    /// (and on separate returns, where the spouse has no income, which we do not model).
+    pub fn additional_standard_deduction_boxes(&self) -> u32 {
+        let spouse_counts = self.filing_status == FilingStatus::MarriedFilingJointly;
+        [
+            self.taxpayer_65_or_older,
+            self.taxpayer_blind,
+            spouse_counts && self.spouse_65_or_older,
+            spouse_counts && self.spouse_blind,
+        ].into_iter().filter(|checked| *checked).count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::TaxInput;
+    use crate::tax_engine::TaxEngine;
+    use crate::utilities::error_bag::ErrorBag;
+
+    #[test]
+    fn rejects_amounts_that_would_overflow() {
+        let data = json!({ "w2s": [{ "wages": 5e28 }, { "wages": 5e28 }] });
+        match TaxInput::from_tax_data(2024, &data, None) {
+            Err(ErrorBag::Validation { field, .. }) => assert_eq!(field, "w2s[0].wages"),
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn names_the_document_record_with_the_large_amount() {
+        let data = json!({ "documents": [{ "type": "1099-INT", "records": [{ "payer": "Bank", "income": "1000000000000000" }] }] });
+        match TaxInput::from_tax_data(2024, &data, None) {
+            Err(ErrorBag::Validation { field, .. }) => assert_eq!(field, "documents[0].records[0].income"),
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_amounts_under_the_limit() {
+        let data = json!({ "w2s": [{ "wages": "999999999999999.99" }], "adjustments": { "other": -5000 } });
+        assert!(TaxInput::from_tax_data(2024, &data, None).is_ok());
+    }
+
+    #[test]
+    fn leaves_identification_numbers_alone() {
+        let data = json!({
+            "w2s": [{ "employer_ein": "9876543210123456789", "wages": 50000 }],
+            "documents": [{ "type": "1099-INT", "records": [{ "payer": "Bank", "payer_tin": "12345678901234567.89", "income": 20 }] }],
+        });
+        assert!(TaxInput::from_tax_data(2024, &data, None).is_ok());
+    }
+
+    #[test]
+    fn rejects_an_option_spread_that_would_overflow() {
+        let data = json!({ "form_3921s": [{ "corporation": "Acme", "shares": "900000000000000", "exercise_price": 1, "fair_market_value": "900000000000000" }] });
+        let input = TaxInput::from_tax_data(2024, &data, None).unwrap();
+        match TaxEngine::calculate(&input) {
+            Err(ErrorBag::Validation { field, .. }) => assert_eq!(field, "form_3921s[0]"),
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Schedule A itemized deductions.
+//!
+//! Medical expenses count above the AGI floor. State and local taxes take the larger of income
+//! taxes (W-2 boxes 17 and 19, estimated and other payments) or general sales tax, plus property
+//! taxes, limited to the year's cap. Mortgage interest on a 1098 is limited to the interest on
+//! qualifying acquisition debt, and charitable gifts to their AGI percentage limits with the
+//! excess carried forward.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+
+/// Loans originated before this date keep the pre-2018 debt limit.
+const GRANDFATHERED_BEFORE: NaiveDate = NaiveDate::from_ymd_opt(2017, 12, 16).unwrap();
+
+/// Schedule A, named after the lines it fills.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleA {
+    /// Line 1
+    pub medical_expenses: Decimal,
+    /// Line 3
+    pub medical_floor: Decimal,
+    /// Line 4
+    pub medical_deduction: Decimal,
+    /// State and local income taxes withheld or paid
+    pub income_taxes: Decimal,
+    pub general_sales_tax: Decimal,
+    /// Line 5a checkbox: general sales tax is larger than income taxes
+    pub sales_tax_elected: bool,
+    /// Line 5b
+    pub real_estate_taxes: Decimal,
+    /// Line 5c
+    pub personal_property_taxes: Decimal,
+    /// Line 5d
+    pub total_taxes: Decimal,
+    pub salt_cap: Decimal,
+    /// Line 5e
+    pub salt_deduction: Decimal,
+    /// Lines 8a and 8b before the debt limit
+    pub mortgage_interest: Decimal,
+    /// Line 8c
+    pub points: Decimal,
+    /// Interest on debt above the acquisition debt limit
+    pub nondeductible_interest: Decimal,
+    /// Line 10
+    pub interest_deduction: Decimal,
+    /// Line 11
+    pub charitable_cash: Decimal,
+    /// Line 12
+    pub charitable_noncash: Decimal,
+    /// Line 13
+    pub charitable_carryover: Decimal,
+    /// Line 14, after the AGI limits
+    pub charitable_deduction: Decimal,
+    /// Contributions over the AGI limits, deductible over the next five years
+    pub charitable_carryforward: Decimal,
+    /// Line 16
+    pub other: Decimal,
+    /// Line 17
+    pub total: Decimal,
+}
+
+impl ScheduleA {
+    pub fn compute(input: &TaxInput, adjusted_gross_income: Decimal, rules: &TaxRules) -> (ScheduleA, Vec<String>) {
+        let entries = &input.itemized;
+        let limits = &rules.itemized;
+        let status = input.filing_status;
+        let agi = adjusted_gross_income.max(Decimal::ZERO);
+        let mut warnings = Vec::new();
+
+        let medical_floor = round_cents(agi * limits.medical_floor);
+        let medical_deduction = (entries.medical_expenses - medical_floor).max(Decimal::ZERO);
+
+        let income_taxes = input.w2s.iter().map(|w2| w2.state_tax_withheld + w2.local_tax_withheld).sum::<Decimal>()
+            + entries.state_estimated_payments
+            + entries.state_income_tax_paid;
+        let sales_tax_elected = entries.general_sales_tax > income_taxes;
+        let real_estate_taxes = entries.real_estate_taxes + input.form_1098s.iter().map(|form| form.real_estate_taxes).sum::<Decimal>();
+        let total_taxes = income_taxes.max(entries.general_sales_tax) + real_estate_taxes + entries.personal_property_taxes;
+        let salt_cap = limits.salt_cap(status, agi);
+        let salt_deduction = total_taxes.min(salt_cap);
+
+        let (mortgage_interest, points, nondeductible_interest) = Self::mortgage_interest(input, rules);
+        if !nondeductible_interest.is_zero() {
+            warnings.push(format!("Mortgage debt exceeds the acquisition debt limit; ${nondeductible_interest} of interest is not deductible"));
+        }
+        let interest_deduction = mortgage_interest + points - nondeductible_interest;
+
+        let (charitable_deduction, charitable_carryforward) = Self::charitable(input, agi, rules);
+        if !charitable_carryforward.is_zero() {
+            warnings.push(format!("Charitable contributions exceed the AGI limits; ${charitable_carryforward} carries forward"));
+        }
+
+        let total = medical_deduction + salt_deduction + interest_deduction + charitable_deduction + entries.other;
+
+        let schedule = ScheduleA {
+            medical_expenses: entries.medical_expenses,
+            medical_floor,
+            medical_deduction,
+            income_taxes,
+            general_sales_tax: entries.general_sales_tax,
+            sales_tax_elected,
+            real_estate_taxes,
+            personal_property_taxes: entries.personal_property_taxes,
+            total_taxes,
+            salt_cap,
+            salt_deduction,
+            mortgage_interest,
+            points,
+            nondeductible_interest,
+            interest_deduction,
+            charitable_cash: entries.charitable_cash,
+            charitable_noncash: entries.charitable_noncash + entries.charitable_capital_gain_property,
+            charitable_carryover: entries.charitable_carryover,
+            charitable_deduction,
+            charitable_carryforward,
+            other: entries.other,
+            total,
+        };
+
+        (schedule, warnings)
+    }
+
+    /// Interest and points from every 1098 plus manual entries, and the part of the 1098
+    /// amounts that is not deductible. Pub. 936 limits the deduction to interest on the
+    /// qualifying share of the debt: grandfathered debt up to its limit, and newer debt up
+    /// to the current limit reduced by the grandfathered debt.
+    fn mortgage_interest(input: &TaxInput, rules: &TaxRules) -> (Decimal, Decimal, Decimal) {
+        let entries = &input.itemized;
+        let status = input.filing_status;
+        let form_interest = input.form_1098s.iter().map(|form| form.mortgage_interest + form.points).sum::<Decimal>();
+        let mortgage_interest = entries.mortgage_interest + input.form_1098s.iter().map(|form| form.mortgage_interest).sum::<Decimal>();
+        let points = entries.points + input.form_1098s.iter().map(|form| form.points).sum::<Decimal>();
+
+        let (grandfathered, current) = input.form_1098s.iter().fold((Decimal::ZERO, Decimal::ZERO), |(old, new), form| {
+            let principal = form.outstanding_principal.unwrap_or_default();
+            match form.origination_date {
+                Some(date) if date < GRANDFATHERED_BEFORE => (old + principal, new),
+                _ => (old, new + principal),
+            }
+        });
+        let debt = grandfathered + current;
+        let qualifying = grandfathered.min(*rules.itemized.grandfathered_mortgage_debt_limit.get(status))
+            + current.min((rules.itemized.mortgage_debt_limit.get(status) - grandfathered).max(Decimal::ZERO));
+        if debt <= qualifying {
+            return (mortgage_interest, points, Decimal::ZERO);
+        }
+
+        let nondeductible = round_cents(form_interest - form_interest * qualifying / debt);
+        (mortgage_interest, points, nondeductible)
+    }
+
+    /// Applies the AGI limits in Pub. 526 order: property gifts first, then cash, which shares
+    /// the 60% limit with them, then carryovers. Returns the deduction and the excess.
+    fn charitable(input: &TaxInput, agi: Decimal, rules: &TaxRules) -> (Decimal, Decimal) {
+        let entries = &input.itemized;
+        let limits = &rules.itemized;
+        let cash_limit = round_cents(agi * limits.charitable_cash_limit);
+        let property_limit = round_cents(agi * limits.charitable_property_limit);
+        let capital_gain_limit = round_cents(agi * limits.charitable_capital_gain_limit);
+
+        let noncash = entries.charitable_noncash.min(property_limit);
+        let capital_gain_property = entries.charitable_capital_gain_property
+            .min(capital_gain_limit)
+            .min(property_limit - noncash);
+        let cash = entries.charitable_cash.min((cash_limit - noncash - capital_gain_property).max(Decimal::ZERO));
+        let carryover = entries.charitable_carryover.min((cash_limit - noncash - capital_gain_property - cash).max(Decimal::ZERO));
+
+        let given = entries.charitable_cash + entries.charitable_noncash + entries.charitable_capital_gain_property + entries.charitable_carryover;
+        let deduction = noncash + capital_gain_property + cash + carryover;
+        (deduction, given - deduction)
+    }
+
+    /// Whether the standard deduction is unavailable, leaving itemizing as the only choice.
+    pub fn must_itemize(input: &TaxInput) -> bool {
+        input.filing_status == FilingStatus::MarriedFilingSeparately && input.itemized.spouse_itemizes
+    }
+}
@@ -0,0 +1,393 @@
+//! Federal and state income tax calculation engine.
+//!
+//! The engine is a pure function of its input: `TaxEngine::calculate` takes a normalized
+//! `TaxInput` (built from a year's `tax_data` row and filing profile) and walks Form 1040
+//! top to bottom, then each state return. It never touches the database, which keeps it testable against the
+//! golden files in `src/tax_engine/golden`.
+//!
+//! Amounts are `Decimal` dollars. Intermediate lines keep cents; tax from the tax table is
+//! whole dollars, as on the printed return.
+
+pub mod alternative_minimum_tax;
+pub mod analytics;
+pub mod anomalies;
+pub mod capital_gains;
+pub mod carryforward;
+pub mod checklist;
+pub mod credits;
+pub mod estimated_tax;
+pub mod filing_status;
+pub mod input;
+pub mod itemized;
+pub mod net_investment_income;
+pub mod payroll;
+pub mod penalties;
+pub mod qualified_business_income;
+pub mod rules;
+pub mod scenario;
+pub mod self_employment;
+pub mod state;
+pub mod summary;
+pub mod trace;
+
+#[cfg(test)]
+mod tests;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::alternative_minimum_tax::AlternativeMinimumTax;
+use crate::tax_engine::capital_gains::{CapitalGains, QualifiedDividendsWorksheet};
+use crate::tax_engine::credits::Credits;
+use crate::tax_engine::input::{Form1099Kind, TaxInput};
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::net_investment_income::NetInvestmentIncomeTax;
+use crate::tax_engine::payroll::PayrollReconciliation;
+use crate::tax_engine::qualified_business_income::QualifiedBusinessIncomeDeduction;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::self_employment::{ScheduleC, ScheduleSe};
+use crate::tax_engine::state::StateReturn;
+use crate::utilities::error_bag::ErrorBag;
+
+pub fn round_cents(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+pub fn round_dollars(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Form 1040 lines 1 through 9.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IncomeSummary {
+    pub wages: Decimal,
+    pub taxable_interest: Decimal,
+    pub ordinary_dividends: Decimal,
+    pub qualified_dividends: Decimal,
+    /// Line 7
+    pub capital_gain: Decimal,
+    /// Schedule 1 line 3
+    pub business_income: Decimal,
+    /// Schedule 1 line 5
+    pub rental_income: Decimal,
+    /// Schedule 1 line 1
+    pub taxable_refunds: Decimal,
+    pub other_income: Decimal,
+    /// Schedule 1 line 8a, as a positive amount that reduces total income
+    pub net_operating_loss: Decimal,
+    pub total: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeductionKind {
+    Standard,
+    Itemized,
+}
+
+/// Form 1040 line 12.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeductionSummary {
+    /// The deduction taken on the return
+    pub kind: DeductionKind,
+    /// The larger deduction, unless the standard deduction is unavailable
+    pub recommended: DeductionKind,
+    pub standard: Decimal,
+    pub itemized: Decimal,
+    pub amount: Decimal,
+    /// How much larger the recommended deduction is than the other one
+    pub advantage: Decimal,
+}
+
+/// Schedule 2, additional taxes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Schedule2 {
+    /// Line 2, from Form 6251
+    pub alternative_minimum_tax: Decimal,
+    /// Line 3, to Form 1040 line 17
+    pub additions_to_tax: Decimal,
+    /// Line 4, from Schedule SE
+    pub self_employment_tax: Decimal,
+    /// Line 11, from Form 8959
+    pub additional_medicare_tax: Decimal,
+    /// Line 12, from Form 8960
+    pub net_investment_income_tax: Decimal,
+    /// Line 21, to Form 1040 line 23
+    pub other_taxes: Decimal,
+}
+
+/// Result of a federal calculation, named after the Form 1040 lines it fills.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxCalculation {
+    pub year: i32,
+    pub filing_status: FilingStatus,
+    pub income: IncomeSummary,
+    pub schedule_c: Vec<ScheduleC>,
+    pub schedule_se: Vec<ScheduleSe>,
+    pub capital_gains: Option<CapitalGains>,
+    /// Line 10
+    pub adjustments: Decimal,
+    /// Line 11
+    pub adjusted_gross_income: Decimal,
+    pub schedule_a: ScheduleA,
+    pub deduction: DeductionSummary,
+    pub qualified_business_income: Option<QualifiedBusinessIncomeDeduction>,
+    /// Line 13
+    pub qualified_business_income_deduction: Decimal,
+    /// Line 15
+    pub taxable_income: Decimal,
+    /// Line 16
+    pub tax: Decimal,
+    /// How line 16 was figured when there are qualified dividends or net capital gain
+    pub qualified_dividends_worksheet: Option<QualifiedDividendsWorksheet>,
+    /// Line 17 is `alternative_minimum_tax.tax`; the minimum tax credit it uses is part of line 21
+    pub alternative_minimum_tax: AlternativeMinimumTax,
+    pub credits: Credits,
+    /// Line 21
+    pub nonrefundable_credits: Decimal,
+    pub net_investment_income_tax: NetInvestmentIncomeTax,
+    pub schedule_2: Schedule2,
+    /// Line 23, Schedule 2 other taxes
+    pub other_taxes: Decimal,
+    /// Line 24
+    pub total_tax: Decimal,
+    /// Line 25d
+    pub withholding: Decimal,
+    /// Line 26
+    pub estimated_payments: Decimal,
+    /// Lines 27 through 29
+    pub refundable_credits: Decimal,
+    /// Line 31, Schedule 3 other payments and refundable credits
+    pub other_payments: Decimal,
+    /// Line 33
+    pub total_payments: Decimal,
+    /// Line 34
+    pub refund: Decimal,
+    /// Line 37
+    pub amount_owed: Decimal,
+    pub marginal_rate: Decimal,
+    pub payroll: PayrollReconciliation,
+    /// One return for each state with W-2 wages
+    pub states: Vec<StateReturn>,
+    pub warnings: Vec<String>,
+}
+
+pub struct TaxEngine;
+
+impl TaxEngine {
+    /// Computes the federal return and each state return for `input.year`, failing for years without a rule table.
+    pub fn calculate(input: &TaxInput) -> Result<TaxCalculation, ErrorBag> {
+        let rules = TaxRules::for_year(input.year)?;
+        let status = input.filing_status;
+
+        let mut warnings = input.warnings.clone();
+        let (schedule_c, routing_warnings) = ScheduleC::for_input(input);
+        warnings.extend(routing_warnings);
+        let schedule_se = ScheduleSe::for_input(input, &schedule_c, rules);
+        let self_employment_tax = schedule_se.iter().map(|se| se.tax).sum::<Decimal>();
+        let self_employment_income = schedule_se.iter().map(|se| se.net_earnings).sum::<Decimal>();
+
+        let mut capital_gains = CapitalGains::compute(input, rules);
+        if let Some(gains) = &capital_gains {
+            warnings.extend(gains.warnings.iter().cloned());
+        }
+
+        let mut income = Self::income(input, &schedule_c, capital_gains.as_ref());
+        let half_self_employment_tax = schedule_se.iter().map(|se| se.deduction).sum::<Decimal>();
+        let adjustments = Self::adjustments(input, rules, income.total, half_self_employment_tax);
+        income.net_operating_loss = Self::net_operating_loss(input, rules, income.total - adjustments);
+        income.total -= income.net_operating_loss;
+        if income.net_operating_loss < input.net_operating_loss_carryover {
+            warnings.push(format!("The net operating loss deduction is limited to {}% of taxable income; ${} carries forward",
+                (rules.net_operating_loss_limit * Decimal::ONE_HUNDRED).normalize(), (input.net_operating_loss_carryover - income.net_operating_loss).normalize()));
+        }
+        let adjusted_gross_income = income.total - adjustments;
+
+        let (schedule_a, itemized_warnings) = ScheduleA::compute(input, adjusted_gross_income, rules);
+        warnings.extend(itemized_warnings);
+        let deduction = Self::deduction(input, rules, &schedule_a);
+        if deduction.kind != deduction.recommended {
+            warnings.push(format!("Itemizing by election lowers the deduction by ${}", deduction.advantage));
+        }
+
+        let net_capital_gain = capital_gains.as_ref().map(CapitalGains::net_capital_gain).unwrap_or_default();
+        let qualified_business_income = QualifiedBusinessIncomeDeduction::compute(input, rules, &schedule_c, &schedule_se,
+            (adjusted_gross_income - deduction.amount).max(Decimal::ZERO), income.qualified_dividends + net_capital_gain);
+        let qualified_business_income_deduction = qualified_business_income.as_ref().map(|qbi| qbi.deduction).unwrap_or_default();
+        let taxable_income = (adjusted_gross_income - deduction.amount - qualified_business_income_deduction).max(Decimal::ZERO);
+
+        let qualified_dividends_worksheet = (income.qualified_dividends > Decimal::ZERO || net_capital_gain > Decimal::ZERO)
+            .then(|| QualifiedDividendsWorksheet::compute(status, taxable_income, income.qualified_dividends, net_capital_gain, rules));
+        let tax = match &qualified_dividends_worksheet {
+            Some(worksheet) => worksheet.tax,
+            None => rules.income_tax(status, taxable_income),
+        };
+        if let Some(gains) = &mut capital_gains {
+            gains.compute_carryover(adjusted_gross_income - deduction.amount - qualified_business_income_deduction);
+        }
+        let mut alternative_minimum_tax = AlternativeMinimumTax::compute(input, rules, &income, adjusted_gross_income, &deduction, &schedule_a,
+            qualified_business_income_deduction)?;
+        let amt = alternative_minimum_tax.compute_tax(status, rules, qualified_dividends_worksheet.as_ref(), tax);
+        let credits = Credits::compute(input, rules, &income, &schedule_c, &schedule_se, adjusted_gross_income, tax + amt);
+        let minimum_tax_credit = alternative_minimum_tax.apply_credit(tax - credits.nonrefundable);
+        let nonrefundable_credits = credits.nonrefundable + minimum_tax_credit;
+        let payroll = PayrollReconciliation::reconcile(&input.w2s, self_employment_income, status, rules);
+        if payroll.additional_medicare_tax.tax > Decimal::ZERO && input.w2s.iter().any(|w2| w2.medicare_wages.is_none()) {
+            warnings.push("Additional Medicare Tax counts box 1 wages for W-2s without box 5 Medicare wages".into());
+        }
+        let net_investment_income_tax = NetInvestmentIncomeTax::compute(status, &income, adjusted_gross_income, rules);
+        let schedule_2 = Schedule2 {
+            alternative_minimum_tax: amt,
+            additions_to_tax: amt,
+            self_employment_tax,
+            additional_medicare_tax: payroll.additional_medicare_tax.tax,
+            net_investment_income_tax: net_investment_income_tax.tax,
+            other_taxes: self_employment_tax + payroll.additional_medicare_tax.tax + net_investment_income_tax.tax,
+        };
+        let other_taxes = schedule_2.other_taxes;
+        let total_tax = tax + amt - nonrefundable_credits + other_taxes;
+
+        // Additional Medicare Tax withheld (Form 8959 line 24) is reported on line 25c
+        let withholding = input.w2s.iter().map(|w2| w2.federal_tax_withheld).sum::<Decimal>()
+            + input.form_1099s.iter().map(|form| form.federal_tax_withheld).sum::<Decimal>()
+            + payroll.additional_medicare_tax.withheld;
+        let other_payments = payroll.excess_social_security;
+        let total_payments = withholding + input.estimated_payments + credits.refundable + other_payments;
+        let balance = total_payments - total_tax;
+
+        let (states, state_warnings) = StateReturn::for_input(input, &income, adjusted_gross_income, &schedule_a, rules);
+        warnings.extend(state_warnings);
+
+        Ok(TaxCalculation {
+            year: input.year,
+            filing_status: status,
+            income,
+            schedule_c,
+            schedule_se,
+            capital_gains,
+            adjustments,
+            adjusted_gross_income,
+            schedule_a,
+            deduction,
+            qualified_business_income,
+            qualified_business_income_deduction,
+            taxable_income,
+            tax,
+            qualified_dividends_worksheet,
+            alternative_minimum_tax,
+            nonrefundable_credits,
+            refundable_credits: credits.refundable,
+            net_investment_income_tax,
+            schedule_2,
+            credits,
+            other_taxes,
+            total_tax,
+            withholding,
+            estimated_payments: input.estimated_payments,
+            other_payments,
+            total_payments,
+            refund: round_cents(balance.max(Decimal::ZERO)),
+            amount_owed: round_cents((-balance).max(Decimal::ZERO)),
+            marginal_rate: rules.marginal_rate(status, taxable_income),
+            payroll,
+            states,
+            warnings,
+        })
+    }
+
+    fn income(input: &TaxInput, schedule_c: &[ScheduleC], capital_gains: Option<&CapitalGains>) -> IncomeSummary {
+        let mut income = IncomeSummary {
+            wages: input.w2s.iter().map(|w2| w2.wages).sum(),
+            capital_gain: capital_gains.map(|gains| gains.schedule_d.capital_gain).unwrap_or_default(),
+            business_income: schedule_c.iter().map(|schedule| schedule.net_profit).sum(),
+            rental_income: input.rental_income,
+            taxable_refunds: input.taxable_state_refund,
+            other_income: input.other_income,
+            ..IncomeSummary::default()
+        };
+
+        for form in &input.form_1099s {
+            match form.kind() {
+                Form1099Kind::Int => income.taxable_interest += form.income,
+                Form1099Kind::Div => {
+                    income.ordinary_dividends += form.income;
+                    income.qualified_dividends += form.qualified_dividends;
+                }
+                // 1099-NEC and 1099-K receipts are counted through Schedule C
+                Form1099Kind::Nec | Form1099Kind::K => {}
+                Form1099Kind::Misc => income.other_income += form.income,
+            }
+        }
+
+        income.total = income.wages + income.taxable_interest + income.ordinary_dividends + income.capital_gain
+            + income.business_income + income.rental_income + income.taxable_refunds + income.other_income;
+        income
+    }
+
+    /// Net operating loss deduction for losses carried in. Losses arising after 2017 may only
+    /// offset `net_operating_loss_limit` of taxable income figured without the deduction, so
+    /// Schedule A is computed here at the AGI before it.
+    fn net_operating_loss(input: &TaxInput, rules: &TaxRules, adjusted_gross_income: Decimal) -> Decimal {
+        let carryover = input.net_operating_loss_carryover.max(Decimal::ZERO);
+        if carryover.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        let (schedule_a, _) = ScheduleA::compute(input, adjusted_gross_income, rules);
+        let taxable_income = (adjusted_gross_income - Self::deduction(input, rules, &schedule_a).amount).max(Decimal::ZERO);
+        carryover.min(round_cents(taxable_income * rules.net_operating_loss_limit))
+    }
+
+    /// Schedule 1 line 26. Educator expenses are capped per educator (two on a joint return).
+    fn adjustments(input: &TaxInput, rules: &TaxRules, total_income: Decimal, half_self_employment_tax: Decimal) -> Decimal {
+        let adjustments = &input.adjustments;
+        let educators = if input.filing_status == FilingStatus::MarriedFilingJointly { 2 } else { 1 };
+
+        let other_adjustments = adjustments.educator_expenses.min(rules.educator_expense_limit * Decimal::from(educators))
+            + adjustments.hsa_deduction
+            + adjustments.ira_deduction
+            + half_self_employment_tax
+            + adjustments.other;
+
+        other_adjustments + Self::student_loan_interest(input, rules, total_income - other_adjustments)
+    }
+
+    /// Capped at the annual limit and phased out over the year's MAGI range, which for this
+    /// deduction is AGI figured without it. Separate returns get a zero-width range.
+    fn student_loan_interest(input: &TaxInput, rules: &TaxRules, modified_agi: Decimal) -> Decimal {
+        if input.filing_status == FilingStatus::MarriedFilingSeparately {
+            return Decimal::ZERO;
+        }
+
+        // The worksheet rounds the reduction ratio to three decimal places.
+        let interest = input.adjustments.student_loan_interest.min(rules.student_loan_interest_limit);
+        let phase_out = rules.phase_outs.student_loan_interest.get(input.filing_status);
+        let reduction = phase_out.reduction_fraction(modified_agi).round_dp(3);
+        interest - round_cents(interest * reduction)
+    }
+
+    /// Recommends the larger of the standard deduction and Schedule A. A separate filer whose
+    /// spouse itemizes has no standard deduction; anyone may elect to itemize regardless.
+    fn deduction(input: &TaxInput, rules: &TaxRules, schedule_a: &ScheduleA) -> DeductionSummary {
+        let must_itemize = ScheduleA::must_itemize(input);
+        let standard = match must_itemize {
+            true => Decimal::ZERO,
+            false => rules.standard_deduction(input.filing_status, input.additional_standard_deduction_boxes()),
+        };
+        let itemized = schedule_a.total;
+        let recommended = if must_itemize || itemized > standard { DeductionKind::Itemized } else { DeductionKind::Standard };
+        let kind = if input.itemized.elect_to_itemize { DeductionKind::Itemized } else { recommended };
+
+        DeductionSummary {
+            kind,
+            recommended,
+            standard,
+            itemized,
+            amount: match kind {
+                DeductionKind::Standard => standard,
+                DeductionKind::Itemized => itemized,
+            },
+            advantage: (standard - itemized).abs(),
+        }
+    }
+}
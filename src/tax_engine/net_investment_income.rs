@@ -0,0 +1,57 @@
+//! Net investment income tax (Form 8960): 3.8% of the smaller of net investment income and
+//! modified AGI over the filing status's threshold.
+//!
+//! Investment income is taxable interest, ordinary dividends, net rental income and the net
+//! capital gain on Form 1040 line 7, which is already limited to the capital loss deduction.
+//! Deductions allocable to investment income (line 9) are not modeled, and with no foreign
+//! earned income exclusion modified AGI is AGI.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::{round_cents, IncomeSummary};
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NetInvestmentIncomeTax {
+    /// Line 1
+    pub taxable_interest: Decimal,
+    /// Line 2
+    pub ordinary_dividends: Decimal,
+    /// Line 4a
+    pub rental_income: Decimal,
+    /// Line 5a
+    pub capital_gain: Decimal,
+    /// Line 12, line 8 not less than zero
+    pub net_investment_income: Decimal,
+    /// Line 13
+    pub modified_adjusted_gross_income: Decimal,
+    /// Line 14
+    pub threshold: Decimal,
+    /// Line 15
+    pub excess: Decimal,
+    /// Line 17, to Schedule 2 line 12
+    pub tax: Decimal,
+}
+
+impl NetInvestmentIncomeTax {
+    pub fn compute(status: FilingStatus, income: &IncomeSummary, adjusted_gross_income: Decimal, rules: &TaxRules) -> NetInvestmentIncomeTax {
+        let niit = &rules.net_investment_income_tax;
+        let net_investment_income = (income.taxable_interest + income.ordinary_dividends + income.rental_income + income.capital_gain)
+            .max(Decimal::ZERO);
+        let threshold = *niit.threshold.get(status);
+        let excess = (adjusted_gross_income - threshold).max(Decimal::ZERO);
+
+        NetInvestmentIncomeTax {
+            taxable_interest: income.taxable_interest,
+            ordinary_dividends: income.ordinary_dividends,
+            rental_income: income.rental_income,
+            capital_gain: income.capital_gain,
+            net_investment_income,
+            modified_adjusted_gross_income: adjusted_gross_income,
+            threshold,
+            excess,
+            tax: round_cents(net_investment_income.min(excess) * niit.rate),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Reconciles Social Security and Medicare withholding across a year's W-2s.
+//!
+//! Each employer withholds as if it were the only one, so two jobs can together exceed the
+//! Social Security wage base (refunded as a credit on Schedule 3 line 11) or cross the
+//! Additional Medicare Tax threshold without any employer withholding it (Form 8959).
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::input::W2;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+
+/// Differences under a dollar are rounding on the employer's side, not errors.
+const TOLERANCE: Decimal = Decimal::ONE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayrollIssue {
+    MissingPayrollBoxes,
+    SocialSecurityWagesOverWageBase,
+    SocialSecurityWithholdingMismatch,
+    EmployerOverWithheldSocialSecurity,
+    MedicareWithholdingMismatch,
+}
+
+/// A W-2 whose boxes 3-6 do not agree with each other or with the year's rates.
+#[derive(Debug, Clone, Serialize)]
+pub struct PayrollFlag {
+    pub employer: String,
+    pub owner: Owner,
+    pub issue: PayrollIssue,
+    pub expected: Option<Decimal>,
+    pub reported: Option<Decimal>,
+    pub message: String,
+}
+
+/// Social Security withheld for one person across all of their employers.
+#[derive(Debug, Clone, Serialize)]
+pub struct SocialSecurityExcess {
+    pub owner: Owner,
+    pub employers: usize,
+    pub wages: Decimal,
+    pub tax_withheld: Decimal,
+    /// Rate times the wage base: the most one person owes for the year
+    pub maximum_tax: Decimal,
+    pub excess: Decimal,
+}
+
+/// Form 8959 parts I, II and V.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AdditionalMedicareTax {
+    /// Line 1
+    pub medicare_wages: Decimal,
+    /// Line 5
+    pub threshold: Decimal,
+    /// Line 7
+    pub wage_tax: Decimal,
+    /// Line 8, Schedule SE net earnings
+    pub self_employment_income: Decimal,
+    /// Line 13
+    pub self_employment_tax: Decimal,
+    /// Line 18, to Schedule 2 line 11
+    pub tax: Decimal,
+    /// Line 19
+    pub medicare_tax_withheld: Decimal,
+    /// Line 21
+    pub regular_medicare_tax: Decimal,
+    /// Line 24, included in Form 1040 line 25c
+    pub withheld: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PayrollReconciliation {
+    pub social_security: Vec<SocialSecurityExcess>,
+    /// Schedule 3 line 11
+    pub excess_social_security: Decimal,
+    pub additional_medicare_tax: AdditionalMedicareTax,
+    pub flags: Vec<PayrollFlag>,
+}
+
+impl PayrollReconciliation {
+    /// `self_employment_income` is the combined Schedule SE line 6 for the return.
+    pub fn reconcile(w2s: &[W2], self_employment_income: Decimal, status: FilingStatus, rules: &TaxRules) -> PayrollReconciliation {
+        let flags = w2s.iter().flat_map(|w2| Self::check(w2, rules)).collect();
+
+        let owners: &[Owner] = match status {
+            FilingStatus::MarriedFilingJointly => &[Owner::Taxpayer, Owner::Spouse],
+            _ => &[Owner::Taxpayer],
+        };
+        let social_security: Vec<SocialSecurityExcess> = owners.iter()
+            .map(|owner| {
+                let owned: Vec<&W2> = w2s.iter().filter(|w2| Self::owner(w2, status) == *owner).collect();
+                Self::social_security(*owner, &owned, rules)
+            })
+            .filter(|person| person.employers > 0)
+            .collect();
+
+        PayrollReconciliation {
+            excess_social_security: social_security.iter().map(|person| person.excess).sum(),
+            social_security,
+            additional_medicare_tax: Self::additional_medicare_tax(w2s, self_employment_income, status, rules),
+            flags,
+        }
+    }
+
+    /// Only a joint return has a spouse whose wages are tracked separately.
+    fn owner(w2: &W2, status: FilingStatus) -> Owner {
+        match status {
+            FilingStatus::MarriedFilingJointly => w2.owner,
+            _ => Owner::Taxpayer,
+        }
+    }
+
+    /// Excess Social Security is only refundable when it comes from two or more employers.
+    /// Anything one employer withholds above the maximum has to be refunded by that employer,
+    /// so each W-2 counts for at most the maximum.
+    fn social_security(owner: Owner, w2s: &[&W2], rules: &TaxRules) -> SocialSecurityExcess {
+        let maximum_tax = round_cents(rules.social_security_wage_base * rules.payroll.social_security_rate);
+        let withheld: Vec<Decimal> = w2s.iter().filter_map(|w2| w2.social_security_tax_withheld).collect();
+        let tax_withheld = withheld.iter().map(|amount| (*amount).min(maximum_tax)).sum::<Decimal>();
+
+        let excess = match withheld.len() {
+            0 | 1 => Decimal::ZERO,
+            _ => (tax_withheld - maximum_tax).max(Decimal::ZERO),
+        };
+
+        SocialSecurityExcess {
+            owner,
+            employers: w2s.len(),
+            wages: w2s.iter().filter_map(|w2| w2.social_security_wages).sum(),
+            tax_withheld,
+            maximum_tax,
+            excess,
+        }
+    }
+
+    /// The threshold applies to combined wages on a joint return, and whatever wages leave
+    /// of it applies to self-employment income. A W-2 without box 5 is counted at its box 1
+    /// wages, and one without box 6 is assumed to have had only the regular rate withheld.
+    fn additional_medicare_tax(w2s: &[W2], self_employment_income: Decimal, status: FilingStatus, rules: &TaxRules) -> AdditionalMedicareTax {
+        let payroll = &rules.payroll;
+        let medicare_wages: Decimal = w2s.iter().map(|w2| w2.medicare_wages.unwrap_or(w2.wages)).sum();
+        let threshold = *payroll.additional_medicare_threshold.get(status);
+        let wage_tax = round_cents((medicare_wages - threshold).max(Decimal::ZERO) * payroll.additional_medicare_rate);
+
+        let self_employment_income = self_employment_income.max(Decimal::ZERO);
+        let remaining_threshold = (threshold - medicare_wages).max(Decimal::ZERO);
+        let self_employment_tax = round_cents((self_employment_income - remaining_threshold).max(Decimal::ZERO) * payroll.additional_medicare_rate);
+
+        let medicare_tax_withheld: Decimal = w2s.iter()
+            .map(|w2| w2.medicare_tax_withheld.unwrap_or_else(|| round_cents(w2.medicare_wages.unwrap_or(w2.wages) * payroll.medicare_rate)))
+            .sum();
+        let regular_medicare_tax = round_cents(medicare_wages * payroll.medicare_rate);
+
+        AdditionalMedicareTax {
+            medicare_wages,
+            threshold,
+            wage_tax,
+            self_employment_income,
+            self_employment_tax,
+            tax: wage_tax + self_employment_tax,
+            medicare_tax_withheld,
+            regular_medicare_tax,
+            withheld: (medicare_tax_withheld - regular_medicare_tax).max(Decimal::ZERO),
+        }
+    }
+
+    /// Compares boxes 4 and 6 with what the employer should have withheld on boxes 3 and 5.
+    fn check(w2: &W2, rules: &TaxRules) -> Vec<PayrollFlag> {
+        let payroll = &rules.payroll;
+        let flag = |issue, expected, reported, message: String| PayrollFlag {
+            employer: w2.employer.clone(),
+            owner: w2.owner,
+            issue,
+            expected,
+            reported,
+            message,
+        };
+        let mut flags = Vec::new();
+
+        let boxes = [w2.social_security_wages, w2.social_security_tax_withheld, w2.medicare_wages, w2.medicare_tax_withheld];
+        if w2.wages > Decimal::ZERO && boxes.iter().any(Option::is_none) {
+            flags.push(flag(PayrollIssue::MissingPayrollBoxes, None, None, format!("W-2 from {} is missing some of boxes 3-6; missing Medicare amounts were estimated from box 1", w2.employer)));
+        }
+
+        let maximum_tax = round_cents(rules.social_security_wage_base * payroll.social_security_rate);
+        if let Some(wages) = w2.social_security_wages.filter(|wages| *wages > rules.social_security_wage_base) {
+            flags.push(flag(PayrollIssue::SocialSecurityWagesOverWageBase, Some(rules.social_security_wage_base), Some(wages),
+                format!("Box 3 wages from {} exceed the {} wage base of ${}", w2.employer, rules.year, rules.social_security_wage_base)));
+        }
+        if let (Some(wages), Some(withheld)) = (w2.social_security_wages, w2.social_security_tax_withheld) {
+            let expected = round_cents(wages.min(rules.social_security_wage_base) * payroll.social_security_rate);
+            if withheld > maximum_tax + TOLERANCE {
+                flags.push(flag(PayrollIssue::EmployerOverWithheldSocialSecurity, Some(maximum_tax), Some(withheld),
+                    format!("{} withheld more than the ${} maximum; the employer must refund the excess, it cannot be claimed as a credit", w2.employer, maximum_tax)));
+            } else if (withheld - expected).abs() > TOLERANCE {
+                flags.push(flag(PayrollIssue::SocialSecurityWithholdingMismatch, Some(expected), Some(withheld),
+                    format!("Box 4 from {} should be ${} for box 3 wages of ${}", w2.employer, expected, wages)));
+            }
+        }
+
+        if let (Some(wages), Some(withheld)) = (w2.medicare_wages, w2.medicare_tax_withheld) {
+            let over_threshold = (wages - payroll.additional_medicare_withholding_threshold).max(Decimal::ZERO);
+            let expected = round_cents(wages * payroll.medicare_rate + over_threshold * payroll.additional_medicare_rate);
+            if (withheld - expected).abs() > TOLERANCE {
+                flags.push(flag(PayrollIssue::MedicareWithholdingMismatch, Some(expected), Some(withheld),
+                    format!("Box 6 from {} should be ${} for box 5 wages of ${}", w2.employer, expected, wages)));
+            }
+        }
+
+        flags
+    }
+}
@@ -0,0 +1,576 @@
+//! Penalties and interest on a return: the Form 2210 underpayment penalty, and for a return
+//! filed or paid after its due date the failure-to-file and failure-to-pay penalties and interest.
+//!
+//! Form 2210 is figured with the penalty worksheet. Each installment's underpayment accrues at
+//! the quarterly underpayment rate, by day and without compounding, from its due date until
+//! payments cover it or the return is due. Withholding counts as paid in four equal amounts on
+//! the installment dates, and payments go to the earliest underpayment still owed.
+//!
+//! The annualized income installment method (Schedule AI) is used when the income through each
+//! period is entered and it lowers the penalty. A period's tax is figured by scaling the year's
+//! income by annualized over actual AGI, as the estimated tax projection does, so the mix of
+//! income is assumed to match the full year and capital gains and deductions are not scaled.
+//!
+//! Interest on unpaid tax compounds daily. Interest on the penalties themselves, extensions and
+//! the higher failure-to-pay rate after a notice of levy are not modeled.
+
+use chrono::{Datelike, Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::tax_engine::estimated_tax::{EstimatedPayment, EstimatedTaxPlanner, PriorYearTax, SafeHarbor};
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::rules::{PenaltyRules, TaxRules};
+use crate::tax_engine::{round_cents, TaxCalculation, TaxEngine};
+use crate::utilities::error_bag::ErrorBag;
+
+/// Months from January 1 to the end of each Schedule AI period.
+const ANNUALIZATION_MONTHS: [u32; 4] = [3, 5, 8, 12];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallmentMethod {
+    Regular,
+    /// Schedule AI, Form 2210 box C
+    AnnualizedIncome,
+}
+
+/// One column of Schedule AI part I.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnualizedInstallment {
+    pub period_end: NaiveDate,
+    /// Line 1
+    pub income: Decimal,
+    /// Line 2
+    pub annualization_factor: Decimal,
+    /// Line 3
+    pub annualized_income: Decimal,
+    /// Tax on the annualized income, less refundable credits
+    pub tax: Decimal,
+    /// Line 21
+    pub applicable_percentage: Decimal,
+    /// Line 25, the annualized income installment
+    pub installment: Decimal,
+    /// Line 28, the regular installment plus what earlier periods did not require
+    pub regular_installment: Decimal,
+    /// Line 29, the smaller of the two
+    pub required: Decimal,
+}
+
+/// One column of Form 2210 part IV.
+#[derive(Debug, Clone, Serialize)]
+pub struct PenaltyInstallment {
+    pub quarter: u32,
+    pub due_date: NaiveDate,
+    /// Line 18
+    pub required: Decimal,
+    /// Part of the installment covered by withholding and payments made by its due date
+    pub paid: Decimal,
+    /// Line 25
+    pub underpayment: Decimal,
+    /// Penalty worksheet, on this installment's underpayment
+    pub penalty: Decimal,
+}
+
+/// Form 2210.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnderpaymentPenalty {
+    /// Line 4, Form 1040 line 24 less refundable credits
+    pub current_year_tax: Decimal,
+    /// Line 6
+    pub withholding: Decimal,
+    pub safe_harbors: Vec<SafeHarbor>,
+    /// Line 9
+    pub required_annual_payment: Decimal,
+    pub method: InstallmentMethod,
+    /// Schedule AI, when the income through each period is entered
+    pub annualized: Vec<AnnualizedInstallment>,
+    pub installments: Vec<PenaltyInstallment>,
+    /// Line 19, to Form 1040 line 38
+    pub penalty: Decimal,
+    pub notes: Vec<String>,
+}
+
+/// IRC 6651 penalties and interest for a return filed or paid after its due date.
+#[derive(Debug, Clone, Serialize)]
+pub struct LateFilingPenalties {
+    pub due_date: NaiveDate,
+    pub filed_on: NaiveDate,
+    pub paid_on: NaiveDate,
+    /// Form 1040 line 37, the tax not paid by the due date
+    pub unpaid_tax: Decimal,
+    /// Months or parts of months after the due date
+    pub months_late_filing: u32,
+    pub months_late_payment: u32,
+    /// After the reduction for the failure-to-pay penalty in the same months
+    pub failure_to_file: Decimal,
+    pub failure_to_pay: Decimal,
+    /// On the unpaid tax from the due date until it is paid
+    pub interest: Decimal,
+    pub total: Decimal,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PenaltyEstimate {
+    pub year: i32,
+    pub underpayment: UnderpaymentPenalty,
+    pub late_filing: LateFilingPenalties,
+    pub total: Decimal,
+}
+
+/// An underpayment still owed, accruing from its installment's due date.
+struct Underpayment {
+    installment: usize,
+    since: NaiveDate,
+    amount: Decimal,
+}
+
+pub struct PenaltyCalculator;
+
+impl PenaltyCalculator {
+    pub fn estimate(
+        input: &TaxInput,
+        calculation: &TaxCalculation,
+        prior_year: Option<PriorYearTax>,
+        payments: &[EstimatedPayment],
+        filed_on: NaiveDate,
+        paid_on: NaiveDate,
+    ) -> Result<PenaltyEstimate, ErrorBag> {
+        let rules = TaxRules::for_year(input.year)?;
+        let underpayment = Self::underpayment(input, calculation, prior_year, payments, rules)?;
+        let late_filing = Self::late_filing(calculation.amount_owed, filed_on, paid_on, &rules.penalties);
+
+        Ok(PenaltyEstimate {
+            year: input.year,
+            total: underpayment.penalty + late_filing.total,
+            underpayment,
+            late_filing,
+        })
+    }
+
+    /// Form 2210, by the regular method and, when the income through each period is entered,
+    /// by Schedule AI; the smaller penalty is used.
+    pub fn underpayment(
+        input: &TaxInput,
+        calculation: &TaxCalculation,
+        prior_year: Option<PriorYearTax>,
+        payments: &[EstimatedPayment],
+        rules: &TaxRules,
+    ) -> Result<UnderpaymentPenalty, ErrorBag> {
+        let estimated = &rules.estimated_tax;
+        let current_year_tax = (calculation.total_tax - calculation.refundable_credits).max(Decimal::ZERO);
+        let withholding = calculation.withholding;
+        let mut notes = Vec::new();
+
+        let safe_harbors = EstimatedTaxPlanner::safe_harbors(current_year_tax, prior_year.as_ref(), rules, &mut notes);
+        let required_annual_payment = safe_harbors.iter().map(|harbor| harbor.required).min().unwrap_or_default();
+        let mut penalty = UnderpaymentPenalty {
+            current_year_tax,
+            withholding,
+            safe_harbors,
+            required_annual_payment,
+            method: InstallmentMethod::Regular,
+            annualized: Vec::new(),
+            installments: Vec::new(),
+            penalty: Decimal::ZERO,
+            notes,
+        };
+
+        if current_year_tax - withholding < estimated.minimum_balance_due {
+            penalty.notes.push(format!("Withholding leaves less than ${} of tax, so there is no penalty", estimated.minimum_balance_due));
+            return Ok(penalty);
+        }
+        if prior_year.as_ref().is_some_and(|prior| prior.tax.is_zero()) {
+            penalty.notes.push(format!("There was no tax for {}, so there is no penalty", rules.year - 1));
+            return Ok(penalty);
+        }
+
+        let mut payments: Vec<(NaiveDate, Decimal)> = payments.iter().map(|payment| (payment.paid_on, payment.amount)).collect();
+        if payments.is_empty() && input.estimated_payments > Decimal::ZERO {
+            penalty.notes.push("Estimated payments have no dates, so they are treated as paid in equal amounts on the installment dates".into());
+            payments.extend(Self::evenly(input.estimated_payments, &estimated.due_dates));
+        }
+        payments.extend(Self::evenly(withholding, &estimated.due_dates));
+        payments.sort_by_key(|(date, _)| *date);
+
+        let quarter = round_cents(required_annual_payment / Decimal::from(4));
+        let regular: Vec<Decimal> = (0..4)
+            .map(|index| match index {
+                3 => required_annual_payment - quarter * Decimal::from(3),
+                _ => quarter,
+            })
+            .collect();
+        penalty.installments = Self::installments(&regular, &payments, rules);
+        penalty.penalty = penalty.installments.iter().map(|installment| installment.penalty).sum();
+
+        if let Some(annualized) = Self::annualized(input, calculation, &regular, rules, &mut penalty.notes)? {
+            let required: Vec<Decimal> = annualized.iter().map(|period| period.required).collect();
+            let installments = Self::installments(&required, &payments, rules);
+            let total = installments.iter().map(|installment| installment.penalty).sum::<Decimal>();
+            if total < penalty.penalty {
+                penalty.notes.push(format!("The annualized income installment method lowers the penalty by ${}", penalty.penalty - total));
+                penalty.method = InstallmentMethod::AnnualizedIncome;
+                penalty.installments = installments;
+                penalty.penalty = total;
+            }
+            penalty.annualized = annualized;
+        }
+
+        Ok(penalty)
+    }
+
+    /// IRC 6651: 5% a month of the unpaid tax for filing late and 0.5% for paying late, each up
+    /// to 25%, with the filing penalty reduced by the payment penalty for months both apply.
+    pub fn late_filing(unpaid_tax: Decimal, filed_on: NaiveDate, paid_on: NaiveDate, rules: &PenaltyRules) -> LateFilingPenalties {
+        let due_date = rules.filing_due_date;
+        let unpaid_tax = unpaid_tax.max(Decimal::ZERO);
+        let months_late_filing = Self::months_late(due_date, filed_on);
+        let months_late_payment = Self::months_late(due_date, paid_on);
+        let mut notes = Vec::new();
+
+        let filing_rate = (rules.failure_to_file_rate * Decimal::from(months_late_filing)).min(rules.failure_to_file_maximum);
+        let payment_rate = (rules.failure_to_pay_rate * Decimal::from(months_late_payment)).min(rules.failure_to_pay_maximum);
+        let filing_months = Decimal::from(months_late_filing).min((rules.failure_to_file_maximum / rules.failure_to_file_rate).floor());
+        let overlap = filing_months.min(Decimal::from(months_late_payment)) * rules.failure_to_pay_rate;
+        let mut failure_to_file = round_cents(unpaid_tax * (filing_rate - overlap).max(Decimal::ZERO));
+        if (filed_on - due_date).num_days() > rules.minimum_penalty_days && unpaid_tax > Decimal::ZERO {
+            let minimum = rules.minimum_failure_to_file_penalty.min(unpaid_tax);
+            if failure_to_file < minimum {
+                notes.push(format!("Filed more than {} days late, so the failure-to-file penalty is at least ${minimum}", rules.minimum_penalty_days));
+                failure_to_file = minimum;
+            }
+        }
+        let failure_to_pay = round_cents(unpaid_tax * payment_rate);
+        let interest = round_cents(Self::compound(unpaid_tax, due_date, paid_on, rules));
+        if unpaid_tax.is_zero() && months_late_filing > 0 {
+            notes.push("No tax is due, so filing late carries no penalty".into());
+        }
+        if paid_on > due_date && rules.interest_rates.last().is_some_and(|rate| rate.from.checked_add_months(Months::new(3)).is_some_and(|end| paid_on >= end)) {
+            notes.push("Interest after the last quarter in the rate table uses that quarter's rate".into());
+        }
+
+        LateFilingPenalties {
+            due_date,
+            filed_on,
+            paid_on,
+            unpaid_tax,
+            months_late_filing,
+            months_late_payment,
+            failure_to_file,
+            failure_to_pay,
+            interest,
+            total: failure_to_file + failure_to_pay + interest,
+            notes,
+        }
+    }
+
+    /// Form 2210 part IV and the penalty worksheet for the installments in `required`.
+    fn installments(required: &[Decimal], payments: &[(NaiveDate, Decimal)], rules: &TaxRules) -> Vec<PenaltyInstallment> {
+        let penalties = &rules.penalties;
+        let mut installments: Vec<PenaltyInstallment> = rules.estimated_tax.due_dates.iter().zip(required)
+            .enumerate()
+            .map(|(index, (due_date, required))| PenaltyInstallment {
+                quarter: index as u32 + 1,
+                due_date: *due_date,
+                required: *required,
+                paid: Decimal::ZERO,
+                underpayment: Decimal::ZERO,
+                penalty: Decimal::ZERO,
+            })
+            .collect();
+        let mut penalty = vec![Decimal::ZERO; installments.len()];
+        let mut owed: Vec<Underpayment> = Vec::new();
+        let mut credit = Decimal::ZERO;
+
+        // Pays the earliest underpayments, closing the penalty on what it covers
+        let mut apply = |date: NaiveDate, amount: Decimal, owed: &mut Vec<Underpayment>, credit: &mut Decimal| {
+            let mut amount = amount;
+            for underpayment in owed.iter_mut().filter(|underpayment| !underpayment.amount.is_zero()) {
+                let covered = amount.min(underpayment.amount);
+                penalty[underpayment.installment] += Self::accrue(covered, underpayment.since, date.min(penalties.filing_due_date), penalties);
+                underpayment.amount -= covered;
+                amount -= covered;
+            }
+            *credit += amount;
+        };
+
+        let mut payments = payments.iter().peekable();
+        for (index, installment) in installments.iter_mut().enumerate() {
+            while let Some((date, amount)) = payments.next_if(|(date, _)| *date <= installment.due_date) {
+                apply(*date, *amount, &mut owed, &mut credit);
+            }
+            installment.paid = credit.min(installment.required);
+            credit -= installment.paid;
+            installment.underpayment = installment.required - installment.paid;
+            if !installment.underpayment.is_zero() {
+                owed.push(Underpayment { installment: index, since: installment.due_date, amount: installment.underpayment });
+            }
+        }
+        for (date, amount) in payments {
+            apply(*date, *amount, &mut owed, &mut credit);
+        }
+        for underpayment in owed.iter().filter(|underpayment| !underpayment.amount.is_zero()) {
+            penalty[underpayment.installment] += Self::accrue(underpayment.amount, underpayment.since, penalties.filing_due_date, penalties);
+        }
+
+        for (installment, penalty) in installments.iter_mut().zip(penalty) {
+            installment.penalty = round_cents(penalty);
+        }
+        installments
+    }
+
+    /// Schedule AI part I. `None` without four periods of income, or when the year's AGI is
+    /// not positive and cannot be scaled.
+    fn annualized(
+        input: &TaxInput,
+        calculation: &TaxCalculation,
+        regular: &[Decimal],
+        rules: &TaxRules,
+        notes: &mut Vec<String>,
+    ) -> Result<Option<Vec<AnnualizedInstallment>>, ErrorBag> {
+        if input.annualized_income.is_empty() {
+            return Ok(None);
+        }
+        if input.annualized_income.len() != ANNUALIZATION_MONTHS.len() {
+            notes.push("Annualized income needs the income through each of the four periods; the regular method is used".into());
+            return Ok(None);
+        }
+        if calculation.adjusted_gross_income <= Decimal::ZERO {
+            notes.push("The annualized income installment method needs positive AGI for the year; the regular method is used".into());
+            return Ok(None);
+        }
+
+        let mut periods = Vec::new();
+        let mut required_so_far = Decimal::ZERO;
+        let mut carried = Decimal::ZERO;
+        for (index, (income, months)) in input.annualized_income.iter().zip(ANNUALIZATION_MONTHS).enumerate() {
+            let annualization_factor = (Decimal::from(12) / Decimal::from(months)).round_dp(1);
+            let annualized_income = round_cents(*income * annualization_factor);
+            let mut projected = EstimatedTaxPlanner::project(input, annualized_income / calculation.adjusted_gross_income)?;
+            projected.annualized_income.clear();
+            let projected = TaxEngine::calculate(&projected)?;
+            let tax = (projected.total_tax - projected.refundable_credits).max(Decimal::ZERO);
+
+            let applicable_percentage = (rules.estimated_tax.current_year_rate * Decimal::from(index as u32 + 1) / Decimal::from(4)).normalize();
+            let installment = (round_cents(tax * applicable_percentage) - required_so_far).max(Decimal::ZERO);
+            let regular_installment = regular[index] + carried;
+            let required = installment.min(regular_installment);
+            carried = regular_installment - required;
+            required_so_far += required;
+
+            periods.push(AnnualizedInstallment {
+                period_end: Self::period_end(rules.year, months),
+                income: *income,
+                annualization_factor,
+                annualized_income,
+                tax,
+                applicable_percentage,
+                installment,
+                regular_installment,
+                required,
+            });
+        }
+
+        Ok(Some(periods))
+    }
+
+    fn evenly(amount: Decimal, due_dates: &[NaiveDate; 4]) -> Vec<(NaiveDate, Decimal)> {
+        let quarter = round_cents(amount / Decimal::from(4));
+        due_dates.iter().enumerate()
+            .map(|(index, date)| (*date, if index == 3 { amount - quarter * Decimal::from(3) } else { quarter }))
+            .filter(|(_, amount)| !amount.is_zero())
+            .collect()
+    }
+
+    /// Simple interest by day at the rate in effect on each day after `from`, through `to`.
+    fn accrue(amount: Decimal, from: NaiveDate, to: NaiveDate, rules: &PenaltyRules) -> Decimal {
+        from.iter_days().skip(1).take_while(|day| *day <= to)
+            .map(|day| amount * rules.interest_rate(day) / Decimal::from(Self::days_in_year(day.year())))
+            .sum()
+    }
+
+    /// Interest compounded daily on `amount` from `from` through `to`.
+    fn compound(amount: Decimal, from: NaiveDate, to: NaiveDate, rules: &PenaltyRules) -> Decimal {
+        from.iter_days().skip(1).take_while(|day| *day <= to)
+            .fold(amount, |balance, day| balance + balance * rules.interest_rate(day) / Decimal::from(Self::days_in_year(day.year())))
+            - amount
+    }
+
+    /// Months or parts of months from `due_date` to `date`.
+    fn months_late(due_date: NaiveDate, date: NaiveDate) -> u32 {
+        (0..).find(|months| due_date.checked_add_months(Months::new(*months)).is_none_or(|end| end >= date)).unwrap_or_default()
+    }
+
+    fn period_end(year: i32, months: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, months + 1, 1)
+            .or_else(|| NaiveDate::from_ymd_opt(year + 1, 1, 1))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or_default()
+    }
+
+    fn days_in_year(year: i32) -> i64 {
+        match NaiveDate::from_ymd_opt(year, 2, 29) {
+            Some(_) => 366,
+            None => 365,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+    use super::{InstallmentMethod, PenaltyCalculator};
+    use crate::models::filing_profile_model::FilingStatus;
+    use crate::tax_engine::estimated_tax::PriorYearTax;
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::rules::TaxRules;
+    use crate::tax_engine::{round_cents, TaxEngine};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn cents(amount: i64) -> Decimal {
+        Decimal::new(amount, 2)
+    }
+
+    /// Single, $100,000 of wages with $5,000 withheld: $13,847 of tax, and a 2023 return with
+    /// $12,000 of tax, so the required annual payment is $12,000 in installments of $3,000.
+    fn input(annualized_income: &[i64]) -> TaxInput {
+        serde_json::from_value(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 100000, "federal_tax_withheld": 5000 }],
+            "annualized_income": annualized_income
+        })).unwrap()
+    }
+
+    fn prior_year() -> Option<PriorYearTax> {
+        Some(PriorYearTax { year: 2023, filing_status: FilingStatus::Single, adjusted_gross_income: 90000.into(), tax: 12000.into() })
+    }
+
+    // Penalty worksheet, 2024 Form 2210 instructions: 8% a year over 366 days through
+    // December 31, 2024 and 7% over 365 days from January 1, 2025. Withholding of $1,250 on each
+    // due date goes to the earliest underpayment:
+    //   Q1: $1,250 Apr 15-Jun 17 and $500 Apr 15-Sep 16                                 $34.04
+    //   Q2: $750 Jun 17-Sep 16, $1,250 Jun 17-Jan 15 and $1,000 Jun 17-Apr 15         $135.54
+    //   Q3: $3,000 Sep 16-Apr 15                                                        $129.92
+    //   Q4: $3,000 Jan 15-Apr 15                                                         $51.78
+    #[test]
+    fn regular_method_follows_the_penalty_worksheet() {
+        let input = input(&[]);
+        let calculation = TaxEngine::calculate(&input).unwrap();
+        let penalty = PenaltyCalculator::underpayment(&input, &calculation, prior_year(), &[], TaxRules::for_year(2024).unwrap()).unwrap();
+
+        assert_eq!(penalty.current_year_tax, Decimal::from(13847));
+        assert_eq!(penalty.required_annual_payment, Decimal::from(12000));
+        assert_eq!(penalty.method, InstallmentMethod::Regular);
+        let underpayments: Vec<Decimal> = penalty.installments.iter().map(|installment| installment.underpayment).collect();
+        let penalties: Vec<Decimal> = penalty.installments.iter().map(|installment| installment.penalty).collect();
+        assert_eq!(underpayments, [1750, 3000, 3000, 3000].map(Decimal::from));
+        assert_eq!(penalties, [3404, 13554, 12992, 5178].map(cents));
+        assert_eq!(penalty.penalty, cents(35128));
+    }
+
+    // Schedule AI with income through March, May, August and December of $10,000, $15,000,
+    // $25,000 and $100,000. Annualized, the wages are $40,000, $36,000, $37,500 and $100,000;
+    // the tax table gives $2,819, $2,339, $2,519 and $13,847, and the applicable percentages
+    // 22.5%, 45%, 67.5% and 90% leave installments of $634.28, $418.27, $647.78 and $10,761.97.
+    // The last is capped at the regular installments not yet required, $10,299.67. Withholding
+    // covers all but $7,000 of it, which runs Jan 15-Apr 15 at 7%: $120.82.
+    #[test]
+    fn annualized_income_method_lowers_the_penalty_for_late_income() {
+        let input = input(&[10000, 15000, 25000, 100000]);
+        let calculation = TaxEngine::calculate(&input).unwrap();
+        let penalty = PenaltyCalculator::underpayment(&input, &calculation, prior_year(), &[], TaxRules::for_year(2024).unwrap()).unwrap();
+
+        let factors: Vec<Decimal> = penalty.annualized.iter().map(|period| period.annualization_factor).collect();
+        let taxes: Vec<Decimal> = penalty.annualized.iter().map(|period| period.tax).collect();
+        let percentages: Vec<Decimal> = penalty.annualized.iter().map(|period| period.applicable_percentage).collect();
+        let required: Vec<Decimal> = penalty.annualized.iter().map(|period| period.required).collect();
+        assert_eq!(factors, [Decimal::from(4), Decimal::new(24, 1), Decimal::new(15, 1), Decimal::ONE]);
+        assert_eq!(taxes, [2819, 2339, 2519, 13847].map(Decimal::from));
+        assert_eq!(percentages, [Decimal::new(225, 3), Decimal::new(45, 2), Decimal::new(675, 3), Decimal::new(9, 1)]);
+        assert_eq!(required, [63428, 41827, 64778, 1029967].map(cents));
+        assert_eq!(penalty.annualized[2].period_end, date(2024, 8, 31));
+
+        assert_eq!(penalty.method, InstallmentMethod::AnnualizedIncome);
+        assert_eq!(penalty.installments[3].underpayment, Decimal::from(7000));
+        assert_eq!(penalty.penalty, cents(12082));
+        assert!(penalty.notes.iter().any(|note| note.contains("lowers the penalty by $230.46")));
+    }
+
+    #[test]
+    fn no_penalty_when_withholding_leaves_under_the_minimum() {
+        let mut input = input(&[]);
+        input.w2s[0].federal_tax_withheld = 13000.into();
+        let calculation = TaxEngine::calculate(&input).unwrap();
+        let penalty = PenaltyCalculator::underpayment(&input, &calculation, prior_year(), &[], TaxRules::for_year(2024).unwrap()).unwrap();
+
+        assert!(penalty.penalty.is_zero());
+        assert!(penalty.installments.is_empty());
+    }
+
+    // Five months late: 25% to file less 2.5% for the months the 0.5% payment penalty also
+    // runs, and 148 days of interest at 7% compounded daily.
+    #[test]
+    fn late_filer_has_the_failure_to_file_penalty_reduced_by_the_failure_to_pay_penalty() {
+        let rules = &TaxRules::for_year(2024).unwrap().penalties;
+        let late = PenaltyCalculator::late_filing(10000.into(), date(2025, 9, 10), date(2025, 9, 10), rules);
+
+        assert_eq!(late.months_late_filing, 5);
+        assert_eq!(late.months_late_payment, 5);
+        assert_eq!(late.failure_to_file, Decimal::from(2250));
+        assert_eq!(late.failure_to_pay, Decimal::from(250));
+        assert_eq!(late.interest, cents(28787));
+        assert_eq!(late.total, cents(278787));
+    }
+
+    #[test]
+    fn failure_to_file_penalty_stops_at_25_percent_while_failure_to_pay_runs_on() {
+        let rules = &TaxRules::for_year(2024).unwrap().penalties;
+        let late = PenaltyCalculator::late_filing(10000.into(), date(2025, 12, 1), date(2025, 12, 1), rules);
+
+        assert_eq!(late.months_late_filing, 8);
+        assert_eq!(late.failure_to_file, Decimal::from(2250));
+        assert_eq!(late.failure_to_pay, Decimal::from(400));
+    }
+
+    #[test]
+    fn minimum_penalty_applies_more_than_60_days_late() {
+        let rules = &TaxRules::for_year(2024).unwrap().penalties;
+        let on_day_60 = PenaltyCalculator::late_filing(1000.into(), date(2025, 6, 14), date(2025, 6, 14), rules);
+        let on_day_66 = PenaltyCalculator::late_filing(1000.into(), date(2025, 6, 20), date(2025, 6, 20), rules);
+        let small_balance = PenaltyCalculator::late_filing(300.into(), date(2025, 6, 20), date(2025, 6, 20), rules);
+
+        assert_eq!(on_day_60.failure_to_file, Decimal::from(90));
+        assert_eq!(on_day_66.failure_to_file, Decimal::from(510));
+        assert_eq!(on_day_66.failure_to_pay, Decimal::from(15));
+        assert_eq!(small_balance.failure_to_file, Decimal::from(300));
+        assert!(on_day_66.notes.iter().any(|note| note.contains("at least $510")));
+    }
+
+    #[test]
+    fn interest_uses_each_days_rate_across_the_rate_table() {
+        let rules = &TaxRules::for_year(2024).unwrap().penalties;
+
+        // Two days at 8%/366 and three at 7%/365
+        let interest = PenaltyCalculator::accrue(10000.into(), date(2024, 12, 29), date(2025, 1, 3), rules);
+        assert_eq!(round_cents(interest), cents(1013));
+
+        let late = PenaltyCalculator::late_filing(10000.into(), date(2025, 4, 15), date(2026, 7, 15), rules);
+        assert!(late.failure_to_file.is_zero());
+        assert!(late.notes.iter().any(|note| note.contains("last quarter in the rate table")));
+    }
+
+    #[test]
+    fn months_late_counts_parts_of_months() {
+        let due = date(2025, 4, 15);
+        assert_eq!(PenaltyCalculator::months_late(due, date(2025, 4, 15)), 0);
+        assert_eq!(PenaltyCalculator::months_late(due, date(2025, 4, 16)), 1);
+        assert_eq!(PenaltyCalculator::months_late(due, date(2025, 5, 15)), 1);
+        assert_eq!(PenaltyCalculator::months_late(due, date(2025, 5, 16)), 2);
+    }
+}
@@ -0,0 +1,189 @@
+//! Qualified business income deduction (Form 8995 or 8995-A, Form 1040 line 13).
+//!
+//! Each Schedule C is a qualified trade or business. Its QBI is the net profit less the part of
+//! the deductible half of self-employment tax attributable to it; self-employed health insurance
+//! and retirement contributions are not split out of the other adjustments, so they do not
+//! reduce it. W-2 wages are the Schedule C line 26 wages.
+//!
+//! At or below the threshold the simplified Form 8995 applies. Above it, Form 8995-A limits each
+//! business to the greater of 50% of its W-2 wages and 25% of wages plus 2.5% of qualified
+//! property, and reduces a specified service business's QBI, wages and property to the
+//! applicable percentage; both are phased in over the phase-in range. Losses from other
+//! businesses and from earlier years are netted against the profitable businesses in proportion
+//! to their QBI (Form 8995-A Schedule C), and a negative total carries forward.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::models::business_model::ExpenseCategory;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::self_employment::{ScheduleC, ScheduleSe};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualifiedBusinessIncomeForm {
+    /// Taxable income at or below the threshold
+    #[serde(rename = "form_8995")]
+    Form8995,
+    #[serde(rename = "form_8995_a")]
+    Form8995A,
+}
+
+/// One business on Form 8995 line 1 or Form 8995-A part II.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualifiedBusiness {
+    pub business_id: Option<Uuid>,
+    pub name: String,
+    pub specified_service: bool,
+    /// Line 2, after the applicable percentage for a specified service business
+    pub qualified_business_income: Decimal,
+    /// Losses netted against this business (Form 8995-A Schedule C), as a positive amount
+    pub loss_netting: Decimal,
+    /// Line 4
+    pub w2_wages: Decimal,
+    /// Line 7
+    pub qualified_property: Decimal,
+    /// Line 3, 20% of QBI after netting
+    pub tentative_deduction: Decimal,
+    /// Line 10, the greater of 50% of wages and 25% of wages plus 2.5% of property
+    pub wage_limit: Decimal,
+    /// Line 15, the QBI component for this business
+    pub component: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QualifiedBusinessIncomeDeduction {
+    pub form: QualifiedBusinessIncomeForm,
+    pub businesses: Vec<QualifiedBusiness>,
+    /// Form 8995 line 3, as a positive amount
+    pub loss_carryover: Decimal,
+    /// Form 8995 line 4, total QBI after the carryover
+    pub total_qualified_business_income: Decimal,
+    /// Form 8995 line 10, total QBI component
+    pub component: Decimal,
+    /// Line 11, taxable income before the deduction
+    pub taxable_income_before_deduction: Decimal,
+    /// Line 12, qualified dividends plus net capital gain
+    pub net_capital_gain: Decimal,
+    /// Line 14, 20% of line 11 less line 12
+    pub income_limit: Decimal,
+    /// Line 15, to Form 1040 line 13
+    pub deduction: Decimal,
+    /// Line 16, as a positive amount
+    pub loss_carryforward: Decimal,
+}
+
+impl QualifiedBusinessIncomeDeduction {
+    /// `None` when there is no Schedule C and no loss carried in.
+    pub fn compute(
+        input: &TaxInput,
+        rules: &TaxRules,
+        schedule_c: &[ScheduleC],
+        schedule_se: &[ScheduleSe],
+        taxable_income_before_deduction: Decimal,
+        net_capital_gain: Decimal,
+    ) -> Option<QualifiedBusinessIncomeDeduction> {
+        let loss_carryover = input.qualified_business_loss_carryover.max(Decimal::ZERO);
+        if schedule_c.is_empty() && loss_carryover.is_zero() {
+            return None;
+        }
+
+        let qbi = &rules.qualified_business_income;
+        let threshold = *qbi.threshold.get(input.filing_status);
+        let phase_in = ((taxable_income_before_deduction - threshold) / *qbi.phase_in_range.get(input.filing_status))
+            .clamp(Decimal::ZERO, Decimal::ONE);
+        let form = match taxable_income_before_deduction > threshold {
+            true => QualifiedBusinessIncomeForm::Form8995A,
+            false => QualifiedBusinessIncomeForm::Form8995,
+        };
+
+        let mut businesses: Vec<QualifiedBusiness> = schedule_c.iter()
+            .map(|schedule| Self::business(input, schedule, schedule_c, schedule_se, Decimal::ONE - phase_in))
+            .collect();
+
+        let positive = businesses.iter().map(|business| business.qualified_business_income.max(Decimal::ZERO)).sum::<Decimal>();
+        let losses = loss_carryover - businesses.iter().map(|business| business.qualified_business_income.min(Decimal::ZERO)).sum::<Decimal>();
+        let netted = losses.min(positive);
+        for business in businesses.iter_mut().filter(|business| business.qualified_business_income > Decimal::ZERO) {
+            business.loss_netting = round_cents(netted * (business.qualified_business_income / positive));
+            let income = business.qualified_business_income - business.loss_netting;
+            business.tentative_deduction = round_cents(income * qbi.rate);
+            business.wage_limit = (business.w2_wages * qbi.wage_rate)
+                .max(business.w2_wages * qbi.wage_and_property_wage_rate + business.qualified_property * qbi.property_rate);
+            business.component = match form {
+                QualifiedBusinessIncomeForm::Form8995 => business.tentative_deduction,
+                // Part III: the excess over the wage limit is phased in
+                QualifiedBusinessIncomeForm::Form8995A if business.wage_limit < business.tentative_deduction =>
+                    business.tentative_deduction - round_cents((business.tentative_deduction - business.wage_limit) * phase_in),
+                QualifiedBusinessIncomeForm::Form8995A => business.tentative_deduction,
+            };
+        }
+
+        let total_qualified_business_income = businesses.iter().map(|business| business.qualified_business_income).sum::<Decimal>() - loss_carryover;
+        let component = match form {
+            QualifiedBusinessIncomeForm::Form8995 => round_cents(total_qualified_business_income.max(Decimal::ZERO) * qbi.rate),
+            QualifiedBusinessIncomeForm::Form8995A => businesses.iter().map(|business| business.component).sum(),
+        };
+        let income_limit = round_cents((taxable_income_before_deduction - net_capital_gain).max(Decimal::ZERO) * qbi.rate);
+
+        Some(QualifiedBusinessIncomeDeduction {
+            form,
+            businesses,
+            loss_carryover,
+            total_qualified_business_income,
+            component,
+            taxable_income_before_deduction,
+            net_capital_gain,
+            income_limit,
+            deduction: component.min(income_limit),
+            loss_carryforward: (-total_qualified_business_income).max(Decimal::ZERO),
+        })
+    }
+
+    /// QBI, wages and property for one Schedule C, reduced to `applicable_percentage` for a
+    /// specified service business. The owner's self-employment tax deduction is split across
+    /// their profitable businesses by net profit.
+    fn business(
+        input: &TaxInput,
+        schedule: &ScheduleC,
+        schedule_c: &[ScheduleC],
+        schedule_se: &[ScheduleSe],
+        applicable_percentage: Decimal,
+    ) -> QualifiedBusiness {
+        let business = schedule.business_id
+            .and_then(|id| input.businesses.iter().find(|business| business.id == Some(id)));
+        let specified_service = business.is_some_and(|business| business.specified_service);
+        let share = if specified_service { applicable_percentage } else { Decimal::ONE };
+
+        let owner_profit = schedule_c.iter()
+            .filter(|other| other.owner == schedule.owner)
+            .map(|other| other.net_profit.max(Decimal::ZERO))
+            .sum::<Decimal>();
+        let self_employment_deduction = match schedule.net_profit > Decimal::ZERO {
+            true => schedule_se.iter()
+                .find(|se| se.owner == schedule.owner)
+                .map(|se| round_cents(se.deduction * (schedule.net_profit / owner_profit)))
+                .unwrap_or_default(),
+            false => Decimal::ZERO,
+        };
+        let w2_wages = schedule.expenses.iter()
+            .filter(|expense| expense.category == ExpenseCategory::Wages)
+            .map(|expense| expense.deductible)
+            .sum::<Decimal>();
+
+        QualifiedBusiness {
+            business_id: schedule.business_id,
+            name: schedule.name.clone(),
+            specified_service,
+            qualified_business_income: round_cents((schedule.net_profit - self_employment_deduction) * share),
+            loss_netting: Decimal::ZERO,
+            w2_wages: round_cents(w2_wages * share),
+            qualified_property: round_cents(business.map(|business| business.qualified_property).unwrap_or_default() * share),
+            tentative_deduction: Decimal::ZERO,
+            wage_limit: Decimal::ZERO,
+            component: Decimal::ZERO,
+        }
+    }
+}
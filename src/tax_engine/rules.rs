@@ -0,0 +1,738 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::state::StateRules;
+use crate::tax_engine::{round_cents, round_dollars};
+use crate::utilities::error_bag::ErrorBag;
+
+/// One value per filing status. Every status must be present so a rule table can never
+/// silently fall back to another status's numbers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByFilingStatus<T> {
+    pub single: T,
+    pub married_filing_jointly: T,
+    pub married_filing_separately: T,
+    pub head_of_household: T,
+    pub qualifying_surviving_spouse: T,
+}
+
+impl<T> ByFilingStatus<T> {
+    pub fn get(&self, status: FilingStatus) -> &T {
+        match status {
+            FilingStatus::Single => &self.single,
+            FilingStatus::MarriedFilingJointly => &self.married_filing_jointly,
+            FilingStatus::MarriedFilingSeparately => &self.married_filing_separately,
+            FilingStatus::HeadOfHousehold => &self.head_of_household,
+            FilingStatus::QualifyingSurvivingSpouse => &self.qualifying_surviving_spouse,
+        }
+    }
+}
+
+/// A marginal bracket: `rate` applies to income above `over` up to the next bracket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bracket {
+    pub over: Decimal,
+    pub rate: Decimal,
+}
+
+/// Exact tax on `income` across marginal brackets, with no rounding.
+pub fn bracket_tax(brackets: &[Bracket], income: Decimal) -> Decimal {
+    brackets.iter().enumerate().fold(Decimal::ZERO, |tax, (i, bracket)| {
+        if income <= bracket.over {
+            return tax;
+        }
+        let top = brackets.get(i + 1).map(|next| next.over.min(income)).unwrap_or(income);
+        tax + (top - bracket.over) * bracket.rate
+    })
+}
+
+/// Brackets must start at 0 with increasing thresholds and rates between 0 and 1.
+pub fn validate_brackets(name: &str, brackets: &[Bracket]) -> Result<(), String> {
+    match brackets.first() {
+        Some(first) if first.over.is_zero() => {}
+        _ => return Err(format!("{name} brackets must start at 0")),
+    }
+    if brackets.windows(2).any(|pair| pair[1].over <= pair[0].over || pair[1].rate < pair[0].rate) {
+        return Err(format!("{name} brackets must have increasing thresholds and rates"));
+    }
+    if brackets.iter().any(|bracket| bracket.rate <= Decimal::ZERO || bracket.rate >= Decimal::ONE) {
+        return Err(format!("{name} bracket rates must be between 0 and 1"));
+    }
+
+    Ok(())
+}
+
+/// An income range over which a deduction or credit is reduced linearly to zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseOut {
+    pub start: Decimal,
+    pub end: Decimal,
+}
+
+impl PhaseOut {
+    /// Share of the benefit lost at `income`, from 0 (below the range) to 1 (above it).
+    pub fn reduction_fraction(&self, income: Decimal) -> Decimal {
+        if income <= self.start {
+            return Decimal::ZERO;
+        }
+        if income >= self.end {
+            return Decimal::ONE;
+        }
+
+        (income - self.start) / (self.end - self.start)
+    }
+}
+
+/// FICA rates and the Form 8959 thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayrollRates {
+    /// Employee share of Social Security tax, up to the wage base
+    pub social_security_rate: Decimal,
+    /// Employee share of Medicare tax, on all wages
+    pub medicare_rate: Decimal,
+    pub additional_medicare_rate: Decimal,
+    /// Combined Medicare wages above which Additional Medicare Tax is owed
+    pub additional_medicare_threshold: ByFilingStatus<Decimal>,
+    /// Wages paid by one employer above which the employer withholds Additional Medicare Tax
+    pub additional_medicare_withholding_threshold: Decimal,
+    /// Share of net profit subject to self-employment tax (Schedule SE line 4a)
+    pub self_employment_earnings_factor: Decimal,
+    /// Net earnings below this owe no self-employment tax
+    pub self_employment_minimum: Decimal,
+}
+
+/// Form 8960. The thresholds are set by statute and not adjusted for inflation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetInvestmentIncomeTaxRules {
+    pub rate: Decimal,
+    /// Modified AGI above which net investment income is taxed
+    pub threshold: ByFilingStatus<Decimal>,
+}
+
+/// Preferential rates for qualified dividends and long-term gains, and the Schedule D loss limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapitalGainRates {
+    pub fifteen_rate: Decimal,
+    pub twenty_rate: Decimal,
+    /// Taxable income up to which gains are taxed at 0%
+    pub zero_rate_maximum: ByFilingStatus<Decimal>,
+    /// Taxable income up to which gains are taxed at 15%
+    pub fifteen_rate_maximum: ByFilingStatus<Decimal>,
+    /// Net capital loss deductible against other income (Schedule D line 21)
+    pub loss_limit: ByFilingStatus<Decimal>,
+}
+
+/// Reduction of the SALT cap for high incomes: the cap drops by `rate` of AGI over
+/// `threshold`, but not below `minimum`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaltCapPhaseOut {
+    pub threshold: ByFilingStatus<Decimal>,
+    pub rate: Decimal,
+    pub minimum: ByFilingStatus<Decimal>,
+}
+
+/// Schedule A floors and limits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemizedRules {
+    /// Share of AGI medical expenses must exceed (Schedule A line 3)
+    pub medical_floor: Decimal,
+    /// State and local tax deduction limit (Schedule A line 5e)
+    pub salt_cap: ByFilingStatus<Decimal>,
+    pub salt_cap_phase_out: Option<SaltCapPhaseOut>,
+    /// Acquisition debt on which interest is deductible
+    pub mortgage_debt_limit: ByFilingStatus<Decimal>,
+    /// Limit for debt taken on before December 16, 2017
+    pub grandfathered_mortgage_debt_limit: ByFilingStatus<Decimal>,
+    /// Charitable contribution limits as shares of AGI
+    pub charitable_cash_limit: Decimal,
+    pub charitable_property_limit: Decimal,
+    pub charitable_capital_gain_limit: Decimal,
+}
+
+impl ItemizedRules {
+    /// SALT cap after any high-income reduction.
+    pub fn salt_cap(&self, status: FilingStatus, adjusted_gross_income: Decimal) -> Decimal {
+        let cap = *self.salt_cap.get(status);
+        match &self.salt_cap_phase_out {
+            Some(phase_out) if adjusted_gross_income > *phase_out.threshold.get(status) => {
+                let reduction = round_cents((adjusted_gross_income - phase_out.threshold.get(status)) * phase_out.rate);
+                (cap - reduction).max(*phase_out.minimum.get(status))
+            }
+            _ => cap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionLimits {
+    /// 401(k)/403(b) elective deferrals
+    pub elective_deferral: Decimal,
+    pub elective_deferral_catch_up: Decimal,
+    pub ira: Decimal,
+    pub ira_catch_up: Decimal,
+    pub hsa_self_only: Decimal,
+    pub hsa_family: Decimal,
+    pub hsa_catch_up: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseOuts {
+    /// Traditional IRA deduction when covered by a workplace plan
+    pub ira_deduction_covered: ByFilingStatus<PhaseOut>,
+    pub student_loan_interest: ByFilingStatus<PhaseOut>,
+    /// American Opportunity and Lifetime Learning credits
+    pub education: ByFilingStatus<PhaseOut>,
+}
+
+/// Earned income credit parameters for one number of qualifying children.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarnedIncomeSchedule {
+    pub qualifying_children: u32,
+    pub credit_rate: Decimal,
+    /// Earned income at which the maximum credit is reached
+    pub earned_income_amount: Decimal,
+    pub maximum_credit: Decimal,
+    pub phase_out_rate: Decimal,
+    pub phase_out_start: ByFilingStatus<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarnedIncomeCredit {
+    /// Investment income above this disqualifies the credit
+    pub investment_income_limit: Decimal,
+    /// Ordered by number of qualifying children; the last applies to any larger number
+    pub schedules: Vec<EarnedIncomeSchedule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmericanOpportunityCredit {
+    /// Expenses credited at 100%
+    pub first_tier_expenses: Decimal,
+    /// Further expenses credited at `second_tier_rate`
+    pub second_tier_expenses: Decimal,
+    pub second_tier_rate: Decimal,
+    /// Share of the credit that is refundable (Form 8863 line 8)
+    pub refundable_share: Decimal,
+    /// Years the credit can be claimed for one student
+    pub maximum_years: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifetimeLearningCredit {
+    /// Per-return expense limit
+    pub expense_limit: Decimal,
+    pub rate: Decimal,
+}
+
+/// Credit rate for AGI up to `up_to`; AGI above the last tier gets no credit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateTier {
+    pub up_to: Decimal,
+    pub rate: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaversCredit {
+    /// Contributions counted per person
+    pub contribution_limit: Decimal,
+    pub tiers: ByFilingStatus<Vec<RateTier>>,
+}
+
+impl SaversCredit {
+    pub fn rate(&self, status: FilingStatus, adjusted_gross_income: Decimal) -> Decimal {
+        self.tiers.get(status).iter()
+            .find(|tier| adjusted_gross_income <= tier.up_to)
+            .map(|tier| tier.rate)
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependentCareCredit {
+    pub one_person_expense_limit: Decimal,
+    pub two_or_more_expense_limit: Decimal,
+    pub maximum_rate: Decimal,
+    pub minimum_rate: Decimal,
+    /// AGI above which the rate drops one point per `phase_down_step`
+    pub phase_down_start: Decimal,
+    pub phase_down_step: Decimal,
+}
+
+impl DependentCareCredit {
+    /// Form 2441 line 8: one percentage point off the maximum rate for each step (or part
+    /// of a step) of AGI over the start, down to the minimum rate.
+    pub fn rate(&self, adjusted_gross_income: Decimal) -> Decimal {
+        let excess = (adjusted_gross_income - self.phase_down_start).max(Decimal::ZERO);
+        let steps = (excess / self.phase_down_step).ceil();
+        (self.maximum_rate - steps / Decimal::ONE_HUNDRED).max(self.minimum_rate)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditAmounts {
+    pub child_tax_credit: Decimal,
+    /// Refundable portion of the child tax credit per child
+    pub additional_child_tax_credit_max: Decimal,
+    pub other_dependent_credit: Decimal,
+    pub child_tax_credit_phase_out_start: ByFilingStatus<Decimal>,
+    /// Reduction for each $1,000 (or part) of MAGI over the phase-out start
+    pub child_tax_credit_reduction: Decimal,
+    pub additional_child_tax_credit_earned_income_threshold: Decimal,
+    pub additional_child_tax_credit_rate: Decimal,
+    pub earned_income: EarnedIncomeCredit,
+    pub american_opportunity: AmericanOpportunityCredit,
+    pub lifetime_learning: LifetimeLearningCredit,
+    pub savers: SaversCredit,
+    pub dependent_care: DependentCareCredit,
+}
+
+/// Form 1040-ES and Form 2210 safe harbors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedTaxRules {
+    /// Installment due dates, already moved past weekends and holidays
+    pub due_dates: [NaiveDate; 4],
+    /// Share of the current year's tax that avoids the underpayment penalty
+    pub current_year_rate: Decimal,
+    /// Share of the prior year's tax that avoids it, and the share once prior-year AGI is over the threshold
+    pub prior_year_rate: Decimal,
+    pub high_income_prior_year_rate: Decimal,
+    pub high_income_threshold: ByFilingStatus<Decimal>,
+    /// No penalty when the tax left after withholding is less than this
+    pub minimum_balance_due: Decimal,
+}
+
+impl EstimatedTaxRules {
+    /// Share of the prior year's tax the prior-year safe harbor requires.
+    pub fn prior_year_rate(&self, status: FilingStatus, prior_adjusted_gross_income: Decimal) -> Decimal {
+        match prior_adjusted_gross_income > *self.high_income_threshold.get(status) {
+            true => self.high_income_prior_year_rate,
+            false => self.prior_year_rate,
+        }
+    }
+}
+
+/// Underpayment interest rate from `from` until the next entry, the federal short-term rate plus 3%.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterestRate {
+    pub from: NaiveDate,
+    pub rate: Decimal,
+}
+
+/// Form 2210 and the failure-to-file and failure-to-pay penalties of IRC 6651.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PenaltyRules {
+    /// Form 1040 due date without an extension, already moved past weekends and holidays
+    pub filing_due_date: NaiveDate,
+    /// Per month or part of a month, up to the maximum
+    pub failure_to_file_rate: Decimal,
+    pub failure_to_file_maximum: Decimal,
+    pub failure_to_pay_rate: Decimal,
+    pub failure_to_pay_maximum: Decimal,
+    /// A return filed more than `minimum_penalty_days` late owes at least the smaller of this and the unpaid tax
+    pub minimum_failure_to_file_penalty: Decimal,
+    pub minimum_penalty_days: i64,
+    /// Quarterly underpayment rates in date order; the last one continues until the table is extended
+    pub interest_rates: Vec<InterestRate>,
+}
+
+impl PenaltyRules {
+    /// Underpayment rate in effect on `date`.
+    pub fn interest_rate(&self, date: NaiveDate) -> Decimal {
+        self.interest_rates.iter()
+            .take_while(|rate| rate.from <= date)
+            .last()
+            .or(self.interest_rates.first())
+            .map(|rate| rate.rate)
+            .unwrap_or_default()
+    }
+}
+
+/// Forms 8995 and 8995-A. Above `threshold` the deduction for each business is limited by the
+/// W-2 wages it paid and its qualified property, and specified service businesses lose it,
+/// both phased in over `phase_in_range`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualifiedBusinessIncomeRules {
+    pub rate: Decimal,
+    pub threshold: ByFilingStatus<Decimal>,
+    pub phase_in_range: ByFilingStatus<Decimal>,
+    /// Share of W-2 wages that is the limit on its own
+    pub wage_rate: Decimal,
+    /// Share of W-2 wages and of the unadjusted basis of qualified property that together are the other limit
+    pub wage_and_property_wage_rate: Decimal,
+    pub property_rate: Decimal,
+}
+
+/// Form 6251 amounts: the exemption, its phase-out and the two AMT rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternativeMinimumTaxRules {
+    pub exemption: ByFilingStatus<Decimal>,
+    /// AMTI above which the exemption drops by `exemption_phase_out_rate` of the excess
+    pub exemption_phase_out_start: ByFilingStatus<Decimal>,
+    pub exemption_phase_out_rate: Decimal,
+    /// Income taxed at `lower_rate`; the rest is taxed at `upper_rate`
+    pub rate_threshold: ByFilingStatus<Decimal>,
+    pub lower_rate: Decimal,
+    pub upper_rate: Decimal,
+    /// Share of AMTI, figured without it, that the alternative tax NOL deduction may offset
+    pub net_operating_loss_limit: Decimal,
+}
+
+/// Federal and state parameters for one tax year, loaded from `src/tax_engine/tables/<year>.json`
+/// or from a file of the same shape in `TAX_RULES_DIR`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxRules {
+    pub year: i32,
+    /// Revision of the table, bumped whenever published figures are corrected
+    pub version: String,
+    /// Where the figures come from (revenue procedure, form instructions)
+    pub source: String,
+    pub standard_deduction: ByFilingStatus<Decimal>,
+    /// Extra standard deduction per 65+/blind box checked, for married and unmarried filers
+    pub additional_standard_deduction_married: Decimal,
+    pub additional_standard_deduction_unmarried: Decimal,
+    pub brackets: ByFilingStatus<Vec<Bracket>>,
+    pub social_security_wage_base: Decimal,
+    pub payroll: PayrollRates,
+    pub net_investment_income_tax: NetInvestmentIncomeTaxRules,
+    pub capital_gains: CapitalGainRates,
+    pub itemized: ItemizedRules,
+    pub contribution_limits: ContributionLimits,
+    pub phase_outs: PhaseOuts,
+    pub credits: CreditAmounts,
+    pub student_loan_interest_limit: Decimal,
+    pub educator_expense_limit: Decimal,
+    /// Share of taxable income, figured without it, that a net operating loss deduction may offset
+    pub net_operating_loss_limit: Decimal,
+    pub qualified_business_income: QualifiedBusinessIncomeRules,
+    pub alternative_minimum_tax: AlternativeMinimumTaxRules,
+    /// Gross income limit for a qualifying relative
+    pub dependent_gross_income_limit: Decimal,
+    pub estimated_tax: EstimatedTaxRules,
+    pub penalties: PenaltyRules,
+    pub states: StateRules,
+}
+
+/// Taxable income below this is taxed through the IRS tax table rather than the worksheet.
+pub(crate) const TAX_TABLE_LIMIT: i64 = 100_000;
+
+/// Tables compiled into the binary. Files in `TAX_RULES_DIR` replace or extend these.
+const EMBEDDED_TABLES: [(&str, &str); 3] = [
+    ("2023.json", include_str!("tables/2023.json")),
+    ("2024.json", include_str!("tables/2024.json")),
+    ("2025.json", include_str!("tables/2025.json")),
+];
+
+static RULES: OnceLock<BTreeMap<i32, TaxRules>> = OnceLock::new();
+
+impl TaxRules {
+    /// Loads and validates every rule table, failing on the first invalid one.
+    /// Called once at startup; returns the number of supported years.
+    pub fn load(dir: Option<&str>) -> Result<usize, String> {
+        let tables = Self::read_tables(dir)?;
+        let count = tables.len();
+        RULES.set(tables).map_err(|_| "Tax rule tables are already loaded".to_string())?;
+
+        Ok(count)
+    }
+
+    pub fn for_year(year: i32) -> Result<&'static TaxRules, ErrorBag> {
+        Self::tables().get(&year).ok_or_else(|| ErrorBag::Validation {
+            field: "year".into(),
+            message: format!("Tax year {year} is not supported"),
+        })
+    }
+
+    /// Every year with a rule table, oldest first.
+    pub fn supported() -> impl Iterator<Item = &'static TaxRules> {
+        Self::tables().values()
+    }
+
+    /// Falls back to the embedded tables when `load` was never called (e.g. in tests).
+    fn tables() -> &'static BTreeMap<i32, TaxRules> {
+        RULES.get_or_init(|| Self::read_tables(None).expect("embedded tax rule tables are valid"))
+    }
+
+    fn read_tables(dir: Option<&str>) -> Result<BTreeMap<i32, TaxRules>, String> {
+        let mut sources: Vec<(String, String)> = EMBEDDED_TABLES.iter()
+            .map(|(name, contents)| (format!("embedded {name}"), contents.to_string()))
+            .collect();
+
+        if let Some(dir) = dir {
+            let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read tax rules directory {dir}: {e}"))?;
+            let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                let contents = fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+                sources.push((path.display().to_string(), contents));
+            }
+        }
+
+        let mut tables = BTreeMap::new();
+        for (name, contents) in sources {
+            let rules: TaxRules = serde_json::from_str(&contents).map_err(|e| format!("{name}: {e}"))?;
+            rules.validate().map_err(|e| format!("{name}: {e}"))?;
+            tables.insert(rules.year, rules);
+        }
+
+        Ok(tables)
+    }
+
+    /// Structural checks that catch typos in a table before it can produce a wrong return.
+    fn validate(&self) -> Result<(), String> {
+        for status in FilingStatus::ALL {
+            validate_brackets(&status.to_string(), self.brackets.get(status))?;
+
+            if self.standard_deduction.get(status).is_sign_negative() {
+                return Err(format!("{status} standard deduction cannot be negative"));
+            }
+            if self.payroll.additional_medicare_threshold.get(status).is_sign_negative() {
+                return Err(format!("{status} Additional Medicare Tax threshold cannot be negative"));
+            }
+            if self.net_investment_income_tax.threshold.get(status).is_sign_negative() {
+                return Err(format!("{status} net investment income tax threshold cannot be negative"));
+            }
+            let gains = &self.capital_gains;
+            if gains.zero_rate_maximum.get(status).is_sign_negative() || gains.fifteen_rate_maximum.get(status) < gains.zero_rate_maximum.get(status) {
+                return Err(format!("{status} capital gain thresholds must satisfy 0 <= zero rate <= fifteen rate"));
+            }
+            if gains.loss_limit.get(status).is_sign_negative() {
+                return Err(format!("{status} capital loss limit cannot be negative"));
+            }
+            let itemized = &self.itemized;
+            if itemized.salt_cap.get(status).is_sign_negative() {
+                return Err(format!("{status} SALT cap cannot be negative"));
+            }
+            if let Some(phase_out) = &itemized.salt_cap_phase_out
+                && (phase_out.threshold.get(status).is_sign_negative() || phase_out.minimum.get(status) > itemized.salt_cap.get(status)) {
+                return Err(format!("{status} SALT cap phase-out must satisfy 0 <= threshold and minimum <= cap"));
+            }
+            if itemized.mortgage_debt_limit.get(status).is_sign_negative()
+                || itemized.grandfathered_mortgage_debt_limit.get(status) < itemized.mortgage_debt_limit.get(status) {
+                return Err(format!("{status} mortgage debt limits must satisfy 0 <= limit <= grandfathered limit"));
+            }
+            if self.estimated_tax.high_income_threshold.get(status).is_sign_negative() {
+                return Err(format!("{status} estimated tax threshold cannot be negative"));
+            }
+            let qbi = &self.qualified_business_income;
+            if qbi.threshold.get(status).is_sign_negative() || *qbi.phase_in_range.get(status) <= Decimal::ZERO {
+                return Err(format!("{status} QBI threshold cannot be negative and its phase-in range must be positive"));
+            }
+            let amt = &self.alternative_minimum_tax;
+            if amt.exemption.get(status).is_sign_negative()
+                || amt.exemption_phase_out_start.get(status).is_sign_negative()
+                || amt.rate_threshold.get(status).is_sign_negative() {
+                return Err(format!("{status} AMT exemption, phase-out and rate threshold cannot be negative"));
+            }
+            if self.credits.child_tax_credit_phase_out_start.get(status).is_sign_negative() {
+                return Err(format!("{status} child tax credit phase-out cannot be negative"));
+            }
+
+            let credits = &self.credits;
+            if credits.earned_income.schedules.iter().any(|schedule| schedule.phase_out_start.get(status).is_sign_negative()) {
+                return Err(format!("{status} earned income credit phase-out cannot be negative"));
+            }
+            let tiers = credits.savers.tiers.get(status);
+            if tiers.windows(2).any(|pair| pair[1].up_to <= pair[0].up_to || pair[1].rate > pair[0].rate)
+                || tiers.iter().any(|tier| tier.rate.is_sign_negative() || tier.rate >= Decimal::ONE) {
+                return Err(format!("{status} saver's credit tiers must have increasing AGI and decreasing rates between 0 and 1"));
+            }
+
+            let phase_outs = [
+                ("ira_deduction_covered", self.phase_outs.ira_deduction_covered.get(status)),
+                ("student_loan_interest", self.phase_outs.student_loan_interest.get(status)),
+                ("education", self.phase_outs.education.get(status)),
+            ];
+            for (name, phase_out) in phase_outs {
+                if phase_out.start.is_sign_negative() || phase_out.end < phase_out.start {
+                    return Err(format!("{status} {name} phase-out must satisfy 0 <= start <= end"));
+                }
+            }
+        }
+
+        let payroll = &self.payroll;
+        let rates = [
+            payroll.social_security_rate,
+            payroll.medicare_rate,
+            payroll.additional_medicare_rate,
+            payroll.self_employment_earnings_factor,
+            self.net_investment_income_tax.rate,
+            self.capital_gains.fifteen_rate,
+            self.capital_gains.twenty_rate,
+            self.itemized.medical_floor,
+            self.itemized.charitable_cash_limit,
+            self.itemized.charitable_property_limit,
+            self.itemized.charitable_capital_gain_limit,
+            self.credits.additional_child_tax_credit_rate,
+            self.credits.american_opportunity.second_tier_rate,
+            self.credits.american_opportunity.refundable_share,
+            self.credits.lifetime_learning.rate,
+            self.credits.dependent_care.maximum_rate,
+            self.credits.dependent_care.minimum_rate,
+            self.estimated_tax.current_year_rate,
+            self.net_operating_loss_limit,
+            self.qualified_business_income.rate,
+            self.qualified_business_income.wage_rate,
+            self.qualified_business_income.wage_and_property_wage_rate,
+            self.qualified_business_income.property_rate,
+            self.penalties.failure_to_file_rate,
+            self.penalties.failure_to_file_maximum,
+            self.penalties.failure_to_pay_rate,
+            self.penalties.failure_to_pay_maximum,
+            self.alternative_minimum_tax.exemption_phase_out_rate,
+            self.alternative_minimum_tax.lower_rate,
+            self.alternative_minimum_tax.upper_rate,
+            self.alternative_minimum_tax.net_operating_loss_limit,
+        ];
+        if rates.iter().any(|rate| rate.is_sign_negative() || *rate >= Decimal::ONE) {
+            return Err("Rates must be between 0 and 1".into());
+        }
+
+        let limits = &self.contribution_limits;
+        let amounts = [
+            self.additional_standard_deduction_married,
+            self.additional_standard_deduction_unmarried,
+            self.social_security_wage_base,
+            payroll.additional_medicare_withholding_threshold,
+            payroll.self_employment_minimum,
+            limits.elective_deferral,
+            limits.elective_deferral_catch_up,
+            limits.ira,
+            limits.ira_catch_up,
+            limits.hsa_self_only,
+            limits.hsa_family,
+            limits.hsa_catch_up,
+            self.credits.child_tax_credit,
+            self.credits.additional_child_tax_credit_max,
+            self.credits.other_dependent_credit,
+            self.credits.child_tax_credit_reduction,
+            self.credits.additional_child_tax_credit_earned_income_threshold,
+            self.credits.earned_income.investment_income_limit,
+            self.credits.american_opportunity.first_tier_expenses,
+            self.credits.american_opportunity.second_tier_expenses,
+            self.credits.lifetime_learning.expense_limit,
+            self.credits.savers.contribution_limit,
+            self.credits.dependent_care.one_person_expense_limit,
+            self.credits.dependent_care.two_or_more_expense_limit,
+            self.credits.dependent_care.phase_down_start,
+            self.student_loan_interest_limit,
+            self.educator_expense_limit,
+            self.dependent_gross_income_limit,
+            self.estimated_tax.minimum_balance_due,
+            self.penalties.minimum_failure_to_file_penalty,
+        ];
+        if amounts.iter().any(|amount| amount.is_sign_negative()) {
+            return Err("Amounts and limits cannot be negative".into());
+        }
+        let earned_income = &self.credits.earned_income;
+        let schedules_ordered = earned_income.schedules.iter().enumerate()
+            .all(|(children, schedule)| schedule.qualifying_children == children as u32);
+        if earned_income.schedules.is_empty() || !schedules_ordered {
+            return Err("Earned income credit schedules must cover 0, 1, 2, ... qualifying children in order".into());
+        }
+        let mut eitc_rates = earned_income.schedules.iter().flat_map(|schedule| [schedule.credit_rate, schedule.phase_out_rate]);
+        if eitc_rates.any(|rate| rate.is_sign_negative() || rate >= Decimal::ONE) {
+            return Err("Rates must be between 0 and 1".into());
+        }
+        if self.credits.dependent_care.minimum_rate > self.credits.dependent_care.maximum_rate || self.credits.dependent_care.phase_down_step <= Decimal::ZERO {
+            return Err("Dependent care credit rates must satisfy minimum <= maximum with a positive step".into());
+        }
+        if let Some(phase_out) = &self.itemized.salt_cap_phase_out
+            && (phase_out.rate.is_sign_negative() || phase_out.rate >= Decimal::ONE) {
+            return Err("Rates must be between 0 and 1".into());
+        }
+        let estimated = &self.estimated_tax;
+        if estimated.prior_year_rate < Decimal::ONE || estimated.high_income_prior_year_rate < estimated.prior_year_rate {
+            return Err("Prior-year estimated tax rates must satisfy 1 <= rate <= high-income rate".into());
+        }
+        if estimated.due_dates.windows(2).any(|pair| pair[1] <= pair[0]) || estimated.due_dates[0].year() != self.year {
+            return Err("Estimated tax due dates must be in order, starting in the tax year".into());
+        }
+        let penalties = &self.penalties;
+        if penalties.filing_due_date <= estimated.due_dates[3] || penalties.minimum_penalty_days < 0 {
+            return Err("The filing due date must follow the last estimated tax due date".into());
+        }
+        if penalties.interest_rates.first().is_none_or(|rate| rate.from.year() > self.year)
+            || penalties.interest_rates.windows(2).any(|pair| pair[1].from <= pair[0].from)
+            || penalties.interest_rates.iter().any(|rate| rate.rate.is_sign_negative() || rate.rate >= Decimal::ONE) {
+            return Err("Interest rates must be in date order from the start of the tax year, between 0 and 1".into());
+        }
+        if self.alternative_minimum_tax.upper_rate < self.alternative_minimum_tax.lower_rate {
+            return Err("AMT rates must satisfy lower rate <= upper rate".into());
+        }
+        if self.credits.additional_child_tax_credit_max > self.credits.child_tax_credit {
+            return Err("Refundable child tax credit cannot exceed the credit".into());
+        }
+
+        self.states.validate()
+    }
+
+    /// Standard deduction including the additional amount for each 65+/blind box checked.
+    pub fn standard_deduction(&self, status: FilingStatus, additional_boxes: u32) -> Decimal {
+        let additional = match status {
+            FilingStatus::Single | FilingStatus::HeadOfHousehold => self.additional_standard_deduction_unmarried,
+            _ => self.additional_standard_deduction_married,
+        };
+
+        self.standard_deduction.get(status) + additional * Decimal::from(additional_boxes)
+    }
+
+    /// Form 1040 line 16 tax on ordinary income.
+    ///
+    /// Below $100,000 the IRS tax table taxes the midpoint of the row the income falls in
+    /// ($25 rows up to $3,000, $50 rows after) and rounds to whole dollars. At $100,000 and
+    /// above the tax computation worksheet applies the brackets to the exact amount.
+    pub fn income_tax(&self, status: FilingStatus, taxable_income: Decimal) -> Decimal {
+        if taxable_income <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        if taxable_income >= Decimal::from(TAX_TABLE_LIMIT) {
+            return round_cents(self.bracket_tax(status, taxable_income));
+        }
+
+        round_dollars(self.bracket_tax(status, tax_table_midpoint(taxable_income)))
+    }
+
+    /// Exact bracket tax with no table rounding.
+    pub fn bracket_tax(&self, status: FilingStatus, income: Decimal) -> Decimal {
+        bracket_tax(self.brackets.get(status), income)
+    }
+
+    /// Rate applied to the next dollar of ordinary income.
+    pub fn marginal_rate(&self, status: FilingStatus, taxable_income: Decimal) -> Decimal {
+        self.brackets.get(status).iter()
+            .take_while(|bracket| taxable_income >= bracket.over)
+            .last()
+            .map(|bracket| bracket.rate)
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Midpoint of the tax table row containing `income`.
+///
+/// The first rows are $0-$5, $5-$15 and $15-$25, then $25 wide up to $3,000 and $50 wide after.
+fn tax_table_midpoint(income: Decimal) -> Decimal {
+    let income = income.floor();
+    let (low, width) = if income < Decimal::from(5) {
+        return Decimal::ZERO;
+    } else if income < Decimal::from(15) {
+        (Decimal::from(5), Decimal::from(10))
+    } else if income < Decimal::from(25) {
+        (Decimal::from(15), Decimal::from(10))
+    } else if income < Decimal::from(3_000) {
+        let width = Decimal::from(25);
+        ((income / width).floor() * width, width)
+    } else {
+        let width = Decimal::from(50);
+        ((income / width).floor() * width, width)
+    };
+
+    low + width / Decimal::TWO
+}
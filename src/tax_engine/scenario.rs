@@ -0,0 +1,187 @@
+//! Side-by-side comparison of a what-if scenario against the return it was forked from.
+
+use std::collections::BTreeSet;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::tax_engine::state::StateReturn;
+use crate::tax_engine::TaxCalculation;
+
+/// One line of the return in both calculations.
+#[derive(Debug, Clone, Serialize)]
+pub struct LineDelta {
+    /// `TaxCalculation` field the amounts come from, e.g. `taxable_income`
+    pub line: &'static str,
+    pub base: Decimal,
+    pub scenario: Decimal,
+    /// Scenario minus base
+    pub change: Decimal,
+}
+
+impl LineDelta {
+    fn new(line: &'static str, base: Decimal, scenario: Decimal) -> LineDelta {
+        LineDelta { line, base, scenario, change: scenario - base }
+    }
+}
+
+/// A state return in both calculations. A state only one side files counts as zero on the other.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateDelta {
+    pub state: String,
+    pub lines: Vec<LineDelta>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioComparison {
+    pub base: TaxCalculation,
+    pub scenario: TaxCalculation,
+    /// The spouse's return when the scenario files a joint return separately. Its amounts are
+    /// added to the scenario's in the deltas and the net benefit.
+    pub spouse: Option<TaxCalculation>,
+    /// Federal lines, in Form 1040 order
+    pub deltas: Vec<LineDelta>,
+    pub states: Vec<StateDelta>,
+    /// Change in federal and state refunds less balances due. Positive when the scenario
+    /// leaves the taxpayer better off.
+    pub net_benefit: Decimal,
+}
+
+impl ScenarioComparison {
+    pub fn new(base: TaxCalculation, scenario: TaxCalculation, spouse: Option<TaxCalculation>) -> ScenarioComparison {
+        let lines = |calculation: &TaxCalculation| [
+            ("total_income", calculation.income.total),
+            ("adjustments", calculation.adjustments),
+            ("adjusted_gross_income", calculation.adjusted_gross_income),
+            ("deduction", calculation.deduction.amount),
+            ("qualified_business_income_deduction", calculation.qualified_business_income_deduction),
+            ("taxable_income", calculation.taxable_income),
+            ("tax", calculation.tax),
+            ("alternative_minimum_tax", calculation.alternative_minimum_tax.tax),
+            ("nonrefundable_credits", calculation.nonrefundable_credits),
+            ("other_taxes", calculation.other_taxes),
+            ("total_tax", calculation.total_tax),
+            ("total_payments", calculation.total_payments),
+            ("refund", calculation.refund),
+            ("amount_owed", calculation.amount_owed),
+        ];
+        let totals = |returns: &[&TaxCalculation]| {
+            let mut totals = lines(returns[0]);
+            for calculation in &returns[1..] {
+                totals.iter_mut().zip(lines(calculation)).for_each(|(total, (_, amount))| total.1 += amount);
+            }
+            totals
+        };
+        let scenario_returns: Vec<&TaxCalculation> = std::iter::once(&scenario).chain(spouse.as_ref()).collect();
+        let deltas = lines(&base).into_iter().zip(totals(&scenario_returns))
+            .map(|((line, base), (_, scenario))| LineDelta::new(line, base, scenario))
+            .collect();
+
+        let codes: BTreeSet<&str> = scenario_returns.iter().chain([&&base])
+            .flat_map(|calculation| &calculation.states)
+            .map(|state| state.state.as_str())
+            .collect();
+        let state_lines = |returns: &[&TaxCalculation], code: &str| {
+            let states: Vec<&StateReturn> = returns.iter().flat_map(|calculation| &calculation.states).filter(|state| state.state == code).collect();
+            let amount = |field: fn(&StateReturn) -> Decimal| states.iter().map(|state| field(state)).sum::<Decimal>();
+            [
+                ("adjusted_gross_income", amount(|state| state.adjusted_gross_income)),
+                ("taxable_income", amount(|state| state.taxable_income)),
+                ("total_tax", amount(|state| state.total_tax)),
+                ("refund", amount(|state| state.refund)),
+                ("amount_owed", amount(|state| state.amount_owed)),
+            ]
+        };
+        let states = codes.into_iter()
+            .map(|code| StateDelta {
+                state: code.to_string(),
+                lines: state_lines(&[&base], code).into_iter().zip(state_lines(&scenario_returns, code))
+                    .map(|((line, base), (_, scenario))| LineDelta::new(line, base, scenario))
+                    .collect(),
+            })
+            .collect();
+
+        let net_benefit = scenario_returns.iter().map(|calculation| Self::net_refund(calculation)).sum::<Decimal>() - Self::net_refund(&base);
+
+        ScenarioComparison { base, scenario, spouse, deltas, states, net_benefit }
+    }
+
+    /// Federal and state refunds less balances due.
+    fn net_refund(calculation: &TaxCalculation) -> Decimal {
+        calculation.refund - calculation.amount_owed
+            + calculation.states.iter().map(|state| state.refund - state.amount_owed).sum::<Decimal>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde_json::json;
+    use super::ScenarioComparison;
+    use crate::models::filing_profile_model::FilingStatus;
+    use crate::tax_engine::filing_status::FilingStatusOptimizer;
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::TaxEngine;
+
+    fn single(w2_state: &str) -> TaxInput {
+        serde_json::from_value(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 100000, "federal_tax_withheld": 15000, "state": w2_state, "state_tax_withheld": 5000 }]
+        })).unwrap()
+    }
+
+    #[test]
+    fn compares_each_line_and_adds_state_refunds_to_the_net_benefit() {
+        let base = single("IL");
+        let mut scenario = base.clone();
+        scenario.adjustments.ira_deduction = 7000.into();
+        let comparison = ScenarioComparison::new(TaxEngine::calculate(&base).unwrap(), TaxEngine::calculate(&scenario).unwrap(), None);
+        let change = |line| comparison.deltas.iter().find(|delta| delta.line == line).unwrap().change;
+
+        assert_eq!(change("adjusted_gross_income"), Decimal::from(-7000));
+        assert_eq!(change("tax"), Decimal::from(-1540));
+        let illinois = comparison.states.iter().find(|state| state.state == "IL").unwrap();
+        assert_eq!(illinois.lines.iter().find(|line| line.line == "total_tax").unwrap().change, "-346.50".parse::<Decimal>().unwrap());
+        assert_eq!(comparison.net_benefit, "1886.50".parse::<Decimal>().unwrap());
+    }
+
+    #[test]
+    fn counts_a_state_only_one_side_files_as_zero_on_the_other() {
+        let comparison = ScenarioComparison::new(TaxEngine::calculate(&single("IL")).unwrap(), TaxEngine::calculate(&single("TX")).unwrap(), None);
+        let line = |code: &str, line: &str| {
+            let state = comparison.states.iter().find(|state| state.state == code).unwrap();
+            state.lines.iter().find(|delta| delta.line == line).unwrap().clone()
+        };
+
+        assert_eq!(comparison.states.len(), 2);
+        assert!(line("IL", "total_tax").scenario.is_zero());
+        assert!(line("TX", "total_tax").base.is_zero());
+        assert_eq!(comparison.net_benefit, line("IL", "total_tax").base);
+    }
+
+    #[test]
+    fn filing_separately_matches_the_filing_status_optimizer() {
+        let input: TaxInput = serde_json::from_value(json!({
+            "year": 2024,
+            "filing_status": "married_filing_jointly",
+            "w2s": [
+                { "employer": "Acme", "owner": "taxpayer", "wages": 90000, "federal_tax_withheld": 9000 },
+                { "employer": "Globex", "owner": "spouse", "wages": 30000, "federal_tax_withheld": 2500 }
+            ],
+            "form_1099s": [{ "payer": "Bank", "owner": "spouse", "form": "INT", "income": 4000 }],
+            "capital_lots": [{ "description": "Fund", "owner": "spouse", "date_acquired": "2020-01-02", "date_sold": "2024-06-03", "proceeds": 9000, "cost_basis": 1000 }],
+            "capital_loss_carryover": { "short_term": 0, "long_term": 2000 },
+            "itemized": { "mortgage_interest": 18000, "real_estate_taxes": 8000, "charitable_cash": 3000 }
+        })).unwrap();
+
+        let comparison = FilingStatusOptimizer::compare(&input).unwrap();
+        let option = |status| comparison.options.iter().find(|option| option.filing_status == status).unwrap();
+        let (taxpayer, spouse, _) = FilingStatusOptimizer::separate_returns(&input).unwrap();
+        let scenario = ScenarioComparison::new(TaxEngine::calculate(&input).unwrap(), taxpayer, Some(spouse));
+
+        let separate = option(FilingStatus::MarriedFilingSeparately);
+        assert_eq!(scenario.net_benefit, separate.net_refund - option(FilingStatus::MarriedFilingJointly).net_refund);
+        let total_tax = scenario.deltas.iter().find(|delta| delta.line == "total_tax").unwrap();
+        assert_eq!(total_tax.scenario, separate.returns.iter().map(|r| r.total_tax).sum());
+        assert!(scenario.spouse.as_ref().is_some_and(|spouse| spouse.income.taxable_interest == 4000.into()));
+    }
+}
@@ -0,0 +1,203 @@
+//! Schedule C profit or loss and Schedule SE self-employment tax.
+//!
+//! 1099-NEC and 1099-K receipts are routed to the business named by `Form1099::business_id`.
+//! When there is only one business they go to it; otherwise unassigned receipts are reported
+//! on a separate "unassigned" Schedule C so they are never silently dropped.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::models::business_model::ExpenseCategory;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::input::{Business, Form1099, Form1099Kind, TaxInput};
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::TaxRules;
+
+const UNASSIGNED_BUSINESS: &str = "Unassigned 1099 income";
+
+/// One Schedule C part II line.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpenseLine {
+    pub category: ExpenseCategory,
+    pub line: &'static str,
+    pub amount: Decimal,
+    pub deductible: Decimal,
+}
+
+/// Schedule C for one business.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleC {
+    pub business_id: Option<Uuid>,
+    pub name: String,
+    pub owner: Owner,
+    /// Part of line 1 reported on 1099-NEC/1099-K
+    pub form_1099_receipts: Decimal,
+    /// Line 1
+    pub gross_receipts: Decimal,
+    /// Line 2
+    pub returns_and_allowances: Decimal,
+    /// Line 4
+    pub cost_of_goods_sold: Decimal,
+    /// Line 5
+    pub gross_profit: Decimal,
+    /// Line 6
+    pub other_income: Decimal,
+    /// Line 7
+    pub gross_income: Decimal,
+    pub expenses: Vec<ExpenseLine>,
+    /// Line 28
+    pub total_expenses: Decimal,
+    /// Line 29
+    pub tentative_profit: Decimal,
+    /// Line 30, limited to the tentative profit
+    pub business_use_of_home: Decimal,
+    /// Line 31
+    pub net_profit: Decimal,
+}
+
+/// Schedule SE for one person.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleSe {
+    pub owner: Owner,
+    /// Line 3
+    pub net_profit: Decimal,
+    /// Line 6
+    pub net_earnings: Decimal,
+    /// Line 8d, wages already taxed for Social Security
+    pub social_security_wages: Decimal,
+    /// Line 10
+    pub social_security_tax: Decimal,
+    /// Line 11
+    pub medicare_tax: Decimal,
+    /// Line 12, to Schedule 2 line 4
+    pub tax: Decimal,
+    /// Line 13, to Schedule 1 line 15
+    pub deduction: Decimal,
+}
+
+impl ScheduleC {
+    /// Builds a Schedule C per business, plus one for receipts no business claims.
+    pub fn for_input(input: &TaxInput) -> (Vec<ScheduleC>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut receipts = vec![Decimal::ZERO; input.businesses.len()];
+        let mut unassigned = Decimal::ZERO;
+
+        let forms = input.form_1099s.iter().filter(|form| matches!(form.kind(), Form1099Kind::Nec | Form1099Kind::K));
+        for form in forms {
+            match Self::business_index(input, form) {
+                Some(index) => receipts[index] += form.income,
+                None => {
+                    warnings.push(format!("1099 from {} is not assigned to a business; reported on its own Schedule C", form.payer));
+                    unassigned += form.income;
+                }
+            }
+        }
+
+        let mut schedules: Vec<ScheduleC> = input.businesses.iter().zip(receipts)
+            .map(|(business, receipts)| Self::compute(business, receipts))
+            .collect();
+        if !unassigned.is_zero() {
+            let business = Business { name: UNASSIGNED_BUSINESS.into(), ..Business::default() };
+            schedules.push(Self::compute(&business, unassigned));
+        }
+
+        (schedules, warnings)
+    }
+
+    /// Position in `input.businesses` of the business a 1099-NEC or 1099-K is counted on. A form
+    /// without a business goes to the only business, if there is just one.
+    pub(crate) fn business_index(input: &TaxInput, form: &Form1099) -> Option<usize> {
+        match form.business_id {
+            Some(id) => input.businesses.iter().position(|business| business.id == Some(id)),
+            None if input.businesses.len() == 1 => Some(0),
+            None => None,
+        }
+    }
+
+    fn compute(business: &Business, form_1099_receipts: Decimal) -> ScheduleC {
+        let gross_receipts = form_1099_receipts + business.other_receipts;
+        let gross_profit = gross_receipts - business.returns_and_allowances - business.cost_of_goods_sold;
+        let gross_income = gross_profit + business.other_income;
+
+        let (home, expenses): (Vec<_>, Vec<_>) = business.expenses.iter()
+            .partition(|expense| expense.category == ExpenseCategory::BusinessUseOfHome);
+        let expenses: Vec<ExpenseLine> = expenses.into_iter()
+            .map(|expense| ExpenseLine {
+                category: expense.category,
+                line: expense.category.line(),
+                amount: expense.amount,
+                deductible: round_cents(expense.amount * expense.category.deductible_share()),
+            })
+            .collect();
+
+        let total_expenses = expenses.iter().map(|expense| expense.deductible).sum::<Decimal>();
+        let tentative_profit = gross_income - total_expenses;
+        let business_use_of_home = home.iter().map(|expense| expense.amount).sum::<Decimal>()
+            .min(tentative_profit.max(Decimal::ZERO));
+
+        ScheduleC {
+            business_id: business.id,
+            name: business.name.clone(),
+            owner: business.owner,
+            form_1099_receipts,
+            gross_receipts,
+            returns_and_allowances: business.returns_and_allowances,
+            cost_of_goods_sold: business.cost_of_goods_sold,
+            gross_profit,
+            other_income: business.other_income,
+            gross_income,
+            expenses,
+            total_expenses,
+            tentative_profit,
+            business_use_of_home,
+            net_profit: tentative_profit - business_use_of_home,
+        }
+    }
+}
+
+impl ScheduleSe {
+    /// One Schedule SE per person with self-employment income. Only a joint return has a
+    /// spouse; otherwise every business is the taxpayer's.
+    pub fn for_input(input: &TaxInput, schedules: &[ScheduleC], rules: &TaxRules) -> Vec<ScheduleSe> {
+        let joint = input.filing_status == FilingStatus::MarriedFilingJointly;
+        let owner_of = |owner: Owner| if joint { owner } else { Owner::Taxpayer };
+
+        Owner::ALL.into_iter()
+            .filter(|owner| schedules.iter().any(|schedule| owner_of(schedule.owner) == *owner))
+            .filter_map(|owner| {
+                let net_profit = schedules.iter().filter(|s| owner_of(s.owner) == owner).map(|s| s.net_profit).sum();
+                let social_security_wages = input.w2s.iter()
+                    .filter(|w2| owner_of(w2.owner) == owner)
+                    .map(|w2| w2.social_security_wages.unwrap_or(w2.wages))
+                    .sum();
+                Self::compute(owner, net_profit, social_security_wages, rules)
+            })
+            .collect()
+    }
+
+    /// Self-employment tax is twice the employee FICA rates. Wages already taxed for
+    /// Social Security use up the wage base first. Returns `None` below the $400 floor.
+    fn compute(owner: Owner, net_profit: Decimal, social_security_wages: Decimal, rules: &TaxRules) -> Option<ScheduleSe> {
+        let payroll = &rules.payroll;
+        let net_earnings = round_cents(net_profit * payroll.self_employment_earnings_factor);
+        if net_earnings < payroll.self_employment_minimum {
+            return None;
+        }
+
+        let remaining_base = (rules.social_security_wage_base - social_security_wages).max(Decimal::ZERO);
+        let social_security_tax = round_cents(net_earnings.min(remaining_base) * payroll.social_security_rate * Decimal::TWO);
+        let medicare_tax = round_cents(net_earnings * payroll.medicare_rate * Decimal::TWO);
+        let tax = social_security_tax + medicare_tax;
+
+        Some(ScheduleSe {
+            owner,
+            net_profit,
+            net_earnings,
+            social_security_wages,
+            social_security_tax,
+            medicare_tax,
+            tax,
+            deduction: round_cents(tax / Decimal::TWO),
+        })
+    }
+}
@@ -0,0 +1,102 @@
+//! California Form 540.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::{bracket_tax, validate_brackets, Bracket, ByFilingStatus};
+use crate::tax_engine::state::{StateContext, StateRules, StateTax, StateTaxCalculator};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaliforniaRules {
+    pub standard_deduction: ByFilingStatus<Decimal>,
+    pub brackets: ByFilingStatus<Vec<Bracket>>,
+    /// Personal, senior and blind exemption credit per box checked (Form 540 lines 7-9)
+    pub personal_exemption_credit: Decimal,
+    /// Form 540 line 10, per dependent
+    pub dependent_exemption_credit: Decimal,
+    /// Federal AGI above which every exemption credit is reduced
+    pub exemption_phase_out_start: ByFilingStatus<Decimal>,
+    /// Each credit loses `exemption_phase_out_reduction` per step (or part of a step) over the start
+    pub exemption_phase_out_step: ByFilingStatus<Decimal>,
+    pub exemption_phase_out_reduction: Decimal,
+    /// Form 540 line 62: `mental_health_services_rate` of taxable income over the threshold
+    pub mental_health_services_threshold: Decimal,
+    pub mental_health_services_rate: Decimal,
+}
+
+impl CaliforniaRules {
+    pub fn validate(&self) -> Result<(), String> {
+        for status in FilingStatus::ALL {
+            validate_brackets(&format!("CA {status}"), self.brackets.get(status))?;
+            if self.standard_deduction.get(status).is_sign_negative() || self.exemption_phase_out_start.get(status).is_sign_negative() {
+                return Err(format!("CA {status} amounts cannot be negative"));
+            }
+            if *self.exemption_phase_out_step.get(status) <= Decimal::ZERO {
+                return Err(format!("CA {status} exemption phase-out step must be positive"));
+            }
+        }
+        if self.mental_health_services_rate.is_sign_negative() || self.mental_health_services_rate >= Decimal::ONE {
+            return Err("CA rates must be between 0 and 1".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// California starts from federal AGI and adds back the HSA deduction, which it does not allow.
+/// Itemized deductions drop state income taxes and the SALT cap. Exemption credits are taken
+/// against the tax and reduced above the federal AGI threshold. Part-year and nonresident
+/// returns (Form 540NR) prorate the taxable income, tax and credits by the income ratio.
+pub struct California;
+
+impl StateTaxCalculator for California {
+    fn code(&self) -> &'static str {
+        "CA"
+    }
+
+    fn calculate(&self, context: &StateContext, rules: &StateRules) -> StateTax {
+        let rules = &rules.california;
+        let input = context.input;
+        let status = input.filing_status;
+
+        let adjusted_gross_income = context.adjusted_gross_income + input.adjustments.hsa_deduction;
+        let standard = *rules.standard_deduction.get(status);
+        let itemized = context.itemized_without_income_taxes();
+        let deduction = if ScheduleA::must_itemize(input) { itemized } else { standard.max(itemized) };
+        let total_taxable_income = (adjusted_gross_income - deduction).max(Decimal::ZERO);
+        let ratio = context.income_ratio();
+        let taxable_income = round_cents(total_taxable_income * ratio);
+        let tax = round_cents(bracket_tax(rules.brackets.get(status), total_taxable_income) * ratio);
+
+        let personal = match status {
+            FilingStatus::MarriedFilingJointly | FilingStatus::QualifyingSurvivingSpouse => 2,
+            _ => 1,
+        } + input.additional_standard_deduction_boxes();
+        let dependents = input.dependents.len() as u32;
+        let excess = (context.adjusted_gross_income - rules.exemption_phase_out_start.get(status)).max(Decimal::ZERO);
+        let reduction = (excess / rules.exemption_phase_out_step.get(status)).ceil() * rules.exemption_phase_out_reduction;
+        let credits = (rules.personal_exemption_credit - reduction).max(Decimal::ZERO) * Decimal::from(personal)
+            + (rules.dependent_exemption_credit - reduction).max(Decimal::ZERO) * Decimal::from(dependents);
+        let credits = round_cents(credits * ratio);
+
+        let surtax = round_cents((taxable_income - rules.mental_health_services_threshold).max(Decimal::ZERO) * rules.mental_health_services_rate);
+
+        let mut notes = Vec::new();
+        if !input.adjustments.hsa_deduction.is_zero() {
+            notes.push(format!("HSA deduction of ${} added back", input.adjustments.hsa_deduction));
+        }
+
+        StateTax {
+            adjusted_gross_income,
+            deduction,
+            exemptions: Decimal::ZERO,
+            taxable_income,
+            tax,
+            credits,
+            surtax,
+            notes,
+        }
+    }
+}
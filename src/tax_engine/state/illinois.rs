@@ -0,0 +1,77 @@
+//! Illinois Form IL-1040.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::ByFilingStatus;
+use crate::tax_engine::state::{Residency, StateContext, StateRules, StateTax, StateTaxCalculator};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IllinoisRules {
+    pub rate: Decimal,
+    /// Per person: taxpayer, spouse on a joint return and each dependent
+    pub exemption: Decimal,
+    /// Per 65+/blind box checked
+    pub senior_blind_exemption: Decimal,
+    /// Federal AGI above which no exemption is allowed
+    pub exemption_income_limit: ByFilingStatus<Decimal>,
+}
+
+impl IllinoisRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if FilingStatus::ALL.into_iter().any(|status| self.exemption_income_limit.get(status).is_sign_negative())
+            || self.exemption.is_sign_negative()
+            || self.senior_blind_exemption.is_sign_negative() {
+            return Err("IL amounts cannot be negative".into());
+        }
+        if self.rate <= Decimal::ZERO || self.rate >= Decimal::ONE {
+            return Err("IL rate must be between 0 and 1".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Illinois taxes base income, here federal AGI, at a flat rate after exemptions. Someone
+/// who can be claimed as a dependent keeps their own exemption only while base income does
+/// not exceed it. Part-year and nonresident returns (Schedule NR) tax only the allocated
+/// income and prorate the exemptions by the income ratio.
+pub struct Illinois;
+
+impl StateTaxCalculator for Illinois {
+    fn code(&self) -> &'static str {
+        "IL"
+    }
+
+    fn calculate(&self, context: &StateContext, rules: &StateRules) -> StateTax {
+        let rules = &rules.illinois;
+        let input = context.input;
+
+        let adjusted_gross_income = context.adjusted_gross_income;
+        let exemptions = if adjusted_gross_income > *rules.exemption_income_limit.get(input.filing_status) {
+            Decimal::ZERO
+        } else {
+            let personal = match input.credits.claimed_as_dependent && adjusted_gross_income > rules.exemption {
+                true => 0,
+                false => context.personal_exemptions(),
+            };
+            let exemptions = rules.exemption * Decimal::from(personal + input.dependents.len() as u32)
+                + rules.senior_blind_exemption * Decimal::from(input.additional_standard_deduction_boxes());
+            round_cents(exemptions * context.income_ratio())
+        };
+        let allocated_income = match context.residency {
+            Residency::Resident => adjusted_gross_income,
+            _ => context.allocated_income(),
+        };
+        let taxable_income = (allocated_income - exemptions).max(Decimal::ZERO);
+
+        StateTax {
+            adjusted_gross_income,
+            exemptions,
+            taxable_income,
+            tax: round_cents(taxable_income * rules.rate),
+            ..StateTax::default()
+        }
+    }
+}
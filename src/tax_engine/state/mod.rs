@@ -0,0 +1,383 @@
+//! State income tax returns.
+//!
+//! Every state the taxpayer lived in or has wages from (box 15 of a W-2) gets a return from its
+//! `StateTaxCalculator`. Calculators start from the federal figures, apply the state's own
+//! deductions, exemptions and rates from the `states` section of the year's rule table, and
+//! the box 17 withholding for the state is reconciled against the result.
+//!
+//! Residency periods decide how income is allocated. Wages follow the state on their W-2,
+//! except that wages from a state the taxpayer never lived in are also taxed by the state of
+//! residence, which then credits the tax paid to the other state. All other income is
+//! allocated by the days lived in each state.
+
+pub mod california;
+pub mod illinois;
+pub mod new_york;
+pub mod no_income_tax;
+pub mod pennsylvania;
+
+use std::collections::BTreeMap;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::input::{TaxInput, W2};
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::rules::TaxRules;
+use crate::tax_engine::state::california::{California, CaliforniaRules};
+use crate::tax_engine::state::illinois::{Illinois, IllinoisRules};
+use crate::tax_engine::state::new_york::{NewYork, NewYorkRules};
+use crate::tax_engine::state::no_income_tax::NoIncomeTax;
+use crate::tax_engine::state::pennsylvania::{Pennsylvania, PennsylvaniaRules};
+use crate::tax_engine::{round_cents, IncomeSummary};
+
+/// State parameters for one tax year, keyed by postal code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRules {
+    /// Where the figures come from (state form instructions)
+    pub source: String,
+    #[serde(rename = "CA")]
+    pub california: CaliforniaRules,
+    #[serde(rename = "NY")]
+    pub new_york: NewYorkRules,
+    #[serde(rename = "IL")]
+    pub illinois: IllinoisRules,
+    #[serde(rename = "PA")]
+    pub pennsylvania: PennsylvaniaRules,
+}
+
+impl StateRules {
+    pub fn validate(&self) -> Result<(), String> {
+        self.california.validate()?;
+        self.new_york.validate()?;
+        self.illinois.validate()?;
+        self.pennsylvania.validate()
+    }
+}
+
+/// How the taxpayer relates to a state for the year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Residency {
+    Resident,
+    PartYear,
+    /// Only wages reported to the state are taxed there
+    Nonresident,
+}
+
+/// Federal figures, W-2 totals and residency a calculator starts from.
+pub struct StateContext<'a> {
+    pub input: &'a TaxInput,
+    pub income: &'a IncomeSummary,
+    pub adjusted_gross_income: Decimal,
+    pub schedule_a: &'a ScheduleA,
+    pub state: &'a str,
+    pub residency: Residency,
+    /// Share of the year lived in the state: 1 for a full-year resident, 0 for a nonresident
+    pub resident_share: Decimal,
+    /// Box 16 wages reported to the state
+    pub state_wages: Decimal,
+}
+
+impl StateContext<'_> {
+    /// Schedule A without the SALT cap and without state and local income taxes, which
+    /// California and New York both start their itemized deductions from.
+    pub fn itemized_without_income_taxes(&self) -> Decimal {
+        let schedule_a = self.schedule_a;
+        schedule_a.total - schedule_a.salt_deduction + schedule_a.real_estate_taxes + schedule_a.personal_property_taxes
+    }
+
+    /// Taxpayer and spouse on a joint return, otherwise the taxpayer alone.
+    pub fn personal_exemptions(&self) -> u32 {
+        if self.input.filing_status == FilingStatus::MarriedFilingJointly { 2 } else { 1 }
+    }
+
+    /// Share of a W-2's wages sourced to this state: all of it when the W-2 is for this state,
+    /// none when it is for another state the taxpayer lived in, and the resident share when
+    /// it is for a state the taxpayer only worked in.
+    pub fn wage_share(&self, w2: &W2) -> Decimal {
+        match w2_state(w2) {
+            Some(state) if state == self.state => Decimal::ONE,
+            Some(state) if self.input.residency.iter().any(|period| period.state.eq_ignore_ascii_case(&state)) => Decimal::ZERO,
+            _ => self.resident_share,
+        }
+    }
+
+    /// Federal AGI sourced to the state: wages by `wage_share`, all other income by the share
+    /// of the year lived in the state.
+    pub fn allocated_income(&self) -> Decimal {
+        let wages = self.input.w2s.iter().map(|w2| w2.wages).sum::<Decimal>();
+        let sourced_wages = self.input.w2s.iter().map(|w2| w2.wages * self.wage_share(w2)).sum::<Decimal>();
+        round_cents(sourced_wages + (self.adjusted_gross_income - wages) * self.resident_share)
+    }
+
+    /// Allocated income over federal AGI, to four decimal places as on the nonresident forms.
+    /// Part-year and nonresident returns scale the full-year tax by it.
+    pub fn income_ratio(&self) -> Decimal {
+        if self.residency == Residency::Resident || self.adjusted_gross_income <= Decimal::ZERO {
+            return Decimal::ONE;
+        }
+
+        (self.allocated_income() / self.adjusted_gross_income).clamp(Decimal::ZERO, Decimal::ONE).round_dp(4)
+    }
+}
+
+/// Box 15 of a W-2, normalized to an upper-case postal code.
+fn w2_state(w2: &W2) -> Option<String> {
+    w2.state.as_deref().map(str::trim).filter(|state| !state.is_empty()).map(str::to_uppercase)
+}
+
+/// What a calculator figures; withholding is reconciled by `StateReturn`.
+#[derive(Debug, Clone, Default)]
+pub struct StateTax {
+    pub adjusted_gross_income: Decimal,
+    pub deduction: Decimal,
+    pub exemptions: Decimal,
+    pub taxable_income: Decimal,
+    pub tax: Decimal,
+    /// Nonrefundable credits, limited to `tax`
+    pub credits: Decimal,
+    /// Taxes added after credits, such as California's mental health services tax
+    pub surtax: Decimal,
+    pub notes: Vec<String>,
+}
+
+pub trait StateTaxCalculator: Sync {
+    /// Postal code, as in `states.code`
+    fn code(&self) -> &'static str;
+
+    /// Whether the state taxes wages at all.
+    fn taxes_wages(&self) -> bool {
+        true
+    }
+
+    fn calculate(&self, context: &StateContext, rules: &StateRules) -> StateTax;
+}
+
+static CALCULATORS: [&dyn StateTaxCalculator; 13] = [
+    &California,
+    &NewYork,
+    &Illinois,
+    &Pennsylvania,
+    &NoIncomeTax { code: "AK", note: None },
+    &NoIncomeTax { code: "FL", note: None },
+    &NoIncomeTax { code: "NV", note: None },
+    &NoIncomeTax { code: "NH", note: Some("New Hampshire's interest and dividends tax is not calculated") },
+    &NoIncomeTax { code: "SD", note: None },
+    &NoIncomeTax { code: "TN", note: None },
+    &NoIncomeTax { code: "TX", note: None },
+    &NoIncomeTax { code: "WA", note: Some("Washington's capital gains excise tax is not calculated") },
+    &NoIncomeTax { code: "WY", note: None },
+];
+
+/// Calculator for a postal code, if the state is supported yet.
+pub fn calculator(code: &str) -> Option<&'static dyn StateTaxCalculator> {
+    CALCULATORS.iter().copied().find(|calculator| calculator.code().eq_ignore_ascii_case(code))
+}
+
+/// One state's return with its withholding reconciled.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateReturn {
+    pub state: String,
+    /// False when there is no calculator for the state yet; the amounts are then only the W-2 totals
+    pub supported: bool,
+    pub taxes_wages: bool,
+    pub residency: Residency,
+    pub days_resident: i64,
+    /// Box 16
+    pub wages: Decimal,
+    pub adjusted_gross_income: Decimal,
+    /// Federal AGI sourced to the state
+    pub allocated_income: Decimal,
+    pub income_ratio: Decimal,
+    pub deduction: Decimal,
+    pub exemptions: Decimal,
+    pub taxable_income: Decimal,
+    pub tax: Decimal,
+    pub credits: Decimal,
+    /// Credit for tax paid to nonresident states on income this state also taxes
+    pub other_state_credit: Decimal,
+    pub surtax: Decimal,
+    pub total_tax: Decimal,
+    /// Box 17
+    pub withholding: Decimal,
+    pub refund: Decimal,
+    pub amount_owed: Decimal,
+    pub notes: Vec<String>,
+}
+
+impl StateReturn {
+    /// A return for each state the taxpayer lived in or has W-2 wages from, in postal code
+    /// order, and warnings about income or withholding no return accounts for.
+    ///
+    /// Without residency periods every W-2 state is figured as a full-year resident return.
+    /// Box 16 falls back to box 1 when the W-2 does not state it.
+    pub fn for_input(
+        input: &TaxInput,
+        income: &IncomeSummary,
+        adjusted_gross_income: Decimal,
+        schedule_a: &ScheduleA,
+        rules: &TaxRules,
+    ) -> (Vec<StateReturn>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut states: BTreeMap<String, (Decimal, Decimal)> = BTreeMap::new();
+        for w2 in &input.w2s {
+            match w2_state(w2) {
+                Some(state) => {
+                    let (wages, withheld) = states.entry(state).or_default();
+                    *wages += w2.state_wages.unwrap_or(w2.wages);
+                    *withheld += w2.state_tax_withheld;
+                }
+                None if !w2.state_tax_withheld.is_zero() => warnings.push(format!(
+                    "W-2 from {} has ${} of state tax withheld but no state in box 15; it is not credited to a state return",
+                    w2.employer, w2.state_tax_withheld
+                )),
+                None => {}
+            }
+        }
+
+        let days_in_year = i64::from(NaiveDate::from_ymd_opt(input.year, 12, 31).map(|date| date.ordinal()).unwrap_or(365));
+        let mut days_resident: BTreeMap<String, i64> = BTreeMap::new();
+        for period in &input.residency {
+            *days_resident.entry(period.state.trim().to_uppercase()).or_default() += period.days_in(input.year);
+            states.entry(period.state.trim().to_uppercase()).or_default();
+        }
+        let days_covered = days_resident.values().sum::<i64>();
+        if input.residency.is_empty() && states.len() > 1 {
+            warnings.push("No residency periods; every state is figured as a full-year resident return".to_string());
+        } else if !input.residency.is_empty() && days_covered < days_in_year {
+            warnings.push(format!("Residency periods cover {days_covered} of {days_in_year} days; the rest of the year's income is not allocated to a state"));
+        }
+
+        let contexts: Vec<(StateContext, Decimal, i64)> = states.iter().map(|(state, (wages, withholding))| {
+            let days = match input.residency.is_empty() {
+                true => days_in_year,
+                false => days_resident.get(state).copied().unwrap_or_default(),
+            };
+            let residency = match days {
+                0 => Residency::Nonresident,
+                days if days >= days_in_year => Residency::Resident,
+                _ => Residency::PartYear,
+            };
+            let context = StateContext {
+                input,
+                income,
+                adjusted_gross_income,
+                schedule_a,
+                state,
+                residency,
+                resident_share: (Decimal::from(days) / Decimal::from(days_in_year)).min(Decimal::ONE),
+                state_wages: *wages,
+            };
+            (context, *withholding, days)
+        }).collect();
+
+        let mut returns: Vec<StateReturn> = contexts.iter()
+            .map(|(context, withholding, days)| Self::reconcile(context, *withholding, *days, &rules.states))
+            .collect();
+        Self::credit_other_states(&mut returns, &contexts);
+
+        for state_return in &returns {
+            let state = &state_return.state;
+            if !state_return.supported {
+                warnings.push(format!("{state} income tax is not calculated yet"));
+            }
+            if !state_return.taxes_wages && !state_return.withholding.is_zero() {
+                warnings.push(format!("{state} has no income tax on wages but ${} was withheld; ask the employer for a corrected W-2", state_return.withholding));
+            }
+        }
+
+        (returns, warnings)
+    }
+
+    fn reconcile(context: &StateContext, withholding: Decimal, days_resident: i64, rules: &StateRules) -> StateReturn {
+        let state = context.state;
+        let mut state_return = StateReturn {
+            state: state.to_string(),
+            supported: false,
+            taxes_wages: true,
+            residency: context.residency,
+            days_resident,
+            wages: context.state_wages,
+            adjusted_gross_income: Decimal::ZERO,
+            allocated_income: context.allocated_income(),
+            income_ratio: context.income_ratio(),
+            deduction: Decimal::ZERO,
+            exemptions: Decimal::ZERO,
+            taxable_income: Decimal::ZERO,
+            tax: Decimal::ZERO,
+            credits: Decimal::ZERO,
+            other_state_credit: Decimal::ZERO,
+            surtax: Decimal::ZERO,
+            total_tax: Decimal::ZERO,
+            withholding,
+            refund: Decimal::ZERO,
+            amount_owed: Decimal::ZERO,
+            notes: Vec::new(),
+        };
+        let Some(calculator) = calculator(state) else {
+            state_return.notes.push(format!("No calculator for {state} yet"));
+            return state_return;
+        };
+
+        let tax = calculator.calculate(context, rules);
+        let credits = tax.credits.min(tax.tax);
+        state_return.supported = true;
+        state_return.taxes_wages = calculator.taxes_wages();
+        state_return.adjusted_gross_income = tax.adjusted_gross_income;
+        state_return.deduction = tax.deduction;
+        state_return.exemptions = tax.exemptions;
+        state_return.taxable_income = tax.taxable_income;
+        state_return.tax = tax.tax;
+        state_return.credits = credits;
+        state_return.surtax = tax.surtax;
+        state_return.total_tax = tax.tax - credits + tax.surtax;
+        state_return.notes = tax.notes;
+        state_return.settle();
+        state_return
+    }
+
+    /// Credits each resident and part-year return for tax paid to nonresident states on wages
+    /// both states tax. The credit is the smaller of the other state's tax on those wages and
+    /// this state's tax on them, each prorated by the wages' share of the state's income.
+    fn credit_other_states(returns: &mut [StateReturn], contexts: &[(StateContext, Decimal, i64)]) {
+        let credits: Vec<(Decimal, Vec<String>)> = returns.iter().zip(contexts).map(|(resident, (context, _, _))| {
+            let mut credit = Decimal::ZERO;
+            let mut notes = Vec::new();
+            if resident.residency == Residency::Nonresident || resident.allocated_income <= Decimal::ZERO {
+                return (credit, notes);
+            }
+
+            for other in returns.iter().filter(|other| other.residency == Residency::Nonresident && other.total_tax > Decimal::ZERO) {
+                let both_taxed = context.input.w2s.iter()
+                    .filter(|w2| w2_state(w2).as_deref() == Some(other.state.as_str()))
+                    .map(|w2| w2.wages * context.wage_share(w2))
+                    .sum::<Decimal>();
+                if both_taxed <= Decimal::ZERO || other.allocated_income <= Decimal::ZERO {
+                    continue;
+                }
+
+                let paid = other.total_tax * (both_taxed / other.allocated_income).min(Decimal::ONE);
+                let limit = resident.total_tax * (both_taxed / resident.allocated_income).min(Decimal::ONE);
+                let allowed = round_cents(paid.min(limit));
+                credit += allowed;
+                notes.push(format!("Credit of ${allowed} for tax paid to {}", other.state));
+            }
+            (credit, notes)
+        }).collect();
+
+        for (state_return, (credit, notes)) in returns.iter_mut().zip(credits) {
+            state_return.other_state_credit = credit.min(state_return.total_tax);
+            state_return.total_tax -= state_return.other_state_credit;
+            state_return.notes.extend(notes);
+            state_return.settle();
+        }
+    }
+
+    /// Refund or amount owed after withholding.
+    fn settle(&mut self) {
+        let balance = self.withholding - self.total_tax;
+        self.refund = round_cents(balance.max(Decimal::ZERO));
+        self.amount_owed = round_cents((-balance).max(Decimal::ZERO));
+    }
+}
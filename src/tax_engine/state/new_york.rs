@@ -0,0 +1,77 @@
+//! New York Form IT-201.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::itemized::ScheduleA;
+use crate::tax_engine::round_cents;
+use crate::tax_engine::rules::{bracket_tax, validate_brackets, Bracket, ByFilingStatus};
+use crate::tax_engine::state::{StateContext, StateRules, StateTax, StateTaxCalculator};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewYorkRules {
+    pub standard_deduction: ByFilingStatus<Decimal>,
+    pub brackets: ByFilingStatus<Vec<Bracket>>,
+    /// IT-201 line 36, per dependent
+    pub dependent_exemption: Decimal,
+    /// NY AGI above which the tax benefit recapture worksheets apply
+    pub recapture_threshold: Decimal,
+}
+
+impl NewYorkRules {
+    pub fn validate(&self) -> Result<(), String> {
+        for status in FilingStatus::ALL {
+            validate_brackets(&format!("NY {status}"), self.brackets.get(status))?;
+            if self.standard_deduction.get(status).is_sign_negative() {
+                return Err(format!("NY {status} standard deduction cannot be negative"));
+            }
+        }
+        if self.dependent_exemption.is_sign_negative() || self.recapture_threshold.is_sign_negative() {
+            return Err("NY amounts cannot be negative".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// New York starts from federal AGI and takes the larger of its standard deduction and
+/// Schedule A without state income taxes or the SALT cap, less $1,000 per dependent.
+/// Part-year and nonresident returns (IT-203) scale the tax by the income percentage.
+/// The tax benefit recapture above the recapture threshold is not applied yet; such returns
+/// carry a note so the tax is not mistaken for final.
+pub struct NewYork;
+
+impl StateTaxCalculator for NewYork {
+    fn code(&self) -> &'static str {
+        "NY"
+    }
+
+    fn calculate(&self, context: &StateContext, rules: &StateRules) -> StateTax {
+        let rules = &rules.new_york;
+        let input = context.input;
+        let status = input.filing_status;
+
+        let adjusted_gross_income = context.adjusted_gross_income;
+        let standard = *rules.standard_deduction.get(status);
+        let itemized = context.itemized_without_income_taxes();
+        let deduction = if ScheduleA::must_itemize(input) { itemized } else { standard.max(itemized) };
+        let exemptions = rules.dependent_exemption * Decimal::from(input.dependents.len() as u32);
+        let taxable_income = (adjusted_gross_income - deduction - exemptions).max(Decimal::ZERO);
+        let tax = round_cents(bracket_tax(rules.brackets.get(status), taxable_income) * context.income_ratio());
+
+        let mut notes = Vec::new();
+        if adjusted_gross_income > rules.recapture_threshold {
+            notes.push(format!("Tax benefit recapture for NY AGI over ${} is not applied", rules.recapture_threshold));
+        }
+
+        StateTax {
+            adjusted_gross_income,
+            deduction,
+            exemptions,
+            taxable_income,
+            tax,
+            notes,
+            ..StateTax::default()
+        }
+    }
+}
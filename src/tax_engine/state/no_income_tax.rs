@@ -0,0 +1,27 @@
+//! States without an individual income tax on wages.
+
+use crate::tax_engine::state::{StateContext, StateRules, StateTax, StateTaxCalculator};
+
+/// Any withholding is refundable in full; `StateReturn` also warns that it should not have been withheld.
+pub struct NoIncomeTax {
+    pub code: &'static str,
+    /// Other state taxes on individuals that are out of scope
+    pub note: Option<&'static str>,
+}
+
+impl StateTaxCalculator for NoIncomeTax {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+
+    fn taxes_wages(&self) -> bool {
+        false
+    }
+
+    fn calculate(&self, _context: &StateContext, _rules: &StateRules) -> StateTax {
+        StateTax {
+            notes: self.note.map(String::from).into_iter().collect(),
+            ..StateTax::default()
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Pennsylvania Form PA-40.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::tax_engine::round_cents;
+use crate::tax_engine::state::{StateContext, StateRules, StateTax, StateTaxCalculator};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PennsylvaniaRules {
+    pub rate: Decimal,
+}
+
+impl PennsylvaniaRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rate <= Decimal::ZERO || self.rate >= Decimal::ONE {
+            return Err("PA rate must be between 0 and 1".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Pennsylvania has no deductions or exemptions and does not start from federal AGI. It taxes
+/// each class of income separately, and a loss in one class cannot reduce another. Compensation
+/// is box 16 on Pennsylvania W-2s, which includes elective deferrals, and the sourced share of
+/// box 1 on the others; the other classes count for the share of the year lived in the state.
+/// Tax forgiveness (Schedule SP) is not applied.
+pub struct Pennsylvania;
+
+impl StateTaxCalculator for Pennsylvania {
+    fn code(&self) -> &'static str {
+        "PA"
+    }
+
+    fn calculate(&self, context: &StateContext, rules: &StateRules) -> StateTax {
+        let income = context.income;
+        let share = context.resident_share;
+        let compensation = context.state_wages + context.input.w2s.iter()
+            .filter(|w2| !w2.state.as_deref().is_some_and(|state| state.trim().eq_ignore_ascii_case(self.code())))
+            .map(|w2| w2.wages * context.wage_share(w2))
+            .sum::<Decimal>();
+        let classes = [
+            income.taxable_interest,
+            income.ordinary_dividends,
+            income.business_income,
+            income.rental_income,
+            income.capital_gain,
+        ];
+        let other_income = classes.into_iter().map(|class| class.max(Decimal::ZERO) * share).sum::<Decimal>();
+        let taxable_income = round_cents(compensation.max(Decimal::ZERO) + other_income);
+
+        StateTax {
+            adjusted_gross_income: taxable_income,
+            taxable_income,
+            tax: round_cents(taxable_income * rules.pennsylvania.rate),
+            ..StateTax::default()
+        }
+    }
+}
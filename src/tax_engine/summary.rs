@@ -0,0 +1,218 @@
+//! Refund or balance-due summary for the dashboard, with a checklist of inputs the
+//! calculation is still missing.
+
+use std::collections::BTreeSet;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::models::filing_profile_model::FilingStatus;
+use crate::tax_engine::input::TaxInput;
+use crate::tax_engine::{IncomeSummary, TaxCalculation};
+
+/// An input the return needs but does not have yet.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingInput {
+    /// Stable key the dashboard can link to the right form, e.g. `filing_profile` or `w2_boxes`
+    pub input: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Completeness {
+    pub complete: bool,
+    pub missing: Vec<MissingInput>,
+}
+
+impl Completeness {
+    /// Gaps visible in the engine input. Callers add the ones only the database can see,
+    /// such as a missing filing profile.
+    pub fn for_input(input: &TaxInput) -> Completeness {
+        let mut completeness = Completeness { complete: true, missing: Vec::new() };
+
+        let has_income = !input.w2s.is_empty() || !input.form_1099s.is_empty() || !input.businesses.is_empty()
+            || !input.capital_lots.is_empty() || !input.rental_income.is_zero() || !input.other_income.is_zero();
+        if !has_income {
+            completeness.add("income", format!("No W-2s, 1099s or other income for {}", input.year));
+        }
+        if input.taxpayer_age.is_none() {
+            completeness.add("date_of_birth", "Taxpayer date of birth is needed to confirm age for the earned income and saver's credits");
+        }
+        if input.filing_status == FilingStatus::MarriedFilingJointly && input.spouse_age.is_none() {
+            completeness.add("date_of_birth", "Spouse date of birth is needed to confirm age for the earned income and saver's credits");
+        }
+
+        for w2 in &input.w2s {
+            if w2.social_security_wages.is_none() || w2.medicare_wages.is_none() {
+                completeness.add("w2_boxes", format!("W-2 from {} is missing boxes 3-6; Social Security and Medicare cannot be reconciled", w2.employer));
+            }
+            if w2.state.is_none() && !w2.state_tax_withheld.is_zero() {
+                completeness.add("w2_state", format!("W-2 from {} has state tax withheld but no state in box 15", w2.employer));
+            }
+        }
+        let w2_states: BTreeSet<String> = input.w2s.iter()
+            .filter_map(|w2| w2.state.as_deref())
+            .map(|state| state.trim().to_uppercase())
+            .collect();
+        if w2_states.len() > 1 && input.residency.is_empty() {
+            completeness.add("residency", "W-2s list more than one state; add residency periods to allocate income between them");
+        }
+        for form in input.form_1098s.iter().filter(|form| form.outstanding_principal.is_none()) {
+            completeness.add("form_1098", format!("1098 from {} is missing box 2; the mortgage debt limit cannot be checked", form.lender));
+        }
+
+        completeness
+    }
+
+    pub fn add(&mut self, input: &'static str, message: impl Into<String>) {
+        self.complete = false;
+        self.missing.push(MissingInput { input, message: message.into() });
+    }
+}
+
+/// Refund or amount owed for one state return.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateBalance {
+    pub state: String,
+    pub refund: Decimal,
+    pub balance_due: Decimal,
+}
+
+/// Answers "am I getting money back?" from a finished calculation.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxSummary {
+    pub year: i32,
+    pub filing_status: FilingStatus,
+    /// Income by category, with the Form 1040 line 9 total
+    pub income: IncomeSummary,
+    pub adjusted_gross_income: Decimal,
+    pub deduction: Decimal,
+    pub taxable_income: Decimal,
+    pub total_tax: Decimal,
+    pub withholding: Decimal,
+    pub estimated_payments: Decimal,
+    pub refundable_credits: Decimal,
+    pub other_payments: Decimal,
+    pub total_payments: Decimal,
+    pub refund: Decimal,
+    pub balance_due: Decimal,
+    /// Total tax over total income
+    pub effective_rate: Decimal,
+    pub marginal_rate: Decimal,
+    pub states: Vec<StateBalance>,
+    pub completeness: Completeness,
+}
+
+impl TaxSummary {
+    pub fn new(calculation: &TaxCalculation, completeness: Completeness) -> TaxSummary {
+        let effective_rate = match calculation.income.total > Decimal::ZERO {
+            true => (calculation.total_tax / calculation.income.total).round_dp(4),
+            false => Decimal::ZERO,
+        };
+
+        TaxSummary {
+            year: calculation.year,
+            filing_status: calculation.filing_status,
+            income: calculation.income.clone(),
+            adjusted_gross_income: calculation.adjusted_gross_income,
+            deduction: calculation.deduction.amount,
+            taxable_income: calculation.taxable_income,
+            total_tax: calculation.total_tax,
+            withholding: calculation.withholding,
+            estimated_payments: calculation.estimated_payments,
+            refundable_credits: calculation.refundable_credits,
+            other_payments: calculation.other_payments,
+            total_payments: calculation.total_payments,
+            refund: calculation.refund,
+            balance_due: calculation.amount_owed,
+            effective_rate,
+            marginal_rate: calculation.marginal_rate,
+            states: calculation.states.iter()
+                .map(|state| StateBalance { state: state.state.clone(), refund: state.refund, balance_due: state.amount_owed })
+                .collect(),
+            completeness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use super::{Completeness, TaxSummary};
+    use crate::tax_engine::input::TaxInput;
+    use crate::tax_engine::TaxEngine;
+
+    fn input(data: Value) -> TaxInput {
+        serde_json::from_value(data).unwrap()
+    }
+
+    fn summary(input: &TaxInput) -> TaxSummary {
+        TaxSummary::new(&TaxEngine::calculate(input).unwrap(), Completeness::for_input(input))
+    }
+
+    fn missing(completeness: &Completeness) -> Vec<&str> {
+        completeness.missing.iter().map(|missing| missing.input).collect()
+    }
+
+    #[test]
+    fn refund_when_withholding_covers_the_tax() {
+        // $35,400 taxable: $4,019 from the tax table
+        let input = input(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "taxpayer_age": 30,
+            "w2s": [{
+                "employer": "Acme", "wages": 50000, "federal_tax_withheld": 6000,
+                "social_security_wages": 50000, "social_security_tax_withheld": 3100, "medicare_wages": 50000, "medicare_tax_withheld": 725
+            }]
+        }));
+        let summary = summary(&input);
+
+        assert_eq!(summary.taxable_income, Decimal::from(35400));
+        assert_eq!(summary.total_tax, Decimal::from(4019));
+        assert_eq!(summary.refund, Decimal::from(1981));
+        assert!(summary.balance_due.is_zero());
+        assert_eq!(summary.effective_rate, Decimal::new(804, 4));
+        assert_eq!(summary.marginal_rate, Decimal::new(12, 2));
+        assert!(summary.completeness.complete);
+        assert!(summary.completeness.missing.is_empty());
+    }
+
+    #[test]
+    fn balance_due_when_withholding_falls_short() {
+        // $85,400 taxable: $13,847 from the tax table
+        let input = input(json!({
+            "year": 2024,
+            "filing_status": "single",
+            "w2s": [{ "employer": "Acme", "wages": 100000, "federal_tax_withheld": 10000, "state_tax_withheld": 2000 }],
+            "form_1098s": [{ "lender": "Chase", "mortgage_interest": 5000 }]
+        }));
+        let summary = summary(&input);
+
+        assert_eq!(summary.total_tax, Decimal::from(13847));
+        assert!(summary.refund.is_zero());
+        assert_eq!(summary.balance_due, Decimal::from(3847));
+        assert_eq!(summary.effective_rate, Decimal::new(1385, 4));
+        assert_eq!(summary.marginal_rate, Decimal::new(22, 2));
+        assert!(!summary.completeness.complete);
+        assert_eq!(missing(&summary.completeness), ["date_of_birth", "w2_boxes", "w2_state", "form_1098"]);
+    }
+
+    #[test]
+    fn joint_return_without_income_or_residency() {
+        let empty = input(json!({ "year": 2024, "filing_status": "married_filing_jointly", "taxpayer_age": 40 }));
+        let two_states = input(json!({
+            "year": 2024,
+            "filing_status": "married_filing_jointly",
+            "taxpayer_age": 40,
+            "spouse_age": 40,
+            "w2s": [
+                { "employer": "Acme", "wages": 40000, "state": "CA", "social_security_wages": 40000, "medicare_wages": 40000 },
+                { "employer": "Globex", "owner": "spouse", "wages": 30000, "state": "ny", "social_security_wages": 30000, "medicare_wages": 30000 }
+            ]
+        }));
+
+        assert_eq!(missing(&Completeness::for_input(&empty)), ["income", "date_of_birth"]);
+        assert_eq!(missing(&Completeness::for_input(&two_states)), ["residency"]);
+        assert!(TaxSummary::new(&TaxEngine::calculate(&empty).unwrap(), Completeness::default()).effective_rate.is_zero());
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::tax_engine::TaxEngine;
+use crate::tax_engine::input::TaxInput;
+
+/// A worked example: `expected` maps dotted paths into the serialized `TaxCalculation`
+/// (e.g. `deduction.amount`, or `payroll.flags.0.issue` into arrays) to the value the return should show.
+#[derive(Debug, Deserialize)]
+struct GoldenCase {
+    description: String,
+    input: TaxInput,
+    expected: serde_json::Map<String, Value>,
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => value.get(key),
+    })
+}
+
+fn as_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Every golden file by file name, in name order.
+fn golden_cases() -> Vec<(String, GoldenCase)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tax_engine/golden");
+    let mut files: Vec<_> = fs::read_dir(&dir).expect("golden directory").filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    files.sort();
+    assert!(!files.is_empty(), "no golden files in {}", dir.display());
+
+    files.into_iter()
+        .map(|file| {
+            let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let case = serde_json::from_str(&fs::read_to_string(&file).expect("readable golden file"))
+                .unwrap_or_else(|e| panic!("{name}: invalid golden file: {e}"));
+            (name, case)
+        })
+        .collect()
+}
+
+/// The input of every golden file, for tests that check properties of any return rather than worked amounts.
+pub(super) fn golden_inputs() -> Vec<(String, TaxInput)> {
+    golden_cases().into_iter().map(|(name, case)| (name, case.input)).collect()
+}
+
+#[test]
+fn golden_files_match_worked_examples() {
+    let mut failures = Vec::new();
+    for (name, case) in golden_cases() {
+        let calculation = match TaxEngine::calculate(&case.input) {
+            Ok(calculation) => serde_json::to_value(calculation).expect("serializable calculation"),
+            Err(e) => {
+                failures.push(format!("{name}: calculation failed: {e}"));
+                continue;
+            }
+        };
+
+        for (path, expected) in &case.expected {
+            let actual = lookup(&calculation, path);
+            let matches = match (actual.and_then(as_decimal), as_decimal(expected)) {
+                (Some(actual), Some(expected)) => actual == expected,
+                _ => actual == Some(expected),
+            };
+            if !matches {
+                failures.push(format!("{name} ({}): {path} expected {expected}, got {}", case.description, actual.unwrap_or(&Value::Null)));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "golden mismatches:\n{}", failures.join("\n"));
+}
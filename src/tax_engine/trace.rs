@@ -0,0 +1,914 @@
+//! Line-by-line explanation of a calculation. Each traced line records how it is figured,
+//! the lines and entries it is figured from, the uploaded or entered records behind it and the
+//! rule table entries it used, so any number on the return can be followed back to its origin.
+//!
+//! The trace is rebuilt from the finished `TaxCalculation` and the `TaxInput` it came from
+//! rather than recorded while calculating, which keeps the engine free of bookkeeping; the tests
+//! check it against the calculation for every golden return. Line ids name the form and line:
+//! `1040.11`, `schedule_a.5e`, `schedule_c[0].31`, `states.CA.refund`.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use crate::models::filing_profile_model::{FilingStatus, Owner};
+use crate::tax_engine::input::{Form1099Kind, RecordSource, TaxInput};
+use crate::tax_engine::qualified_business_income::QualifiedBusinessIncomeForm;
+use crate::tax_engine::rules::{TaxRules, TAX_TABLE_LIMIT};
+use crate::tax_engine::self_employment::ScheduleC;
+use crate::tax_engine::{DeductionKind, TaxCalculation};
+use crate::utilities::error_bag::ErrorBag;
+
+/// A line or entered amount a traced line is figured from.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceInput {
+    /// Id of another traced line, or the `tax_data` entry for amounts used as entered
+    pub name: String,
+    pub amount: Decimal,
+}
+
+/// A document record behind a traced line.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceSource {
+    /// Missing for records that were not loaded from `tax_data`
+    #[serde(flatten)]
+    pub record: Option<RecordSource>,
+    pub description: String,
+    pub amount: Decimal,
+}
+
+/// The rule table entry at `path` (e.g. `standard_deduction.single`) and its value for the year.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleReference {
+    pub path: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceLine {
+    pub line: String,
+    pub label: String,
+    pub amount: Decimal,
+    pub formula: String,
+    pub inputs: Vec<TraceInput>,
+    pub sources: Vec<TraceSource>,
+    pub rules: Vec<RuleReference>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl TraceLine {
+    fn new(line: impl Into<String>, label: impl Into<String>, amount: Decimal, formula: impl Into<String>) -> TraceLine {
+        TraceLine {
+            line: line.into(),
+            label: label.into(),
+            amount,
+            formula: formula.into(),
+            inputs: Vec::new(),
+            sources: Vec::new(),
+            rules: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    fn input(mut self, name: impl Into<String>, amount: Decimal) -> TraceLine {
+        self.inputs.push(TraceInput { name: name.into(), amount });
+        self
+    }
+
+    fn source(mut self, record: Option<&RecordSource>, description: impl Into<String>, amount: Decimal) -> TraceLine {
+        self.sources.push(TraceSource { record: record.cloned(), description: description.into(), amount });
+        self
+    }
+
+    fn rule(mut self, rule: RuleReference) -> TraceLine {
+        self.rules.push(rule);
+        self
+    }
+
+    fn rules(mut self, rules: impl IntoIterator<Item = RuleReference>) -> TraceLine {
+        self.rules.extend(rules);
+        self
+    }
+
+    fn notes(mut self, notes: &[String]) -> TraceLine {
+        self.notes.extend(notes.iter().cloned());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalculationTrace {
+    pub year: i32,
+    pub filing_status: FilingStatus,
+    /// Rule table the calculation used
+    pub rules_version: String,
+    pub rules_source: String,
+    pub lines: Vec<TraceLine>,
+}
+
+impl CalculationTrace {
+    /// Traces every line of `calculation`, which must have been computed from `input`.
+    pub fn new(input: &TaxInput, calculation: &TaxCalculation) -> Result<CalculationTrace, ErrorBag> {
+        let rules = TaxRules::for_year(calculation.year)?;
+        let table = serde_json::to_value(rules).map_err(|e| ErrorBag::InternalServerError(e.to_string()))?;
+        let mut tracer = Tracer { input, calculation, table, lines: Vec::new() };
+
+        tracer.income();
+        tracer.schedule_c();
+        tracer.capital_gains();
+        tracer.schedule_1();
+        tracer.adjusted_gross_income();
+        tracer.deduction();
+        tracer.tax();
+        tracer.alternative_minimum_tax()?;
+        tracer.credits();
+        tracer.other_taxes();
+        tracer.payments();
+        tracer.states();
+
+        Ok(CalculationTrace {
+            year: calculation.year,
+            filing_status: calculation.filing_status,
+            rules_version: rules.version.clone(),
+            rules_source: rules.source.clone(),
+            lines: tracer.lines,
+        })
+    }
+
+    pub fn line(&self, line: &str) -> Option<&TraceLine> {
+        self.lines.iter().find(|traced| traced.line == line)
+    }
+
+    /// Lines whose label shares the most words with `question`, best first: whole words count
+    /// double, and ties go to lines with an amount and then to Form 1040 itself. Falls back to
+    /// AGI, taxable income, total tax and the refund or balance due when nothing matches.
+    pub fn relevant(&self, question: &str, limit: usize) -> Vec<&TraceLine> {
+        const IGNORED: [&str; 16] = ["why", "how", "what", "the", "was", "did", "does", "is", "my", "for", "and", "this", "that", "so", "come", "from"];
+        let question = question.to_lowercase();
+        let words: Vec<&str> = question.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() > 2 && !IGNORED.contains(word))
+            .collect();
+
+        let mut scored: Vec<(usize, &TraceLine)> = self.lines.iter()
+            .map(|line| {
+                let label = line.label.to_lowercase();
+                let label_words: Vec<&str> = label.split(|c: char| !c.is_alphanumeric()).collect();
+                let score = words.iter()
+                    .map(|word| match (label_words.contains(word) || line.line == *word, label.contains(word)) {
+                        (true, _) => 2,
+                        (false, true) => 1,
+                        (false, false) => 0,
+                    })
+                    .sum::<usize>();
+                (score, line)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        scored.sort_by_key(|(score, line)| (std::cmp::Reverse(*score), line.amount.is_zero(), !line.line.starts_with("1040.")));
+        if !scored.is_empty() {
+            return scored.into_iter().take(limit).map(|(_, line)| line).collect();
+        }
+
+        ["1040.11", "1040.15", "1040.24", "1040.34", "1040.37"].iter()
+            .filter_map(|id| self.line(id))
+            .filter(|line| !line.amount.is_zero() || line.line == "1040.24")
+            .take(limit)
+            .collect()
+    }
+}
+
+struct Tracer<'a> {
+    input: &'a TaxInput,
+    calculation: &'a TaxCalculation,
+    /// The year's rule table as JSON, so entries can be quoted by path
+    table: Value,
+    lines: Vec<TraceLine>,
+}
+
+impl Tracer<'_> {
+    fn rule(&self, path: &str) -> RuleReference {
+        let value = path.split('.').try_fold(&self.table, |value, key| match value {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+            _ => value.get(key),
+        });
+        RuleReference { path: path.into(), value: value.cloned().unwrap_or(Value::Null) }
+    }
+
+    /// The entry at `path` when the year's table has one, for rules that only some years or states define.
+    fn optional_rule(&self, path: &str) -> Option<RuleReference> {
+        Some(self.rule(path)).filter(|rule| !rule.value.is_null())
+    }
+
+    /// Rule path for the return's filing status, e.g. `standard_deduction.single`.
+    fn status_rule(&self, path: &str) -> RuleReference {
+        self.rule(&format!("{path}.{}", self.calculation.filing_status.as_str()))
+    }
+
+    fn push(&mut self, line: TraceLine) {
+        self.lines.push(line);
+    }
+
+    /// Form 1040 line 22.
+    fn tax_after_credits(calculation: &TaxCalculation) -> Decimal {
+        calculation.tax + calculation.alternative_minimum_tax.tax - calculation.nonrefundable_credits
+    }
+
+    fn income(&mut self) {
+        let input = self.input;
+        let income = &self.calculation.income;
+
+        let mut wages = TraceLine::new("1040.1a", "Form 1040 line 1a, wages", income.wages, "Sum of W-2 box 1");
+        for w2 in &input.w2s {
+            wages = wages.source(w2.source.as_ref(), format!("W-2 from {}, box 1", w2.employer), w2.wages);
+        }
+        self.push(wages);
+
+        let mut interest = TraceLine::new("1040.2b", "Form 1040 line 2b, taxable interest", income.taxable_interest, "Sum of 1099-INT box 1");
+        let mut ordinary = TraceLine::new("1040.3b", "Form 1040 line 3b, ordinary dividends", income.ordinary_dividends, "Sum of 1099-DIV box 1a");
+        let mut qualified = TraceLine::new("1040.3a", "Form 1040 line 3a, qualified dividends", income.qualified_dividends, "Sum of 1099-DIV box 1b");
+        for form in &input.form_1099s {
+            match form.kind() {
+                Form1099Kind::Int => interest = interest.source(form.source.as_ref(), format!("1099-INT from {}, box 1", form.payer), form.income),
+                Form1099Kind::Div => {
+                    ordinary = ordinary.source(form.source.as_ref(), format!("1099-DIV from {}, box 1a", form.payer), form.income);
+                    qualified = qualified.source(form.source.as_ref(), format!("1099-DIV from {}, box 1b", form.payer), form.qualified_dividends);
+                }
+                _ => {}
+            }
+        }
+        self.push(interest);
+        self.push(qualified);
+        self.push(ordinary);
+    }
+
+    fn schedule_c(&mut self) {
+        let input = self.input;
+        for (index, schedule) in self.calculation.schedule_c.iter().enumerate() {
+            let prefix = format!("schedule_c[{index}]");
+            let mut line = TraceLine::new(
+                format!("{prefix}.31"),
+                format!("Schedule C line 31, net profit or loss of {}", schedule.name),
+                schedule.net_profit,
+                "Line 1 gross receipts − line 2 returns − line 4 cost of goods sold + line 6 other income − line 28 expenses − line 30 business use of home",
+            )
+                .input(format!("{prefix}.1"), schedule.gross_receipts)
+                .input(format!("{prefix}.2"), schedule.returns_and_allowances)
+                .input(format!("{prefix}.4"), schedule.cost_of_goods_sold)
+                .input(format!("{prefix}.6"), schedule.other_income)
+                .input(format!("{prefix}.28"), schedule.total_expenses)
+                .input(format!("{prefix}.30"), schedule.business_use_of_home);
+            let forms = input.form_1099s.iter()
+                .filter(|form| matches!(form.kind(), Form1099Kind::Nec | Form1099Kind::K))
+                .filter(|form| ScheduleC::business_index(input, form).unwrap_or(input.businesses.len()) == index);
+            for form in forms {
+                line = line.source(form.source.as_ref(), format!("{} from {}, box 1", form.kind().form_name(), form.payer), form.income);
+            }
+            if schedule.expenses.iter().any(|expense| expense.deductible != expense.amount) {
+                line = line.notes(&["Business meals are deductible at 50% on line 24b".to_string()]);
+            }
+            self.push(line);
+        }
+
+        for schedule in &self.calculation.schedule_se {
+            let prefix = format!("schedule_se.{}", schedule.owner.as_str());
+            let line = TraceLine::new(
+                format!("{prefix}.12"),
+                format!("Schedule SE line 12, self-employment tax{}", match schedule.owner {
+                    Owner::Taxpayer => "",
+                    Owner::Spouse => " (spouse)",
+                }),
+                schedule.tax,
+                "Line 6 net earnings (line 3 × the earnings factor) taxed for Social Security up to the wage base less line 8d wages, plus Medicare on all of it",
+            )
+                .input(format!("{prefix}.3"), schedule.net_profit)
+                .input(format!("{prefix}.6"), schedule.net_earnings)
+                .input(format!("{prefix}.8d"), schedule.social_security_wages)
+                .input(format!("{prefix}.10"), schedule.social_security_tax)
+                .input(format!("{prefix}.11"), schedule.medicare_tax)
+                .rule(self.rule("payroll.self_employment_earnings_factor"))
+                .rule(self.rule("social_security_wage_base"))
+                .rule(self.rule("payroll.social_security_rate"))
+                .rule(self.rule("payroll.medicare_rate"));
+            self.push(line);
+        }
+    }
+
+    fn capital_gains(&mut self) {
+        let Some(gains) = &self.calculation.capital_gains else {
+            return;
+        };
+        let schedule = &gains.schedule_d;
+
+        let mut net_gain = TraceLine::new("schedule_d.16", "Schedule D line 16, net capital gain or loss", schedule.net_gain, "Line 7 net short-term + line 15 net long-term")
+            .input("schedule_d.1-5", schedule.short_term_transactions)
+            .input("schedule_d.6", -schedule.short_term_carryover)
+            .input("schedule_d.7", schedule.net_short_term)
+            .input("schedule_d.8-12", schedule.long_term_transactions)
+            .input("schedule_d.13", schedule.capital_gain_distributions)
+            .input("schedule_d.14", -schedule.long_term_carryover)
+            .input("schedule_d.15", schedule.net_long_term)
+            .notes(&gains.warnings);
+        for lot in self.input.capital_lots.iter().filter(|lot| lot.date_sold.is_some()) {
+            let description = format!("{} sold {}", lot.description, lot.date_sold.map(|date| date.to_string()).unwrap_or_default());
+            net_gain = net_gain.source(lot.source.as_ref(), description, lot.proceeds - lot.cost_basis);
+        }
+        for form in self.input.form_1099s.iter().filter(|form| !form.capital_gain_distributions.is_zero()) {
+            net_gain = net_gain.source(form.source.as_ref(), format!("1099-DIV from {}, box 2a", form.payer), form.capital_gain_distributions);
+        }
+        self.push(net_gain);
+
+        let line = TraceLine::new("1040.7", "Form 1040 line 7, capital gain or loss", schedule.capital_gain, "Schedule D line 16 when a gain; a loss is limited to the filing status's loss limit (line 21)")
+            .input("schedule_d.16", schedule.net_gain)
+            .rule(self.status_rule("capital_gains.loss_limit"));
+        self.push(line);
+    }
+
+    fn schedule_1(&mut self) {
+        let input = self.input;
+        let income = &self.calculation.income;
+
+        let refunds = TraceLine::new("schedule_1.1", "Schedule 1 line 1, taxable state and local income tax refunds", income.taxable_refunds,
+            "Part of the prior year's state refund that lowered the prior year's tax, from the carryforward ledger or as entered")
+            .input("taxable_state_refund", input.taxable_state_refund);
+        self.push(refunds);
+
+        let mut business = TraceLine::new("schedule_1.3", "Schedule 1 line 3, business income or loss", income.business_income, "Sum of Schedule C line 31");
+        for (index, schedule) in self.calculation.schedule_c.iter().enumerate() {
+            business = business.input(format!("schedule_c[{index}].31"), schedule.net_profit);
+        }
+        self.push(business);
+
+        let rental = TraceLine::new("schedule_1.5", "Schedule 1 line 5, rental real estate income or loss", income.rental_income,
+            "Schedule E line 26 as entered, after the passive activity loss limits")
+            .input("rental_income", input.rental_income);
+        self.push(rental);
+
+        let net_operating_loss = TraceLine::new("schedule_1.8a", "Schedule 1 line 8a, net operating loss deduction", income.net_operating_loss,
+            "Losses carried in, limited to a share of taxable income figured without the deduction")
+            .input("net_operating_loss_carryover", input.net_operating_loss_carryover)
+            .rule(self.rule("net_operating_loss_limit"));
+        self.push(net_operating_loss);
+
+        let mut other = TraceLine::new("schedule_1.8z", "Schedule 1 line 8z, other income", income.other_income, "Other income as entered + 1099-MISC box 3")
+            .input("other_income", input.other_income);
+        for form in input.form_1099s.iter().filter(|form| form.kind() == Form1099Kind::Misc) {
+            other = other.source(form.source.as_ref(), format!("1099-MISC from {}", form.payer), form.income);
+        }
+        self.push(other);
+
+        let additional = income.taxable_refunds + income.business_income + income.rental_income + income.other_income - income.net_operating_loss;
+        let line = TraceLine::new("1040.8", "Form 1040 line 8, additional income from Schedule 1", additional, "Schedule 1 lines 1 + 3 + 5 + 8z − 8a")
+            .input("schedule_1.1", income.taxable_refunds)
+            .input("schedule_1.3", income.business_income)
+            .input("schedule_1.5", income.rental_income)
+            .input("schedule_1.8a", income.net_operating_loss)
+            .input("schedule_1.8z", income.other_income);
+        self.push(line);
+
+        let line = TraceLine::new("1040.9", "Form 1040 line 9, total income", income.total, "Lines 1a + 2b + 3b + 7 + 8")
+            .input("1040.1a", income.wages)
+            .input("1040.2b", income.taxable_interest)
+            .input("1040.3b", income.ordinary_dividends)
+            .input("1040.7", income.capital_gain)
+            .input("1040.8", additional);
+        self.push(line);
+    }
+
+    fn adjusted_gross_income(&mut self) {
+        let calculation = self.calculation;
+        let adjustments = &self.input.adjustments;
+        let half_self_employment_tax = calculation.schedule_se.iter().map(|se| se.deduction).sum::<Decimal>();
+
+        let line = TraceLine::new("1040.10", "Form 1040 line 10, adjustments to income", calculation.adjustments,
+            "Schedule 1 line 26: educator expenses up to the limit per educator, HSA and IRA deductions, half of self-employment tax, \
+            student loan interest up to the limit and phased out by income, and other adjustments")
+            .input("adjustments.educator_expenses", adjustments.educator_expenses)
+            .input("adjustments.hsa_deduction", adjustments.hsa_deduction)
+            .input("adjustments.ira_deduction", adjustments.ira_deduction)
+            .input("schedule_1.15", half_self_employment_tax)
+            .input("adjustments.student_loan_interest", adjustments.student_loan_interest)
+            .input("adjustments.other", adjustments.other)
+            .rule(self.rule("educator_expense_limit"))
+            .rule(self.rule("student_loan_interest_limit"))
+            .rule(self.status_rule("phase_outs.student_loan_interest"));
+        self.push(line);
+
+        let line = TraceLine::new("1040.11", "Form 1040 line 11, adjusted gross income (AGI)", calculation.adjusted_gross_income, "Line 9 − line 10")
+            .input("1040.9", calculation.income.total)
+            .input("1040.10", calculation.adjustments);
+        self.push(line);
+    }
+
+    fn deduction(&mut self) {
+        let input = self.input;
+        let calculation = self.calculation;
+        let schedule_a = &calculation.schedule_a;
+
+        let line = TraceLine::new("schedule_a.4", "Schedule A line 4, medical and dental expenses", schedule_a.medical_deduction, "Line 1 expenses − line 3 floor (a share of AGI)")
+            .input("schedule_a.1", schedule_a.medical_expenses)
+            .input("schedule_a.3", schedule_a.medical_floor)
+            .input("1040.11", calculation.adjusted_gross_income)
+            .rule(self.rule("itemized.medical_floor"));
+        self.push(line);
+
+        let mut salt = TraceLine::new("schedule_a.5e", "Schedule A line 5e, state and local taxes (SALT)", schedule_a.salt_deduction,
+            "Income tax, or general sales tax when larger (5a), + real estate tax (5b) + personal property tax (5c), limited to the SALT cap")
+            .input("schedule_a.5a", match schedule_a.sales_tax_elected {
+                true => schedule_a.general_sales_tax,
+                false => schedule_a.income_taxes,
+            })
+            .input("schedule_a.5b", schedule_a.real_estate_taxes)
+            .input("schedule_a.5c", schedule_a.personal_property_taxes)
+            .input("salt_cap", schedule_a.salt_cap)
+            .rule(self.status_rule("itemized.salt_cap"))
+            .rules(self.optional_rule("itemized.salt_cap_phase_out"));
+        for w2 in input.w2s.iter().filter(|w2| !(w2.state_tax_withheld + w2.local_tax_withheld).is_zero()) {
+            salt = salt.source(w2.source.as_ref(), format!("W-2 from {}, boxes 17 and 19", w2.employer), w2.state_tax_withheld + w2.local_tax_withheld);
+        }
+        for form in input.form_1098s.iter().filter(|form| !form.real_estate_taxes.is_zero()) {
+            salt = salt.source(form.source.as_ref(), format!("1098 from {}, box 10", form.lender), form.real_estate_taxes);
+        }
+        self.push(salt);
+
+        let mut interest = TraceLine::new("schedule_a.10", "Schedule A line 10, home mortgage interest", schedule_a.interest_deduction,
+            "Lines 8a-8c interest and points − interest on debt over the acquisition debt limit")
+            .input("schedule_a.8a-8b", schedule_a.mortgage_interest)
+            .input("schedule_a.8c", schedule_a.points)
+            .input("nondeductible_interest", -schedule_a.nondeductible_interest)
+            .rule(self.status_rule("itemized.mortgage_debt_limit"))
+            .rule(self.status_rule("itemized.grandfathered_mortgage_debt_limit"));
+        for form in &input.form_1098s {
+            interest = interest.source(form.source.as_ref(), format!("1098 from {}, boxes 1 and 6", form.lender), form.mortgage_interest + form.points);
+        }
+        self.push(interest);
+
+        let line = TraceLine::new("schedule_a.14", "Schedule A line 14, gifts to charity", schedule_a.charitable_deduction,
+            "Cash (line 11), property (line 12) and carryover (line 13) gifts, each limited to its share of AGI")
+            .input("schedule_a.11", schedule_a.charitable_cash)
+            .input("schedule_a.12", schedule_a.charitable_noncash)
+            .input("schedule_a.13", schedule_a.charitable_carryover)
+            .input("charitable_carryforward", schedule_a.charitable_carryforward)
+            .rule(self.rule("itemized.charitable_cash_limit"))
+            .rule(self.rule("itemized.charitable_property_limit"))
+            .rule(self.rule("itemized.charitable_capital_gain_limit"));
+        self.push(line);
+
+        let line = TraceLine::new("schedule_a.17", "Schedule A line 17, total itemized deductions", schedule_a.total, "Lines 4 + 5e + 10 + 14 + 16")
+            .input("schedule_a.4", schedule_a.medical_deduction)
+            .input("schedule_a.5e", schedule_a.salt_deduction)
+            .input("schedule_a.10", schedule_a.interest_deduction)
+            .input("schedule_a.14", schedule_a.charitable_deduction)
+            .input("schedule_a.16", schedule_a.other);
+        self.push(line);
+
+        let deduction = &calculation.deduction;
+        let formula = match (deduction.kind, deduction.recommended) {
+            (DeductionKind::Standard, _) => "Standard deduction for the filing status plus the additional amount per 65-or-older or blind box",
+            (DeductionKind::Itemized, DeductionKind::Itemized) if deduction.standard.is_zero() => "Schedule A line 17; a separate filer whose spouse itemizes has no standard deduction",
+            (DeductionKind::Itemized, DeductionKind::Itemized) => "Schedule A line 17, which is larger than the standard deduction",
+            (DeductionKind::Itemized, DeductionKind::Standard) => "Schedule A line 17, itemized by election although the standard deduction is larger",
+        };
+        let additional = match calculation.filing_status {
+            FilingStatus::Single | FilingStatus::HeadOfHousehold => "additional_standard_deduction_unmarried",
+            _ => "additional_standard_deduction_married",
+        };
+        let line = TraceLine::new("1040.12", "Form 1040 line 12, standard or itemized deduction", deduction.amount, formula)
+            .input("standard_deduction", deduction.standard)
+            .input("schedule_a.17", deduction.itemized)
+            .rule(self.status_rule("standard_deduction"))
+            .rule(self.rule(additional));
+        self.push(line);
+
+        if let Some(qbi) = &calculation.qualified_business_income {
+            let formula = match qbi.form {
+                QualifiedBusinessIncomeForm::Form8995 => "Form 8995: 20% of QBI less the loss carryover, limited to 20% of taxable income before the deduction less net capital gain",
+                QualifiedBusinessIncomeForm::Form8995A => "Form 8995-A: each business's 20% limited by W-2 wages and qualified property, specified service income reduced; limited to 20% of taxable income before the deduction less net capital gain",
+            };
+            let mut line = TraceLine::new("1040.13", "Form 1040 line 13, qualified business income deduction", calculation.qualified_business_income_deduction, formula)
+                .input("form_8995.3", qbi.loss_carryover)
+                .input("form_8995.10", qbi.component)
+                .input("form_8995.11", qbi.taxable_income_before_deduction)
+                .input("form_8995.12", qbi.net_capital_gain)
+                .input("form_8995.14", qbi.income_limit)
+                .rule(self.rule("qualified_business_income.rate"))
+                .rule(self.status_rule("qualified_business_income.threshold"));
+            if qbi.form == QualifiedBusinessIncomeForm::Form8995A {
+                line = line.rule(self.status_rule("qualified_business_income.phase_in_range"));
+            }
+            for business in &qbi.businesses {
+                line = line.source(None, format!("QBI component for {}", business.name), business.component);
+            }
+            self.push(line);
+        }
+
+        let line = TraceLine::new("1040.15", "Form 1040 line 15, taxable income", calculation.taxable_income, "Line 11 − line 12 − line 13, not less than zero")
+            .input("1040.11", calculation.adjusted_gross_income)
+            .input("1040.12", deduction.amount)
+            .input("1040.13", calculation.qualified_business_income_deduction);
+        self.push(line);
+    }
+
+    fn tax(&mut self) {
+        let calculation = self.calculation;
+        let line = match &calculation.qualified_dividends_worksheet {
+            Some(worksheet) => TraceLine::new("1040.16", "Form 1040 line 16, tax", calculation.tax,
+                "Qualified Dividends and Capital Gain Tax Worksheet: ordinary income at the bracket rates, and qualified dividends and net capital gain at 0%, 15% and 20%, if less than tax on all of it at the bracket rates")
+                .input("1040.15", worksheet.taxable_income)
+                .input("1040.3a", worksheet.qualified_dividends)
+                .input("net_capital_gain", worksheet.net_capital_gain)
+                .input("ordinary_income", worksheet.ordinary_income)
+                .input("taxed_at_0", worksheet.zero_rate_amount)
+                .input("taxed_at_15", worksheet.fifteen_rate_amount)
+                .input("taxed_at_20", worksheet.twenty_rate_amount)
+                .input("regular_tax", worksheet.regular_tax)
+                .rule(self.status_rule("brackets"))
+                .rule(self.status_rule("capital_gains.zero_rate_maximum"))
+                .rule(self.status_rule("capital_gains.fifteen_rate_maximum")),
+            None if calculation.taxable_income < Decimal::from(TAX_TABLE_LIMIT) => TraceLine::new("1040.16", "Form 1040 line 16, tax", calculation.tax,
+                "Tax table: the bracket rates applied to the middle of the table row line 15 falls in, rounded to whole dollars")
+                .input("1040.15", calculation.taxable_income)
+                .rule(self.status_rule("brackets")),
+            None => TraceLine::new("1040.16", "Form 1040 line 16, tax", calculation.tax, "Tax Computation Worksheet: the bracket rates applied to line 15")
+                .input("1040.15", calculation.taxable_income)
+                .rule(self.status_rule("brackets")),
+        };
+        self.push(line);
+    }
+
+    fn alternative_minimum_tax(&mut self) -> Result<(), ErrorBag> {
+        let input = self.input;
+        let calculation = self.calculation;
+        let amt = &calculation.alternative_minimum_tax;
+
+        let mut options = TraceLine::new("form_6251.2i", "Form 6251 line 2i, incentive stock options", amt.incentive_stock_options,
+            "Form 3921 box 4 − box 3, times box 5, for shares not sold in the year, + entered ISO adjustments")
+            .input("amt_adjustments.incentive_stock_options", input.amt_adjustments.incentive_stock_options);
+        for form in input.form_3921s.iter() {
+            options = options.source(form.source.as_ref(), format!("Form 3921 from {}", form.corporation), form.bargain_element()?);
+        }
+        self.push(options);
+
+        let mut bonds = TraceLine::new("form_6251.2g", "Form 6251 line 2g, private activity bond interest", amt.private_activity_bond_interest,
+            "1099-INT box 9 + 1099-DIV box 13 + entered private activity bond interest")
+            .input("amt_adjustments.private_activity_bond_interest", input.amt_adjustments.private_activity_bond_interest);
+        for form in input.form_1099s.iter().filter(|form| !form.private_activity_bond_interest.is_zero()) {
+            bonds = bonds.source(form.source.as_ref(), format!("{} from {}", form.kind().form_name(), form.payer), form.private_activity_bond_interest);
+        }
+        self.push(bonds);
+
+        let line = TraceLine::new("form_6251.4", "Form 6251 line 4, alternative minimum taxable income", amt.alternative_minimum_taxable_income,
+            "Line 1 (line 11 − line 14) + taxes or the standard deduction (2a) − taxable refunds (2b) + the NOL deduction (2e) − the alternative tax NOL deduction (2f) + lines 2g, 2i and 3")
+            .input("form_6251.1", amt.taxable_income)
+            .input("form_6251.2a", amt.taxes_or_standard_deduction)
+            .input("form_6251.2b", amt.tax_refund)
+            .input("form_6251.2e", amt.net_operating_loss_deduction)
+            .input("form_6251.2f", amt.alternative_net_operating_loss_deduction)
+            .input("form_6251.2g", amt.private_activity_bond_interest)
+            .input("form_6251.2i", amt.incentive_stock_options)
+            .input("form_6251.3", amt.other_adjustments)
+            .rule(self.rule("alternative_minimum_tax.net_operating_loss_limit"));
+        self.push(line);
+
+        let line = TraceLine::new("form_6251.5", "Form 6251 line 5, AMT exemption", amt.exemption,
+            "The exemption less 25% of line 4 over the phase-out threshold, not less than zero")
+            .input("form_6251.4", amt.alternative_minimum_taxable_income)
+            .rule(self.status_rule("alternative_minimum_tax.exemption"))
+            .rule(self.status_rule("alternative_minimum_tax.exemption_phase_out_start"));
+        self.push(line);
+
+        let formula = match &amt.capital_gains_tax {
+            Some(_) => "Part III: line 6 less qualified dividends and net capital gain at 26% and 28%, and those at 0%, 15% and 20%, if less than all of line 6 at 26% and 28%",
+            None => "Line 6 (line 4 − line 5) at 26%, and at 28% above the rate threshold",
+        };
+        let mut tentative = TraceLine::new("form_6251.9", "Form 6251 line 9, tentative minimum tax", amt.tentative_minimum_tax, formula)
+            .input("form_6251.6", amt.taxable_excess)
+            .rule(self.status_rule("alternative_minimum_tax.rate_threshold"));
+        if let Some(part) = &amt.capital_gains_tax {
+            tentative = tentative
+                .input("form_6251.13", part.preferential_income)
+                .input("form_6251.18", part.ordinary_tax)
+                .input("form_6251.38", part.tax)
+                .input("form_6251.39", part.tax_without_preferential_rates);
+        }
+        self.push(tentative);
+
+        let line = TraceLine::new("1040.17", "Form 1040 line 17, alternative minimum tax (AMT)", amt.tax,
+            "Form 6251 line 11: tentative minimum tax (line 9) − regular tax (line 10), not less than zero")
+            .input("form_6251.9", amt.tentative_minimum_tax)
+            .input("form_6251.10", amt.regular_tax);
+        self.push(line);
+
+        let line = TraceLine::new("1040.18", "Form 1040 line 18", calculation.tax + amt.tax, "Line 16 + line 17")
+            .input("1040.16", calculation.tax)
+            .input("1040.17", amt.tax);
+        self.push(line);
+        Ok(())
+    }
+
+    fn credits(&mut self) {
+        let calculation = self.calculation;
+        for credit in &calculation.credits.credits {
+            let formula = match credit.refundable {
+                true => "Figured on its form or worksheet; refundable, so it is paid even beyond the tax",
+                false => "Figured on its form or worksheet, then limited to the tax left after the credits before it",
+            };
+            let line = TraceLine::new(format!("credits.{}", credit.credit.as_str()), format!("{} {}", credit.line, credit.credit.name()), credit.allowed, formula)
+                .input("tentative", credit.tentative)
+                .notes(&credit.explanation);
+            self.push(line);
+        }
+
+        let amt = &calculation.alternative_minimum_tax;
+        let line = TraceLine::new("schedule_3.6b", "Schedule 3 line 6b, minimum tax credit for AMT paid in earlier years", amt.credit_allowed,
+            "Form 8801: the credit carried in, limited to the regular tax less the other nonrefundable credits over the tentative minimum tax")
+            .input("minimum_tax_credit_carryover", amt.credit_carryover)
+            .input("form_6251.9", amt.tentative_minimum_tax);
+        self.push(line);
+
+        let mut nonrefundable = TraceLine::new("1040.21", "Form 1040 line 21, nonrefundable credits", calculation.nonrefundable_credits, "Sum of the nonrefundable credits allowed");
+        for credit in calculation.credits.credits.iter().filter(|credit| !credit.refundable) {
+            nonrefundable = nonrefundable.input(format!("credits.{}", credit.credit.as_str()), credit.allowed);
+        }
+        nonrefundable = nonrefundable.input("schedule_3.6b", amt.credit_allowed);
+        self.push(nonrefundable);
+
+        let line = TraceLine::new("1040.22", "Form 1040 line 22, tax after credits", Self::tax_after_credits(calculation), "Line 18 − line 21")
+            .input("1040.18", calculation.tax + amt.tax)
+            .input("1040.21", calculation.nonrefundable_credits);
+        self.push(line);
+    }
+
+    fn other_taxes(&mut self) {
+        let calculation = self.calculation;
+        let additional_medicare = &calculation.payroll.additional_medicare_tax;
+
+        let mut medicare = TraceLine::new("form_8959.18", "Form 8959 line 18, Additional Medicare Tax", additional_medicare.tax,
+            "The Additional Medicare Tax rate on Medicare wages (line 1) and self-employment earnings (line 8) over the filing status's threshold")
+            .input("form_8959.1", additional_medicare.medicare_wages)
+            .input("form_8959.5", additional_medicare.threshold)
+            .input("form_8959.7", additional_medicare.wage_tax)
+            .input("form_8959.8", additional_medicare.self_employment_income)
+            .input("form_8959.13", additional_medicare.self_employment_tax)
+            .rule(self.rule("payroll.additional_medicare_rate"))
+            .rule(self.status_rule("payroll.additional_medicare_threshold"));
+        for w2 in self.input.w2s.iter().filter(|w2| w2.medicare_wages.is_some()) {
+            medicare = medicare.source(w2.source.as_ref(), format!("W-2 from {}, box 5", w2.employer), w2.medicare_wages.unwrap_or_default());
+        }
+        self.push(medicare);
+
+        let niit = &calculation.net_investment_income_tax;
+        let line = TraceLine::new("form_8960.17", "Form 8960 line 17, net investment income tax (NIIT)", niit.tax,
+            "The NIIT rate on the smaller of net investment income (line 12) and modified AGI over the filing status's threshold (line 15)")
+            .input("form_8960.1", niit.taxable_interest)
+            .input("form_8960.2", niit.ordinary_dividends)
+            .input("form_8960.4a", niit.rental_income)
+            .input("form_8960.5a", niit.capital_gain)
+            .input("form_8960.12", niit.net_investment_income)
+            .input("form_8960.13", niit.modified_adjusted_gross_income)
+            .input("form_8960.14", niit.threshold)
+            .rule(self.rule("net_investment_income_tax.rate"))
+            .rule(self.status_rule("net_investment_income_tax.threshold"));
+        self.push(line);
+
+        let mut other = TraceLine::new("1040.23", "Form 1040 line 23, other taxes from Schedule 2", calculation.other_taxes,
+            "Schedule 2 line 21: self-employment tax (line 4) + Additional Medicare Tax (line 11) + net investment income tax (line 12)")
+            .input("form_8959.18", additional_medicare.tax)
+            .input("form_8960.17", niit.tax);
+        for schedule in &calculation.schedule_se {
+            other = other.input(format!("schedule_se.{}.12", schedule.owner.as_str()), schedule.tax);
+        }
+        self.push(other);
+
+        let line = TraceLine::new("1040.24", "Form 1040 line 24, total tax", calculation.total_tax, "Line 22 + line 23")
+            .input("1040.22", Self::tax_after_credits(calculation))
+            .input("1040.23", calculation.other_taxes);
+        self.push(line);
+    }
+
+    fn payments(&mut self) {
+        let input = self.input;
+        let calculation = self.calculation;
+
+        let mut withholding = TraceLine::new("1040.25d", "Form 1040 line 25d, federal income tax withheld", calculation.withholding,
+            "W-2 box 2 + 1099 box 4 + Additional Medicare Tax withheld (Form 8959 line 24)")
+            .input("form_8959.24", calculation.payroll.additional_medicare_tax.withheld);
+        for w2 in &input.w2s {
+            withholding = withholding.source(w2.source.as_ref(), format!("W-2 from {}, box 2", w2.employer), w2.federal_tax_withheld);
+        }
+        for form in input.form_1099s.iter().filter(|form| !form.federal_tax_withheld.is_zero()) {
+            withholding = withholding.source(form.source.as_ref(), format!("1099 from {}, box 4", form.payer), form.federal_tax_withheld);
+        }
+        self.push(withholding);
+
+        let line = TraceLine::new("1040.26", "Form 1040 line 26, estimated tax payments", calculation.estimated_payments,
+            "Recorded 1040-ES payments for the year, or the amount entered when none are recorded")
+            .input("estimated_payments", input.estimated_payments);
+        self.push(line);
+
+        let mut excess = TraceLine::new("1040.31", "Form 1040 line 31, excess Social Security tax withheld", calculation.other_payments,
+            "Schedule 3 line 11: Social Security tax withheld by more than one employer on wages over the wage base")
+            .rule(self.rule("social_security_wage_base"))
+            .rule(self.rule("payroll.social_security_rate"));
+        for w2 in input.w2s.iter().filter(|w2| w2.social_security_tax_withheld.is_some()) {
+            excess = excess.source(w2.source.as_ref(), format!("W-2 from {}, box 4", w2.employer), w2.social_security_tax_withheld.unwrap_or_default());
+        }
+        self.push(excess);
+
+        let other_payments = calculation.refundable_credits + calculation.other_payments;
+        let mut refundable = TraceLine::new("1040.32", "Form 1040 line 32, refundable credits and other payments", other_payments,
+            "Refundable credits (lines 27 through 29) + line 31");
+        for credit in calculation.credits.credits.iter().filter(|credit| credit.refundable) {
+            refundable = refundable.input(format!("credits.{}", credit.credit.as_str()), credit.allowed);
+        }
+        self.push(refundable.input("1040.31", calculation.other_payments));
+
+        let line = TraceLine::new("1040.33", "Form 1040 line 33, total payments", calculation.total_payments, "Lines 25d + 26 + 32")
+            .input("1040.25d", calculation.withholding)
+            .input("1040.26", calculation.estimated_payments)
+            .input("1040.32", other_payments);
+        self.push(line);
+
+        let line = TraceLine::new("1040.34", "Form 1040 line 34, refund (overpaid)", calculation.refund, "Line 33 − line 24, when payments are larger")
+            .input("1040.33", calculation.total_payments)
+            .input("1040.24", calculation.total_tax);
+        self.push(line);
+        let line = TraceLine::new("1040.37", "Form 1040 line 37, amount you owe (balance due)", calculation.amount_owed, "Line 24 − line 33, when the tax is larger")
+            .input("1040.24", calculation.total_tax)
+            .input("1040.33", calculation.total_payments);
+        self.push(line);
+    }
+
+    fn states(&mut self) {
+        for state in &self.calculation.states {
+            let prefix = format!("states.{}", state.state);
+            let table = self.optional_rule(&prefix);
+
+            let line = TraceLine::new(format!("{prefix}.taxable_income"), format!("{} taxable income", state.state), state.taxable_income,
+                "State AGI (federal AGI allocated to the state for part-year and nonresident returns) − deduction − exemptions")
+                .input("1040.11", self.calculation.adjusted_gross_income)
+                .input(format!("{prefix}.adjusted_gross_income"), state.adjusted_gross_income)
+                .input(format!("{prefix}.allocated_income"), state.allocated_income)
+                .input(format!("{prefix}.income_ratio"), state.income_ratio)
+                .input(format!("{prefix}.deduction"), state.deduction)
+                .input(format!("{prefix}.exemptions"), state.exemptions)
+                .rules(table.clone());
+            self.push(line);
+
+            let line = TraceLine::new(format!("{prefix}.total_tax"), format!("{} state income tax", state.state), state.total_tax,
+                "Tax on taxable income (prorated by the income ratio when not a full-year resident) − credits − credit for tax paid to other states + surtax")
+                .input(format!("{prefix}.tax"), state.tax)
+                .input(format!("{prefix}.credits"), state.credits)
+                .input(format!("{prefix}.other_state_credit"), state.other_state_credit)
+                .input(format!("{prefix}.surtax"), state.surtax)
+                .rules(table)
+                .notes(&state.notes);
+            self.push(line);
+
+            let mut withholding = TraceLine::new(format!("{prefix}.withholding"), format!("{} state tax withheld", state.state), state.withholding, "Sum of W-2 box 17 for the state");
+            for w2 in self.input.w2s.iter().filter(|w2| w2.state.as_deref().is_some_and(|code| code.eq_ignore_ascii_case(&state.state))) {
+                withholding = withholding.source(w2.source.as_ref(), format!("W-2 from {}, box 17", w2.employer), w2.state_tax_withheld);
+            }
+            self.push(withholding);
+
+            let (line, label, amount, formula) = match state.amount_owed.is_zero() {
+                true => ("refund", "state refund", state.refund, "Withholding − state income tax, when withholding is larger"),
+                false => ("amount_owed", "state balance due", state.amount_owed, "State income tax − withholding, when the tax is larger"),
+            };
+            let line = TraceLine::new(format!("{prefix}.{line}"), format!("{} {label}", state.state), amount, formula)
+                .input(format!("{prefix}.withholding"), state.withholding)
+                .input(format!("{prefix}.total_tax"), state.total_tax);
+            self.push(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use super::CalculationTrace;
+    use crate::tax_engine::tests::golden_inputs;
+    use crate::tax_engine::{TaxCalculation, TaxEngine};
+
+    fn traces() -> Vec<(String, TaxCalculation, CalculationTrace)> {
+        golden_inputs().into_iter()
+            .map(|(name, input)| {
+                let calculation = TaxEngine::calculate(&input).unwrap_or_else(|e| panic!("{name}: {e}"));
+                let trace = CalculationTrace::new(&input, &calculation).unwrap_or_else(|e| panic!("{name}: {e}"));
+                (name, calculation, trace)
+            })
+            .collect()
+    }
+
+    /// The calculation's value for each Form 1040 line, figured from it independently of the trace.
+    fn form_1040(calculation: &TaxCalculation, line: &str) -> Option<Decimal> {
+        let income = &calculation.income;
+        Some(match line {
+            "1040.1a" => income.wages,
+            "1040.2b" => income.taxable_interest,
+            "1040.3a" => income.qualified_dividends,
+            "1040.3b" => income.ordinary_dividends,
+            "1040.7" => income.capital_gain,
+            "1040.8" => income.taxable_refunds + income.business_income + income.rental_income + income.other_income - income.net_operating_loss,
+            "1040.9" => income.total,
+            "1040.10" => calculation.adjustments,
+            "1040.11" => calculation.adjusted_gross_income,
+            "1040.12" => calculation.deduction.amount,
+            "1040.13" => calculation.qualified_business_income_deduction,
+            "1040.15" => calculation.taxable_income,
+            "1040.16" => calculation.tax,
+            "1040.17" => calculation.alternative_minimum_tax.tax,
+            "1040.18" => calculation.tax + calculation.alternative_minimum_tax.tax,
+            "1040.21" => calculation.nonrefundable_credits,
+            "1040.22" => calculation.total_tax - calculation.other_taxes,
+            "1040.23" => calculation.other_taxes,
+            "1040.24" => calculation.total_tax,
+            "1040.25d" => calculation.withholding,
+            "1040.26" => calculation.estimated_payments,
+            "1040.31" => calculation.other_payments,
+            "1040.32" => calculation.refundable_credits + calculation.other_payments,
+            "1040.33" => calculation.total_payments,
+            "1040.34" => calculation.refund,
+            "1040.37" => calculation.amount_owed,
+            _ => return None,
+        })
+    }
+
+    #[test]
+    fn form_1040_lines_match_the_calculation() {
+        let mut failures = Vec::new();
+        for (name, calculation, trace) in traces() {
+            for line in trace.lines.iter().filter(|line| line.line.starts_with("1040.")) {
+                match form_1040(&calculation, &line.line) {
+                    Some(expected) if expected == line.amount => {}
+                    Some(expected) => failures.push(format!("{name}: {} traced as {}, calculated as {expected}", line.line, line.amount)),
+                    None => failures.push(format!("{name}: {} has no calculated amount to check against", line.line)),
+                }
+            }
+            for line in ["1040.9", "1040.11", "1040.15", "1040.24", "1040.33"] {
+                if trace.line(line).is_none() {
+                    failures.push(format!("{name}: {line} is not traced"));
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "trace mismatches:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn inputs_carry_the_amount_of_the_line_they_name() {
+        let mut failures = Vec::new();
+        for (name, _, trace) in traces() {
+            for line in &trace.lines {
+                for input in &line.inputs {
+                    if let Some(traced) = trace.line(&input.name) && traced.amount != input.amount {
+                        failures.push(format!("{name}: {} uses {} as {}, traced as {}", line.line, input.name, input.amount, traced.amount));
+                    }
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "trace mismatches:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn rule_references_resolve_in_the_rule_table() {
+        let mut failures = Vec::new();
+        for (name, _, trace) in traces() {
+            for line in &trace.lines {
+                for rule in line.rules.iter().filter(|rule| rule.value.is_null()) {
+                    failures.push(format!("{name}: {} cites {}, which is not in the {} rules", line.line, rule.path, trace.year));
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "unresolved rules:\n{}", failures.join("\n"));
+    }
+
+    /// Line ids `relevant` picks for `question` on the golden return in `file`.
+    fn relevant(file: &str, question: &str) -> Vec<String> {
+        let (input, calculation) = golden_inputs().into_iter()
+            .find(|(name, _)| name == file)
+            .map(|(_, input)| {
+                let calculation = TaxEngine::calculate(&input).unwrap();
+                (input, calculation)
+            })
+            .unwrap_or_else(|| panic!("no golden file {file}"));
+        let trace = CalculationTrace::new(&input, &calculation).unwrap();
+        trace.relevant(question, 3).into_iter().map(|line| line.line.clone()).collect()
+    }
+
+    #[test]
+    fn relevant_answers_why_questions_with_the_line_asked_about() {
+        assert_eq!(relevant("2024_single_w2.json", "Why is my refund so small?")[0], "1040.34");
+        assert_eq!(relevant("2024_single_w2.json", "Why did I get the standard deduction?")[0], "1040.12");
+        assert_eq!(relevant("2024_single_w2.json", "What is my AGI?"), ["1040.11"]);
+        assert_eq!(relevant("2024_single_schedule_c_self_employment.json", "Why do I owe self-employment tax?")[0], "schedule_se.taxpayer.12");
+        assert_eq!(relevant("2024_single_schedule_c_self_employment.json", "Why do I owe money?"), ["1040.37"]);
+        assert_eq!(relevant("2024_single_capital_gains_qdcg_worksheet.json", "How was my capital gain taxed?"), ["1040.7", "schedule_d.16"]);
+    }
+
+    #[test]
+    fn relevant_falls_back_to_the_main_lines() {
+        assert_eq!(relevant("2024_single_w2.json", "hello"), ["1040.11", "1040.15", "1040.24"]);
+    }
+}
@@ -8,4 +8,26 @@ where
         .map_err(|e| ErrorBag::Json(e.to_string()))?;
 
     Ok(value)
-}
\ No newline at end of file
+}
+
+/// Applies a JSON merge patch (RFC 7396): objects merge key by key, `null` removes a key,
+/// and anything else, arrays included, replaces the target value.
+pub fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(changes) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(fields) = target {
+        for (key, value) in changes {
+            match value {
+                serde_json::Value::Null => {
+                    fields.remove(key);
+                }
+                _ => merge_patch(fields.entry(key.clone()).or_insert(serde_json::Value::Null), value),
+            }
+        }
+    }
+}